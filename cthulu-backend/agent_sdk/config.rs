@@ -7,9 +7,15 @@ use claude_agent_sdk_rust::{ClaudeAgentOptions, PermissionMode, SystemPrompt};
 #[derive(Debug, Default)]
 pub struct SessionConfig {
     pub cwd: Option<String>,
+    /// Extra directories claude may access (`--add-dir`).
+    pub add_dirs: Vec<PathBuf>,
     pub system_prompt: Option<String>,
     pub allowed_tools: Vec<String>,
     pub permission_mode: Option<String>,
+    /// Model override (`--model`); `None` uses claude's default.
+    pub model: Option<String>,
+    /// Turn cap (`--max-turns`); `None` leaves claude uncapped.
+    pub max_turns: Option<u32>,
     pub session_id: Option<String>,
     pub resume: Option<String>,
     pub include_partial_messages: bool,
@@ -22,6 +28,7 @@ impl SessionConfig {
         if let Some(cwd) = &self.cwd {
             opts.cwd = Some(PathBuf::from(cwd));
         }
+        opts.add_dirs = self.add_dirs;
 
         if let Some(prompt) = &self.system_prompt {
             opts.system_prompt = Some(SystemPrompt::Text(prompt.clone()));
@@ -40,6 +47,8 @@ impl SessionConfig {
             });
         }
 
+        opts.model = self.model;
+        opts.max_turns = self.max_turns;
         opts.resume = self.resume;
         opts.session_id = self.session_id;
         opts.include_partial_messages = self.include_partial_messages;
@@ -65,6 +65,114 @@ fn default_max_turns() -> u32 { 10 }
 /// Sub-agent map keyed by agent name (e.g. "bugs-bunny").
 pub type SubAgents = HashMap<String, SubAgentDef>;
 
+// ---------------------------------------------------------------------------
+// Permission modes — how the claude CLI gates tool use
+// ---------------------------------------------------------------------------
+
+/// Claude CLI permission mode. An agent without one keeps the legacy
+/// behaviour: an allowlist becomes `--allowedTools`, an empty one is unrestricted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionMode {
+    AcceptEdits,
+    Plan,
+    Default,
+    Bypass,
+}
+
+impl PermissionMode {
+    /// Value for claude's `--permission-mode` flag (and the SDK's mode string).
+    pub fn as_cli(self) -> &'static str {
+        match self {
+            PermissionMode::AcceptEdits => "acceptEdits",
+            PermissionMode::Plan => "plan",
+            PermissionMode::Default => "default",
+            PermissionMode::Bypass => "bypassPermissions",
+        }
+    }
+}
+
+/// Tool-permission flags shared by every claude spawn (interactive chat and executors).
+///
+/// Without a `mode`, an empty allowlist means unrestricted: print-mode executors
+/// skip permissions (`bypass_if_unrestricted`), while interactive sessions leave
+/// Claude's default model on so PermissionRequest hooks can ask the user.
+pub fn permission_args(
+    allowed_tools: &[String],
+    mode: Option<PermissionMode>,
+    disallowed_tools: &[String],
+    bypass_if_unrestricted: bool,
+) -> Vec<String> {
+    let mut args = Vec::new();
+
+    match mode {
+        Some(PermissionMode::Bypass) => args.push("--dangerously-skip-permissions".to_string()),
+        Some(mode) => {
+            args.push("--permission-mode".to_string());
+            args.push(mode.as_cli().to_string());
+        }
+        None if allowed_tools.is_empty() && bypass_if_unrestricted => {
+            args.push("--dangerously-skip-permissions".to_string());
+        }
+        None => {}
+    }
+
+    if !allowed_tools.is_empty() {
+        args.push("--allowedTools".to_string());
+        args.push(allowed_tools.join(","));
+    }
+    if !disallowed_tools.is_empty() {
+        args.push("--disallowedTools".to_string());
+        args.push(disallowed_tools.join(","));
+    }
+
+    args
+}
+
+/// `--add-dir` flags giving claude access to roots beyond its working dir.
+/// Callers validate the dirs first (see `WorkingDirPolicy::check_additional`).
+pub fn add_dir_args(dirs: &[std::path::PathBuf]) -> Vec<String> {
+    dirs.iter()
+        .flat_map(|dir| ["--add-dir".to_string(), dir.to_string_lossy().to_string()])
+        .collect()
+}
+
+/// An executor node's `model` override, if set.
+pub fn node_model(config: &serde_json::Value) -> Option<String> {
+    config["model"].as_str().map(str::trim).filter(|m| !m.is_empty()).map(String::from)
+}
+
+/// An executor node's `max_turns` cap, if set (0 means no cap).
+pub fn node_max_turns(config: &serde_json::Value) -> Option<u32> {
+    config["max_turns"].as_u64().filter(|&n| n > 0).map(|n| n.min(u32::MAX as u64) as u32)
+}
+
+/// Default wall-clock limit for an executor node's claude process.
+pub const DEFAULT_EXECUTOR_TIMEOUT_SECS: u64 = 600;
+
+/// An executor node's `timeout_secs`, falling back to the default when unset or 0.
+pub fn node_timeout(config: &serde_json::Value) -> std::time::Duration {
+    let secs = config["timeout_secs"]
+        .as_u64()
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_EXECUTOR_TIMEOUT_SECS);
+    std::time::Duration::from_secs(secs)
+}
+
+/// `--model` flags for an optional model override.
+pub fn model_args(model: Option<&str>) -> Vec<String> {
+    model
+        .map(|m| vec!["--model".to_string(), m.to_string()])
+        .unwrap_or_default()
+}
+
+/// `--max-turns` flags for an optional turn cap.
+pub fn max_turns_args(max_turns: Option<u32>) -> Vec<String> {
+    max_turns
+        .map(|n| vec!["--max-turns".to_string(), n.to_string()])
+        .unwrap_or_default()
+}
+
 // ---------------------------------------------------------------------------
 // Agent
 // ---------------------------------------------------------------------------
@@ -81,6 +189,12 @@ pub struct Agent {
     pub prompt: String,
     #[serde(default)]
     pub permissions: Vec<String>,
+    /// Claude permission mode; `None` keeps the allowlist-or-bypass default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionMode>,
+    /// Tools passed to `--disallowedTools`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disallowed_tools: Vec<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub append_system_prompt: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -109,6 +223,8 @@ impl Agent {
             description: String::new(),
             prompt: String::new(),
             permissions: Vec::new(),
+            permission_mode: None,
+            disallowed_tools: Vec::new(),
             append_system_prompt: None,
             working_dir: None,
             hooks: HashMap::new(),
@@ -130,6 +246,8 @@ pub struct AgentBuilder<State = NeedsName> {
     description: String,
     prompt: String,
     permissions: Vec<String>,
+    permission_mode: Option<PermissionMode>,
+    disallowed_tools: Vec<String>,
     append_system_prompt: Option<String>,
     working_dir: Option<String>,
     hooks: AgentHooks,
@@ -147,6 +265,8 @@ impl AgentBuilder<NeedsName> {
             description: self.description,
             prompt: self.prompt,
             permissions: self.permissions,
+            permission_mode: self.permission_mode,
+            disallowed_tools: self.disallowed_tools,
             append_system_prompt: self.append_system_prompt,
             working_dir: self.working_dir,
             hooks: self.hooks,
@@ -173,6 +293,16 @@ impl<S> AgentBuilder<S> {
         self
     }
 
+    pub fn permission_mode(mut self, m: PermissionMode) -> Self {
+        self.permission_mode = Some(m);
+        self
+    }
+
+    pub fn disallowed_tools(mut self, t: Vec<String>) -> Self {
+        self.disallowed_tools = t;
+        self
+    }
+
     pub fn append_system_prompt(mut self, s: impl Into<String>) -> Self {
         self.append_system_prompt = Some(s.into());
         self
@@ -209,6 +339,8 @@ impl AgentBuilder<Ready> {
             description: self.description,
             prompt: self.prompt,
             permissions: self.permissions,
+            permission_mode: self.permission_mode,
+            disallowed_tools: self.disallowed_tools,
             append_system_prompt: self.append_system_prompt,
             working_dir: self.working_dir,
             hooks: self.hooks,
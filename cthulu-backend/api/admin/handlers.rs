@@ -0,0 +1,279 @@
+/// Operator endpoints for inspecting and draining the server's child processes.
+///
+/// GET  /api/admin/processes — live Claude processes plus stream/buffer counts
+/// POST /api/admin/drain     — stop accepting work, wait for busy turns, kill the rest
+/// POST /api/admin/resume    — accept work again after a drain
+use axum::extract::{Query, State};
+use axum::Json;
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::api::local_auth::AuthUser;
+use crate::api::{AppState, LiveClaudeProcess};
+
+/// Default time `drain` waits for busy processes before killing them.
+const DEFAULT_DRAIN_GRACE_SECS: u64 = 10;
+/// Upper bound on the drain grace period so a request can't hang forever.
+const MAX_DRAIN_GRACE_SECS: u64 = 120;
+/// How often `drain` re-checks whether busy processes have finished.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// One entry in the live process inventory.
+#[derive(Debug, Serialize)]
+pub struct ProcessInfo {
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub busy: bool,
+    pub pid: Option<u32>,
+    pub uptime_secs: u64,
+    /// The child has exited but its entry hasn't been cleaned up yet.
+    pub exited: bool,
+}
+
+/// Split a process pool key (`agent::{id}::session::{sid}`) into its ids.
+fn parse_process_key(key: &str) -> (Option<String>, Option<String>) {
+    let Some(rest) = key.strip_prefix("agent::") else {
+        return (None, None);
+    };
+    match rest.split_once("::session::") {
+        Some((agent, session)) => (Some(agent.to_string()), Some(session.to_string())),
+        None => (Some(rest.to_string()), None),
+    }
+}
+
+/// Snapshot every process in the pool, sorted by key.
+pub fn live_process_inventory(pool: &mut HashMap<String, LiveClaudeProcess>) -> Vec<ProcessInfo> {
+    let mut list: Vec<ProcessInfo> = pool
+        .iter_mut()
+        .map(|(key, proc)| {
+            let (agent_id, session_id) = parse_process_key(key);
+            ProcessInfo {
+                key: key.clone(),
+                agent_id,
+                session_id,
+                busy: proc.busy,
+                pid: proc.child.id(),
+                uptime_secs: proc.started_at.elapsed().as_secs(),
+                exited: matches!(proc.child.try_wait(), Ok(Some(_))),
+            }
+        })
+        .collect();
+    list.sort_by(|a, b| a.key.cmp(&b.key));
+    list
+}
+
+/// GET /admin/processes — read-only inventory of running child processes.
+pub(crate) async fn list_processes(
+    State(state): State<AppState>,
+    _user: AuthUser,
+) -> Json<Value> {
+    let live = {
+        let mut pool = state.live_processes.lock().await;
+        live_process_inventory(&mut pool)
+    };
+    let sdk_sessions = state.sdk_sessions.lock().await.len();
+    let session_streams = state.session_streams.lock().await.len();
+    let event_buffers = state.chat_event_buffers.lock().await.len();
+
+    Json(json!({
+        "live_processes": live,
+        "sdk_sessions": sdk_sessions,
+        "session_streams": session_streams,
+        "chat_event_buffers": event_buffers,
+    }))
+}
+
+/// 503 if the server is draining, so callers can bail out with `?`.
+pub(crate) fn ensure_not_draining(draining: &AtomicBool) -> Result<(), (StatusCode, Json<Value>)> {
+    if draining.load(Ordering::SeqCst) {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "server is draining for maintenance; try again later" })),
+        ));
+    }
+    Ok(())
+}
+
+/// Wait until no live process is busy, or until `grace` elapses.
+/// Returns true if everything went idle in time.
+async fn wait_for_idle(pool: &Mutex<HashMap<String, LiveClaudeProcess>>, grace: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + grace;
+    loop {
+        if !pool.lock().await.values().any(|p| p.busy) {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+    }
+}
+
+/// Kill and remove every process in the pool. Returns the keys that were stopped.
+pub async fn kill_live_processes(pool: &mut HashMap<String, LiveClaudeProcess>) -> Vec<String> {
+    let mut killed = Vec::new();
+    for (key, mut proc) in pool.drain() {
+        if let Err(e) = proc.child.kill().await {
+            tracing::trace!(key = %key, error = %e, "live process kill on drain");
+        }
+        killed.push(key);
+    }
+    killed.sort();
+    killed
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DrainParams {
+    grace_secs: Option<u64>,
+}
+
+/// POST /admin/drain — reject new chat/trigger requests, give busy turns
+/// `grace_secs` (default 10) to finish, then kill whatever is still running.
+pub(crate) async fn drain(
+    State(state): State<AppState>,
+    _user: AuthUser,
+    Query(params): Query<DrainParams>,
+) -> Json<Value> {
+    let already_draining = state.draining.swap(true, Ordering::SeqCst);
+    tracing::warn!(already_draining, "draining: rejecting new work");
+
+    let grace = Duration::from_secs(
+        params
+            .grace_secs
+            .unwrap_or(DEFAULT_DRAIN_GRACE_SECS)
+            .min(MAX_DRAIN_GRACE_SECS),
+    );
+    let went_idle = wait_for_idle(&state.live_processes, grace).await;
+
+    let (killed, mut busy_at_kill) = {
+        let mut pool = state.live_processes.lock().await;
+        let busy: Vec<String> = pool
+            .iter()
+            .filter(|(_, p)| p.busy)
+            .map(|(k, _)| k.clone())
+            .collect();
+        (kill_live_processes(&mut pool).await, busy)
+    };
+    busy_at_kill.sort();
+
+    let mut disconnected = Vec::new();
+    {
+        let mut pool = state.sdk_sessions.lock().await;
+        for (key, mut session) in pool.drain() {
+            if let Err(e) = session.disconnect().await {
+                tracing::trace!(key = %key, error = %e, "SDK session disconnect on drain");
+            }
+            disconnected.push(key);
+        }
+    }
+    disconnected.sort();
+
+    tracing::warn!(
+        killed = killed.len(),
+        interrupted = busy_at_kill.len(),
+        sdk_sessions = disconnected.len(),
+        "drain complete"
+    );
+
+    Json(json!({
+        "draining": true,
+        "went_idle": went_idle,
+        "killed_processes": killed,
+        "interrupted_processes": busy_at_kill,
+        "disconnected_sdk_sessions": disconnected,
+    }))
+}
+
+/// POST /admin/resume — accept new work again.
+pub(crate) async fn resume(State(state): State<AppState>, _user: AuthUser) -> Json<Value> {
+    let was_draining = state.draining.swap(false, Ordering::SeqCst);
+    if was_draining {
+        tracing::info!("drain lifted: accepting new work");
+    }
+    Json(json!({ "draining": false, "was_draining": was_draining }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+
+    fn spawn_stub() -> LiveClaudeProcess {
+        let mut child = tokio::process::Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .expect("spawn cat");
+        let stdin = child.stdin.take().expect("stdin piped");
+        let (_stdout_tx, stdout_lines) = tokio::sync::mpsc::unbounded_channel();
+        let (_stderr_tx, stderr_lines) = tokio::sync::mpsc::unbounded_channel();
+        LiveClaudeProcess {
+            stdin,
+            stdout_lines,
+            stderr_lines,
+            child,
+            busy: true,
+            started_at: std::time::Instant::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spawned_process_appears_in_inventory() {
+        let mut pool = HashMap::new();
+        let stub = spawn_stub();
+        let pid = stub.child.id();
+        pool.insert("agent::a1::session::s1".to_string(), stub);
+
+        let list = live_process_inventory(&mut pool);
+        assert_eq!(list.len(), 1);
+        let info = &list[0];
+        assert_eq!(info.agent_id.as_deref(), Some("a1"));
+        assert_eq!(info.session_id.as_deref(), Some("s1"));
+        assert!(info.busy);
+        assert!(!info.exited);
+        assert!(pid.is_some());
+        assert_eq!(info.pid, pid);
+    }
+
+    #[tokio::test]
+    async fn test_drain_rejects_new_work_and_kills_processes() {
+        let draining = AtomicBool::new(false);
+        assert!(ensure_not_draining(&draining).is_ok());
+
+        draining.store(true, Ordering::SeqCst);
+        let (status, body) = ensure_not_draining(&draining).unwrap_err();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.0["error"].as_str().unwrap().contains("draining"));
+
+        let pool = Mutex::new(HashMap::new());
+        pool.lock()
+            .await
+            .insert("agent::a1::session::s1".to_string(), spawn_stub());
+        // The stub stays busy, so the grace period runs out.
+        assert!(!wait_for_idle(&pool, Duration::from_millis(300)).await);
+
+        let killed = kill_live_processes(&mut *pool.lock().await).await;
+        assert_eq!(killed, vec!["agent::a1::session::s1".to_string()]);
+        assert!(pool.lock().await.is_empty());
+        assert!(wait_for_idle(&pool, Duration::ZERO).await);
+    }
+
+    #[test]
+    fn test_parse_process_key() {
+        assert_eq!(
+            parse_process_key("agent::a1::session::s1"),
+            (Some("a1".to_string()), Some("s1".to_string()))
+        );
+        assert_eq!(parse_process_key("flow::node"), (None, None));
+    }
+}
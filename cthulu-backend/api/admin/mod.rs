@@ -0,0 +1,13 @@
+pub mod handlers;
+
+use axum::routing::{get, post};
+use axum::Router;
+
+use crate::api::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin/processes", get(handlers::list_processes))
+        .route("/admin/drain", post(handlers::drain))
+        .route("/admin/resume", post(handlers::resume))
+}
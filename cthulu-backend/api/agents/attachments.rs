@@ -0,0 +1,297 @@
+//! Files attached to an agent chat turn.
+//!
+//! Attachments arrive inline (base64) or by name from the node's upload
+//! directory. They are validated before the session is claimed, then written
+//! under the session's working dir so claude can read them. Inline copies are
+//! deleted when the turn ends.
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Most attachments accepted on a single chat turn.
+pub const MAX_ATTACHMENTS: usize = 10;
+/// Largest accepted attachment, after base64 decoding.
+pub const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+/// Request body limit for a chat turn: every attachment at full size, base64
+/// encoded, plus headroom for the prompt and the rest of the JSON.
+pub const MAX_CHAT_BODY_BYTES: usize = MAX_ATTACHMENTS * MAX_ATTACHMENT_BYTES / 3 * 4 + 1024 * 1024;
+/// Where attachments land, relative to the session's working dir.
+const ATTACHMENTS_SUBDIR: &str = ".skills/chat-attachments";
+
+/// Accepted file extensions and their media types.
+const ALLOWED_TYPES: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("pdf", "application/pdf"),
+    ("txt", "text/plain"),
+    ("log", "text/plain"),
+    ("md", "text/markdown"),
+    ("csv", "text/csv"),
+    ("json", "application/json"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+];
+
+#[derive(Deserialize)]
+pub(crate) struct ChatAttachment {
+    /// Plain file name (no directories).
+    pub name: String,
+    /// Defaults to the type implied by the file extension.
+    #[serde(default)]
+    pub media_type: Option<String>,
+    /// Inline base64 content. When absent, `name` refers to a file already
+    /// uploaded to the node's attachments directory.
+    #[serde(default)]
+    pub data: Option<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct PreparedAttachment {
+    /// Path relative to the working dir, as claude should refer to it.
+    pub rel_path: String,
+    pub media_type: String,
+    /// Base64 content for types claude reads inline (images, PDFs).
+    pub inline_base64: Option<String>,
+}
+
+/// Attachments ready for a turn. Inline files are removed on drop, so keep
+/// this alive until the turn has finished.
+#[derive(Debug, Default)]
+pub(crate) struct PreparedAttachments {
+    pub items: Vec<PreparedAttachment>,
+    temp_files: Vec<PathBuf>,
+}
+
+impl PreparedAttachments {
+    /// Plain-text pointer to the attached files, for paths that can only send text.
+    pub fn prompt_note(&self) -> Option<String> {
+        if self.items.is_empty() {
+            return None;
+        }
+        let lines: Vec<String> = self
+            .items
+            .iter()
+            .map(|a| format!("- {} ({})", a.rel_path, a.media_type))
+            .collect();
+        Some(format!("Attached files (in the working directory):\n{}", lines.join("\n")))
+    }
+}
+
+impl Drop for PreparedAttachments {
+    fn drop(&mut self) {
+        for path in &self.temp_files {
+            if let Err(e) = std::fs::remove_file(path) {
+                tracing::debug!(path = %path.display(), error = %e, "failed to remove chat attachment");
+            }
+        }
+    }
+}
+
+fn media_type_for(name: &str, declared: Option<&str>) -> Result<String, String> {
+    let ext = Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let by_ext = ALLOWED_TYPES.iter().find(|(e, _)| *e == ext).map(|(_, t)| *t);
+    match declared {
+        Some(declared) if ALLOWED_TYPES.iter().any(|(_, t)| *t == declared) => Ok(declared.to_string()),
+        Some(declared) => Err(format!("attachment '{name}' has unsupported type '{declared}'")),
+        None => by_ext
+            .map(String::from)
+            .ok_or_else(|| format!("attachment '{name}' has an unsupported file type")),
+    }
+}
+
+fn is_inline_readable(media_type: &str) -> bool {
+    media_type.starts_with("image/") || media_type == "application/pdf"
+}
+
+/// A validated attachment, not yet written anywhere.
+#[derive(Debug)]
+pub(crate) struct AttachmentFile {
+    file_name: String,
+    media_type: String,
+    bytes: Vec<u8>,
+    /// Inline upload, deleted after the turn.
+    is_temp: bool,
+}
+
+/// Decode and check attachments (count, name, type, size). `upload_dir` is
+/// where previously uploaded files are looked up by name.
+pub(crate) fn validate_attachments(
+    attachments: &[ChatAttachment],
+    upload_dir: Option<&Path>,
+) -> Result<Vec<AttachmentFile>, String> {
+    if attachments.len() > MAX_ATTACHMENTS {
+        return Err(format!("at most {MAX_ATTACHMENTS} attachments per message"));
+    }
+
+    attachments
+        .iter()
+        .map(|att| {
+            let name = att.name.trim();
+            if name.is_empty()
+                || name.starts_with('.')
+                || Path::new(name).file_name().and_then(|n| n.to_str()) != Some(name)
+            {
+                return Err(format!("invalid attachment name '{}'", att.name));
+            }
+            let media_type = media_type_for(name, att.media_type.as_deref())?;
+
+            let (bytes, file_name, is_temp) = match &att.data {
+                Some(data) => {
+                    let bytes = base64::engine::general_purpose::STANDARD
+                        .decode(data.trim())
+                        .map_err(|_| format!("attachment '{name}' is not valid base64"))?;
+                    // Prefix inline files so concurrent turns can't clobber each other.
+                    let prefix = &uuid::Uuid::new_v4().simple().to_string()[..8];
+                    (bytes, format!("{prefix}-{name}"), true)
+                }
+                None => {
+                    let dir = upload_dir.ok_or_else(|| {
+                        format!("attachment '{name}' has no data and no upload directory is available")
+                    })?;
+                    let bytes = std::fs::read(dir.join(name))
+                        .map_err(|_| format!("uploaded attachment '{name}' not found"))?;
+                    (bytes, name.to_string(), false)
+                }
+            };
+            if bytes.len() > MAX_ATTACHMENT_BYTES {
+                return Err(format!(
+                    "attachment '{name}' is {} bytes, over the {MAX_ATTACHMENT_BYTES} byte limit",
+                    bytes.len()
+                ));
+            }
+
+            Ok(AttachmentFile {
+                file_name,
+                media_type,
+                bytes,
+                is_temp,
+            })
+        })
+        .collect()
+}
+
+/// Write validated attachments under `working_dir`. A file that can't be
+/// written is logged and left out rather than failing the turn.
+pub(crate) fn write_attachments(files: Vec<AttachmentFile>, working_dir: &Path) -> PreparedAttachments {
+    let mut prepared = PreparedAttachments::default();
+    if files.is_empty() {
+        return prepared;
+    }
+
+    let dest_dir = working_dir.join(ATTACHMENTS_SUBDIR);
+    if let Err(e) = std::fs::create_dir_all(&dest_dir) {
+        tracing::warn!(dir = %dest_dir.display(), error = %e, "failed to create chat attachments dir");
+        return prepared;
+    }
+
+    for file in files {
+        let dest = dest_dir.join(&file.file_name);
+        if let Err(e) = std::fs::write(&dest, &file.bytes) {
+            tracing::warn!(path = %dest.display(), error = %e, "failed to write chat attachment");
+            continue;
+        }
+        if file.is_temp {
+            prepared.temp_files.push(dest);
+        }
+        prepared.items.push(PreparedAttachment {
+            rel_path: format!("{ATTACHMENTS_SUBDIR}/{}", file.file_name),
+            inline_base64: is_inline_readable(&file.media_type)
+                .then(|| base64::engine::general_purpose::STANDARD.encode(&file.bytes)),
+            media_type: file.media_type,
+        });
+    }
+
+    prepared
+}
+
+/// Content block for one attachment: images and PDFs inline, everything else
+/// as a pointer to the file in the working dir.
+pub(crate) fn attachment_block(att: &PreparedAttachment) -> Value {
+    match &att.inline_base64 {
+        Some(data) => json!({
+            "type": if att.media_type == "application/pdf" { "document" } else { "image" },
+            "source": {
+                "type": "base64",
+                "media_type": att.media_type,
+                "data": data,
+            }
+        }),
+        None => json!({
+            "type": "text",
+            "text": format!("Attached file: {} ({}). Read it from the working directory.", att.rel_path, att.media_type),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inline(name: &str, bytes: &[u8]) -> ChatAttachment {
+        ChatAttachment {
+            name: name.to_string(),
+            media_type: None,
+            data: Some(base64::engine::general_purpose::STANDARD.encode(bytes)),
+        }
+    }
+
+    #[test]
+    fn test_inline_attachments_written_and_cleaned_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let files =
+            validate_attachments(&[inline("shot.png", b"\x89PNG"), inline("notes.md", b"# hi")], None).unwrap();
+        let prepared = write_attachments(files, dir.path());
+
+        assert_eq!(prepared.items.len(), 2);
+        assert_eq!(prepared.items[0].media_type, "image/png");
+        assert!(prepared.items[0].inline_base64.is_some());
+        assert!(prepared.items[1].inline_base64.is_none());
+        let written: Vec<PathBuf> = prepared
+            .items
+            .iter()
+            .map(|a| dir.path().join(&a.rel_path))
+            .collect();
+        assert!(written.iter().all(|p| p.exists()));
+
+        drop(prepared);
+        assert!(written.iter().all(|p| !p.exists()));
+    }
+
+    #[test]
+    fn test_uploaded_attachment_is_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let uploads = tempfile::tempdir().unwrap();
+        std::fs::write(uploads.path().join("data.csv"), "a,b\n1,2\n").unwrap();
+
+        let att = ChatAttachment { name: "data.csv".to_string(), media_type: None, data: None };
+        let files = validate_attachments(&[att], Some(uploads.path())).unwrap();
+        let prepared = write_attachments(files, dir.path());
+        let path = dir.path().join(&prepared.items[0].rel_path);
+        drop(prepared);
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_attachment_validation() {
+        let err = validate_attachments(&[inline("../escape.png", b"x")], None).unwrap_err();
+        assert!(err.contains("invalid attachment name"));
+
+        let err = validate_attachments(&[inline("tool.exe", b"MZ")], None).unwrap_err();
+        assert!(err.contains("unsupported"));
+
+        let big = vec![0u8; MAX_ATTACHMENT_BYTES + 1];
+        let err = validate_attachments(&[inline("big.txt", &big)], None).unwrap_err();
+        assert!(err.contains("byte limit"));
+
+        let missing = ChatAttachment { name: "gone.txt".to_string(), media_type: None, data: None };
+        assert!(validate_attachments(&[missing], None).is_err());
+    }
+}
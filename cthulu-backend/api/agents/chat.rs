@@ -1,21 +1,30 @@
-use axum::extract::{Path, State};
-use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::extract::{Path, Query, State};
+use axum::response::sse::{Event, Sse};
 use axum::Json;
 use chrono::Utc;
-use futures::stream::Stream;
+use futures::stream::{Stream, StreamExt};
 use hyper::StatusCode;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::convert::Infallible;
 use std::pin::Pin;
 use uuid::Uuid;
 
+use super::attachments::{
+    attachment_block, validate_attachments, write_attachments, ChatAttachment, PreparedAttachment,
+    PreparedAttachments,
+};
+use super::system_prompt::{render_system_prompt, SystemPromptKind};
+use super::transcript;
 use crate::agent_sdk::config::SessionConfig;
 use crate::api::AppState;
+use crate::api::{recv_with_idle_timeout, release_turn_stream, wait_for_stream_drain, IdleRecv};
 use crate::api::FlowSessions;
 use crate::api::InteractSession;
 use crate::api::LiveClaudeProcess;
+use crate::api::session_log::{self, SessionLogWriter};
 use crate::flows::{Edge, Flow, Node, NodeType};
+use crate::tasks::executors::json_lines::{JsonLineAssembler, ParsedLine};
 use tokio::sync::broadcast;
 
 /// Boxed SSE stream type — used when multiple code paths (SDK vs legacy) can
@@ -58,6 +67,31 @@ fn attachments_path(data_dir: &std::path::Path, flow_id: &str, node_id: &str) ->
     data_dir.join("attachments").join(flow_id).join(node_id)
 }
 
+/// Whether an id is safe to use as a single path component.
+fn is_safe_path_component(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Check a chat's flow context before anything is spawned or written: both
+/// ids name directories (see `attachments_path`), and the node must be an
+/// executor in the flow.
+fn check_flow_context(flow: Option<&Flow>, flow_id: &str, node_id: &str) -> Result<(), String> {
+    if !is_safe_path_component(flow_id) {
+        return Err(format!("invalid flow_id '{flow_id}'"));
+    }
+    if !is_safe_path_component(node_id) {
+        return Err(format!("invalid node_id '{node_id}'"));
+    }
+    let flow = flow.ok_or_else(|| format!("flow {flow_id} not found"))?;
+    match flow.nodes.iter().find(|n| n.id == node_id) {
+        Some(node) if node.node_type == NodeType::Executor => Ok(()),
+        Some(_) => Err(format!("node {node_id} is not an executor node")),
+        None => Err(format!("node {node_id} not found in flow {flow_id}")),
+    }
+}
+
 /// The session key for an agent — just `"agent::{id}"`.
 fn agent_key(agent_id: &str) -> String {
     format!("agent::{agent_id}")
@@ -68,6 +102,60 @@ fn process_key(agent_id: &str, session_id: &str) -> String {
     format!("agent::{agent_id}::session::{session_id}")
 }
 
+/// How a client-supplied `session_id` relates to the session store.
+#[derive(Debug, PartialEq)]
+enum SessionClaim {
+    /// The id already belongs to this agent: resume it.
+    Resume(String),
+    /// A well-formed UUID nobody is using: start a new session under it.
+    Fresh(String),
+}
+
+/// Validate a client-supplied `session_id` before it can reach `--session-id`.
+///
+/// Only ids owned by `owner_key` may be resumed; ids owned by any other agent
+/// or flow are rejected with 409, and unknown ids must be UUIDs.
+fn claim_session_id(
+    all_sessions: &std::collections::HashMap<String, FlowSessions>,
+    owner_key: &str,
+    requested: &str,
+) -> Result<SessionClaim, (StatusCode, Json<Value>)> {
+    if all_sessions
+        .get(owner_key)
+        .is_some_and(|fs| fs.get_session(requested).is_some())
+    {
+        return Ok(SessionClaim::Resume(requested.to_string()));
+    }
+
+    let canonical = Uuid::parse_str(requested)
+        .map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "session_id must be a UUID" })),
+            )
+        })?
+        .to_string();
+
+    for (key, fs) in all_sessions {
+        let Some(existing) = fs
+            .sessions
+            .iter()
+            .find(|s| s.session_id.eq_ignore_ascii_case(&canonical))
+        else {
+            continue;
+        };
+        if key == owner_key {
+            return Ok(SessionClaim::Resume(existing.session_id.clone()));
+        }
+        return Err((
+            StatusCode::CONFLICT,
+            Json(json!({ "error": "session_id is already in use by another session" })),
+        ));
+    }
+
+    Ok(SessionClaim::Fresh(canonical))
+}
+
 /// Check if the Agent SDK integration is enabled via AGENT_SDK_ENABLED env var.
 /// When enabled, new chat sessions use `AgentSession` (SDK) instead of `LiveClaudeProcess`.
 fn agent_sdk_enabled() -> bool {
@@ -76,12 +164,342 @@ fn agent_sdk_enabled() -> bool {
         .unwrap_or(false)
 }
 
+/// Whether a session whose claude conversation has expired restarts fresh on
+/// the next message (`CHAT_EXPIRED_SESSION_RESTART=auto`, the default) or waits
+/// for the user to start a new session (`prompt`).
+fn expired_session_auto_restart() -> bool {
+    std::env::var("CHAT_EXPIRED_SESSION_RESTART")
+        .map(|v| v.to_lowercase() != "prompt")
+        .unwrap_or(true)
+}
+
+/// What claude reports when a `--resume` target no longer exists.
+const EXPIRED_SESSION_MESSAGE: &str = "No conversation found with session ID";
+
+/// Whether an error message from claude (a stderr line, or an error
+/// result's message) is the expired-session error.
+fn is_session_expired_error(message: &str) -> bool {
+    let message = message.trim();
+    message.strip_prefix("Error: ").unwrap_or(message).starts_with(EXPIRED_SESSION_MESSAGE)
+}
+
+/// The messages of a stream-json error result; empty for any other line, so
+/// assistant text can never be mistaken for an error.
+fn result_error_messages(line: &str) -> Vec<String> {
+    let Ok(event) = serde_json::from_str::<Value>(line) else {
+        return Vec::new();
+    };
+    if event["type"] != "result" || event["is_error"] != true {
+        return Vec::new();
+    }
+    let mut messages: Vec<String> = event["errors"]
+        .as_array()
+        .map(|errors| errors.iter().filter_map(|e| e.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    if let Some(result) = event["result"].as_str() {
+        messages.push(result.to_string());
+    }
+    messages
+}
+
+/// `session_expired` event for a resumed turn whose error `message` is the
+/// expired-session error.
+fn session_expired_event(message: &str, session_id: &str, auto_restart: bool) -> Option<String> {
+    if !is_session_expired_error(message) {
+        return None;
+    }
+    let data = json!({
+        "session_id": session_id,
+        "message": message.trim(),
+        "restart": if auto_restart { "auto" } else { "prompt" },
+    });
+    Some(format!("session_expired:{data}"))
+}
+
+/// How a persistent-process chat turn ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TurnOutcome {
+    /// claude sent its `result` line.
+    Completed,
+    /// The process went away without one. `exit_code` is `None` when unknown
+    /// (killed by a signal, or removed from the pool).
+    Crashed { exit_code: Option<i32> },
+}
+
+impl TurnOutcome {
+    fn new(saw_result: bool, exit_code: Option<i32>) -> Self {
+        if saw_result { Self::Completed } else { Self::Crashed { exit_code } }
+    }
+
+    /// Exit code for the `done` event.
+    fn exit_code(self) -> Option<i32> {
+        match self {
+            Self::Completed => Some(0),
+            Self::Crashed { exit_code } => exit_code,
+        }
+    }
+
+    /// `error` event for a crashed turn.
+    fn error_event(self) -> Option<String> {
+        let Self::Crashed { exit_code } = self else {
+            return None;
+        };
+        let message = match exit_code {
+            Some(code) => format!("claude exited with code {code} before finishing the turn"),
+            None => "claude stopped before finishing the turn".to_string(),
+        };
+        Some(format!("error:{}", json!({ "message": message, "exit_code": exit_code })))
+    }
+
+    /// Settle the session after the turn. Only a completed turn counts as a
+    /// message and adds its cost; `reset` starts a new conversation either way.
+    fn record(self, session: &mut InteractSession, cost: f64, reset: bool) {
+        session.busy = false;
+        session.busy_since = None;
+        if reset {
+            session.message_count = 0;
+        } else if self == Self::Completed {
+            session.message_count += 1;
+        }
+        if self == Self::Completed {
+            session.total_cost += cost;
+        }
+    }
+}
+
 /// Maximum number of interactive sessions per agent.
 const MAX_INTERACTIVE_SESSIONS: usize = 5;
 
+/// Share of a session's cost budget after which turns start with a warning.
+const BUDGET_WARNING_FRACTION: f64 = 0.9;
+
+/// Where a session's spend stands against its cost budget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BudgetCheck {
+    Within,
+    /// Within 10% of the budget: the turn runs, with a warning.
+    Near { spent: f64, budget: f64 },
+    /// Spent: no new turns. A turn that started under budget may have
+    /// pushed it over, since its cost is only known once it finishes.
+    Exhausted { spent: f64, budget: f64 },
+}
+
+fn check_budget(spent: f64, budget: Option<f64>) -> BudgetCheck {
+    match budget {
+        Some(budget) if spent >= budget => BudgetCheck::Exhausted { spent, budget },
+        Some(budget) if spent >= budget * BUDGET_WARNING_FRACTION => BudgetCheck::Near { spent, budget },
+        _ => BudgetCheck::Within,
+    }
+}
+
+/// Sent at the start of a turn when the session is close to its budget.
+fn budget_warning_event(spent: f64, budget: f64) -> Event {
+    Event::default().event("system").data(
+        serde_json::to_string(&json!({
+            "message": format!("Session has used ${spent:.2} of its ${budget:.2} cost budget"),
+            "status": "budget_warning",
+            "total_cost": spent,
+            "cost_budget_usd": budget,
+        }))
+        .unwrap(),
+    )
+}
+
 /// Duration after which a busy session with no live process is considered stale.
 const STALE_BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
 
+/// A flow-context turn's claim on its working dir, so it doesn't run claude
+/// alongside that flow's executor in the same directory.
+struct DirLockRequest {
+    locks: crate::flows::dir_lock::WorkingDirLocks,
+    flow_id: String,
+    dir: std::path::PathBuf,
+}
+
+/// Sent when a turn is queued behind a flow run holding its working dir.
+fn waiting_for_lock_event() -> Event {
+    Event::default().event("system").data(
+        serde_json::to_string(&json!({
+            "message": "Waiting for a flow run to release the working directory",
+            "status": "waiting_for_lock",
+        }))
+        .unwrap(),
+    )
+}
+
+/// A non-fatal condition sent to chat clients as a `warning` SSE event, so
+/// they can show a banner instead of treating it as an error.
+#[derive(Debug, Serialize)]
+pub(crate) struct StreamWarning {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl StreamWarning {
+    /// A busy session with no live process was reset so this turn could run.
+    pub const STALE_SESSION_RECOVERED: &'static str = "stale_session_recovered";
+    /// The client fell behind the session broadcast and missed events.
+    pub const EVENTS_SKIPPED: &'static str = "events_skipped";
+
+    fn events_skipped(n: u64) -> Self {
+        StreamWarning {
+            code: Self::EVENTS_SKIPPED,
+            message: format!("Stream fell behind; {n} events were skipped"),
+        }
+    }
+
+    fn to_event(&self) -> Event {
+        Event::default().event("warning").data(serde_json::to_string(self).unwrap())
+    }
+}
+
+/// Prefix a chat stream with `warning` events.
+fn with_warnings(
+    warnings: Vec<StreamWarning>,
+    stream: impl Stream<Item = Result<Event, Infallible>>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    futures::stream::iter(warnings.into_iter().map(|w| Ok(w.to_event()))).chain(stream)
+}
+
+/// Whether a busy session's turn is really over: its process has exited, its
+/// SDK session disconnected, or it has had no process for `STALE_BUSY_TIMEOUT`.
+async fn is_stale_busy(
+    state: &AppState,
+    proc_k: &str,
+    busy_since: Option<chrono::DateTime<chrono::Utc>>,
+) -> bool {
+    let mut pool = state.live_processes.lock().await;
+    if let Some(proc) = pool.get_mut(proc_k) {
+        // Process exists — check if it's actually dead
+        return matches!(proc.child.try_wait(), Ok(Some(_)));
+    }
+    let sdk_pool = state.sdk_sessions.lock().await;
+    if let Some(sdk_session) = sdk_pool.get(proc_k) {
+        // SDK session exists — it's stale if disconnected
+        return !sdk_session.is_connected();
+    }
+    busy_past_timeout(busy_since, chrono::Utc::now())
+}
+
+/// No process in any pool: stale once busy longer than `STALE_BUSY_TIMEOUT`.
+fn busy_past_timeout(
+    busy_since: Option<chrono::DateTime<chrono::Utc>>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> bool {
+    busy_since
+        .map(|since| now.signed_duration_since(since).to_std().unwrap_or_default() > STALE_BUSY_TIMEOUT)
+        .unwrap_or(true) // No timestamp = definitely stale
+}
+
+/// Whether a session is mid-turn, for read-only callers: busy, and not yet
+/// past `STALE_BUSY_TIMEOUT` (recovering stale flags is left to writers).
+async fn session_still_busy(
+    sessions: &tokio::sync::RwLock<std::collections::HashMap<String, FlowSessions>>,
+    agent_key: &str,
+    session_id: &str,
+) -> bool {
+    let sessions = sessions.read().await;
+    sessions
+        .get(agent_key)
+        .and_then(|fs| fs.get_session(session_id))
+        .is_some_and(|s| s.busy && !busy_past_timeout(s.busy_since, Utc::now()))
+}
+
+/// The chat session a relayed turn belongs to.
+struct TurnWatch {
+    sessions: std::sync::Arc<tokio::sync::RwLock<std::collections::HashMap<String, FlowSessions>>>,
+    agent_key: String,
+    session_id: String,
+}
+
+impl TurnWatch {
+    /// Whether the turn is still running. Only the busy flag counts, with no
+    /// `STALE_BUSY_TIMEOUT` cutoff: a tool call can be quiet for longer than
+    /// that, and the turn's reader clears the flag when it ends.
+    async fn in_progress(&self) -> bool {
+        let sessions = self.sessions.read().await;
+        sessions
+            .get(&self.agent_key)
+            .and_then(|fs| fs.get_session(&self.session_id))
+            .is_some_and(|s| s.busy)
+    }
+}
+
+/// Relay a turn's `event:data` broadcast as SSE until `done`, after skipping
+/// the first `skip` events (already replayed from the buffer). Going idle
+/// only closes the stream once the turn is over.
+fn relay_turn_events(
+    mut rx: broadcast::Receiver<String>,
+    idle_timeout: std::time::Duration,
+    mut skip: usize,
+    turn: TurnWatch,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    async_stream::stream! {
+        loop {
+            match recv_with_idle_timeout(&mut rx, idle_timeout).await {
+                IdleRecv::Event(event_str) => {
+                    if skip > 0 {
+                        skip -= 1;
+                        continue;
+                    }
+                    if let Some((event_type, data)) = event_str.split_once(':') {
+                        yield Ok(Event::default().event(event_type).data(data));
+                        if event_type == "done" {
+                            break;
+                        }
+                    }
+                }
+                IdleRecv::Lagged(n) => {
+                    tracing::warn!(session_id = %turn.session_id, skipped = n, "agent chat subscriber lagged");
+                    yield Ok(StreamWarning::events_skipped(n).to_event());
+                }
+                IdleRecv::Closed => break,
+                IdleRecv::Idle => {
+                    // A quiet stretch mid-turn (a long tool call) isn't the end
+                    if turn.in_progress().await {
+                        continue;
+                    }
+                    tracing::warn!(session_id = %turn.session_id, "agent chat stream idle, closing");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Clear the busy state of a session whose process is gone.
+fn recover_stale_session(session: &mut InteractSession) -> StreamWarning {
+    session.busy = false;
+    session.busy_since = None;
+    session.active_pid = None;
+    StreamWarning {
+        code: StreamWarning::STALE_SESSION_RECOVERED,
+        message: "The previous turn's process had exited; the session was reset".to_string(),
+    }
+}
+
+/// Point an idle session at `dir`. Returns whether it changed, in which case
+/// any live process (still in the old dir) must be dropped so the next turn
+/// respawns there.
+fn set_session_working_dir(session: &mut InteractSession, dir: String) -> bool {
+    if session.working_dir == dir {
+        return false;
+    }
+    session.working_dir = dir;
+    session.active_pid = None;
+    true
+}
+
+/// The persistent `claude` process for a chat session, run in `working_dir`.
+fn claude_command(args: &[String], working_dir: &str) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("claude");
+    cmd.args(args)
+        .current_dir(working_dir)
+        .env_remove("CLAUDECODE")
+        .env("CLAUDECODE", "");
+    cmd
+}
+
 // ---------------------------------------------------------------------------
 // Agent chat endpoints
 // ---------------------------------------------------------------------------
@@ -118,6 +536,7 @@ pub(crate) async fn list_sessions(
                     "summary": s.summary,
                     "message_count": s.message_count,
                     "total_cost": s.total_cost,
+                    "cost_budget_usd": s.cost_budget_usd,
                     "created_at": s.created_at,
                     "busy": s.busy,
                     "kind": s.kind,
@@ -224,6 +643,7 @@ pub(crate) async fn new_session(
         busy_since: None,
         message_count: 0,
         total_cost: 0.0,
+        cost_budget_usd: None,
         created_at: now.clone(),
         skills_dir: None,
         kind: "interactive".to_string(),
@@ -401,6 +821,102 @@ pub(crate) async fn session_status(
         "process_alive": process_alive,
         "message_count": session.message_count,
         "total_cost": session.total_cost,
+        "cost_budget_usd": session.cost_budget_usd,
+    })))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct UpdateSessionRequest {
+    pub working_dir: Option<String>,
+    /// `0` clears the budget.
+    pub cost_budget_usd: Option<f64>,
+}
+
+/// PATCH /agents/{id}/sessions/{session_id} — change the session's working
+/// dir and/or cost budget
+pub(crate) async fn update_session(
+    State(state): State<AppState>,
+    Path((id, session_id)): Path<(String, String)>,
+    Json(body): Json<UpdateSessionRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let requested = body.working_dir.filter(|d| !d.trim().is_empty());
+    if requested.is_none() && body.cost_budget_usd.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "working_dir or cost_budget_usd is required" })),
+        ));
+    }
+    if body.cost_budget_usd.is_some_and(|usd| !usd.is_finite() || usd < 0.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "cost_budget_usd must be a non-negative number" })),
+        ));
+    }
+    let working_dir = requested
+        .map(|requested| {
+            state
+                .working_dir_policy
+                .check(std::path::Path::new(&requested))
+                .map(|dir| dir.to_string_lossy().to_string())
+                .map_err(|e| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(json!({ "error": format!("invalid working_dir: {e:#}") })),
+                    )
+                })
+        })
+        .transpose()?;
+
+    let key = agent_key(&id);
+    let proc_k = process_key(&id, &session_id);
+    let mut all_sessions = state.interact_sessions.write().await;
+    let session = all_sessions
+        .get_mut(&key)
+        .and_then(|fs| fs.get_session_mut(&session_id))
+        .ok_or_else(|| (StatusCode::NOT_FOUND, Json(json!({ "error": "session not found" }))))?;
+
+    // Take the stale process out of the pools while still holding the
+    // sessions lock, so a turn can't start on it in between.
+    let mut stale_sdk = None;
+    if working_dir.is_some() && session.busy {
+        if !is_stale_busy(&state, &proc_k, session.busy_since).await {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(json!({ "error": "session is busy; stop it or wait for the turn to finish" })),
+            ));
+        }
+        tracing::warn!(session_id = %session_id, "auto-recovering stale busy session");
+        recover_stale_session(session);
+        state.live_processes.lock().await.remove(&proc_k);
+        stale_sdk = state.sdk_sessions.lock().await.remove(&proc_k);
+    }
+    if let Some(working_dir) = working_dir
+        && set_session_working_dir(session, working_dir.clone())
+    {
+        state.live_processes.lock().await.remove(&proc_k);
+        stale_sdk = stale_sdk.or(state.sdk_sessions.lock().await.remove(&proc_k));
+        tracing::info!(session_id = %session_id, working_dir = %working_dir, "changed session working dir");
+    }
+    // Takes effect from the next turn; one in flight finishes regardless
+    if let Some(usd) = body.cost_budget_usd {
+        session.cost_budget_usd = (usd > 0.0).then_some(usd);
+    }
+    let (working_dir, cost_budget_usd) = (session.working_dir.clone(), session.cost_budget_usd);
+
+    let sessions_snapshot = all_sessions.clone();
+    drop(all_sessions);
+    state.save_sessions_to_disk(&sessions_snapshot);
+
+    if let Some(mut sdk_session) = stale_sdk
+        && let Err(e) = sdk_session.disconnect().await
+    {
+        tracing::warn!(error = %e, "failed to disconnect SDK session on working dir change");
+    }
+
+    Ok(Json(json!({
+        "session_id": session_id,
+        "working_dir": working_dir,
+        "cost_budget_usd": cost_budget_usd,
     })))
 }
 
@@ -485,6 +1001,33 @@ pub(crate) struct ChatRequest {
     pub node_id: Option<String>,
     /// Optional image attachments (base64-encoded).
     pub images: Option<Vec<ImageAttachment>>,
+    /// Optional files (inline or previously uploaded), written into the
+    /// session's working dir and referenced in the user message.
+    pub attachments: Option<Vec<ChatAttachment>>,
+}
+
+/// Build the stream-json user message content: a plain string for text-only
+/// turns, otherwise an array of text, image, and attachment blocks.
+fn build_user_content(prompt: &str, images: &[ImageAttachment], attachments: &[PreparedAttachment]) -> Value {
+    if images.is_empty() && attachments.is_empty() {
+        return json!(prompt);
+    }
+    let mut blocks: Vec<Value> = Vec::new();
+    if !prompt.trim().is_empty() {
+        blocks.push(json!({ "type": "text", "text": prompt }));
+    }
+    for img in images {
+        blocks.push(json!({
+            "type": "image",
+            "source": {
+                "type": "base64",
+                "media_type": img.media_type,
+                "data": img.data,
+            }
+        }));
+    }
+    blocks.extend(attachments.iter().map(attachment_block));
+    json!(blocks)
 }
 
 #[derive(Deserialize)]
@@ -492,24 +1035,37 @@ pub(crate) struct StopRequest {
     pub session_id: Option<String>,
 }
 
+/// Settings a flow's executor node applies to chat turns in its context.
+#[derive(Debug, Clone, Default)]
+struct NodeOverrides {
+    /// Extra `--add-dir` roots, already checked against the working-dir policy.
+    additional_dirs: Vec<std::path::PathBuf>,
+    model: Option<String>,
+    max_turns: Option<u32>,
+}
+
 fn build_sdk_config(
     agent: &crate::agents::Agent,
     session_id: &str,
     working_dir: &str,
     is_new: bool,
     system_prompt: Option<&str>,
+    overrides: &NodeOverrides,
 ) -> SessionConfig {
-    let permission_mode = if agent.permissions.is_empty() {
-        Some("bypassPermissions".to_string())
-    } else {
-        Some("default".to_string())
+    let permission_mode = match agent.permission_mode {
+        Some(mode) => Some(mode.as_cli().to_string()),
+        None if agent.permissions.is_empty() => Some("bypassPermissions".to_string()),
+        None => Some("default".to_string()),
     };
 
     SessionConfig {
         cwd: Some(working_dir.to_string()),
+        add_dirs: overrides.additional_dirs.clone(),
         system_prompt: system_prompt.map(String::from),
         allowed_tools: agent.permissions.clone(),
         permission_mode,
+        model: overrides.model.clone(),
+        max_turns: overrides.max_turns,
         session_id: if is_new { Some(session_id.to_string()) } else { None },
         resume: if !is_new { Some(session_id.to_string()) } else { None },
         include_partial_messages: true,
@@ -521,13 +1077,27 @@ fn chat_sdk_stream(
     state: AppState,
     id: String,
     prompt: String,
+    attachments: PreparedAttachments,
+    dir_lock: Option<DirLockRequest>,
     target_session_id: String,
     is_new: bool,
     working_dir: String,
     system_prompt: Option<String>,
+    overrides: NodeOverrides,
     agent: crate::agents::Agent,
 ) -> impl Stream<Item = Result<Event, Infallible>> {
     async_stream::stream! {
+        let dir_lock = match dir_lock {
+            Some(req) => match req.locks.try_acquire(&req.flow_id, &req.dir) {
+                Some(guard) => Some(guard),
+                None => {
+                    yield Ok(waiting_for_lock_event());
+                    Some(req.locks.acquire(&req.flow_id, &req.dir).await)
+                }
+            },
+            None => None,
+        };
+
         let key_for_stream = agent_key(&id);
         let proc_key = process_key(&id, &target_session_id);
         let sessions_ref = state.interact_sessions.clone();
@@ -536,6 +1106,8 @@ fn chat_sdk_stream(
         let session_streams = state.session_streams.clone();
         let chat_event_buffers = state.chat_event_buffers.clone();
         let data_dir = state.data_dir.clone();
+        let idle_timeout = state.sse.idle_timeout;
+        let done_grace = state.sse.done_grace;
 
         // Create or get the SDK session
         let needs_create = {
@@ -550,6 +1122,7 @@ fn chat_sdk_stream(
                 &working_dir,
                 is_new,
                 system_prompt.as_deref(),
+                &overrides,
             );
 
             match crate::agent_sdk::AgentSession::create(
@@ -599,10 +1172,6 @@ fn chat_sdk_stream(
             buffers.insert(proc_key.clone(), Vec::new());
         }
 
-        let logs_dir = data_dir.join("session_logs");
-        let _ = std::fs::create_dir_all(&logs_dir);
-        let log_path = logs_dir.join(format!("{target_session_id}.jsonl"));
-
         {
             let bc_tx = bc_tx.clone();
             let sdk_sessions = sdk_sessions.clone();
@@ -613,21 +1182,13 @@ fn chat_sdk_stream(
             let key_for_bg = key_for_stream.clone();
             let sid_for_bg = target_session_id.clone();
             let sessions_path = sessions_path.clone();
-            let log_path = log_path.clone();
+            let session_log = SessionLogWriter::spawn(&data_dir, &target_session_id);
 
             tokio::spawn(async move {
                 let mut session_cost: f64 = 0.0;
+                let mut saw_result = false;
 
-                let append_log = |line: &str| {
-                    use std::io::Write;
-                    if let Ok(mut f) = std::fs::OpenOptions::new()
-                        .create(true)
-                        .append(true)
-                        .open(&log_path)
-                    {
-                        let _ = writeln!(f, "{}", line);
-                    }
-                };
+                let append_log = |line: &str| session_log.append(line);
                 append_log(&format!("user:{}", serde_json::to_string(&json!({"text": prompt})).unwrap_or_default()));
                 let result = {
                     let mut pool = sdk_sessions.lock().await;
@@ -642,6 +1203,7 @@ fn chat_sdk_stream(
                                                 serde_json::to_string(&sse_event.data).unwrap_or_default());
 
                                             if sse_event.event_type == "result" {
+                                                saw_result = true;
                                                 session_cost = sse_event.data.get("cost")
                                                     .and_then(|v| v.as_f64())
                                                     .unwrap_or(0.0);
@@ -680,9 +1242,15 @@ fn chat_sdk_stream(
                     }
                 };
 
-                if let Err(e) = result {
-                    tracing::error!(error = %e, "SDK send_message failed");
-                    let error_event = format!("error:{}", serde_json::to_string(&json!({"message": e.to_string()})).unwrap_or_default());
+                let outcome = TurnOutcome::new(saw_result, None);
+                let error_event = match result {
+                    Err(e) => {
+                        tracing::error!(error = %e, "SDK send_message failed");
+                        Some(format!("error:{}", serde_json::to_string(&json!({"message": e.to_string()})).unwrap_or_default()))
+                    }
+                    Ok(()) => outcome.error_event(),
+                };
+                if let Some(error_event) = error_event {
                     let _ = bc_tx.send(error_event.clone());
                     append_log(&error_event);
                     {
@@ -715,7 +1283,7 @@ fn chat_sdk_stream(
                 }
 
                 // Send done event
-                let done_data = serde_json::to_string(&json!({"exit_code": 0})).unwrap();
+                let done_data = serde_json::to_string(&json!({"exit_code": outcome.exit_code()})).unwrap();
                 let done_event = format!("done:{done_data}");
                 let _ = bc_tx.send(done_event.clone());
                 append_log(&done_event);
@@ -725,16 +1293,15 @@ fn chat_sdk_stream(
                         buf.push(done_event);
                     }
                 }
+                // The whole turn is on disk before the stream goes away
+                session_log.close().await;
 
                 // Update session state
                 {
                     let mut all_sessions = sessions_ref.write().await;
                     if let Some(fs) = all_sessions.get_mut(&key_for_bg) {
                         if let Some(s) = fs.get_session_mut(&sid_for_bg) {
-                            s.busy = false;
-                            s.busy_since = None;
-                            s.message_count += 1;
-                            s.total_cost += session_cost;
+                            outcome.record(s, session_cost, false);
                         }
                     }
                     let sessions_snapshot = all_sessions.clone();
@@ -742,39 +1309,26 @@ fn chat_sdk_stream(
                     crate::api::save_sessions(&sessions_path, &sessions_snapshot);
                 }
 
-                // Cleanup after delay
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                {
-                    let mut streams = session_streams.lock().await;
-                    streams.remove(&proc_key);
-                }
-                {
-                    let mut buffers = chat_event_buffers.lock().await;
-                    buffers.remove(&proc_key);
-                }
+                // The turn is over; remove inline attachment files and
+                // release the working dir.
+                drop(attachments);
+                drop(dir_lock);
+
+                // Keep the stream for reconnects until it has drained
+                wait_for_stream_drain(&bc_tx, done_grace).await;
+                release_turn_stream(&session_streams, &chat_event_buffers, &proc_key, &bc_tx).await;
             });
         }
 
         // Subscribe to the broadcast channel and yield events as SSE
-        let mut rx = bc_tx.subscribe();
-        loop {
-            match rx.recv().await {
-                Ok(event_str) => {
-                    if let Some((event_type, data)) = event_str.split_once(':') {
-                        yield Ok(Event::default().event(event_type).data(data));
-                        if event_type == "done" {
-                            break;
-                        }
-                    }
-                }
-                Err(broadcast::error::RecvError::Lagged(n)) => {
-                    tracing::warn!(skipped = n, "agent chat SDK broadcast subscriber lagged");
-                    continue;
-                }
-                Err(broadcast::error::RecvError::Closed) => {
-                    break;
-                }
-            }
+        let turn = TurnWatch {
+            sessions: sessions_ref.clone(),
+            agent_key: key_for_stream.clone(),
+            session_id: target_session_id.clone(),
+        };
+        let mut relay = std::pin::pin!(relay_turn_events(bc_tx.subscribe(), idle_timeout, 0, turn));
+        while let Some(event) = relay.next().await {
+            yield event;
         }
     }
 }
@@ -788,20 +1342,25 @@ pub(crate) async fn chat(
     Sse<axum::response::sse::KeepAliveStream<BoxSseStream>>,
     (StatusCode, Json<Value>),
 > {
-    let agent = state.agent_repo.get(&id).await.ok_or_else(|| {
+    state.ensure_accepting_work()?;
+
+    let mut agent = state.agent_repo.get(&id).await.ok_or_else(|| {
         (StatusCode::NOT_FOUND, Json(json!({ "error": "agent not found" })))
     })?;
 
     let prompt = body.prompt;
     let images = body.images.unwrap_or_default();
-    if prompt.trim().is_empty() && images.is_empty() {
+    let requested_attachments = body.attachments.unwrap_or_default();
+    if prompt.trim().is_empty() && images.is_empty() && requested_attachments.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(json!({ "error": "prompt is required" })),
         ));
     }
 
-    let permissions = agent.permissions.clone();
+    let keep_alive = state.sse_keep_alive();
+    let idle_timeout = state.sse.idle_timeout;
+    let done_grace = state.sse.done_grace;
     let append_system_prompt = agent.append_system_prompt.clone();
 
     let default_working_dir = agent.working_dir.clone().unwrap_or_else(|| {
@@ -815,11 +1374,70 @@ pub(crate) async fn chat(
 
     // Load the flow if flow context was provided (for .skills/ generation)
     let flow_context = if let (Some(flow_id), Some(node_id)) = (&body.flow_id, &body.node_id) {
-        state.flow_repo.get_flow(flow_id).await.map(|flow| (flow, node_id.clone()))
+        let flow = state.flow_repo.get_flow(flow_id).await;
+        check_flow_context(flow.as_ref(), flow_id, node_id)
+            .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))))?;
+        flow.map(|flow| (flow, node_id.clone()))
     } else {
         None
     };
 
+    // Flow-context sessions write .skills/ into the working dir; reject (or
+    // replace) an unusable one now instead of failing on the first write.
+    let default_working_dir = if flow_context.is_some() {
+        state
+            .working_dir_policy
+            .resolve(Some(&default_working_dir))
+            .map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("invalid working_dir: {e:#}") })),
+                )
+            })?
+            .to_string_lossy()
+            .to_string()
+    } else {
+        default_working_dir
+    };
+
+    // Extra `--add-dir` roots from the executor node, held to the same policy.
+    let additional_dirs = match &flow_context {
+        Some((flow, node_id)) => flow
+            .nodes
+            .iter()
+            .find(|n| &n.id == node_id)
+            .map(|node| state.working_dir_policy.check_additional(&node.config["additional_dirs"]))
+            .transpose()
+            .map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("invalid additional_dirs: {e:#}") })),
+                )
+            })?
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    // An agent that doesn't restrict its tools gets the node's
+    // `chat_permissions` or the configured safe default, not every tool.
+    let node_permissions = flow_context
+        .as_ref()
+        .and_then(|(flow, node_id)| flow.nodes.iter().find(|n| &n.id == node_id))
+        .map(|node| node.config["chat_permissions"].clone())
+        .unwrap_or_default();
+    agent.permissions = state
+        .chat_permissions
+        .resolve(&agent.permissions, agent.permission_mode, &node_permissions);
+    let context_node = flow_context
+        .as_ref()
+        .and_then(|(flow, node_id)| flow.nodes.iter().find(|n| &n.id == node_id));
+    let overrides = NodeOverrides {
+        additional_dirs,
+        model: context_node.and_then(|node| crate::agents::node_model(&node.config)),
+        max_turns: context_node.and_then(|node| crate::agents::node_max_turns(&node.config)),
+    };
+    let permissions = agent.permissions.clone();
+
     // Pre-create worktree group if this agent has no sessions yet (outside write lock)
     let needs_new_entry = {
         let sessions = state.interact_sessions.read().await;
@@ -852,11 +1470,33 @@ pub(crate) async fn chat(
         None
     };
 
+    // Validate attachments before claiming the session so a bad upload
+    // doesn't leave it marked busy.
+    let upload_dir = flow_context
+        .as_ref()
+        .map(|(flow, node_id)| attachments_path(&state.data_dir, &flow.id, node_id));
+    let attachment_files = validate_attachments(&requested_attachments, upload_dir.as_deref())
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))))?;
+
+    // Non-fatal conditions reported at the start of the stream.
+    let mut warnings: Vec<StreamWarning> = Vec::new();
+    let default_budget = flow_context.as_ref().and_then(|(flow, _)| flow.default_session_budget_usd);
+
     // Look up or create the session
-    let (target_session_id, is_new, working_dir) = {
+    let (target_session_id, is_new, working_dir, budget_event) = {
         let mut all_sessions = state.interact_sessions.write().await;
         let mut used_prepared_worktree = false;
 
+        let claim = match body.session_id.as_deref() {
+            Some(requested) => Some(claim_session_id(&all_sessions, &key, requested)?),
+            None => None,
+        };
+        // A fresh client-chosen id names the auto-created session directly.
+        let initial_session_id = match &claim {
+            Some(SessionClaim::Fresh(sid)) => sid.clone(),
+            _ => pre_session_id.clone(),
+        };
+
         let flow_sessions = all_sessions
             .entry(key.clone())
             .or_insert_with(|| {
@@ -867,9 +1507,9 @@ pub(crate) async fn chat(
                 };
                 FlowSessions {
                     flow_name: agent.name.clone(),
-                    active_session: pre_session_id.clone(),
+                    active_session: initial_session_id.clone(),
                     sessions: vec![InteractSession {
-                        session_id: pre_session_id.clone(),
+                        session_id: initial_session_id.clone(),
                         summary: make_summary(&prompt),
                         node_id: None,
                         working_dir: wdir,
@@ -878,6 +1518,7 @@ pub(crate) async fn chat(
                         busy_since: None,
                         message_count: 0,
                         total_cost: 0.0,
+                        cost_budget_usd: None,
                         created_at: Utc::now().to_rfc3339(),
                         skills_dir: None,
                         kind: "interactive".to_string(),
@@ -896,8 +1537,41 @@ pub(crate) async fn chat(
             }
         }
 
-        let target_sid = body.session_id
-            .unwrap_or_else(|| flow_sessions.active_session.clone());
+        let target_sid = match claim {
+            Some(SessionClaim::Resume(sid)) => sid,
+            Some(SessionClaim::Fresh(sid)) => {
+                if flow_sessions.get_session(&sid).is_none() {
+                    let interactive_count = flow_sessions.sessions.iter()
+                        .filter(|s| s.kind == "interactive")
+                        .count();
+                    if interactive_count >= MAX_INTERACTIVE_SESSIONS {
+                        return Err((
+                            StatusCode::TOO_MANY_REQUESTS,
+                            Json(json!({ "error": format!("session limit reached ({MAX_INTERACTIVE_SESSIONS} interactive sessions max). Close an existing session first.") })),
+                        ));
+                    }
+                    flow_sessions.sessions.push(InteractSession {
+                        session_id: sid.clone(),
+                        summary: make_summary(&prompt),
+                        node_id: None,
+                        working_dir: default_working_dir.clone(),
+                        active_pid: None,
+                        busy: false,
+                        busy_since: None,
+                        message_count: 0,
+                        total_cost: 0.0,
+                        cost_budget_usd: None,
+                        created_at: Utc::now().to_rfc3339(),
+                        skills_dir: None,
+                        kind: "interactive".to_string(),
+                        flow_run: None,
+                        worktree_group: None,
+                    });
+                }
+                sid
+            }
+            None => flow_sessions.active_session.clone(),
+        };
 
         let session = match flow_sessions.get_session_mut(&target_sid) {
             Some(s) => s,
@@ -912,25 +1586,7 @@ pub(crate) async fn chat(
         if session.busy {
             // Check for stale busy — process might be dead
             let proc_k = process_key(&id, &session.session_id);
-            let is_stale = {
-                let mut pool = state.live_processes.lock().await;
-                if let Some(proc) = pool.get_mut(&proc_k) {
-                    // Process exists — check if it's actually dead
-                    matches!(proc.child.try_wait(), Ok(Some(_)))
-                } else {
-                    // Check SDK sessions
-                    let sdk_pool = state.sdk_sessions.lock().await;
-                    if let Some(sdk_session) = sdk_pool.get(&proc_k) {
-                        // SDK session exists — it's stale if disconnected
-                        !sdk_session.is_connected()
-                    } else {
-                        // No process in any pool — check if it's been busy too long
-                        session.busy_since
-                            .map(|since| chrono::Utc::now().signed_duration_since(since).to_std().unwrap_or_default() > STALE_BUSY_TIMEOUT)
-                            .unwrap_or(true) // No timestamp = definitely stale
-                    }
-                }
-            };
+            let is_stale = is_stale_busy(&state, &proc_k, session.busy_since).await;
 
             if is_stale {
                 tracing::warn!(
@@ -946,9 +1602,7 @@ pub(crate) async fn chat(
                 let mut sdk_pool = state.sdk_sessions.lock().await;
                 sdk_pool.remove(&proc_k);
                 drop(sdk_pool);
-                session.busy = false;
-                session.busy_since = None;
-                session.active_pid = None;
+                warnings.push(recover_stale_session(session));
             } else {
                 return Err((
                     StatusCode::CONFLICT,
@@ -957,6 +1611,21 @@ pub(crate) async fn chat(
             }
         }
 
+        let budget_event = match check_budget(session.total_cost, session.cost_budget_usd.or(default_budget)) {
+            BudgetCheck::Exhausted { spent, budget } => {
+                return Err((
+                    StatusCode::PAYMENT_REQUIRED,
+                    Json(json!({
+                        "error": format!("session cost budget exhausted (${spent:.2} of ${budget:.2})"),
+                        "total_cost": spent,
+                        "cost_budget_usd": budget,
+                    })),
+                ));
+            }
+            BudgetCheck::Near { spent, budget } => Some(budget_warning_event(spent, budget)),
+            BudgetCheck::Within => None,
+        };
+
         let is_new = session.message_count == 0;
 
         if is_new && session.summary.is_empty() {
@@ -974,9 +1643,17 @@ pub(crate) async fn chat(
         drop(all_sessions);
         state.save_sessions_to_disk(&sessions_snapshot);
 
-        (sid, is_new, wdir)
+        (sid, is_new, wdir, budget_event)
     };
 
+    let attachments = write_attachments(attachment_files, std::path::Path::new(&working_dir));
+    // Flow-context turns share the working dir with that flow's executors.
+    let dir_lock = flow_context.as_ref().map(|(flow, _)| DirLockRequest {
+        locks: state.dir_locks.clone(),
+        flow_id: flow.id.clone(),
+        dir: std::path::PathBuf::from(&working_dir),
+    });
+
     // On first message: generate .skills/ context files and build system prompt
     let data_dir = state.data_dir.clone();
 
@@ -1126,25 +1803,16 @@ pub(crate) async fn chat(
             }
 
             // 4. Build scoped system prompt for flow executor agents
-            let mut sys_prompt = format!(
-                "You are \"{agent_name}\", an AI agent.\n\n\
-                 CRITICAL RULES — FOLLOW EXACTLY:\n\n\
-                 1. FIRST: Read .skills/Skill.md and .skills/AGENT.md — these contain ALL your context.\n\
-                 2. SCOPE LOCKED: Do NOT explore, search, or read ANY files outside .skills/ directory.\n\
-                    No find, no grep, no ls, no bash exploration, no reading source code, no git commands.\n\
-                    Your .skills/ files already contain your workflow context, pipeline info, and rules.\n\
-                 3. ANSWER FROM CONTEXT: When asked about the workflow, answer from .skills/Skill.md\n\
-                    and .skills/workflow.json — do NOT go looking for more information.\n\
-                 4. IF THE USER WANTS MORE: Only if the user explicitly says \"read the codebase\",\n\
-                    \"explore the project\", or similar — THEN you may expand scope. Not before.\n\
-                 5. BE EFFICIENT: Short answers. No preamble. No filler. Batch tool calls.\n\
-                    Read .skills/AGENT.md for full efficiency rules.\n\n\
-                 Files in .skills/:\n\
-                 - .skills/AGENT.md — agent rules, scope boundaries, efficiency rules\n\
-                 - .skills/Skill.md — your position in the pipeline and workflow context\n\
-                 - .skills/workflow.json — full workflow definition",
-                agent_name = agent.name,
-            );
+            let mut vars = std::collections::HashMap::from([
+                ("agent_name".to_string(), agent.name.clone()),
+                ("working_dir".to_string(), working_dir.clone()),
+            ]);
+            if let Some((ref flow, ref node_id)) = flow_context {
+                vars.insert("flow_id".to_string(), flow.id.clone());
+                vars.insert("flow_name".to_string(), flow.name.clone());
+                vars.insert("node_id".to_string(), node_id.clone());
+            }
+            let mut sys_prompt = render_system_prompt(&data_dir, SystemPromptKind::FlowAgent, &vars);
 
             if let Some(ref extra) = append_system_prompt {
                 if !extra.is_empty() {
@@ -1155,13 +1823,11 @@ pub(crate) async fn chat(
             Some(sys_prompt)
         } else {
             // Standalone agent — simple system prompt, no .skills/ scope lock
-            let mut sys_prompt = format!(
-                "You are \"{agent_name}\", an AI assistant. \
-                 Your working directory is: {working_dir}\n\
-                 Be efficient: short answers, no preamble, batch tool calls when possible.",
-                agent_name = agent.name,
-                working_dir = working_dir,
-            );
+            let vars = std::collections::HashMap::from([
+                ("agent_name".to_string(), agent.name.clone()),
+                ("working_dir".to_string(), working_dir.clone()),
+            ]);
+            let mut sys_prompt = render_system_prompt(&data_dir, SystemPromptKind::StandaloneAgent, &vars);
 
             if let Some(ref extra) = append_system_prompt {
                 if !extra.is_empty() {
@@ -1180,20 +1846,26 @@ pub(crate) async fn chat(
     // LiveClaudeProcess. Returns early with the SDK stream.
     // -----------------------------------------------------------------------
     if agent_sdk_enabled() {
+        // The SDK path sends text only, so point claude at the files instead.
+        let prompt = match attachments.prompt_note() {
+            Some(note) => format!("{prompt}\n\n{note}"),
+            None => prompt,
+        };
         let stream = chat_sdk_stream(
             state,
             id,
             prompt,
+            attachments,
+            dir_lock,
             target_session_id,
             is_new,
             working_dir,
             system_prompt,
+            overrides,
             agent,
         );
-        let boxed: BoxSseStream = Box::pin(stream);
-        return Ok(Sse::new(boxed).keep_alive(
-            KeepAlive::new().interval(std::time::Duration::from_secs(15)),
-        ));
+        let boxed: BoxSseStream = Box::pin(with_warnings(warnings, futures::stream::iter(budget_event.map(Ok)).chain(stream)));
+        return Ok(Sse::new(boxed).keep_alive(keep_alive));
     }
 
     // -----------------------------------------------------------------------
@@ -1212,7 +1884,17 @@ pub(crate) async fn chat(
     let stream = async_stream::stream! {
         use std::process::Stdio;
         use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-        use tokio::process::Command;
+
+        let dir_lock = match dir_lock {
+            Some(req) => match req.locks.try_acquire(&req.flow_id, &req.dir) {
+                Some(guard) => Some(guard),
+                None => {
+                    yield Ok(waiting_for_lock_event());
+                    Some(req.locks.acquire(&req.flow_id, &req.dir).await)
+                }
+            },
+            None => None,
+        };
 
         // Check if we have a live persistent process for this session.
         // Hold the lock across check + spawn + insert to prevent TOCTOU races.
@@ -1230,16 +1912,21 @@ pub(crate) async fn chat(
                     "stream-json".to_string(),
                 ];
 
-                if !permissions.is_empty() {
-                    // Agent has explicit allowedTools — use them.
-                    // Tools NOT in this list trigger Claude's permission model,
-                    // which fires the PermissionRequest hook.
-                    args.push("--allowedTools".to_string());
-                    args.push(permissions.join(","));
-                }
-                // When no explicit permissions: Claude's default permission model
-                // kicks in and fires PermissionRequest hooks (configured in
-                // .claude/settings.local.json) for tools that need approval.
+                // Explicit allowedTools are used as-is; tools NOT in the list
+                // trigger Claude's permission model, which fires the
+                // PermissionRequest hook. With no explicit permissions (and no
+                // permission_mode) Claude's default model kicks in and fires
+                // PermissionRequest hooks (configured in .claude/settings.local.json)
+                // for tools that need approval.
+                args.extend(crate::agents::permission_args(
+                    &permissions,
+                    agent.permission_mode,
+                    &agent.disallowed_tools,
+                    false,
+                ));
+                args.extend(crate::agents::add_dir_args(&overrides.additional_dirs));
+                args.extend(crate::agents::model_args(overrides.model.as_deref()));
+                args.extend(crate::agents::max_turns_args(overrides.max_turns));
 
                 // Pass sub-agent definitions via Claude Code's native --agents flag.
                 // This lets the parent session delegate to specialized sub-agents.
@@ -1274,11 +1961,7 @@ pub(crate) async fn chat(
                     "spawning persistent claude for agent chat"
                 );
 
-                match Command::new("claude")
-                    .args(&args)
-                    .current_dir(&working_dir)
-                    .env_remove("CLAUDECODE")
-                    .env("CLAUDECODE", "")
+                match claude_command(&args, &working_dir)
                     .stdin(Stdio::piped())
                     .stdout(Stdio::piped())
                     .stderr(Stdio::piped())
@@ -1326,6 +2009,7 @@ pub(crate) async fn chat(
                             stderr_lines: stderr_rx,
                             child,
                             busy: false,
+                            started_at: std::time::Instant::now(),
                         };
 
                         pool.insert(proc_key_for_stream.clone(), live_proc);
@@ -1367,26 +2051,7 @@ pub(crate) async fn chat(
         {
             let mut pool = live_processes.lock().await;
             if let Some(proc) = pool.get_mut(&proc_key_for_stream) {
-                // Build content: plain string when no images, content block array when images present
-                let content = if images.is_empty() {
-                    json!(prompt)
-                } else {
-                    let mut blocks: Vec<Value> = Vec::new();
-                    if !prompt.trim().is_empty() {
-                        blocks.push(json!({ "type": "text", "text": prompt }));
-                    }
-                    for img in &images {
-                        blocks.push(json!({
-                            "type": "image",
-                            "source": {
-                                "type": "base64",
-                                "media_type": img.media_type,
-                                "data": img.data,
-                            }
-                        }));
-                    }
-                    json!(blocks)
-                };
+                let content = build_user_content(&prompt, &images, &attachments.items);
                 let input_msg = serde_json::to_string(&json!({
                     "type": "user",
                     "message": {
@@ -1453,6 +2118,8 @@ pub(crate) async fn chat(
             let sessions_path = sessions_path.clone();
             let data_dir_for_bg = data_dir.clone();
             let prompt_for_log = prompt.clone();
+            let resumed = !is_new;
+            let auto_restart = expired_session_auto_restart();
 
             tokio::spawn(async move {
                 tracing::info!(
@@ -1461,22 +2128,15 @@ pub(crate) async fn chat(
                 );
                 let mut session_cost: f64 = 0.0;
                 let mut event_count: u64 = 0;
+                let mut assembler = JsonLineAssembler::new();
+                let mut session_expired = false;
+                let mut saw_result = false;
+                // Exit code, if the process ended before sending a result
+                let mut exit_code: Option<i32> = None;
 
-                // Set up JSONL log file for session history persistence
-                let logs_dir = data_dir_for_bg.join("session_logs");
-                let _ = std::fs::create_dir_all(&logs_dir);
-                let log_path = logs_dir.join(format!("{sid_for_bg}.jsonl"));
-
-                let append_log = |line: &str| {
-                    use std::io::Write;
-                    if let Ok(mut f) = std::fs::OpenOptions::new()
-                        .create(true)
-                        .append(true)
-                        .open(&log_path)
-                    {
-                        let _ = writeln!(f, "{}", line);
-                    }
-                };
+                // Session history, appended off the relay path
+                let session_log = SessionLogWriter::spawn(&data_dir_for_bg, &sid_for_bg);
+                let append_log = |line: &str| session_log.append(line);
 
                 // Log the user prompt that initiated this turn
                 append_log(&format!("user:{}", serde_json::to_string(&json!({"text": prompt_for_log})).unwrap_or_default()));
@@ -1496,6 +2156,29 @@ pub(crate) async fn chat(
                         }
                     };
 
+                    // A resumed conversation claude has since discarded: tell the
+                    // client instead of ending with a generic failure. Only
+                    // stderr and error results are checked, never assistant text.
+                    let expired_event = (resumed && !session_expired)
+                        .then(|| {
+                            stderr_batch
+                                .iter()
+                                .cloned()
+                                .chain(line.iter().flat_map(|l| result_error_messages(l)))
+                                .find_map(|m| session_expired_event(&m, &sid_for_bg, auto_restart))
+                        })
+                        .flatten();
+                    if let Some(event) = expired_event {
+                        tracing::warn!(session_id = %sid_for_bg, "claude session expired, cannot resume");
+                        session_expired = true;
+                        let _ = bc_tx.send(event.clone());
+                        append_log(&event);
+                        let mut buffers = chat_event_buffers.lock().await;
+                        if let Some(buf) = buffers.get_mut(&proc_key) {
+                            buf.push(event);
+                        }
+                    }
+
                     for err_line in stderr_batch {
                         tracing::debug!(stderr = %err_line, "claude stderr");
                         let event = format!("stderr:{err_line}");
@@ -1512,7 +2195,7 @@ pub(crate) async fn chat(
                             continue;
                         }
 
-                        let events = parse_claude_line_to_sse_events(&line);
+                        let events = parse_claude_line_to_sse_events(&mut assembler, &line);
                         let mut is_result = false;
 
                         for (event_type, data_json) in &events {
@@ -1560,6 +2243,7 @@ pub(crate) async fn chat(
                                     }
                                 }
                             }
+                            saw_result = true;
                             break;
                         }
                     } else {
@@ -1567,7 +2251,8 @@ pub(crate) async fn chat(
 
                         let mut pool = live_processes.lock().await;
                         if let Some(proc) = pool.get_mut(&proc_key) {
-                            if let Ok(Some(_status)) = proc.child.try_wait() {
+                            if let Ok(Some(status)) = proc.child.try_wait() {
+                                exit_code = status.code();
                                 pool.remove(&proc_key);
                                 break;
                             }
@@ -1577,6 +2262,23 @@ pub(crate) async fn chat(
                     }
                 }
 
+                // The turn is over; remove inline attachment files and
+                // release the working dir.
+                drop(attachments);
+                drop(dir_lock);
+
+                let outcome = TurnOutcome::new(saw_result, exit_code);
+                // An expired session already told the client what happened
+                if let (Some(event), false) = (outcome.error_event(), session_expired) {
+                    tracing::warn!(proc_key = %proc_key, ?exit_code, "claude exited before finishing the turn");
+                    let _ = bc_tx.send(event.clone());
+                    append_log(&event);
+                    let mut buffers = chat_event_buffers.lock().await;
+                    if let Some(buf) = buffers.get_mut(&proc_key) {
+                        buf.push(event);
+                    }
+                }
+
                 // Mark session as not busy, update stats
                 {
                     let mut pool = live_processes.lock().await;
@@ -1588,10 +2290,7 @@ pub(crate) async fn chat(
                     let mut all_sessions = sessions_ref.write().await;
                     if let Some(fs) = all_sessions.get_mut(&key_for_bg) {
                         if let Some(s) = fs.get_session_mut(&sid_for_bg) {
-                            s.busy = false;
-                            s.busy_since = None;
-                            s.message_count += 1;
-                            s.total_cost += session_cost;
+                            outcome.record(s, session_cost, session_expired && auto_restart);
                         }
                     }
                     let sessions_snapshot = all_sessions.clone();
@@ -1605,7 +2304,7 @@ pub(crate) async fn chat(
                     event_count,
                     "[RECONNECT-DEBUG] Background reader task DONE, sending done event"
                 );
-                let done_data = serde_json::to_string(&json!({"exit_code": 0})).unwrap();
+                let done_data = serde_json::to_string(&json!({"exit_code": outcome.exit_code()})).unwrap();
                 let done_event = format!("done:{done_data}");
                 let _ = bc_tx.send(done_event.clone());
                 append_log(&done_event);
@@ -1615,145 +2314,143 @@ pub(crate) async fn chat(
                         buf.push(done_event);
                     }
                 }
+                // The whole turn is on disk before the stream goes away
+                session_log.close().await;
 
-                // Clean up broadcast channel after a delay to allow reconnects to catch the done event
+                // Keep the stream for reconnects until the grace period has
+                // passed and every subscriber has drained it
                 tracing::info!(
                     proc_key = %proc_key,
-                    "[RECONNECT-DEBUG] Waiting 5s before cleanup..."
+                    grace_secs = done_grace.as_secs(),
+                    "[RECONNECT-DEBUG] Waiting before cleanup..."
+                );
+                wait_for_stream_drain(&bc_tx, done_grace).await;
+                let released =
+                    release_turn_stream(&session_streams, &chat_event_buffers, &proc_key, &bc_tx).await;
+                tracing::info!(
+                    proc_key = %proc_key,
+                    released,
+                    "[RECONNECT-DEBUG] Cleaned up broadcast channel and event buffer. Background task EXIT."
                 );
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                {
-                    let mut streams = session_streams.lock().await;
-                    streams.remove(&proc_key);
-                    tracing::info!(
-                        proc_key = %proc_key,
-                        remaining_streams = streams.len(),
-                        "[RECONNECT-DEBUG] Cleaned up broadcast channel"
-                    );
-                }
-                {
-                    let mut buffers = chat_event_buffers.lock().await;
-                    buffers.remove(&proc_key);
-                    tracing::info!(
-                        proc_key = %proc_key,
-                        "[RECONNECT-DEBUG] Cleaned up event buffer. Background task EXIT."
-                    );
-                }
             });
         }
 
         // Subscribe to the broadcast channel and yield events as SSE
-        let mut rx = bc_tx.subscribe();
-        loop {
-            match rx.recv().await {
-                Ok(event_str) => {
-                    if let Some((event_type, data)) = event_str.split_once(':') {
-                        yield Ok(Event::default().event(event_type).data(data));
-                        if event_type == "done" {
-                            break;
-                        }
-                    }
-                }
-                Err(broadcast::error::RecvError::Lagged(n)) => {
-                    tracing::warn!(skipped = n, "agent chat broadcast subscriber lagged");
-                    continue;
-                }
-                Err(broadcast::error::RecvError::Closed) => {
-                    break;
-                }
-            }
+        let turn = TurnWatch {
+            sessions: sessions_ref.clone(),
+            agent_key: key_for_stream.clone(),
+            session_id: session_id_for_stream.clone(),
+        };
+        let mut relay = std::pin::pin!(relay_turn_events(bc_tx.subscribe(), idle_timeout, 0, turn));
+        while let Some(event) = relay.next().await {
+            yield event;
         }
     };
 
-    let boxed: BoxSseStream = Box::pin(stream);
-    Ok(Sse::new(boxed).keep_alive(KeepAlive::new().interval(std::time::Duration::from_secs(15))))
+    let boxed: BoxSseStream = Box::pin(with_warnings(warnings, futures::stream::iter(budget_event.map(Ok)).chain(stream)));
+    Ok(Sse::new(boxed).keep_alive(keep_alive))
 }
 
 /// Parse a raw Claude stdout line into a list of (event_type, data_json) pairs
 /// for broadcasting. Same parsing logic as the old inline read loop.
-fn parse_claude_line_to_sse_events(line: &str) -> Vec<(String, String)> {
+///
+/// Lines go through `assembler` so a JSON object split across reads is
+/// buffered until complete, and malformed JSON is dropped rather than being
+/// shown to the user as chat text.
+fn parse_claude_line_to_sse_events(
+    assembler: &mut JsonLineAssembler,
+    line: &str,
+) -> Vec<(String, String)> {
     let mut events = Vec::new();
 
-    if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(line) {
-        let event_type = json_val.get("type").and_then(|v| v.as_str()).unwrap_or("unknown");
+    let json_val = match assembler.push(line) {
+        ParsedLine::Json(val) => val,
+        ParsedLine::Text(text) => {
+            events.push((
+                "text".to_string(),
+                serde_json::to_string(&json!({"text": text})).unwrap(),
+            ));
+            return events;
+        }
+        ParsedLine::Pending => return events,
+        ParsedLine::Discarded(raw) => {
+            tracing::warn!(len = raw.len(), "dropping malformed JSON line from claude stdout");
+            return events;
+        }
+    };
+    let event_type = json_val.get("type").and_then(|v| v.as_str()).unwrap_or("unknown");
 
-        match event_type {
-            "system" => {
-                // Skip system events on resume
-            }
-            "content_block_delta" => {
-                if let Some(delta) = json_val.get("delta") {
-                    let delta_type = delta.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                    if delta_type == "text_delta" {
-                        let text = delta.get("text").and_then(|v| v.as_str()).unwrap_or("");
-                        if !text.is_empty() {
-                            events.push((
-                                "text".to_string(),
-                                serde_json::to_string(&json!({"text": text})).unwrap(),
-                            ));
-                        }
-                    }
-                }
-            }
-            "content_block_start" => {
-                if let Some(content_block) = json_val.get("content_block") {
-                    let block_type = content_block.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                    if block_type == "tool_use" {
-                        let tool = content_block.get("name").and_then(|v| v.as_str()).unwrap_or("?");
+    match event_type {
+        "system" => {
+            // Skip system events on resume
+        }
+        "content_block_delta" => {
+            if let Some(delta) = json_val.get("delta") {
+                let delta_type = delta.get("type").and_then(|v| v.as_str()).unwrap_or("");
+                if delta_type == "text_delta" {
+                    let text = delta.get("text").and_then(|v| v.as_str()).unwrap_or("");
+                    if !text.is_empty() {
                         events.push((
-                            "tool_use".to_string(),
-                            serde_json::to_string(&json!({"tool": tool, "input": ""})).unwrap(),
+                            "text".to_string(),
+                            serde_json::to_string(&json!({"text": text})).unwrap(),
                         ));
                     }
                 }
             }
-            "assistant" => {
-                if let Some(content) = json_val.get("message").and_then(|m| m.get("content")).and_then(|c| c.as_array()) {
-                    for block in content {
-                        let block_type = block.get("type").and_then(|v| v.as_str()).unwrap_or("");
-                        match block_type {
-                            "tool_use" => {
-                                let tool = block.get("name").and_then(|v| v.as_str()).unwrap_or("?");
-                                let input = block.get("input").map(|v| {
-                                    if v.is_string() {
-                                        v.as_str().unwrap_or("").to_string()
-                                    } else {
-                                        serde_json::to_string(v).unwrap_or_default()
-                                    }
-                                }).unwrap_or_default();
-                                events.push((
-                                    "tool_use".to_string(),
-                                    serde_json::to_string(&json!({"tool": tool, "input": input})).unwrap(),
-                                ));
-                            }
-                            "tool_result" => {
-                                let result_content = block.get("content").and_then(|v| v.as_str()).unwrap_or("");
-                                events.push((
-                                    "tool_result".to_string(),
-                                    serde_json::to_string(&json!({"content": result_content})).unwrap(),
-                                ));
-                            }
-                            _ => {}
+        }
+        "content_block_start" => {
+            if let Some(content_block) = json_val.get("content_block") {
+                let block_type = content_block.get("type").and_then(|v| v.as_str()).unwrap_or("");
+                if block_type == "tool_use" {
+                    let tool = content_block.get("name").and_then(|v| v.as_str()).unwrap_or("?");
+                    events.push((
+                        "tool_use".to_string(),
+                        serde_json::to_string(&json!({"tool": tool, "input": ""})).unwrap(),
+                    ));
+                }
+            }
+        }
+        "assistant" => {
+            if let Some(content) = json_val.get("message").and_then(|m| m.get("content")).and_then(|c| c.as_array()) {
+                for block in content {
+                    let block_type = block.get("type").and_then(|v| v.as_str()).unwrap_or("");
+                    match block_type {
+                        "tool_use" => {
+                            let tool = block.get("name").and_then(|v| v.as_str()).unwrap_or("?");
+                            let input = block.get("input").map(|v| {
+                                if v.is_string() {
+                                    v.as_str().unwrap_or("").to_string()
+                                } else {
+                                    serde_json::to_string(v).unwrap_or_default()
+                                }
+                            }).unwrap_or_default();
+                            events.push((
+                                "tool_use".to_string(),
+                                serde_json::to_string(&json!({"tool": tool, "input": input})).unwrap(),
+                            ));
+                        }
+                        "tool_result" => {
+                            let result_content = block.get("content").and_then(|v| v.as_str()).unwrap_or("");
+                            events.push((
+                                "tool_result".to_string(),
+                                serde_json::to_string(&json!({"content": result_content})).unwrap(),
+                            ));
                         }
+                        _ => {}
                     }
                 }
             }
-            "result" => {
-                let cost = json_val.get("total_cost_usd").and_then(|v| v.as_f64()).unwrap_or(0.0);
-                let turns = json_val.get("num_turns").and_then(|v| v.as_u64()).unwrap_or(0);
-                let result_text = json_val.get("result").and_then(|v| v.as_str()).unwrap_or("");
-                events.push((
-                    "result".to_string(),
-                    serde_json::to_string(&json!({"text": result_text, "cost": cost, "turns": turns})).unwrap(),
-                ));
-            }
-            _ => {}
         }
-    } else {
-        events.push((
-            "text".to_string(),
-            serde_json::to_string(&json!({"text": line})).unwrap(),
-        ));
+        "result" => {
+            let cost = json_val.get("total_cost_usd").and_then(|v| v.as_f64()).unwrap_or(0.0);
+            let turns = json_val.get("num_turns").and_then(|v| v.as_u64()).unwrap_or(0);
+            let result_text = json_val.get("result").and_then(|v| v.as_str()).unwrap_or("");
+            events.push((
+                "result".to_string(),
+                serde_json::to_string(&json!({"text": result_text, "cost": cost, "turns": turns})).unwrap(),
+            ));
+        }
+        _ => {}
     }
 
     events
@@ -1792,6 +2489,8 @@ pub(crate) async fn stream_agent_chat(
 
     let session_streams = state.session_streams.clone();
     let chat_event_buffers = state.chat_event_buffers.clone();
+    let keep_alive = state.sse_keep_alive();
+    let idle_timeout = state.sse.idle_timeout;
 
     let stream = async_stream::stream! {
         // 1. Replay buffered events (catch-up)
@@ -1838,38 +2537,20 @@ pub(crate) async fn stream_agent_chat(
                 streams.get(&proc_key).map(|tx| tx.subscribe())
             };
 
-            if let Some(mut rx) = rx {
+            if let Some(rx) = rx {
                 tracing::info!(
                     proc_key = %proc_key,
                     "[RECONNECT-DEBUG] Subscribed to broadcast, starting live relay"
                 );
                 // Skip events we already replayed from the buffer
-                let replay_count = buffered_events.len();
-                let mut skipped = 0;
-
-                loop {
-                    match rx.recv().await {
-                        Ok(event_str) => {
-                            // Skip events that were in the buffer at replay time
-                            if skipped < replay_count {
-                                skipped += 1;
-                                continue;
-                            }
-                            if let Some((event_type, data)) = event_str.split_once(':') {
-                                yield Ok(Event::default().event(event_type).data(data));
-                                if event_type == "done" {
-                                    break;
-                                }
-                            }
-                        }
-                        Err(broadcast::error::RecvError::Lagged(n)) => {
-                            tracing::warn!(skipped = n, "agent chat reconnect subscriber lagged");
-                            continue;
-                        }
-                        Err(broadcast::error::RecvError::Closed) => {
-                            break;
-                        }
-                    }
+                let turn = TurnWatch {
+                    sessions: state.interact_sessions.clone(),
+                    agent_key: key.clone(),
+                    session_id: session_id.clone(),
+                };
+                let mut relay = std::pin::pin!(relay_turn_events(rx, idle_timeout, buffered_events.len(), turn));
+                while let Some(event) = relay.next().await {
+                    yield event;
                 }
             } else {
                 // No broadcast channel — session is not actively streaming.
@@ -1898,22 +2579,26 @@ pub(crate) async fn stream_agent_chat(
         }
     };
 
-    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(std::time::Duration::from_secs(15))))
+    Ok(Sse::new(stream).keep_alive(keep_alive))
 }
 
 // ---------------------------------------------------------------------------
 // Flow-run session streaming endpoints
 // ---------------------------------------------------------------------------
 
-/// GET /agents/{id}/sessions/{session_id}/stream — SSE stream for flow-run session
+/// How often a session log stream checks for a busy session's broadcaster.
+const SESSION_STREAM_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// GET /agents/{id}/sessions/{session_id}/stream — SSE stream of a session's log
 pub(crate) async fn stream_session_log(
     State(state): State<AppState>,
     Path((id, session_id)): Path<(String, String)>,
 ) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, Json<Value>)> {
     let key = agent_key(&id);
 
-    // Verify the session exists and is a flow_run session
-    {
+    // Verify the session exists. Flow-run sessions broadcast under their
+    // session id, interactive turns under the process key.
+    let stream_key = {
         let sessions = state.interact_sessions.read().await;
         let flow_sessions = sessions.get(&key).ok_or_else(|| {
             (StatusCode::NOT_FOUND, Json(json!({ "error": "no sessions for this agent" })))
@@ -1921,19 +2606,19 @@ pub(crate) async fn stream_session_log(
         let session = flow_sessions.get_session(&session_id).ok_or_else(|| {
             (StatusCode::NOT_FOUND, Json(json!({ "error": "session not found" })))
         })?;
-        if session.kind != "flow_run" {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(json!({ "error": "session is not a flow_run session" })),
-            ));
+        if session.kind == "flow_run" {
+            session_id.clone()
+        } else {
+            process_key(&id, &session_id)
         }
-    }
+    };
 
-    let logs_dir = state.data_dir.join("session_logs");
-    let log_path = logs_dir.join(format!("{session_id}.jsonl"));
+    let log_path = session_log::log_path(&state.data_dir, &session_id);
     let session_streams = state.session_streams.clone();
     let interact_sessions = state.interact_sessions.clone();
     let agent_key_owned = key;
+    let keep_alive = state.sse_keep_alive();
+    let idle_timeout = state.sse.idle_timeout;
 
     let stream = async_stream::stream! {
         // 1. Replay existing lines from JSONL file (catch-up)
@@ -1947,34 +2632,42 @@ pub(crate) async fn stream_session_log(
             }
         }
 
-        // 2. Subscribe to broadcast for live lines (if session is still busy)
-        let is_busy = {
-            let sessions = interact_sessions.read().await;
-            sessions.get(&agent_key_owned)
-                .and_then(|fs| fs.get_session(&session_id))
-                .map(|s| s.busy)
-                .unwrap_or(false)
-        };
+        // 2. Follow live lines while the session is busy. `chat()` marks the
+        // session busy before its broadcaster exists, so wait for it to appear.
+        let mut rx = None;
+        while session_still_busy(&interact_sessions, &agent_key_owned, &session_id).await {
+            if let Some(tx) = session_streams.lock().await.get(&stream_key) {
+                rx = Some(tx.subscribe());
+                break;
+            }
+            tokio::time::sleep(SESSION_STREAM_POLL_INTERVAL).await;
+        }
 
-        if is_busy {
-            let mut rx = {
-                let streams = session_streams.lock().await;
-                streams.get(&session_id).map(|tx| tx.subscribe())
+        if let Some(ref mut rx) = rx {
+            let turn = TurnWatch {
+                sessions: interact_sessions.clone(),
+                agent_key: agent_key_owned.clone(),
+                session_id: session_id.clone(),
             };
-
-            if let Some(ref mut rx) = rx {
-                loop {
-                    match rx.recv().await {
-                        Ok(line) => {
-                            yield Ok(Event::default().event("line").data(line));
-                        }
-                        Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                            tracing::warn!(session_id = %session_id, skipped = n, "session stream subscriber lagged");
+            loop {
+                match recv_with_idle_timeout(rx, idle_timeout).await {
+                    IdleRecv::Event(line) => {
+                        yield Ok(Event::default().event("line").data(line));
+                    }
+                    IdleRecv::Lagged(n) => {
+                        tracing::warn!(session_id = %session_id, skipped = n, "session stream subscriber lagged");
+                        continue;
+                    }
+                    IdleRecv::Closed => {
+                        break;
+                    }
+                    IdleRecv::Idle => {
+                        // A quiet stretch mid-turn (a long tool call) isn't the end
+                        if turn.in_progress().await {
                             continue;
                         }
-                        Err(tokio::sync::broadcast::error::RecvError::Closed) => {
-                            break;
-                        }
+                        tracing::warn!(session_id = %session_id, "session stream idle, closing");
+                        break;
                     }
                 }
             }
@@ -1985,7 +2678,7 @@ pub(crate) async fn stream_session_log(
         ));
     };
 
-    Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(std::time::Duration::from_secs(15))))
+    Ok(Sse::new(stream).keep_alive(keep_alive))
 }
 
 /// GET /agents/{id}/sessions/{session_id}/log — full JSONL log as JSON array
@@ -1993,8 +2686,7 @@ pub(crate) async fn get_session_log(
     State(state): State<AppState>,
     Path((_id, session_id)): Path<(String, String)>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
-    let logs_dir = state.data_dir.join("session_logs");
-    let log_path = logs_dir.join(format!("{session_id}.jsonl"));
+    let log_path = session_log::log_path(&state.data_dir, &session_id);
 
     if !log_path.exists() {
         return Ok(Json(json!({ "lines": [] })));
@@ -2008,6 +2700,70 @@ pub(crate) async fn get_session_log(
     Ok(Json(json!({ "lines": lines })))
 }
 
+#[derive(Deserialize)]
+pub(crate) struct ExportSessionQuery {
+    /// `markdown` (default) or `json`.
+    format: Option<String>,
+}
+
+/// GET /agents/{id}/sessions/{session_id}/export — the session's
+/// conversation, rebuilt from its log, as markdown or JSON
+pub(crate) async fn export_session(
+    State(state): State<AppState>,
+    Path((id, session_id)): Path<(String, String)>,
+    Query(query): Query<ExportSessionQuery>,
+) -> Result<axum::response::Response, (StatusCode, Json<Value>)> {
+    use axum::response::IntoResponse;
+
+    let as_json = match query.format.as_deref().unwrap_or("markdown") {
+        "markdown" | "md" => false,
+        "json" => true,
+        other => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": format!("unknown format '{other}', expected markdown or json") })),
+            ));
+        }
+    };
+
+    // Only sessions of this agent, which also keeps `session_id` a known id
+    // before it becomes part of a path
+    let summary = {
+        let sessions = state.interact_sessions.read().await;
+        let session = sessions
+            .get(&agent_key(&id))
+            .and_then(|fs| fs.get_session(&session_id))
+            .ok_or_else(|| (StatusCode::NOT_FOUND, Json(json!({ "error": "session not found" }))))?;
+        session.summary.clone()
+    };
+
+    let log_path = session_log::log_path(&state.data_dir, &session_id);
+    let content = match tokio::fs::read_to_string(&log_path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": format!("failed to read log: {e}") })),
+            ));
+        }
+    };
+    let entries = transcript::from_log(&content);
+
+    if as_json {
+        return Ok(Json(json!({ "session_id": session_id, "summary": summary, "entries": entries })).into_response());
+    }
+    let title = if summary.is_empty() { format!("Session {session_id}") } else { summary };
+    let headers = [
+        (axum::http::header::CONTENT_TYPE, "text/markdown; charset=utf-8".to_string()),
+        (
+            axum::http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"session-{session_id}.md\""),
+        ),
+    ];
+    Ok((headers, transcript::to_markdown(&title, &entries)).into_response())
+}
+
 // ---------------------------------------------------------------------------
 // Skill context generation helpers
 // ---------------------------------------------------------------------------
@@ -2016,7 +2772,13 @@ pub(crate) async fn get_session_log(
 fn node_config_summary(node: &Node) -> String {
     match node.kind.as_str() {
         "cron" => {
-            let schedule = node.config.get("schedule").and_then(|v| v.as_str()).unwrap_or("?");
+            let schedule = match node.config.get("schedule") {
+                Some(Value::Array(items)) => {
+                    items.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(", ")
+                }
+                Some(v) => v.as_str().unwrap_or("?").to_string(),
+                None => "?".to_string(),
+            };
             format!("schedule: {schedule}")
         }
         "rss" => {
@@ -2024,6 +2786,16 @@ fn node_config_summary(node: &Node) -> String {
             let limit = node.config.get("limit").and_then(|v| v.as_u64()).unwrap_or(10);
             format!("url: {url}, limit: {limit}")
         }
+        "hackernews" => {
+            let story_type = node.config.get("story_type").and_then(|v| v.as_str()).unwrap_or("top");
+            let limit = node.config.get("limit").and_then(|v| v.as_u64()).unwrap_or(10);
+            format!("{story_type} stories, limit: {limit}")
+        }
+        "reddit" => {
+            let subreddit = node.config.get("subreddit").and_then(|v| v.as_str()).unwrap_or("?");
+            let sort = node.config.get("sort").and_then(|v| v.as_str()).unwrap_or("hot");
+            format!("r/{}, {sort}", subreddit.trim_start_matches("r/"))
+        }
         "web-scrape" | "web-scraper" => {
             let url = node.config.get("url").and_then(|v| v.as_str()).unwrap_or("?");
             format!("url: {url}")
@@ -2034,6 +2806,13 @@ fn node_config_summary(node: &Node) -> String {
                 .unwrap_or_else(|| "?".into());
             format!("repos: {repos}")
         }
+        "github-issues" => {
+            let repos = node.config.get("repos").and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(", "))
+                .unwrap_or_else(|| "?".into());
+            let state = node.config.get("state").and_then(|v| v.as_str()).unwrap_or("open");
+            format!("repos: {repos} ({state})")
+        }
         "github-pr" => {
             let poll = node.config.get("poll_interval").and_then(|v| v.as_u64()).unwrap_or(60);
             format!("poll: {poll}s")
@@ -2041,7 +2820,10 @@ fn node_config_summary(node: &Node) -> String {
         "market-data" => "(fetches BTC/ETH, Fear & Greed, S&P 500)".into(),
         "claude-code" => {
             let prompt = node.config.get("prompt").and_then(|v| v.as_str()).unwrap_or("(inline)");
-            format!("prompt: {prompt}")
+            match crate::agents::node_model(&node.config) {
+                Some(model) => format!("prompt: {prompt}, model: {model}"),
+                None => format!("prompt: {prompt}"),
+            }
         }
         "slack" => {
             let channel = node.config.get("channel").and_then(|v| v.as_str()).unwrap_or("?");
@@ -2052,9 +2834,44 @@ fn node_config_summary(node: &Node) -> String {
             };
             format!("{method}, channel: {channel}")
         }
+        "discord" => {
+            let env = node.config.get("webhook_url_env").and_then(|v| v.as_str()).unwrap_or("?");
+            format!("webhook: ${env}")
+        }
+        "telegram" => {
+            let chat = match node.config.get("chat_id") {
+                Some(Value::String(id)) => id.clone(),
+                Some(Value::Number(id)) => id.to_string(),
+                _ => "?".to_string(),
+            };
+            format!("chat: {chat}")
+        }
+        "file" => {
+            let path = node.config.get("path").and_then(|v| v.as_str()).unwrap_or("?");
+            format!("path: {path}")
+        }
+        "email" => {
+            let to = match node.config.get("to") {
+                Some(Value::String(to)) => to.clone(),
+                Some(Value::Array(list)) => {
+                    list.iter().filter_map(|a| a.as_str()).collect::<Vec<_>>().join(", ")
+                }
+                _ => "?".to_string(),
+            };
+            format!("to: {to}")
+        }
+        "http-post" => {
+            let url = node.config.get("url").and_then(|v| v.as_str()).unwrap_or("?");
+            format!("POST {url}")
+        }
         "notion" => {
-            let db = node.config.get("database_id").and_then(|v| v.as_str()).unwrap_or("?");
-            format!("database: {db}")
+            match node.config.get("append_to_page").and_then(|v| v.as_str()) {
+                Some(page) if !page.is_empty() => format!("append to page: {page}"),
+                _ => {
+                    let db = node.config.get("database_id").and_then(|v| v.as_str()).unwrap_or("?");
+                    format!("database: {db}")
+                }
+            }
         }
         "manual" => "(triggered manually)".into(),
         "webhook" => {
@@ -2227,3 +3044,363 @@ fn build_workflow_context_md(flow: &Flow, node_id: &str) -> String {
     )
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn session(id: &str) -> InteractSession {
+        InteractSession {
+            session_id: id.to_string(),
+            summary: String::new(),
+            node_id: None,
+            working_dir: ".".to_string(),
+            active_pid: None,
+            busy: false,
+            busy_since: None,
+            message_count: 1,
+            total_cost: 0.0,
+            cost_budget_usd: None,
+            created_at: Utc::now().to_rfc3339(),
+            skills_dir: None,
+            kind: "interactive".to_string(),
+            flow_run: None,
+            worktree_group: None,
+        }
+    }
+
+    fn store() -> HashMap<String, FlowSessions> {
+        let mut all = HashMap::new();
+        for (agent, sid) in [
+            ("a1", "11111111-1111-4111-8111-111111111111"),
+            ("a2", "22222222-2222-4222-8222-222222222222"),
+        ] {
+            all.insert(
+                agent_key(agent),
+                FlowSessions {
+                    flow_name: agent.to_string(),
+                    active_session: sid.to_string(),
+                    sessions: vec![session(sid)],
+                },
+            );
+        }
+        all
+    }
+
+    #[test]
+    fn test_build_user_content_with_attachments() {
+        assert_eq!(build_user_content("hi", &[], &[]), json!("hi"));
+
+        let images = vec![ImageAttachment {
+            media_type: "image/png".to_string(),
+            data: "aW1n".to_string(),
+        }];
+        let attachments = vec![
+            PreparedAttachment {
+                rel_path: ".skills/chat-attachments/spec.pdf".to_string(),
+                media_type: "application/pdf".to_string(),
+                inline_base64: Some("cGRm".to_string()),
+            },
+            PreparedAttachment {
+                rel_path: ".skills/chat-attachments/data.csv".to_string(),
+                media_type: "text/csv".to_string(),
+                inline_base64: None,
+            },
+        ];
+
+        let content = build_user_content("look at these", &images, &attachments);
+        let blocks = content.as_array().unwrap();
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[0], json!({ "type": "text", "text": "look at these" }));
+        assert_eq!(blocks[1]["type"], "image");
+        assert_eq!(blocks[1]["source"]["data"], "aW1n");
+        assert_eq!(blocks[2]["type"], "document");
+        assert_eq!(blocks[2]["source"]["media_type"], "application/pdf");
+        assert_eq!(blocks[3]["type"], "text");
+        assert!(blocks[3]["text"].as_str().unwrap().contains(".skills/chat-attachments/data.csv"));
+
+        // Attachment-only turns skip the empty text block.
+        let content = build_user_content("  ", &[], &attachments[1..]);
+        assert_eq!(content.as_array().unwrap().len(), 1);
+    }
+
+    fn flow_with_nodes() -> Flow {
+        let node = |id: &str, node_type: NodeType| Node {
+            id: id.to_string(),
+            node_type,
+            kind: "k".to_string(),
+            config: json!({}),
+            position: crate::flows::Position { x: 0.0, y: 0.0 },
+            label: id.to_string(),
+        };
+        Flow {
+            id: "flow-1".to_string(),
+            name: "f".to_string(),
+            description: String::new(),
+            enabled: true,
+            nodes: vec![node("src", NodeType::Source), node("exec-1", NodeType::Executor)],
+            edges: vec![],
+            version: 0,
+            max_runtime_secs: None,
+            capture_prompts: true,
+            sandbox_backend: None,
+            cost_cap: None,
+            default_session_budget_usd: None,
+            output_transform: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_check_flow_context_node_membership() {
+        let flow = flow_with_nodes();
+        assert!(check_flow_context(Some(&flow), "flow-1", "exec-1").is_ok());
+
+        let err = check_flow_context(Some(&flow), "flow-1", "exec-typo").unwrap_err();
+        assert!(err.contains("not found"));
+        let err = check_flow_context(Some(&flow), "flow-1", "src").unwrap_err();
+        assert!(err.contains("not an executor"));
+        assert!(check_flow_context(None, "flow-1", "exec-1").is_err());
+    }
+
+    #[test]
+    fn test_check_flow_context_rejects_unsafe_ids() {
+        let flow = flow_with_nodes();
+        for bad in ["../exec-1", "exec/1", "..", "", "exec-1\\x"] {
+            let err = check_flow_context(Some(&flow), "flow-1", bad).unwrap_err();
+            assert!(err.contains("invalid node_id"), "{bad:?}: {err}");
+        }
+        let err = check_flow_context(Some(&flow), "../../etc", "exec-1").unwrap_err();
+        assert!(err.contains("invalid flow_id"));
+    }
+
+    #[test]
+    fn test_expired_session_maps_to_dedicated_event() {
+        let sid = "11111111-1111-4111-8111-111111111111";
+        let event = session_expired_event(
+            &format!("No conversation found with session ID: {sid}"),
+            sid,
+            true,
+        )
+        .unwrap();
+        let (event_type, data) = event.split_once(':').unwrap();
+        assert_eq!(event_type, "session_expired");
+        let data: Value = serde_json::from_str(data).unwrap();
+        assert_eq!(data["session_id"], sid);
+        assert_eq!(data["restart"], "auto");
+
+        let prompt = session_expired_event(&format!("Error: No conversation found with session ID: {sid}"), sid, false).unwrap();
+        assert!(prompt.contains(r#""restart":"prompt""#));
+
+        assert!(session_expired_event("Error: rate limit exceeded", sid, true).is_none());
+        // Ordinary replies that happen to mention a missing session don't count
+        assert!(session_expired_event("The session file was not found, so I created it.", sid, true).is_none());
+    }
+
+    #[test]
+    fn test_result_error_messages_only_reads_error_results() {
+        let sid = "11111111-1111-4111-8111-111111111111";
+        let error_result = format!(
+            r#"{{"type":"result","subtype":"error_during_execution","is_error":true,"errors":["No conversation found with session ID: {sid}"]}}"#
+        );
+        let messages = result_error_messages(&error_result);
+        assert!(messages.iter().any(|m| is_session_expired_error(m)));
+
+        let reply = format!(
+            r#"{{"type":"assistant","message":{{"content":[{{"type":"text","text":"No conversation found with session ID: {sid}"}}]}}}}"#
+        );
+        assert!(result_error_messages(&reply).is_empty());
+        let ok_result = format!(r#"{{"type":"result","is_error":false,"result":"No conversation found with session ID: {sid}"}}"#);
+        assert!(result_error_messages(&ok_result).is_empty());
+    }
+
+    #[test]
+    fn test_stale_session_recovery_emits_warning() {
+        let mut s = session("11111111-1111-4111-8111-111111111111");
+        s.busy = true;
+        s.busy_since = Some(Utc::now());
+        s.active_pid = Some(42);
+
+        let warning = recover_stale_session(&mut s);
+        assert!(!s.busy && s.busy_since.is_none() && s.active_pid.is_none());
+
+        let payload: Value = serde_json::from_str(&serde_json::to_string(&warning).unwrap()).unwrap();
+        assert_eq!(payload["code"], StreamWarning::STALE_SESSION_RECOVERED);
+        assert!(payload["message"].as_str().is_some_and(|m| !m.is_empty()));
+        // Sent as `warning`, never `error`.
+        assert!(format!("{:?}", warning.to_event()).contains("warning"));
+    }
+
+    fn busy_turn(busy: bool) -> TurnWatch {
+        let sid = "11111111-1111-4111-8111-111111111111";
+        let mut all = store();
+        let s = all.get_mut(&agent_key("a1")).unwrap().get_session_mut(sid).unwrap();
+        s.busy = busy;
+        // Busy for longer than the stale cutoff, as in a long quiet turn
+        s.busy_since = Some(Utc::now() - chrono::Duration::seconds(3600));
+        TurnWatch {
+            sessions: std::sync::Arc::new(tokio::sync::RwLock::new(all)),
+            agent_key: agent_key("a1"),
+            session_id: sid.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_relay_stays_open_through_a_quiet_turn() {
+        let (tx, rx) = broadcast::channel(16);
+        let idle = std::time::Duration::from_millis(20);
+        let mut relay = std::pin::pin!(relay_turn_events(rx, idle, 1, busy_turn(true)));
+
+        tx.send("text:{\"replayed\":true}".to_string()).unwrap();
+        tx.send("text:{}".to_string()).unwrap();
+        assert!(relay.next().await.is_some());
+
+        // Many idle periods mid-turn: the stream must not end
+        let quiet = tokio::time::timeout(idle * 10, relay.next()).await;
+        assert!(quiet.is_err(), "relay closed mid-turn");
+
+        tx.send("done:{}".to_string()).unwrap();
+        assert!(relay.next().await.is_some());
+        assert!(relay.next().await.is_none());
+
+        // Once the turn is over, going idle closes a relay that never saw done
+        let (_tx, rx) = broadcast::channel::<String>(16);
+        let mut relay = std::pin::pin!(relay_turn_events(rx, idle, 0, busy_turn(false)));
+        let ended = tokio::time::timeout(idle * 10, relay.next()).await;
+        assert!(matches!(ended, Ok(None)));
+    }
+
+    #[test]
+    fn test_busy_past_timeout() {
+        let now = Utc::now();
+        assert!(!busy_past_timeout(Some(now - chrono::Duration::seconds(10)), now));
+        assert!(busy_past_timeout(Some(now - chrono::Duration::seconds(301)), now));
+        assert!(busy_past_timeout(None, now));
+    }
+
+    #[test]
+    fn test_claim_session_id_resumes_own_session() {
+        let all = store();
+        let sid = "11111111-1111-4111-8111-111111111111";
+        assert_eq!(
+            claim_session_id(&all, &agent_key("a1"), sid).unwrap(),
+            SessionClaim::Resume(sid.to_string())
+        );
+        // Case variations still resolve to the stored id.
+        assert_eq!(
+            claim_session_id(&all, &agent_key("a1"), &sid.to_uppercase()).unwrap(),
+            SessionClaim::Resume(sid.to_string())
+        );
+    }
+
+    #[test]
+    fn test_claim_session_id_rejects_hijack() {
+        let all = store();
+        let foreign = "22222222-2222-4222-8222-222222222222";
+        let (status, _) = claim_session_id(&all, &agent_key("a1"), foreign).unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) =
+            claim_session_id(&all, &agent_key("a1"), &foreign.to_uppercase()).unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn test_claim_session_id_fresh_and_malformed() {
+        let all = store();
+        let fresh = "33333333-3333-4333-8333-333333333333";
+        assert_eq!(
+            claim_session_id(&all, &agent_key("a1"), fresh).unwrap(),
+            SessionClaim::Fresh(fresh.to_string())
+        );
+        let (status, _) =
+            claim_session_id(&all, &agent_key("a1"), "--dangerously-skip-permissions").unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_make_summary_multibyte() {
+        assert_eq!(make_summary("  héllo wörld  "), "héllo wörld");
+
+        // 79 two-byte chars then more words: cut on a char, not a byte, boundary
+        let text = format!("{} über alles und mehr", "é".repeat(79));
+        let summary = make_summary(&text);
+        assert_eq!(summary, format!("{}...", "é".repeat(79)));
+
+        // No space to break at: keep the first 80 chars
+        let summary = make_summary(&"日".repeat(100));
+        assert_eq!(summary, format!("{}...", "日".repeat(80)));
+    }
+
+    #[test]
+    fn test_nonzero_exit_without_result_is_an_error() {
+        let mut s = session("11111111-1111-4111-8111-111111111111");
+        s.busy = true;
+        s.busy_since = Some(Utc::now());
+
+        // claude died with exit 1 and never printed a result line
+        let outcome = TurnOutcome::new(false, Some(1));
+        assert_eq!(outcome.exit_code(), Some(1));
+        let event = outcome.error_event().unwrap();
+        let (event_type, data) = event.split_once(':').unwrap();
+        assert_eq!(event_type, "error");
+        let data: Value = serde_json::from_str(data).unwrap();
+        assert_eq!(data["exit_code"], 1);
+        assert!(data["message"].as_str().unwrap().contains("code 1"));
+
+        outcome.record(&mut s, 0.42, false);
+        assert!(!s.busy && s.busy_since.is_none());
+        assert_eq!(s.message_count, 1);
+        assert_eq!(s.total_cost, 0.0);
+
+        // A completed turn still counts
+        let outcome = TurnOutcome::new(true, None);
+        assert!(outcome.error_event().is_none());
+        outcome.record(&mut s, 0.42, false);
+        assert_eq!((s.message_count, s.total_cost), (2, 0.42));
+    }
+
+    #[test]
+    fn test_turn_that_crosses_budget_blocks_the_next_one() {
+        let mut s = session("11111111-1111-4111-8111-111111111111");
+        s.cost_budget_usd = Some(1.0);
+        s.total_cost = 0.5;
+        assert_eq!(check_budget(s.total_cost, s.cost_budget_usd), BudgetCheck::Within);
+
+        // Allowed to start under budget, then a single expensive turn lands past it
+        TurnOutcome::Completed.record(&mut s, 0.8, false);
+        assert_eq!(
+            check_budget(s.total_cost, s.cost_budget_usd),
+            BudgetCheck::Exhausted { spent: 1.3, budget: 1.0 }
+        );
+
+        // Exactly at the budget is spent; the last 10% warns
+        assert!(matches!(check_budget(1.0, Some(1.0)), BudgetCheck::Exhausted { .. }));
+        assert!(matches!(check_budget(0.95, Some(1.0)), BudgetCheck::Near { .. }));
+        assert_eq!(check_budget(0.85, Some(1.0)), BudgetCheck::Within);
+        assert_eq!(check_budget(1_000.0, None), BudgetCheck::Within);
+    }
+
+    #[test]
+    fn test_changed_working_dir_is_used_by_next_spawn() {
+        let dir = tempfile::tempdir().unwrap();
+        let new_dir = dir.path().to_string_lossy().to_string();
+        let mut s = session("11111111-1111-4111-8111-111111111111");
+        s.active_pid = Some(4242);
+
+        assert!(set_session_working_dir(&mut s, new_dir.clone()));
+        assert_eq!(s.active_pid, None);
+        // Setting the same dir again leaves a live process alone
+        assert!(!set_session_working_dir(&mut s, new_dir.clone()));
+
+        let cmd = claude_command(&[], &s.working_dir);
+        assert_eq!(cmd.as_std().get_current_dir(), Some(dir.path()));
+    }
+
+    #[test]
+    fn test_kill_pid_ignores_bogus_pid() {
+        // Above any pid_max, so there's nothing to signal
+        kill_pid(999_999_999);
+    }
+}
@@ -8,7 +8,7 @@ use uuid::Uuid;
 
 use crate::api::AppState;
 use crate::api::changes::{ChangeType, ResourceChangeEvent, ResourceType};
-use crate::agents::{Agent, AgentHooks, SubAgents, STUDIO_ASSISTANT_ID};
+use crate::agents::{Agent, AgentHooks, PermissionMode, SubAgents, STUDIO_ASSISTANT_ID};
 
 pub(crate) async fn list_agents(State(state): State<AppState>) -> Json<Value> {
     let agents = state.agent_repo.list().await;
@@ -21,6 +21,7 @@ pub(crate) async fn list_agents(State(state): State<AppState>) -> Json<Value> {
                 "name": a.name,
                 "description": a.description,
                 "permissions": a.permissions,
+                "permission_mode": a.permission_mode,
                 "hooks": a.hooks,
                 "subagent_only": a.subagent_only,
                 "subagent_count": a.subagents.len(),
@@ -57,6 +58,10 @@ pub(crate) struct CreateAgentRequest {
     #[serde(default)]
     permissions: Vec<String>,
     #[serde(default)]
+    permission_mode: Option<PermissionMode>,
+    #[serde(default)]
+    disallowed_tools: Vec<String>,
+    #[serde(default)]
     append_system_prompt: Option<String>,
     #[serde(default)]
     working_dir: Option<String>,
@@ -77,9 +82,13 @@ pub(crate) async fn create_agent(
         .description(body.description)
         .prompt(body.prompt)
         .permissions(body.permissions)
+        .disallowed_tools(body.disallowed_tools)
         .hooks(body.hooks)
         .subagents(body.subagents)
         .subagent_only(body.subagent_only);
+    if let Some(m) = body.permission_mode {
+        builder = builder.permission_mode(m);
+    }
     if let Some(s) = body.append_system_prompt {
         builder = builder.append_system_prompt(s);
     }
@@ -117,6 +126,10 @@ pub(crate) struct UpdateAgentRequest {
     #[serde(default)]
     permissions: Option<Vec<String>>,
     #[serde(default)]
+    permission_mode: Option<Option<PermissionMode>>,
+    #[serde(default)]
+    disallowed_tools: Option<Vec<String>>,
+    #[serde(default)]
     append_system_prompt: Option<Option<String>>,
     #[serde(default)]
     working_dir: Option<Option<String>>,
@@ -152,6 +165,12 @@ pub(crate) async fn update_agent(
     if let Some(permissions) = body.permissions {
         agent.permissions = permissions;
     }
+    if let Some(permission_mode) = body.permission_mode {
+        agent.permission_mode = permission_mode;
+    }
+    if let Some(disallowed_tools) = body.disallowed_tools {
+        agent.disallowed_tools = disallowed_tools;
+    }
     if let Some(append_system_prompt) = body.append_system_prompt {
         agent.append_system_prompt = append_system_prompt;
     }
@@ -1,6 +1,10 @@
+pub mod attachments;
 pub mod chat;
 pub mod handlers;
+pub mod system_prompt;
+pub mod transcript;
 
+use axum::extract::DefaultBodyLimit;
 use axum::routing::{delete, get, post};
 use axum::Router;
 
@@ -23,7 +27,7 @@ pub fn router() -> Router<AppState> {
         )
         .route(
             "/agents/{id}/sessions/{session_id}",
-            delete(chat::delete_session),
+            delete(chat::delete_session).patch(chat::update_session),
         )
         .route(
             "/agents/{id}/sessions/{session_id}/status",
@@ -53,7 +57,14 @@ pub fn router() -> Router<AppState> {
             "/agents/{id}/sessions/{session_id}/log",
             get(chat::get_session_log),
         )
-        .route("/agents/{id}/chat", post(chat::chat))
+        .route(
+            "/agents/{id}/sessions/{session_id}/export",
+            get(chat::export_session),
+        )
+        .route(
+            "/agents/{id}/chat",
+            post(chat::chat).layer(DefaultBodyLimit::max(attachments::MAX_CHAT_BODY_BYTES)),
+        )
         .route("/agents/{id}/chat/stop", post(chat::stop_chat))
         // File explorer (read-only)
         .route(
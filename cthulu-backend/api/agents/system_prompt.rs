@@ -0,0 +1,129 @@
+//! System prompts for agent chat sessions.
+//!
+//! Each prompt is a template rendered with [`render_prompt`]. Operators can
+//! override one by dropping a file under `<data_dir>/system_prompts/`; the
+//! built-in text below is used when the file is absent or empty.
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::tasks::context::render_prompt;
+
+/// Where override templates live, relative to the data dir.
+const TEMPLATES_SUBDIR: &str = "system_prompts";
+
+/// Agent chatting with flow context (an executor node); scoped to `.skills/`.
+/// Vars: `agent_name`, `working_dir`, `flow_id`, `flow_name`, `node_id`.
+const FLOW_AGENT_DEFAULT: &str = "You are \"{{agent_name}}\", an AI agent.\n\n\
+     CRITICAL RULES — FOLLOW EXACTLY:\n\n\
+     1. FIRST: Read .skills/Skill.md and .skills/AGENT.md — these contain ALL your context.\n\
+     2. SCOPE LOCKED: Do NOT explore, search, or read ANY files outside .skills/ directory.\n\
+        No find, no grep, no ls, no bash exploration, no reading source code, no git commands.\n\
+        Your .skills/ files already contain your workflow context, pipeline info, and rules.\n\
+     3. ANSWER FROM CONTEXT: When asked about the workflow, answer from .skills/Skill.md\n\
+        and .skills/workflow.json — do NOT go looking for more information.\n\
+     4. IF THE USER WANTS MORE: Only if the user explicitly says \"read the codebase\",\n\
+        \"explore the project\", or similar — THEN you may expand scope. Not before.\n\
+     5. BE EFFICIENT: Short answers. No preamble. No filler. Batch tool calls.\n\
+        Read .skills/AGENT.md for full efficiency rules.\n\n\
+     Files in .skills/:\n\
+     - .skills/AGENT.md — agent rules, scope boundaries, efficiency rules\n\
+     - .skills/Skill.md — your position in the pipeline and workflow context\n\
+     - .skills/workflow.json — full workflow definition";
+
+/// Agent chatting on its own. Vars: `agent_name`, `working_dir`.
+const STANDALONE_AGENT_DEFAULT: &str = "You are \"{{agent_name}}\", an AI assistant. \
+     Your working directory is: {{working_dir}}\n\
+     Be efficient: short answers, no preamble, batch tool calls when possible.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SystemPromptKind {
+    FlowAgent,
+    StandaloneAgent,
+}
+
+impl SystemPromptKind {
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::FlowAgent => "flow_agent.md",
+            Self::StandaloneAgent => "standalone_agent.md",
+        }
+    }
+
+    fn default_template(self) -> &'static str {
+        match self {
+            Self::FlowAgent => FLOW_AGENT_DEFAULT,
+            Self::StandaloneAgent => STANDALONE_AGENT_DEFAULT,
+        }
+    }
+}
+
+/// The override template for `kind` under `data_dir`, else the built-in one.
+fn load_template(data_dir: &Path, kind: SystemPromptKind) -> String {
+    let path = data_dir.join(TEMPLATES_SUBDIR).join(kind.file_name());
+    match std::fs::read_to_string(&path) {
+        Ok(custom) if !custom.trim().is_empty() => custom,
+        Ok(_) => kind.default_template().to_string(),
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(path = %path.display(), error = %e, "failed to read system prompt template");
+            }
+            kind.default_template().to_string()
+        }
+    }
+}
+
+/// Render the system prompt for `kind` with `vars`.
+pub(crate) fn render_system_prompt(
+    data_dir: &Path,
+    kind: SystemPromptKind,
+    vars: &HashMap<String, String>,
+) -> String {
+    render_prompt(&load_template(data_dir, kind), vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> HashMap<String, String> {
+        HashMap::from([
+            ("agent_name".to_string(), "Scout".to_string()),
+            ("working_dir".to_string(), "/work/repo".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_default_template_used_when_absent() {
+        let dir = tempfile::tempdir().unwrap();
+        let prompt = render_system_prompt(dir.path(), SystemPromptKind::StandaloneAgent, &vars());
+        assert_eq!(
+            prompt,
+            "You are \"Scout\", an AI assistant. Your working directory is: /work/repo\n\
+             Be efficient: short answers, no preamble, batch tool calls when possible."
+        );
+
+        let prompt = render_system_prompt(dir.path(), SystemPromptKind::FlowAgent, &vars());
+        assert!(prompt.starts_with("You are \"Scout\", an AI agent."));
+        assert!(!prompt.contains("{{"));
+    }
+
+    #[test]
+    fn test_custom_template_overrides_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let templates = dir.path().join(TEMPLATES_SUBDIR);
+        std::fs::create_dir_all(&templates).unwrap();
+        std::fs::write(
+            templates.join("standalone_agent.md"),
+            "{{agent_name}} follows house style in {{working_dir}}.",
+        )
+        .unwrap();
+        // An empty override falls back to the default.
+        std::fs::write(templates.join("flow_agent.md"), "  \n").unwrap();
+
+        let prompt = render_system_prompt(dir.path(), SystemPromptKind::StandaloneAgent, &vars());
+        assert_eq!(prompt, "Scout follows house style in /work/repo.");
+
+        let prompt = render_system_prompt(dir.path(), SystemPromptKind::FlowAgent, &vars());
+        assert!(prompt.starts_with("You are \"Scout\", an AI agent."));
+    }
+}
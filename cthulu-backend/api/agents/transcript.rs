@@ -0,0 +1,217 @@
+//! Rebuild a session's conversation from its `session_logs/{sid}.jsonl` log.
+//!
+//! Interactive sessions log the chat stream's events as `event_type:{json}`
+//! lines; flow-run sessions log claude's raw stream-json objects. Both are
+//! read into the same turn-by-turn transcript.
+
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Entry {
+    User { text: String },
+    Assistant { text: String },
+    ToolCall { tool: String, input: String },
+    ToolResult { content: String },
+}
+
+/// Parse a whole session log. Lines that aren't part of the conversation
+/// (errors, git snapshots, `done` markers, system events) are skipped.
+pub fn from_log(content: &str) -> Vec<Entry> {
+    let mut transcript = Transcript::default();
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if line.starts_with('{') {
+            if let Ok(event) = serde_json::from_str::<Value>(line) {
+                transcript.push_raw(&event);
+            }
+        } else if let Some((event_type, data)) = line.split_once(':')
+            && let Ok(data) = serde_json::from_str::<Value>(data)
+        {
+            transcript.push_event(event_type, &data);
+        }
+    }
+    transcript.entries
+}
+
+#[derive(Default)]
+struct Transcript {
+    entries: Vec<Entry>,
+    /// Whether the current turn has any assistant text yet.
+    turn_has_text: bool,
+}
+
+impl Transcript {
+    /// Streamed text deltas join the assistant entry they continue.
+    fn push_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.turn_has_text = true;
+        if let Some(Entry::Assistant { text: current }) = self.entries.last_mut() {
+            current.push_str(text);
+        } else {
+            self.entries.push(Entry::Assistant { text: text.to_string() });
+        }
+    }
+
+    fn push_user(&mut self, text: &str) {
+        self.turn_has_text = false;
+        self.entries.push(Entry::User { text: text.to_string() });
+    }
+
+    /// The final result repeats the turn's text; only used when none streamed.
+    fn push_result(&mut self, text: &str) {
+        if !self.turn_has_text {
+            self.push_text(text);
+        }
+    }
+
+    /// An interactive log line, as broadcast to the chat stream.
+    fn push_event(&mut self, event_type: &str, data: &Value) {
+        let str_of = |key: &str| data[key].as_str().unwrap_or_default();
+        match event_type {
+            "user" => self.push_user(str_of("text")),
+            "text" => self.push_text(str_of("text")),
+            "tool_use" => self.entries.push(Entry::ToolCall {
+                tool: str_of("tool").to_string(),
+                input: str_of("input").to_string(),
+            }),
+            "tool_result" => self.entries.push(Entry::ToolResult { content: str_of("content").to_string() }),
+            "result" => self.push_result(str_of("text")),
+            _ => {}
+        }
+    }
+
+    /// A flow-run log line: one of claude's stream-json events.
+    fn push_raw(&mut self, event: &Value) {
+        let blocks = || event["message"]["content"].as_array().cloned().unwrap_or_default();
+        match event["type"].as_str().unwrap_or_default() {
+            "assistant" => {
+                for block in blocks() {
+                    match block["type"].as_str().unwrap_or_default() {
+                        "text" => self.push_text(block["text"].as_str().unwrap_or_default()),
+                        "tool_use" => self.entries.push(Entry::ToolCall {
+                            tool: block["name"].as_str().unwrap_or("?").to_string(),
+                            input: match &block["input"] {
+                                Value::String(s) => s.clone(),
+                                Value::Null => String::new(),
+                                other => other.to_string(),
+                            },
+                        }),
+                        _ => {}
+                    }
+                }
+            }
+            "user" => {
+                // Tool results come back as user messages
+                for block in blocks() {
+                    if block["type"] == "tool_result" {
+                        self.entries.push(Entry::ToolResult { content: tool_result_text(&block["content"]) });
+                    }
+                }
+            }
+            "result" => self.push_result(event["result"].as_str().unwrap_or_default()),
+            _ => {}
+        }
+    }
+}
+
+/// A tool result's content: a string, or text blocks.
+fn tool_result_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Render a transcript as markdown, tool calls and results in fenced blocks.
+pub fn to_markdown(title: &str, entries: &[Entry]) -> String {
+    let mut md = format!("# {title}\n");
+    for entry in entries {
+        match entry {
+            Entry::User { text } => md.push_str(&format!("\n## User\n\n{}\n", text.trim())),
+            Entry::Assistant { text } => md.push_str(&format!("\n## Assistant\n\n{}\n", text.trim())),
+            Entry::ToolCall { tool, input } => {
+                md.push_str(&format!("\n**Tool call: {tool}**\n\n{}\n", fenced(input)));
+            }
+            Entry::ToolResult { content } => {
+                md.push_str(&format!("\n**Tool result**\n\n{}\n", fenced(content)));
+            }
+        }
+    }
+    md
+}
+
+/// Fence `text` with more backticks than any run inside it.
+fn fenced(text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{fence}\n{}\n{fence}", text.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interactive_log() {
+        let log = [
+            r#"user:{"text":"List the files"}"#,
+            r#"text:{"text":"Let me "}"#,
+            r#"text:{"text":"check."}"#,
+            r#"tool_use:{"tool":"Bash","input":"{\"command\":\"ls\"}"}"#,
+            r#"tool_result:{"content":"a.rs"}"#,
+            r#"result:{"text":"Let me check.","cost":0.01,"turns":1}"#,
+            r#"done:{"session_id":"s"}"#,
+            r#"user:{"text":"Thanks"}"#,
+            r#"result:{"text":"You're welcome.","cost":0.0,"turns":1}"#,
+        ]
+        .join("\n");
+        assert_eq!(
+            from_log(&log),
+            vec![
+                Entry::User { text: "List the files".into() },
+                Entry::Assistant { text: "Let me check.".into() },
+                Entry::ToolCall { tool: "Bash".into(), input: r#"{"command":"ls"}"#.into() },
+                Entry::ToolResult { content: "a.rs".into() },
+                Entry::User { text: "Thanks".into() },
+                // No streamed text this turn, so the result stands in
+                Entry::Assistant { text: "You're welcome.".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flow_run_log_and_markdown() {
+        let log = [
+            r#"{"type":"system","subtype":"init"}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Reading."},{"type":"tool_use","name":"Read","input":{"path":"x"}}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":[{"type":"text","text":"```fn main() {}```"}]}]}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Done."}]}}"#,
+            r#"{"type":"result","result":"Reading.Done."}"#,
+        ]
+        .join("\n");
+        let entries = from_log(&log);
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0], Entry::Assistant { text: "Reading.".into() });
+        assert_eq!(entries[2], Entry::ToolResult { content: "```fn main() {}```".into() });
+        // Text after a tool result starts a new assistant entry; the result adds nothing
+        assert_eq!(entries[3], Entry::Assistant { text: "Done.".into() });
+
+        let md = to_markdown("Session s", &entries);
+        assert!(md.starts_with("# Session s\n\n## Assistant\n\nReading.\n"));
+        assert!(md.contains("**Tool call: Read**\n\n```\n{\"path\":\"x\"}\n```"));
+        // The result's own fence can't close the block early
+        assert!(md.contains("````\n```fn main() {}```\n````"));
+    }
+}
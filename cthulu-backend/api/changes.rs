@@ -1,5 +1,5 @@
 use axum::extract::State;
-use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::sse::{Event, Sse};
 use axum::routing::get;
 use axum::Router;
 use futures::stream::Stream;
@@ -46,6 +46,7 @@ pub(crate) async fn stream_changes(
     State(state): State<AppState>,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     let mut rx = state.changes_tx.subscribe();
+    let keep_alive = state.sse_keep_alive();
     let stream = async_stream::stream! {
         loop {
             match rx.recv().await {
@@ -64,7 +65,7 @@ pub(crate) async fn stream_changes(
             }
         }
     };
-    Sse::new(stream).keep_alive(KeepAlive::new().interval(std::time::Duration::from_secs(15)))
+    Sse::new(stream).keep_alive(keep_alive)
 }
 
 pub fn router() -> Router<AppState> {
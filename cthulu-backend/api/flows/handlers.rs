@@ -1,20 +1,29 @@
-use axum::extract::{Path, State};
-use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::extract::{Path, Query, State};
+use axum::response::sse::{Event, Sse};
 use axum::Json;
 use chrono::Utc;
 use futures::stream::Stream;
 use hyper::StatusCode;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::convert::Infallible;
 use uuid::Uuid;
 
 use crate::api::AppState;
 use crate::api::changes::{ChangeType, ResourceChangeEvent, ResourceType};
+use crate::flows::cost_cap::CostCap;
+use crate::flows::output_transform::OutputTransform;
+use crate::flows::export::{self, RunFilter};
+use crate::flows::history::{FlowRunResponse, RunTrigger, MAX_RUNS_PER_FLOW};
+use crate::flows::runner::apply_prompt_overrides;
+use crate::flows::{bundle, graph, import, scheduler, webhook};
 use crate::flows::{Edge, Flow, Node};
+use crate::github::webhook as gh_webhook;
 
 pub(crate) async fn list_flows(State(state): State<AppState>) -> Json<Value> {
     let flows = state.flow_repo.list_flows().await;
+    let last_runs = state.flow_repo.last_runs().await;
 
     let summaries: Vec<Value> = flows
         .iter()
@@ -28,6 +37,7 @@ pub(crate) async fn list_flows(State(state): State<AppState>) -> Json<Value> {
                 "edge_count": f.edges.len(),
                 "created_at": f.created_at,
                 "updated_at": f.updated_at,
+                "last_run": last_runs.get(&f.id),
             })
         })
         .collect();
@@ -58,6 +68,18 @@ pub(crate) struct CreateFlowRequest {
     nodes: Vec<Node>,
     #[serde(default)]
     edges: Vec<Edge>,
+    #[serde(default)]
+    max_runtime_secs: Option<u64>,
+    #[serde(default)]
+    capture_prompts: Option<bool>,
+    #[serde(default)]
+    sandbox_backend: Option<String>,
+    #[serde(default)]
+    cost_cap: Option<CostCap>,
+    #[serde(default, alias = "session_budget_usd")]
+    default_session_budget_usd: Option<f64>,
+    #[serde(default)]
+    output_transform: Vec<OutputTransform>,
 }
 
 pub(crate) async fn create_flow(
@@ -73,9 +95,18 @@ pub(crate) async fn create_flow(
         nodes: body.nodes,
         edges: body.edges,
         version: 0,
+        max_runtime_secs: body.max_runtime_secs.filter(|secs| *secs > 0),
+        capture_prompts: body.capture_prompts.unwrap_or(true),
+        sandbox_backend: body.sandbox_backend.filter(|b| !b.is_empty()),
+        cost_cap: body.cost_cap.filter(|cap| cap.max_usd > 0.0),
+        default_session_budget_usd: body.default_session_budget_usd.filter(|usd| *usd > 0.0),
+        output_transform: body.output_transform,
         created_at: now,
         updated_at: now,
     };
+    if let Err(problems) = graph::validate_flow(&flow) {
+        return invalid_flow(problems);
+    }
 
     let id = flow.id.clone();
     if let Err(e) = state.flow_repo.save_flow(flow).await {
@@ -100,6 +131,176 @@ pub(crate) async fn create_flow(
     (StatusCode::CREATED, Json(json!({ "id": id })))
 }
 
+/// 422 listing every structural problem `validate_flow` found.
+fn invalid_flow(problems: Vec<String>) -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(json!({
+            "error": format!("invalid flow: {}", problems.join("; ")),
+            "problems": problems,
+        })),
+    )
+}
+
+/// POST /flows/{id}/duplicate — save a disabled copy with fresh ids.
+pub(crate) async fn duplicate_flow(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    let Some(flow) = state.flow_repo.get_flow(&id).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        );
+    };
+
+    let copy = flow.duplicate();
+    let new_id = copy.id.clone();
+    if let Err(e) = state.flow_repo.save_flow(copy).await {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to save flow: {e}") })),
+        );
+    }
+    tracing::info!(flow_id = %id, copy_id = %new_id, "duplicated flow");
+
+    let _ = state.changes_tx.send(ResourceChangeEvent {
+        resource_type: ResourceType::Flow,
+        change_type: ChangeType::Created,
+        resource_id: new_id.clone(),
+        timestamp: chrono::Utc::now(),
+    });
+
+    (StatusCode::CREATED, Json(json!({ "id": new_id })))
+}
+
+/// 422 for a flow document `import::parse_flow_yaml` refused.
+fn import_rejected(e: anyhow::Error) -> (StatusCode, Json<Value>) {
+    match e.downcast::<import::InvalidFlow>() {
+        Ok(invalid) => invalid_flow(invalid.problems),
+        Err(e) => invalid_flow(vec![format!("{e:#}")]),
+    }
+}
+
+/// GET /flows/{id}/export — the flow as a YAML download that
+/// `POST /flows/import` accepts.
+pub(crate) async fn export_flow(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl axum::response::IntoResponse, (StatusCode, Json<Value>)> {
+    let Some(flow) = state.flow_repo.get_flow(&id).await else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        ));
+    };
+    let yaml = import::flow_to_yaml(&flow).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("{e:#}") })),
+        )
+    })?;
+
+    let headers = [
+        (axum::http::header::CONTENT_TYPE, "application/yaml".to_string()),
+        (
+            axum::http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"flow-{id}.yaml\""),
+        ),
+    ];
+    Ok((headers, yaml))
+}
+
+/// POST /flows/import — validate a flow YAML body and save it as a new
+/// disabled flow with fresh ids.
+pub(crate) async fn import_flow(
+    State(state): State<AppState>,
+    body: String,
+) -> (StatusCode, Json<Value>) {
+    if body.len() > import::MAX_IMPORT_BYTES {
+        return (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(json!({ "error": format!("flow YAML is larger than {} bytes", import::MAX_IMPORT_BYTES) })),
+        );
+    }
+
+    let flow = match import::parse_flow_yaml(&body) {
+        Ok(flow) => flow,
+        Err(e) => return import_rejected(e),
+    };
+
+    let id = flow.id.clone();
+    if let Err(e) = state.flow_repo.save_flow(flow).await {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to save flow: {e}") })),
+        );
+    }
+    tracing::info!(flow_id = %id, "imported flow");
+
+    let _ = state.changes_tx.send(ResourceChangeEvent {
+        resource_type: ResourceType::Flow,
+        change_type: ChangeType::Created,
+        resource_id: id.clone(),
+        timestamp: chrono::Utc::now(),
+    });
+
+    (StatusCode::CREATED, Json(json!({ "id": id })))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ImportUrlRequest {
+    url: String,
+}
+
+/// POST /flows/import-url — fetch a flow YAML over https, validate it and
+/// save it as a new disabled flow with fresh ids.
+pub(crate) async fn import_flow_url(
+    State(state): State<AppState>,
+    Json(body): Json<ImportUrlRequest>,
+) -> (StatusCode, Json<Value>) {
+    let allowed_hosts = import::allowed_hosts_from_env();
+    if let Err(e) = import::check_url(&body.url, &allowed_hosts) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": format!("{e:#}") })),
+        );
+    }
+
+    let yaml = match import::fetch_flow_yaml(&body.url, &allowed_hosts).await {
+        Ok(yaml) => yaml,
+        Err(e) => {
+            return (
+                StatusCode::BAD_GATEWAY,
+                Json(json!({ "error": format!("{e:#}") })),
+            );
+        }
+    };
+
+    let flow = match import::parse_flow_yaml(&yaml) {
+        Ok(flow) => flow,
+        Err(e) => return import_rejected(e),
+    };
+
+    let id = flow.id.clone();
+    if let Err(e) = state.flow_repo.save_flow(flow).await {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to save flow: {e}") })),
+        );
+    }
+    tracing::info!(flow_id = %id, url = %body.url, "imported flow from URL");
+
+    let _ = state.changes_tx.send(ResourceChangeEvent {
+        resource_type: ResourceType::Flow,
+        change_type: ChangeType::Created,
+        resource_id: id.clone(),
+        timestamp: chrono::Utc::now(),
+    });
+
+    (StatusCode::CREATED, Json(json!({ "id": id })))
+}
+
 #[derive(Deserialize)]
 pub(crate) struct UpdateFlowRequest {
     #[serde(default)]
@@ -114,6 +315,23 @@ pub(crate) struct UpdateFlowRequest {
     edges: Option<Vec<Edge>>,
     #[serde(default)]
     version: Option<u64>,
+    /// `0` clears the limit.
+    #[serde(default)]
+    max_runtime_secs: Option<u64>,
+    #[serde(default)]
+    capture_prompts: Option<bool>,
+    /// `""` clears it.
+    #[serde(default)]
+    sandbox_backend: Option<String>,
+    /// A `max_usd` of `0` clears it.
+    #[serde(default)]
+    cost_cap: Option<CostCap>,
+    /// `0` clears it.
+    #[serde(default, alias = "session_budget_usd")]
+    default_session_budget_usd: Option<f64>,
+    /// `[]` clears it.
+    #[serde(default)]
+    output_transform: Option<Vec<OutputTransform>>,
 }
 
 pub(crate) async fn update_flow(
@@ -150,12 +368,38 @@ pub(crate) async fn update_flow(
     if let Some(enabled) = body.enabled {
         flow.enabled = enabled;
     }
+    let graph_changed = body.nodes.is_some() || body.edges.is_some();
     if let Some(nodes) = body.nodes {
         flow.nodes = nodes;
     }
     if let Some(edges) = body.edges {
         flow.edges = edges;
     }
+    if let Some(secs) = body.max_runtime_secs {
+        flow.max_runtime_secs = (secs > 0).then_some(secs);
+    }
+    if let Some(capture_prompts) = body.capture_prompts {
+        flow.capture_prompts = capture_prompts;
+    }
+    if let Some(backend) = body.sandbox_backend {
+        flow.sandbox_backend = (!backend.is_empty()).then_some(backend);
+    }
+    if let Some(cap) = body.cost_cap {
+        flow.cost_cap = (cap.max_usd > 0.0).then_some(cap);
+    }
+    if let Some(usd) = body.default_session_budget_usd {
+        flow.default_session_budget_usd = (usd > 0.0).then_some(usd);
+    }
+    if let Some(transforms) = body.output_transform {
+        flow.output_transform = transforms;
+    }
+    // Only graph edits are checked, so a legacy flow can still be renamed or disabled
+    if graph_changed {
+        graph::validate_flow(&flow).map_err(invalid_flow)?;
+    }
+    if body.enabled == Some(true) {
+        graph::require_executor(&flow).map_err(|e| invalid_flow(vec![e]))?;
+    }
     flow.version += 1;
     flow.updated_at = Utc::now();
 
@@ -216,6 +460,41 @@ pub(crate) async fn delete_flow(
 pub(crate) struct TriggerFlowRequest {
     repo: Option<String>,
     pr: Option<u64>,
+    /// Executor prompts to use for this run only, by node id.
+    #[serde(default)]
+    prompt_overrides: HashMap<String, String>,
+    /// Render prompts but stop before executors and sinks.
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// What a trigger request body asks for.
+#[derive(Debug, PartialEq)]
+enum TriggerBody {
+    /// Empty body or JSON without PR fields — run the flow once, with any
+    /// `prompt_overrides` applied, or only up to its executors if `dry_run`.
+    OneShot { prompt_overrides: HashMap<String, String>, dry_run: bool },
+    /// `{"repo": "...", "pr": N}` — review a specific pull request.
+    PrReview { repo: String, pr: u64 },
+}
+
+/// Parse a trigger body strictly: an empty body is a one-shot run, but
+/// malformed JSON (or a half-specified PR target) is rejected.
+fn parse_trigger_body(body: &str) -> Result<TriggerBody, String> {
+    if body.trim().is_empty() {
+        return Ok(TriggerBody::OneShot { prompt_overrides: HashMap::new(), dry_run: false });
+    }
+    let req: TriggerFlowRequest =
+        serde_json::from_str(body).map_err(|e| format!("invalid JSON body: {e}"))?;
+    match (req.repo, req.pr) {
+        (Some(_), Some(_)) if !req.prompt_overrides.is_empty() => {
+            Err("prompt_overrides can't be used with a PR trigger".to_string())
+        }
+        (Some(_), Some(_)) if req.dry_run => Err("dry_run can't be used with a PR trigger".to_string()),
+        (Some(repo), Some(pr)) => Ok(TriggerBody::PrReview { repo, pr }),
+        (None, None) => Ok(TriggerBody::OneShot { prompt_overrides: req.prompt_overrides, dry_run: req.dry_run }),
+        _ => Err("PR trigger requires both \"repo\" and \"pr\"".to_string()),
+    }
 }
 
 pub(crate) async fn trigger_flow(
@@ -223,6 +502,8 @@ pub(crate) async fn trigger_flow(
     Path(id): Path<String>,
     body: String,
 ) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    state.ensure_accepting_work()?;
+
     let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
@@ -230,18 +511,15 @@ pub(crate) async fn trigger_flow(
         )
     })?;
 
-    // Check if this is a PR trigger request
-    let trigger_body: Option<TriggerFlowRequest> = if body.trim().is_empty() {
-        None
-    } else {
-        serde_json::from_str(&body).ok()
-    };
+    let trigger_body = parse_trigger_body(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))))?;
+    graph::require_executor(&flow).map_err(|e| invalid_flow(vec![e]))?;
 
-    if let Some(trigger_body) = &trigger_body {
-        if let (Some(repo), Some(pr)) = (&trigger_body.repo, trigger_body.pr) {
+    let (prompt_overrides, dry_run) = match trigger_body {
+        TriggerBody::OneShot { prompt_overrides, dry_run } => (prompt_overrides, dry_run),
+        TriggerBody::PrReview { repo, pr } => {
             let scheduler = state.scheduler.clone();
             let flow_id = id.clone();
-            let repo = repo.clone();
             let repo_for_response = repo.clone();
 
             tokio::spawn(async move {
@@ -255,54 +533,568 @@ pub(crate) async fn trigger_flow(
                 Json(json!({ "status": "pr_review_started", "flow_id": id, "repo": repo_for_response, "pr": pr })),
             ));
         }
-    }
+    };
+
+    // Default: one-shot flow execution. Overrides apply to this run's copy
+    // of the flow only.
+    let flow = apply_prompt_overrides(&flow, &prompt_overrides)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(json!({ "error": e.to_string() }))))?;
+    let runner = flow_runner(&state);
+
+    let flow_repo = state.flow_repo.clone();
+    let flow_name = flow.name.clone();
 
-    // Default: one-shot flow execution
+    tokio::spawn(async move {
+        let result = if dry_run {
+            runner.execute_dry_run(&flow, &*flow_repo).await
+        } else {
+            runner.execute(&flow, &*flow_repo, None, RunTrigger::Manual).await
+        };
+        match result {
+            Ok(run) => {
+                tracing::info!(
+                    flow = %flow_name,
+                    run_id = %run.id,
+                    dry_run,
+                    "Flow execution completed"
+                );
+            }
+            Err(e) => {
+                tracing::error!(flow = %flow_name, error = %e, "Flow execution failed");
+            }
+        }
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "status": "triggered", "flow_id": id, "dry_run": dry_run })),
+    ))
+}
+
+fn flow_runner(state: &AppState) -> crate::flows::runner::FlowRunner {
     let session_bridge = crate::flows::session_bridge::SessionBridge {
         sessions: state.interact_sessions.clone(),
         sessions_path: state.sessions_path.clone(),
         data_dir: state.data_dir.clone(),
         session_streams: state.session_streams.clone(),
+        dir_locks: state.dir_locks.clone(),
     };
-    let runner = crate::flows::runner::FlowRunner {
+    crate::flows::runner::FlowRunner {
         http_client: state.http_client.clone(),
         github_client: state.github_client.clone(),
         events_tx: Some(state.events_tx.clone()),
-        sandbox_provider: Some(state.sandbox_provider.clone()),
+        sandbox_providers: Some(state.sandbox_providers.clone()),
         agent_repo: Some(state.agent_repo.clone()),
         session_bridge: Some(session_bridge),
+        working_dir_policy: state.working_dir_policy.clone(),
+        run_preview: state.run_preview,
+    }
+}
+
+/// POST /webhooks/github — GitHub push endpoint for PR reviews. Deliveries
+/// must be signed with `GITHUB_WEBHOOK_SECRET`; `pull_request` opened and
+/// synchronize events start a review in every flow watching the repo, other
+/// events get 204.
+pub(crate) async fn receive_github_webhook(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<axum::response::Response, (StatusCode, Json<Value>)> {
+    use axum::response::IntoResponse;
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let secret = std::env::var(gh_webhook::WEBHOOK_SECRET_ENV).unwrap_or_default();
+    if secret.is_empty() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": format!("{} is not set", gh_webhook::WEBHOOK_SECRET_ENV) })),
+        ));
+    }
+    let signature = header(gh_webhook::SIGNATURE_HEADER).unwrap_or_default();
+    if !gh_webhook::verify_signature(&secret, &body, signature) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid webhook signature" })),
+        ));
+    }
+    state.ensure_accepting_work()?;
+
+    let event = header(gh_webhook::EVENT_HEADER).unwrap_or_default();
+    let pushed = match gh_webhook::parse_event(event, &body) {
+        Ok(Some(pushed)) => pushed,
+        Ok(None) => return Ok(StatusCode::NO_CONTENT.into_response()),
+        Err(e) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": format!("{e:#}") })),
+            ));
+        }
     };
 
-    let flow_repo = state.flow_repo.clone();
-    let flow_name = flow.name.clone();
+    let flows = state
+        .scheduler
+        .pr_review_flows(&pushed.repo, pushed.draft, pushed.new_commits)
+        .await;
+    let flow_ids: Vec<String> = flows.iter().map(|(flow_id, _)| flow_id.clone()).collect();
+    for (flow_id, repo) in flows {
+        let scheduler = state.scheduler.clone();
+        let pr = pushed.number;
+        tokio::spawn(async move {
+            if !scheduler.cost_cap_allows(&flow_id).await {
+                return;
+            }
+            if let Err(e) = scheduler.trigger_pr_review(&flow_id, &repo, pr).await {
+                tracing::error!(flow_id = %flow_id, repo = %repo, pr, error = %e, "GitHub webhook PR review failed");
+            }
+        });
+    }
+    tracing::info!(repo = %pushed.repo, pr = pushed.number, flows = flow_ids.len(), "GitHub pull_request webhook received");
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "status": "pr_review_started", "flow_ids": flow_ids, "repo": pushed.repo, "pr": pushed.number })),
+    )
+        .into_response())
+}
+
+/// Run every enabled flow whose webhook trigger listens on `path`. The
+/// trigger's `var_map` turns fields of the JSON body into template variables,
+/// and the raw body is available as `webhook_payload`. A trigger with a
+/// `secret` only fires when `X-Webhook-Secret` matches it (401 if no trigger
+/// accepted the delivery). A trigger with an idempotency key configured skips
+/// deliveries whose key it has already seen within its TTL.
+pub(crate) async fn receive_webhook(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+    headers: axum::http::HeaderMap,
+    body: String,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    state.ensure_accepting_work()?;
+
+    let payload: Value = if body.trim().is_empty() {
+        json!({})
+    } else {
+        serde_json::from_str(&body).map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": format!("invalid JSON body: {e}") })),
+            )
+        })?
+    };
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    // Every enabled flow listening on the path fires, each checking its own secret
+    let mut triggered = Vec::new();
+    let mut duplicates = Vec::new();
+    let mut unauthorized = 0;
+    for flow in state.flow_repo.list_flows().await {
+        if !flow.enabled {
+            continue;
+        }
+        let Some(trigger) = webhook::find_trigger(&flow, &path) else {
+            continue;
+        };
+        if !webhook::secret_matches(&trigger.config, header(webhook::SECRET_HEADER)) {
+            tracing::warn!(flow = %flow.name, path = %path, "webhook secret mismatch");
+            unauthorized += 1;
+            continue;
+        }
+
+        if let Some(key) = webhook::idempotency_key(&trigger.config, header, &payload) {
+            let scope = format!("{}:{}", flow.id, trigger.id);
+            let ttl = webhook::idempotency_ttl(&trigger.config);
+            if !state.webhook_deliveries.first_delivery(&scope, &key, ttl, std::time::Instant::now()) {
+                tracing::info!(flow = %flow.name, key = %key, "duplicate webhook delivery, skipping");
+                duplicates.push(flow.id.clone());
+                continue;
+            }
+        }
+
+        let (mut context, unresolved) = webhook::build_context(&trigger.config["var_map"], &payload);
+        context.entry("webhook_payload".to_string()).or_insert_with(|| body.clone());
+        for var in &unresolved {
+            tracing::warn!(
+                flow = %flow.name,
+                var = %var,
+                "webhook var_map path did not resolve, rendering empty"
+            );
+        }
 
+        let runner = flow_runner(&state);
+        let flow_repo = state.flow_repo.clone();
+        let events_tx = state.events_tx.clone();
+        let changes_tx = state.changes_tx.clone();
+        triggered.push(flow.id.clone());
+        tokio::spawn(async move {
+            if !scheduler::cost_cap_allows(&flow, &*flow_repo, &events_tx, &changes_tx).await {
+                return;
+            }
+            match runner.execute(&flow, &*flow_repo, Some(context), RunTrigger::Webhook).await {
+                Ok(run) => {
+                    tracing::info!(flow = %flow.name, run_id = %run.id, "Webhook flow execution completed");
+                }
+                Err(e) => {
+                    tracing::error!(flow = %flow.name, error = %e, "Webhook flow execution failed");
+                }
+            }
+        });
+    }
+
+    if triggered.is_empty() && !duplicates.is_empty() {
+        return Ok((
+            StatusCode::OK,
+            Json(json!({ "status": "already processed", "flow_ids": duplicates })),
+        ));
+    }
+    if triggered.is_empty() && unauthorized > 0 {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid webhook secret" })),
+        ));
+    }
+    if triggered.is_empty() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "no enabled flow listens on this webhook path" })),
+        ));
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "status": "triggered", "flow_ids": triggered, "duplicate_flow_ids": duplicates })),
+    ))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct GraphQuery {
+    #[serde(default)]
+    format: Option<String>,
+}
+
+/// GET /flows/{id}/graph?format=dot|mermaid — render the flow graph as text.
+pub(crate) async fn get_flow_graph(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<GraphQuery>,
+) -> Result<([(axum::http::header::HeaderName, &'static str); 1], String), (StatusCode, Json<Value>)> {
+    let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })?;
+
+    let body = match query.format.as_deref().unwrap_or("mermaid") {
+        "dot" => crate::flows::graph::to_dot(&flow.name, &flow.nodes, &flow.edges),
+        "mermaid" => crate::flows::graph::to_mermaid(&flow.nodes, &flow.edges),
+        other => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": format!("unsupported format '{other}', expected dot or mermaid") })),
+            ));
+        }
+    };
+
+    Ok(([(axum::http::header::CONTENT_TYPE, "text/plain; charset=utf-8")], body))
+}
+
+/// GET /flows/{id}/validate — report structural problems without saving.
+pub(crate) async fn validate_flow(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })?;
+
+    let warnings: Vec<String> = crate::flows::graph::orphaned_executors(&flow.nodes, &flow.edges)
+        .into_iter()
+        .map(|n| {
+            format!(
+                "Executor '{}' has no incoming edges and will run with empty input",
+                n.label
+            )
+        })
+        .collect();
+
+    Ok(Json(json!({ "warnings": warnings })))
+}
+
+/// POST /flows/{id}/estimate — dry-render each executor's prompt and report its size.
+pub(crate) async fn estimate_flow(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })?;
+
+    let estimates = flow_runner(&state).estimate_prompts(&flow).await.map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": format!("{e:#}") })),
+        )
+    })?;
+
+    Ok(Json(json!({ "flow_id": flow.id, "executors": estimates })))
+}
+
+pub(crate) async fn get_runs(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(filter): Query<RunFilter>,
+) -> Json<Value> {
+    let mut runs = filter.apply(state.flow_repo.get_runs(&id, 100).await);
+    // The list only carries previews; full output and prompts are served by run detail.
+    for run in &mut runs {
+        for node_run in &mut run.node_runs {
+            node_run.output = None;
+            node_run.prompt = None;
+            node_run.checkpoint = None;
+        }
+    }
+    let runs: Vec<FlowRunResponse> = runs.iter().map(FlowRunResponse::from).collect();
+    Json(json!({ "runs": runs }))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ExportRunsQuery {
+    /// `csv` (default) or `json`.
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    tag: Option<String>,
+    #[serde(default)]
+    since: Option<chrono::DateTime<Utc>>,
+}
+
+/// GET /flows/{id}/runs/export?format=csv|json&since=&tag= — one row per run,
+/// as a download. `all` as the id exports every flow's runs.
+pub(crate) async fn export_runs(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<ExportRunsQuery>,
+) -> Result<impl axum::response::IntoResponse, (StatusCode, Json<Value>)> {
+    let format = export::ExportFormat::parse(query.format.as_deref()).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "format must be csv or json" })),
+        )
+    })?;
+    let filter = RunFilter { tag: query.tag, since: query.since };
+
+    let flows = if id == "all" {
+        state.flow_repo.list_flows().await
+    } else {
+        let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": "flow not found" })),
+            )
+        })?;
+        vec![flow]
+    };
+
+    let mut rows = Vec::new();
+    for flow in &flows {
+        let runs = filter.apply(state.flow_repo.get_runs(&flow.id, MAX_RUNS_PER_FLOW).await);
+        rows.extend(runs.iter().map(|run| export::RunRow::new(&flow.name, run)));
+    }
+    rows.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+
+    let body = match format {
+        export::ExportFormat::Csv => axum::body::Body::from_stream(futures::stream::iter(
+            export::csv_lines(rows).map(Ok::<_, Infallible>),
+        )),
+        export::ExportFormat::Json => axum::body::Body::from(serde_json::to_vec(&rows).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": format!("failed to serialize runs: {e}") })),
+            )
+        })?),
+    };
+    let headers = [
+        (axum::http::header::CONTENT_TYPE, format.content_type().to_string()),
+        (
+            axum::http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"runs-{id}.{}\"", format.extension()),
+        ),
+    ];
+    Ok((headers, body))
+}
+
+/// GET /flows/{id}/runs/{run_id} — full run record including stored node output.
+pub(crate) async fn get_run(
+    State(state): State<AppState>,
+    Path((id, run_id)): Path<(String, String)>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let mut run = state.flow_repo.get_run(&id, &run_id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "run not found" })),
+        )
+    })?;
+    // Checkpoints are internal retry state, not for display.
+    for node_run in &mut run.node_runs {
+        node_run.checkpoint = None;
+    }
+    Ok(Json(json!({ "run": FlowRunResponse::from(&run) })))
+}
+
+/// POST /flows/{id}/runs/{run_id}/retry — re-run a failed run, reusing the
+/// outputs of nodes that succeeded. Starts a new run linked by `resumed_from`.
+pub(crate) async fn retry_run(
+    State(state): State<AppState>,
+    Path((id, run_id)): Path<(String, String)>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    state.ensure_accepting_work()?;
+
+    let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })?;
+    let original = state.flow_repo.get_run(&id, &run_id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "run not found" })),
+        )
+    })?;
+    crate::flows::runner::check_retryable(&original)
+        .map_err(|e| (StatusCode::CONFLICT, Json(json!({ "error": e.to_string() }))))?;
+
+    let runner = flow_runner(&state);
+    let flow_repo = state.flow_repo.clone();
     tokio::spawn(async move {
-        match runner.execute(&flow, &*flow_repo, None).await {
+        match runner.retry(&flow, &*flow_repo, &original).await {
             Ok(run) => {
-                tracing::info!(
-                    flow = %flow_name,
-                    run_id = %run.id,
-                    "Flow execution completed"
-                );
+                tracing::info!(flow = %flow.name, run_id = %run.id, resumed_from = %original.id, "Flow retry completed");
             }
             Err(e) => {
-                tracing::error!(flow = %flow_name, error = %e, "Flow execution failed");
+                tracing::error!(flow = %flow.name, resumed_from = %original.id, error = %e, "Flow retry failed");
             }
         }
     });
 
     Ok((
         StatusCode::ACCEPTED,
-        Json(json!({ "status": "triggered", "flow_id": id })),
+        Json(json!({ "status": "retry_started", "flow_id": id, "resumed_from": run_id })),
     ))
 }
 
-pub(crate) async fn get_runs(
+/// GET /flows/{id}/runs/{run_id}/bundle — tar.gz support bundle with the run
+/// record, timeline, and per-node prompts/outputs (secrets redacted).
+pub(crate) async fn run_bundle(
+    State(state): State<AppState>,
+    Path((id, run_id)): Path<(String, String)>,
+) -> Result<impl axum::response::IntoResponse, (StatusCode, Json<Value>)> {
+    let flow = state.flow_repo.get_flow(&id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "flow not found" })),
+        )
+    })?;
+    let run = state.flow_repo.get_run(&id, &run_id).await.ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "run not found" })),
+        )
+    })?;
+
+    let entries = bundle::bundle_entries(&flow, &run, &bundle::Redactor::from_env()).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": format!("failed to build bundle: {e}") })),
+        )
+    })?;
+
+    let root = format!("run-{}", run.id);
+    let headers = [
+        (axum::http::header::CONTENT_TYPE, "application/gzip".to_string()),
+        (
+            axum::http::header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{root}.tar.gz\""),
+        ),
+    ];
+    let body = axum::body::Body::from_stream(bundle::stream_bundle(entries, root));
+    Ok((headers, body))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CompareRunsQuery {
+    a: String,
+    b: String,
+}
+
+/// GET /flows/{id}/runs/compare?a={run_a}&b={run_b} — node-by-node diff of two runs.
+pub(crate) async fn compare_runs(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Json<Value> {
-    let runs = state.flow_repo.get_runs(&id, 100).await;
-    Json(json!({ "runs": runs }))
+    Query(query): Query<CompareRunsQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let mut runs = Vec::with_capacity(2);
+    for run_id in [&query.a, &query.b] {
+        let run = state.flow_repo.get_run(&id, run_id).await.ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": format!("run {run_id} not found") })),
+            )
+        })?;
+        runs.push(run);
+    }
+    let comparison = crate::flows::compare::compare_runs(&runs[0], &runs[1]);
+    Ok(Json(json!({ "comparison": comparison })))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AnnotateRunRequest {
+    #[serde(default)]
+    tags: Vec<String>,
+    note: Option<String>,
+}
+
+/// POST /flows/{id}/runs/{run_id}/tags — attach triage tags and/or a note to a run.
+pub(crate) async fn annotate_run(
+    State(state): State<AppState>,
+    Path((id, run_id)): Path<(String, String)>,
+    Json(body): Json<AnnotateRunRequest>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if state.flow_repo.get_run(&id, &run_id).await.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "run not found" })),
+        ));
+    }
+
+    let tags: Vec<String> = body
+        .tags
+        .iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    state
+        .flow_repo
+        .annotate_run(&id, &run_id, tags, body.note)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            )
+        })?;
+
+    let run = state.flow_repo.get_run(&id, &run_id).await;
+    Ok(Json(json!({ "run": run.as_ref().map(FlowRunResponse::from) })))
 }
 
 pub(crate) async fn stream_runs(
@@ -310,6 +1102,7 @@ pub(crate) async fn stream_runs(
     Path(flow_id): Path<String>,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     let mut rx = state.events_tx.subscribe();
+    let keep_alive = state.sse_keep_alive();
     let stream = async_stream::stream! {
         loop {
             match rx.recv().await {
@@ -331,7 +1124,7 @@ pub(crate) async fn stream_runs(
             }
         }
     };
-    Sse::new(stream).keep_alive(KeepAlive::new().interval(std::time::Duration::from_secs(15)))
+    Sse::new(stream).keep_alive(keep_alive)
 }
 
 pub(crate) async fn get_node_types() -> Json<Value> {
@@ -342,7 +1135,7 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "node_type": "trigger",
                 "label": "Cron Schedule",
                 "config_schema": {
-                    "schedule": { "type": "string", "description": "Cron expression (5-field)", "required": true },
+                    "schedule": { "type": "string", "description": "Cron expression (5-field), or an array of them to fire on each", "required": true },
                     "working_dir": { "type": "string", "description": "Working directory", "default": "." }
                 }
             },
@@ -363,7 +1156,11 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "node_type": "trigger",
                 "label": "Webhook",
                 "config_schema": {
-                    "path": { "type": "string", "description": "Webhook URL path", "required": true }
+                    "path": { "type": "string", "description": "Webhook URL path (POST /api/webhooks/{path})", "required": true },
+                    "var_map": { "type": "object", "description": "Template variables from the JSON body, e.g. { \"issue_title\": \"$.issue.title\" }" },
+                    "idempotency_header": { "type": "string", "description": "Header carrying a delivery id; repeats within the TTL are skipped" },
+                    "idempotency_path": { "type": "string", "description": "JSONPath to a delivery id in the body, used when no header is set" },
+                    "idempotency_ttl_secs": { "type": "number", "description": "How long delivery ids are remembered (default 86400)" }
                 }
             },
             {
@@ -379,7 +1176,9 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "config_schema": {
                     "url": { "type": "string", "description": "Feed URL", "required": true },
                     "limit": { "type": "number", "description": "Max items to fetch", "default": 10 },
-                    "keywords": { "type": "array", "description": "Filter items by keywords (case-insensitive, any match)", "default": [] }
+                    "keywords": { "type": "array", "description": "Filter items by keywords (case-insensitive, any match)", "default": [] },
+                    "headers": { "type": "object", "description": "Request headers; values may reference env vars as ${VAR}", "default": {} },
+                    "priority": { "type": "number", "description": "Ordering weight when the executor ranks by source priority (higher first)", "default": 0 }
                 }
             },
             {
@@ -388,7 +1187,9 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "label": "Web Scrape",
                 "config_schema": {
                     "url": { "type": "string", "description": "Page URL to scrape", "required": true },
-                    "keywords": { "type": "array", "description": "Filter by keywords (case-insensitive, any match)", "default": [] }
+                    "keywords": { "type": "array", "description": "Filter by keywords (case-insensitive, any match)", "default": [] },
+                    "headers": { "type": "object", "description": "Request headers; values may reference env vars as ${VAR}", "default": {} },
+                    "priority": { "type": "number", "description": "Ordering weight when the executor ranks by source priority (higher first)", "default": 0 }
                 }
             },
             {
@@ -397,7 +1198,20 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "label": "GitHub Merged PRs",
                 "config_schema": {
                     "repos": { "type": "array", "description": "Repository slugs [\"owner/repo\"]", "required": true },
-                    "since_days": { "type": "number", "description": "Days to look back", "default": 7 }
+                    "since_days": { "type": "number", "description": "Days to look back", "default": 7 },
+                    "priority": { "type": "number", "description": "Ordering weight when the executor ranks by source priority (higher first)", "default": 0 }
+                }
+            },
+            {
+                "kind": "github-issues",
+                "node_type": "source",
+                "label": "GitHub Issues",
+                "config_schema": {
+                    "repos": { "type": "array", "description": "Repository slugs [\"owner/repo\"]", "required": true },
+                    "state": { "type": "string", "description": "open, closed or all", "default": "open" },
+                    "labels": { "type": "array", "description": "Only issues with all of these labels", "default": [] },
+                    "since_days": { "type": "number", "description": "Only issues updated in the last N days", "default": 7 },
+                    "priority": { "type": "number", "description": "Ordering weight when the executor ranks by source priority (higher first)", "default": 0 }
                 }
             },
             {
@@ -413,7 +1227,81 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                     "summary_selector": { "type": "string", "description": "CSS selector for summary within item" },
                     "date_selector": { "type": "string", "description": "CSS selector for date within item" },
                     "date_format": { "type": "string", "description": "Date format string (e.g. %Y-%m-%d)" },
-                    "limit": { "type": "number", "description": "Max items to extract", "default": 10 }
+                    "limit": { "type": "number", "description": "Max items to extract", "default": 10 },
+                    "headers": { "type": "object", "description": "Request headers; values may reference env vars as ${VAR}", "default": {} },
+                    "next_page_selector": { "type": "string", "description": "CSS selector for the next-page link; follows it until limit or max_pages (unset = first page only)" },
+                    "max_pages": { "type": "number", "description": "Max pages to read when next_page_selector is set", "default": 5 },
+                    "priority": { "type": "number", "description": "Ordering weight when the executor ranks by source priority (higher first)", "default": 0 }
+                }
+            },
+            {
+                "kind": "youtube",
+                "node_type": "source",
+                "label": "YouTube",
+                "config_schema": {
+                    "channel_id": { "type": "string", "description": "Channel ID (UC...) — or set playlist_id" },
+                    "playlist_id": { "type": "string", "description": "Playlist ID (PL...) — or set channel_id" },
+                    "limit": { "type": "number", "description": "Max videos to fetch", "default": 10 },
+                    "keywords": { "type": "array", "description": "Filter videos by keywords (case-insensitive, any match)", "default": [] },
+                    "priority": { "type": "number", "description": "Ordering weight when the executor ranks by source priority (higher first)", "default": 0 }
+                }
+            },
+            {
+                "kind": "sitemap",
+                "node_type": "source",
+                "label": "Sitemap",
+                "config_schema": {
+                    "url": { "type": "string", "description": "sitemap.xml or sitemap index URL (.xml.gz supported)", "required": true },
+                    "limit": { "type": "number", "description": "Max pages to include", "default": 10 },
+                    "since_days": { "type": "number", "description": "Only pages with <lastmod> in the last N days", "default": 7 },
+                    "keywords": { "type": "array", "description": "Filter pages by keywords (case-insensitive, any match)", "default": [] },
+                    "fetch_titles": { "type": "boolean", "description": "Fetch each page's <title> instead of using the URL slug", "default": false },
+                    "priority": { "type": "number", "description": "Ordering weight when the executor ranks by source priority (higher first)", "default": 0 }
+                }
+            },
+            {
+                "kind": "hackernews",
+                "node_type": "source",
+                "label": "Hacker News",
+                "config_schema": {
+                    "story_type": { "type": "string", "description": "top, best or new", "default": "top" },
+                    "limit": { "type": "number", "description": "Max stories to fetch", "default": 10 },
+                    "keywords": { "type": "array", "description": "Filter stories by keywords (case-insensitive, any match)", "default": [] },
+                    "priority": { "type": "number", "description": "Ordering weight when the executor ranks by source priority (higher first)", "default": 0 }
+                }
+            },
+            {
+                "kind": "reddit",
+                "node_type": "source",
+                "label": "Reddit",
+                "config_schema": {
+                    "subreddit": { "type": "string", "description": "Subreddit name (e.g. rust or r/rust)", "required": true },
+                    "sort": { "type": "string", "description": "hot, new, top or rising", "default": "hot" },
+                    "limit": { "type": "number", "description": "Max posts to fetch", "default": 10 },
+                    "keywords": { "type": "array", "description": "Filter posts by keywords (case-insensitive, any match)", "default": [] },
+                    "priority": { "type": "number", "description": "Ordering weight when the executor ranks by source priority (higher first)", "default": 0 }
+                }
+            },
+            {
+                "kind": "rest",
+                "node_type": "source",
+                "label": "REST API",
+                "config_schema": {
+                    "url": { "type": "string", "description": "Endpoint URL", "required": true },
+                    "query": { "type": "object", "description": "Query parameters", "default": {} },
+                    "headers": { "type": "object", "description": "Request headers; values may reference env vars as ${VAR}", "default": {} },
+                    "items_path": { "type": "string", "description": "Path to the items array, dot or JSONPath-style (e.g. data.items or $.data.items[*]; empty = response root)", "default": "" },
+                    "title_path": { "type": "string", "description": "Dot path to each item's title", "default": "title" },
+                    "url_path": { "type": "string", "description": "Dot path to each item's URL", "default": "url" },
+                    "summary_path": { "type": "string", "description": "Dot path to each item's summary", "default": "summary" },
+                    "date_path": { "type": "string", "description": "Dot path to each item's date (RFC 3339 or unix seconds)" },
+                    "image_path": { "type": "string", "description": "Dot path to each item's image URL" },
+                    "cursor_path": { "type": "string", "description": "Dot path to the next-page cursor or URL (unset = single page)" },
+                    "cursor_param": { "type": "string", "description": "Query parameter the cursor is sent in", "default": "cursor" },
+                    "max_pages": { "type": "number", "description": "Max pages to follow", "default": 5 },
+                    "limit": { "type": "number", "description": "Max items to fetch", "default": 10 },
+                    "keywords": { "type": "array", "description": "Filter items by keywords (case-insensitive, any match)", "default": [] },
+                    "priority": { "type": "number", "description": "Ordering weight when the executor ranks by source priority (higher first)", "default": 0 }
                 }
             },
             {
@@ -429,7 +1317,20 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "config_schema": {
                     "agent_id": { "type": "string", "description": "ID of the agent to use", "required": true },
                     "prompt": { "type": "string", "description": "Prompt file path or inline prompt", "required": true },
-                    "working_dir": { "type": "string", "description": "Working directory", "default": "." }
+                    "working_dir": { "type": "string", "description": "Working directory; may use {{flow}}, {{date}} and {{run_id}} for a per-run dir, created at run start", "default": "." },
+                    "additional_dirs": { "type": "array", "description": "Extra directories claude can access (--add-dir), subject to the working dir allowlist", "default": [] },
+                    "model": { "type": "string", "description": "Claude model passed as --model, e.g. sonnet, opus or a full model name (default: claude's configured default)" },
+                    "max_turns": { "type": "number", "description": "Cap on agent turns, passed as --max-turns (unset = no cap)" },
+                    "timeout_secs": { "type": "number", "description": "Kill the claude process after this many seconds (default 600)" },
+                    "chat_permissions": { "type": "array", "description": "Tools allowed in interactive chat when the agent sets no permissions (defaults to CHAT_DEFAULT_PERMISSIONS)", "default": [] },
+                    "sandbox_backend": { "type": "string", "description": "Sandbox backend for the sandbox runtime: dangerous or firecracker (default: the flow's sandbox_backend, then the server default)" },
+                    "rank": { "type": "string", "description": "Reorder input items: recency, source_priority, or keyword_score (default: fetch order)" },
+                    "filters": { "type": "array", "description": "Input item filters applied before ranking, in order: date-window (max_age_hours, drop_undated?) or dedup (key? url|title, max_remembered? default 1000) or limit (count, strategy? head|tail|newest), e.g. [{\"kind\": \"dedup\", \"key\": \"url\"}]", "default": [] },
+                    "rank_keywords": { "type": "array", "description": "Keywords scored by the keyword_score ranking", "default": [] },
+                    "include_images": { "type": "boolean", "description": "Include item images in {{content}} as markdown image links", "default": false },
+                    "input_mode": { "type": "string", "description": "When downstream of another executor: replace (previous output), append (original content + previous output), or original (original content only)", "default": "replace" },
+                    "max_prompt_chars": { "type": "number", "description": "Cap on the rendered prompt; oversize {{content}} is trimmed by dropping the lowest-ranked items", "default": 500000 },
+                    "max_prompt_strict": { "type": "boolean", "description": "Fail the node instead of trimming when the prompt exceeds max_prompt_chars", "default": false }
                 }
             },
             {
@@ -439,7 +1340,65 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "config_schema": {
                     "webhook_url_env": { "type": "string", "description": "Env var for webhook URL" },
                     "bot_token_env": { "type": "string", "description": "Env var for bot token" },
-                    "channel": { "type": "string", "description": "Channel name (required with bot_token_env)" }
+                    "channel": { "type": "string", "description": "Channel name (required with bot_token_env)" },
+                    "unfurl_links": { "type": "boolean", "description": "Expand link previews", "default": false },
+                    "unfurl_media": { "type": "boolean", "description": "Expand media previews", "default": false },
+                    "sanitize_mentions": { "type": "boolean", "description": "Neutralize @here/@channel/@everyone in output", "default": true },
+                    "max_blocks": { "type": "number", "description": "Blocks per message before truncating (max 50)", "default": 50 },
+                    "overflow_to_thread": { "type": "boolean", "description": "Post the full output as thread replies when truncated", "default": false },
+                    "max_retries": { "type": "number", "description": "Retries on rate limits and 5xx errors, with backoff", "default": 3 }
+                }
+            },
+            {
+                "kind": "discord",
+                "node_type": "sink",
+                "label": "Discord",
+                "config_schema": {
+                    "webhook_url_env": { "type": "string", "description": "Env var holding the Discord webhook URL", "required": true },
+                    "max_retries": { "type": "number", "description": "Retries on rate limits and 5xx errors, with backoff", "default": 3 }
+                }
+            },
+            {
+                "kind": "telegram",
+                "node_type": "sink",
+                "label": "Telegram",
+                "config_schema": {
+                    "bot_token_env": { "type": "string", "description": "Env var holding the Telegram bot token", "required": true },
+                    "chat_id": { "type": "string", "description": "Chat id or @channelname", "required": true },
+                    "max_retries": { "type": "number", "description": "Retries on rate limits and 5xx errors, with backoff", "default": 3 }
+                }
+            },
+            {
+                "kind": "file",
+                "node_type": "sink",
+                "label": "File",
+                "config_schema": {
+                    "path": { "type": "string", "description": "JSONL file to append to; {flow_id} and {date} are filled in. Must be under NODE_WORKING_DIR_ALLOWLIST when set", "required": true }
+                }
+            },
+            {
+                "kind": "http-post",
+                "node_type": "sink",
+                "label": "HTTP POST",
+                "config_schema": {
+                    "url": { "type": "string", "description": "Endpoint to POST output to", "required": true },
+                    "headers": { "type": "object", "description": "Request headers; values may reference ${ENV_VAR}", "default": {} },
+                    "content_type": { "type": "string", "description": "application/json (sends {\"text\": ...}) or text/plain (raw body)", "default": "application/json" },
+                    "max_retries": { "type": "number", "description": "Retries on rate limits and 5xx errors, with backoff", "default": 3 }
+                }
+            },
+            {
+                "kind": "email",
+                "node_type": "sink",
+                "label": "Email",
+                "config_schema": {
+                    "smtp_host_env": { "type": "string", "description": "Env var holding the SMTP host, optionally host:port (default port 587 STARTTLS; 465 is implicit TLS)", "required": true },
+                    "smtp_user_env": { "type": "string", "description": "Env var holding the SMTP username", "required": true },
+                    "smtp_pass_env": { "type": "string", "description": "Env var holding the SMTP password", "required": true },
+                    "from": { "type": "string", "description": "Sender address, e.g. Reports <reports@example.com>", "required": true },
+                    "to": { "type": "array", "description": "Recipient addresses (or one comma-separated string)", "required": true },
+                    "subject": { "type": "string", "description": "Subject; {flow_name} and {date} are filled in", "default": "{flow_name} - {date}" },
+                    "max_retries": { "type": "number", "description": "Retries on rate limits and 5xx errors, with backoff", "default": 3 }
                 }
             },
             {
@@ -448,48 +1407,184 @@ pub(crate) async fn get_node_types() -> Json<Value> {
                 "label": "Notion",
                 "config_schema": {
                     "token_env": { "type": "string", "description": "Env var for Notion token", "required": true },
-                    "database_id": { "type": "string", "description": "Notion database ID", "required": true }
+                    "database_id": { "type": "string", "description": "Notion database ID (required unless append_to_page is set)" },
+                    "append_to_page": { "type": "string", "description": "Page ID to append the output to as blocks, instead of creating a database row" },
+                    "max_retries": { "type": "number", "description": "Retries on rate limits and 5xx errors, with backoff", "default": 3 }
                 }
             }
          ]
     }))
 }
 
+/// Number of characters of each prompt file returned as a preview.
+const PROMPT_PREVIEW_CHARS: usize = 200;
+
 /// GET /api/prompt-files — list prompt files from examples/prompts/ directory.
 pub(crate) async fn list_prompt_files() -> Json<Value> {
-    Json(serde_json::json!({ "files": list_prompt_files_impl() }))
+    let files = list_prompt_files_impl(std::path::Path::new("examples/prompts"));
+    Json(serde_json::json!({ "files": files }))
 }
 
-fn list_prompt_files_impl() -> Vec<Value> {
-    let dir = std::path::Path::new("examples/prompts");
+fn list_prompt_files_impl(dir: &std::path::Path) -> Vec<Value> {
     let mut files: Vec<Value> = Vec::new();
 
-    if let Ok(entries) = std::fs::read_dir(dir) {
+    // Resolve the root once so symlinked entries can be checked against it.
+    let Ok(root) = dir.canonicalize() else {
+        return files;
+    };
+
+    if let Ok(entries) = std::fs::read_dir(&root) {
         let mut entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
         entries.sort_by_key(|e| e.file_name());
 
         for entry in entries {
             let path = entry.path();
-            if path.extension().and_then(|e| e.to_str()) == Some("md") {
-                let rel_path =
-                    format!("examples/prompts/{}", entry.file_name().to_string_lossy());
-                let title = std::fs::read_to_string(&path)
-                    .ok()
-                    .and_then(|content| {
-                        content.lines().find(|l| !l.trim().is_empty()).map(|l| {
-                            l.trim_start_matches('#').trim().to_string()
-                        })
-                    })
-                    .unwrap_or_else(|| entry.file_name().to_string_lossy().to_string());
-
-                files.push(serde_json::json!({
-                    "path": rel_path,
-                    "filename": entry.file_name().to_string_lossy(),
-                    "title": title,
-                }));
+            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
             }
+            let filename = entry.file_name().to_string_lossy().to_string();
+            let rel_path = format!("{}/{}", dir.display(), filename);
+
+            // A dangling symlink fails to canonicalize — report it as unreadable.
+            // A link resolving outside the prompts dir is skipped entirely.
+            let resolved = path.canonicalize().ok();
+            if let Some(resolved) = &resolved
+                && !resolved.starts_with(&root)
+            {
+                tracing::warn!(path = %path.display(), "skipping prompt file outside prompts dir");
+                continue;
+            }
+
+            let metadata = resolved.as_ref().and_then(|p| std::fs::metadata(p).ok());
+            let content = resolved
+                .as_ref()
+                .and_then(|p| std::fs::read_to_string(p).ok());
+
+            let title = content
+                .as_deref()
+                .and_then(|content| {
+                    content
+                        .lines()
+                        .find(|l| !l.trim().is_empty())
+                        .map(|l| l.trim_start_matches('#').trim().to_string())
+                })
+                .unwrap_or_else(|| filename.clone());
+            let preview = content
+                .as_deref()
+                .map(|c| c.chars().take(PROMPT_PREVIEW_CHARS).collect::<String>());
+            let modified_at = metadata
+                .as_ref()
+                .and_then(|m| m.modified().ok())
+                .map(chrono::DateTime::<Utc>::from);
+
+            files.push(serde_json::json!({
+                "path": rel_path,
+                "filename": filename,
+                "title": title,
+                "preview": preview,
+                "size": metadata.as_ref().map(|m| m.len()),
+                "modified_at": modified_at,
+                "readable": content.is_some(),
+            }));
         }
     }
 
     files
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigger_body_empty_is_one_shot() {
+        assert_eq!(parse_trigger_body(""), Ok(TriggerBody::OneShot { prompt_overrides: HashMap::new(), dry_run: false }));
+        assert_eq!(parse_trigger_body("  \n"), Ok(TriggerBody::OneShot { prompt_overrides: HashMap::new(), dry_run: false }));
+    }
+
+    #[test]
+    fn trigger_body_with_repo_and_pr_is_pr_review() {
+        assert_eq!(
+            parse_trigger_body(r#"{"repo": "acme/api", "pr": 42}"#),
+            Ok(TriggerBody::PrReview { repo: "acme/api".into(), pr: 42 })
+        );
+    }
+
+    #[test]
+    fn trigger_body_unrelated_json_is_one_shot() {
+        assert_eq!(
+            parse_trigger_body(r#"{"note": "hi"}"#),
+            Ok(TriggerBody::OneShot { prompt_overrides: HashMap::new(), dry_run: false })
+        );
+    }
+
+    #[test]
+    fn trigger_body_with_prompt_overrides() {
+        assert_eq!(
+            parse_trigger_body(r#"{"prompt_overrides": {"e1": "Be terse"}}"#),
+            Ok(TriggerBody::OneShot {
+                prompt_overrides: HashMap::from([("e1".to_string(), "Be terse".to_string())]),
+                dry_run: false,
+            })
+        );
+        assert!(parse_trigger_body(r#"{"repo": "acme/api", "pr": 42, "prompt_overrides": {"e1": "x"}}"#).is_err());
+    }
+
+    #[test]
+    fn trigger_body_with_dry_run() {
+        assert_eq!(
+            parse_trigger_body(r#"{"dry_run": true}"#),
+            Ok(TriggerBody::OneShot { prompt_overrides: HashMap::new(), dry_run: true })
+        );
+        assert!(parse_trigger_body(r#"{"repo": "acme/api", "pr": 42, "dry_run": true}"#).is_err());
+    }
+
+    #[test]
+    fn trigger_body_malformed_is_rejected() {
+        assert!(parse_trigger_body(r#"{"repo": "acme/api", "pr": }"#).is_err());
+        assert!(parse_trigger_body(r#"{"repo": "acme/api", "pr": "42"}"#).is_err());
+        assert!(parse_trigger_body(r#"{"repo": "acme/api"}"#).is_err());
+    }
+
+    #[test]
+    fn prompt_files_include_preview_and_metadata() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("daily.md"), "# Daily Digest\n\nSummarize {{content}}").unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "ignored").unwrap();
+
+        let files = list_prompt_files_impl(tmp.path());
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["filename"], "daily.md");
+        assert_eq!(files[0]["title"], "Daily Digest");
+        assert_eq!(files[0]["readable"], true);
+        assert!(files[0]["preview"].as_str().unwrap().starts_with("# Daily Digest"));
+        assert_eq!(files[0]["size"], 37);
+        assert!(files[0]["modified_at"].is_string());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dangling_prompt_file_is_reported_unreadable() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(tmp.path().join("gone.md"), tmp.path().join("broken.md"))
+            .unwrap();
+
+        let files = list_prompt_files_impl(tmp.path());
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["filename"], "broken.md");
+        assert_eq!(files[0]["readable"], false);
+        assert!(files[0]["preview"].is_null());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_outside_prompts_dir_is_skipped() {
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.md"), "secret").unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret.md"), tmp.path().join("escape.md"))
+            .unwrap();
+
+        assert!(list_prompt_files_impl(tmp.path()).is_empty());
+    }
+}
@@ -15,9 +15,24 @@ pub fn router() -> Router<AppState> {
                 .put(handlers::update_flow)
                 .delete(handlers::delete_flow),
         )
+        .route("/flows/import", post(handlers::import_flow))
+        .route("/flows/import-url", post(handlers::import_flow_url))
+        .route("/flows/{id}/export", get(handlers::export_flow))
+        .route("/flows/{id}/duplicate", post(handlers::duplicate_flow))
         .route("/flows/{id}/trigger", post(handlers::trigger_flow))
+        .route("/flows/{id}/validate", get(handlers::validate_flow))
+        .route("/flows/{id}/estimate", post(handlers::estimate_flow))
+        .route("/flows/{id}/graph", get(handlers::get_flow_graph))
         .route("/flows/{id}/runs", get(handlers::get_runs))
         .route("/flows/{id}/runs/live", get(handlers::stream_runs))
+        .route("/flows/{id}/runs/compare", get(handlers::compare_runs))
+        .route("/flows/{id}/runs/export", get(handlers::export_runs))
+        .route("/flows/{id}/runs/{run_id}", get(handlers::get_run))
+        .route("/flows/{id}/runs/{run_id}/tags", post(handlers::annotate_run))
+        .route("/flows/{id}/runs/{run_id}/bundle", get(handlers::run_bundle))
+        .route("/flows/{id}/runs/{run_id}/retry", post(handlers::retry_run))
+        .route("/webhooks/github", post(handlers::receive_github_webhook))
+        .route("/webhooks/{*path}", post(handlers::receive_webhook))
         .route("/node-types", get(handlers::get_node_types))
         .route("/prompt-files", get(handlers::list_prompt_files))
 }
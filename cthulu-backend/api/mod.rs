@@ -1,3 +1,4 @@
+pub mod admin;
 pub mod agents;
 pub mod auth;
 pub mod changes;
@@ -10,6 +11,8 @@ pub mod middleware;
 pub mod prompts;
 mod routes;
 pub mod scheduler;
+pub mod search;
+pub mod session_log;
 pub mod templates;
 pub mod user_context;
 
@@ -30,7 +33,7 @@ use crate::flows::session_bridge::FlowRunMeta;
 use crate::git::WorktreeGroupMeta;
 use crate::github::client::GithubClient;
 use crate::prompts::repository::PromptRepository;
-use crate::sandbox::provider::SandboxProvider;
+use crate::sandbox::provider::SandboxProviders;
 
 
 /// A single Claude Code session (one tab in the History list).
@@ -52,15 +55,22 @@ pub struct InteractSession {
     #[serde(skip)]
     pub active_pid: Option<u32>,
     /// Whether a message is currently being processed.
+    ///
+    /// Busy state is runtime-only: no claude process survives a server restart,
+    /// so it is never persisted and is always cleared on load (see `load_sessions`).
     #[serde(skip)]
     pub busy: bool,
     /// When the session became busy (for stale detection). None when idle.
+    /// Runtime-only, like `busy`.
     #[serde(skip)]
     pub busy_since: Option<chrono::DateTime<chrono::Utc>>,
     /// Number of messages exchanged in this session.
     pub message_count: u64,
     /// Cumulative cost (parsed from claude result events).
     pub total_cost: f64,
+    /// New messages are refused once `total_cost` reaches this (USD).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_budget_usd: Option<f64>,
     /// When this session was created (ISO 8601).
     pub created_at: String,
     /// Path to the .skills/ directory for this session.
@@ -81,6 +91,15 @@ fn default_interactive() -> String {
     "interactive".to_string()
 }
 
+impl InteractSession {
+    /// Clear process-bound state that can't outlive the server process.
+    pub fn reset_runtime_state(&mut self) {
+        self.active_pid = None;
+        self.busy = false;
+        self.busy_since = None;
+    }
+}
+
 /// All sessions for a single workflow.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlowSessions {
@@ -150,11 +169,17 @@ pub fn load_sessions(path: &Path) -> HashMap<String, FlowSessions> {
     };
 
     // Try new format first
-    if let Ok(file) = serde_yaml::from_str::<SessionsFile>(&contents) {
+    if let Ok(mut file) = serde_yaml::from_str::<SessionsFile>(&contents) {
         tracing::info!(
             sessions = file.sessions.len(),
             "loaded persisted sessions"
         );
+        // Nothing can still be running after a restart — start every session idle.
+        for flow_sessions in file.sessions.values_mut() {
+            for session in &mut flow_sessions.sessions {
+                session.reset_runtime_state();
+            }
+        }
         return file.sessions;
     }
 
@@ -179,6 +204,7 @@ pub fn load_sessions(path: &Path) -> HashMap<String, FlowSessions> {
                     busy_since: None,
                     message_count: old.message_count,
                     total_cost: old.total_cost,
+                    cost_budget_usd: None,
                     created_at: chrono::Utc::now().to_rfc3339(),
                     skills_dir: None,
                     kind: default_interactive(),
@@ -226,6 +252,7 @@ pub fn save_sessions(
                         busy_since: None,
                         message_count: s.message_count,
                         total_cost: s.total_cost,
+                        cost_budget_usd: s.cost_budget_usd,
                         created_at: s.created_at.clone(),
                         skills_dir: s.skills_dir.clone(),
                         kind: s.kind.clone(),
@@ -276,6 +303,8 @@ pub struct LiveClaudeProcess {
     pub child: tokio::process::Child,
     /// Whether the process is currently processing a message.
     pub busy: bool,
+    /// When the process was spawned (for uptime reporting).
+    pub started_at: std::time::Instant,
 }
 
 impl Drop for LiveClaudeProcess {
@@ -308,8 +337,8 @@ pub struct AppState {
     pub static_dir: PathBuf,
     /// Persistent Claude CLI processes keyed by session key (flow_id::node_id).
     pub live_processes: Arc<Mutex<HashMap<String, LiveClaudeProcess>>>,
-    /// Sandbox provider for isolated executor runs.
-    pub sandbox_provider: Arc<dyn SandboxProvider>,
+    /// Sandbox providers for isolated executor runs, keyed by backend name.
+    pub sandbox_providers: SandboxProviders,
     /// Claude OAuth access token (read from macOS Keychain or CLAUDE_CODE_OAUTH_TOKEN env).
     /// Wrapped in Arc<RwLock> so it can be refreshed at runtime without a restart.
     pub oauth_token: Arc<RwLock<Option<String>>>,
@@ -340,6 +369,20 @@ pub struct AppState {
     pub jwt_secret: Arc<String>,
     /// In-memory user store (email/password accounts).
     pub user_store: Arc<RwLock<local_auth::UserStore>>,
+    /// Keep-alive and idle-timeout settings shared by all SSE endpoints.
+    pub sse: crate::config::SseConfig,
+    /// Validation rules for executor-node working directories.
+    pub working_dir_policy: crate::config::WorkingDirPolicy,
+    /// Size of the output preview stored on each node run.
+    pub run_preview: crate::config::RunPreviewConfig,
+    /// Tools granted to chat sessions whose agent doesn't restrict them.
+    pub chat_permissions: crate::config::ChatPermissionDefaults,
+    /// Serializes flow executors and chat turns sharing a working dir.
+    pub dir_locks: crate::flows::dir_lock::WorkingDirLocks,
+    /// Recent webhook idempotency keys, to drop retried deliveries.
+    pub webhook_deliveries: crate::flows::webhook::SeenDeliveries,
+    /// Set by `POST /admin/drain`: new chat and trigger requests get 503.
+    pub draining: Arc<std::sync::atomic::AtomicBool>,
 }
 
 impl AppState {
@@ -347,8 +390,198 @@ impl AppState {
     pub fn save_sessions_to_disk(&self, sessions: &HashMap<String, FlowSessions>) {
         save_sessions(&self.sessions_path, sessions);
     }
+
+    /// Keep-alive policy for SSE responses.
+    pub fn sse_keep_alive(&self) -> axum::response::sse::KeepAlive {
+        axum::response::sse::KeepAlive::new().interval(self.sse.keep_alive)
+    }
+
+    /// Reject new work with 503 while the server is draining.
+    pub fn ensure_accepting_work(
+        &self,
+    ) -> Result<(), (hyper::StatusCode, axum::Json<serde_json::Value>)> {
+        admin::handlers::ensure_not_draining(&self.draining)
+    }
+}
+
+/// Result of waiting on a broadcast subscriber with an idle timeout.
+pub(crate) enum IdleRecv<T> {
+    Event(T),
+    Lagged(u64),
+    Closed,
+    /// No event arrived within the idle timeout.
+    Idle,
+}
+
+/// Receive the next broadcast message, giving up once `idle` elapses with
+/// nothing received so that a vanished producer can't pin the subscriber forever.
+pub(crate) async fn recv_with_idle_timeout<T: Clone>(
+    rx: &mut broadcast::Receiver<T>,
+    idle: std::time::Duration,
+) -> IdleRecv<T> {
+    match tokio::time::timeout(idle, rx.recv()).await {
+        Ok(Ok(event)) => IdleRecv::Event(event),
+        Ok(Err(broadcast::error::RecvError::Lagged(n))) => IdleRecv::Lagged(n),
+        Ok(Err(broadcast::error::RecvError::Closed)) => IdleRecv::Closed,
+        Err(_) => IdleRecv::Idle,
+    }
+}
+
+/// How often a finished turn re-checks for subscribers still draining it.
+const DRAIN_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Longest a finished turn waits for subscribers after its grace period. A
+/// client that stops reading without disconnecting must not pin the stream.
+const MAX_DRAIN_WAIT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Wait until a finished chat turn's stream can be dropped: `grace` has
+/// passed, so a reconnecting client can still replay `done`, and no
+/// subscriber is still reading it (or `MAX_DRAIN_WAIT` ran out).
+pub(crate) async fn wait_for_stream_drain<T>(tx: &broadcast::Sender<T>, grace: std::time::Duration) {
+    tokio::time::sleep(grace).await;
+    let drained = tokio::time::timeout(MAX_DRAIN_WAIT, async {
+        while tx.receiver_count() > 0 {
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+    })
+    .await;
+    if drained.is_err() {
+        tracing::warn!(
+            subscribers = tx.receiver_count(),
+            waited_secs = MAX_DRAIN_WAIT.as_secs(),
+            "gave up waiting for chat stream subscribers to drain"
+        );
+    }
+}
+
+/// Drop a finished turn's broadcast channel and replay buffer, unless a
+/// newer turn has already replaced them.
+pub(crate) async fn release_turn_stream(
+    session_streams: &Mutex<HashMap<String, broadcast::Sender<String>>>,
+    chat_event_buffers: &Mutex<HashMap<String, Vec<String>>>,
+    key: &str,
+    tx: &broadcast::Sender<String>,
+) -> bool {
+    let mut streams = session_streams.lock().await;
+    if !streams.get(key).is_some_and(|current| current.same_channel(tx)) {
+        return false;
+    }
+    streams.remove(key);
+    chat_event_buffers.lock().await.remove(key);
+    true
 }
 
 pub fn create_app(state: AppState) -> Router {
     routes::build_router(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_session(id: &str) -> InteractSession {
+        InteractSession {
+            session_id: id.to_string(),
+            summary: String::new(),
+            node_id: None,
+            working_dir: ".".to_string(),
+            active_pid: Some(4242),
+            busy: true,
+            busy_since: Some(chrono::Utc::now()),
+            message_count: 3,
+            total_cost: 0.5,
+            cost_budget_usd: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            skills_dir: None,
+            kind: default_interactive(),
+            flow_run: None,
+            worktree_group: None,
+        }
+    }
+
+    #[test]
+    fn busy_state_is_not_restored_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sessions.yaml");
+
+        let mut sessions = HashMap::new();
+        sessions.insert(
+            "agent::a1".to_string(),
+            FlowSessions {
+                flow_name: "Agent".to_string(),
+                active_session: "s1".to_string(),
+                sessions: vec![test_session("s1")],
+            },
+        );
+        save_sessions(&path, &sessions);
+
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("busy"));
+
+        let loaded = load_sessions(&path);
+        let s = loaded["agent::a1"].get_session("s1").unwrap();
+        assert!(!s.busy);
+        assert!(s.busy_since.is_none());
+        assert!(s.active_pid.is_none());
+        // Durable fields survive
+        assert_eq!(s.message_count, 3);
+        assert_eq!(s.cost_budget_usd, None);
+
+        sessions.get_mut("agent::a1").unwrap().sessions[0].cost_budget_usd = Some(2.5);
+        save_sessions(&path, &sessions);
+        let loaded = load_sessions(&path);
+        assert_eq!(loaded["agent::a1"].get_session("s1").unwrap().cost_budget_usd, Some(2.5));
+    }
+
+    #[tokio::test]
+    async fn cleanup_waits_for_last_subscriber_to_drain() {
+        let (tx, mut rx) = broadcast::channel::<String>(16);
+        let streams = Mutex::new(HashMap::from([("agent::a1::s1".to_string(), tx.clone())]));
+        let buffers = Mutex::new(HashMap::from([("agent::a1::s1".to_string(), vec!["done:{}".to_string()])]));
+        tx.send("done:{}".to_string()).unwrap();
+
+        let grace = std::time::Duration::from_millis(50);
+        let slow = std::time::Duration::from_millis(400);
+        let started = std::time::Instant::now();
+        let reader = tokio::spawn(async move {
+            // A slow client still reading well after the grace period
+            tokio::time::sleep(slow).await;
+            assert_eq!(rx.recv().await.unwrap(), "done:{}");
+        });
+
+        wait_for_stream_drain(&tx, grace).await;
+        assert!(started.elapsed() >= slow);
+        reader.await.unwrap();
+        assert!(release_turn_stream(&streams, &buffers, "agent::a1::s1", &tx).await);
+        assert!(streams.lock().await.is_empty() && buffers.lock().await.is_empty());
+
+        // No subscribers left: just the grace period
+        let started = std::time::Instant::now();
+        wait_for_stream_drain(&tx, grace).await;
+        assert!(started.elapsed() < grace + DRAIN_POLL_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn cleanup_leaves_a_newer_turns_stream() {
+        let (old_tx, _) = broadcast::channel::<String>(16);
+        let (new_tx, _) = broadcast::channel::<String>(16);
+        let streams = Mutex::new(HashMap::from([("k".to_string(), new_tx)]));
+        let buffers = Mutex::new(HashMap::from([("k".to_string(), Vec::new())]));
+
+        assert!(!release_turn_stream(&streams, &buffers, "k", &old_tx).await);
+        assert!(streams.lock().await.contains_key("k") && buffers.lock().await.contains_key("k"));
+    }
+
+    #[tokio::test]
+    async fn idle_subscriber_is_released_after_timeout() {
+        let (tx, mut rx) = broadcast::channel::<String>(16);
+        tx.send("done:{}".to_string()).unwrap();
+
+        let idle = std::time::Duration::from_millis(50);
+        assert!(matches!(recv_with_idle_timeout(&mut rx, idle).await, IdleRecv::Event(e) if e == "done:{}"));
+        // Sender is still alive but nothing else is coming — the wait must end.
+        assert!(matches!(recv_with_idle_timeout(&mut rx, idle).await, IdleRecv::Idle));
+        drop(tx);
+        assert!(matches!(recv_with_idle_timeout(&mut rx, idle).await, IdleRecv::Closed));
+    }
+}
@@ -54,10 +54,12 @@ fn api_router() -> Router<AppState> {
         .merge(super::templates::router())
         .merge(super::auth::router())
         .merge(super::scheduler::router())
+        .merge(super::search::router())
         .merge(super::changes::router())
         .merge(super::hooks::router())
         .merge(super::dashboard::router())
         .merge(super::local_auth::router())
+        .merge(super::admin::router())
 }
 
 async fn not_found(req: axum::extract::Request) -> impl IntoResponse {
@@ -5,6 +5,9 @@ use serde::Deserialize;
 use serde_json::{json, Value};
 
 use crate::api::AppState;
+use crate::flows::cost_cap::window_spend;
+use crate::flows::cron_schedule::CronSchedules;
+use crate::flows::history::MAX_RUNS_PER_FLOW;
 
 use super::repository::SchedulerRepository;
 
@@ -33,11 +36,14 @@ pub(crate) async fn get_schedule(
 
     match trigger_kind {
         "cron" => {
-            let schedule = trigger.config.get("schedule")
-                .and_then(|v| v.as_str())
-                .unwrap_or("");
-
-            if schedule.is_empty() {
+            let schedule = trigger.config.get("schedule").cloned().unwrap_or(Value::Null);
+
+            let is_blank = match &schedule {
+                Value::Null => true,
+                Value::String(s) => s.trim().is_empty(),
+                _ => false,
+            };
+            if is_blank {
                 return Ok(Json(json!({
                     "flow_id": id,
                     "trigger_kind": "cron",
@@ -47,31 +53,30 @@ pub(crate) async fn get_schedule(
                 })));
             }
 
-            match croner::Cron::new(schedule).parse() {
-                Ok(cron) => {
+            match CronSchedules::from_config(&schedule) {
+                Ok(schedules) => {
                     let now = chrono::Utc::now();
-                    let next = cron.find_next_occurrence(&now, false).ok();
-                    let next_runs: Vec<String> = {
-                        let mut runs = Vec::new();
-                        let mut cursor = now;
-                        for _ in 0..5 {
-                            if let Ok(n) = cron.find_next_occurrence(&cursor, false) {
-                                runs.push(n.to_rfc3339());
-                                cursor = n + chrono::Duration::seconds(1);
-                            } else {
-                                break;
-                            }
-                        }
-                        runs
-                    };
+                    let entries: Vec<Value> = schedules
+                        .entries()
+                        .iter()
+                        .map(|e| {
+                            json!({
+                                "schedule": e.expression,
+                                "next_run": e.next_after(&now).map(|n| n.to_rfc3339()),
+                            })
+                        })
+                        .collect();
+                    let next_runs: Vec<String> =
+                        schedules.upcoming(&now, 5).iter().map(|n| n.to_rfc3339()).collect();
 
                     Ok(Json(json!({
                         "flow_id": id,
                         "trigger_kind": "cron",
                         "enabled": flow.enabled,
                         "schedule": schedule,
-                        "next_run": next.map(|n| n.to_rfc3339()),
+                        "next_run": schedules.next_after(&now).map(|n| n.to_rfc3339()),
                         "next_runs": next_runs,
+                        "schedules": entries,
                     })))
                 }
                 Err(e) => {
@@ -80,7 +85,7 @@ pub(crate) async fn get_schedule(
                         "trigger_kind": "cron",
                         "schedule": schedule,
                         "next_run": null,
-                        "error": format!("invalid cron: {e}"),
+                        "error": format!("{e:#}"),
                     })))
                 }
             }
@@ -114,17 +119,34 @@ pub(crate) async fn scheduler_status(
 ) -> Json<Value> {
     let repo = SchedulerRepository::new(state.flow_repo.clone(), state.scheduler.clone());
     let active_ids = repo.active_flow_ids().await;
+    let restart_counts = repo.restart_counts();
     let flows = repo.list_flows().await;
 
-    let flow_statuses: Vec<Value> = flows.iter().map(|f| {
+    let now = chrono::Utc::now();
+    let mut flow_statuses: Vec<Value> = Vec::with_capacity(flows.len());
+    for f in &flows {
         let is_active = active_ids.contains(&f.id);
-        json!({
+        // Spend within the cap's window, for flows that have one
+        let cost_window = match &f.cost_cap {
+            Some(cap) => {
+                let runs = repo.get_runs(&f.id, MAX_RUNS_PER_FLOW).await;
+                json!({
+                    "max_usd": cap.max_usd,
+                    "window_hours": cap.window_hours,
+                    "spent_usd": window_spend(&runs, now, cap.window()),
+                })
+            }
+            None => Value::Null,
+        };
+        flow_statuses.push(json!({
             "flow_id": f.id,
             "name": f.name,
             "enabled": f.enabled,
             "scheduler_active": is_active,
-        })
-    }).collect();
+            "restart_count": restart_counts.get(&f.id).copied().unwrap_or(0),
+            "cost_window": cost_window,
+        }));
+    }
 
     Json(json!({
         "active_count": active_ids.len(),
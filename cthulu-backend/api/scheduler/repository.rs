@@ -1,7 +1,9 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::flows::scheduler::FlowScheduler;
 use crate::flows::repository::FlowRepository;
+use crate::flows::history::FlowRun;
 use crate::flows::Flow;
 
 pub struct SchedulerRepository {
@@ -22,7 +24,15 @@ impl SchedulerRepository {
         self.flow_repo.list_flows().await
     }
 
+    pub async fn get_runs(&self, flow_id: &str, limit: usize) -> Vec<FlowRun> {
+        self.flow_repo.get_runs(flow_id, limit).await
+    }
+
     pub async fn active_flow_ids(&self) -> Vec<String> {
         self.scheduler.active_flow_ids().await
     }
+
+    pub fn restart_counts(&self) -> HashMap<String, u32> {
+        self.scheduler.restart_counts()
+    }
 }
@@ -0,0 +1,208 @@
+use axum::extract::{Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::changes::ResourceType;
+use super::AppState;
+use crate::agents::Agent;
+use crate::flows::Flow;
+use crate::prompts::SavedPrompt;
+
+const DEFAULT_LIMIT: usize = 50;
+
+// Name matches outrank everything else; exact beats prefix beats substring.
+const EXACT_NAME: u32 = 100;
+const NAME_PREFIX: u32 = 60;
+const NAME_CONTAINS: u32 = 40;
+const LABEL_OR_TAG: u32 = 20;
+const DESCRIPTION: u32 = 10;
+
+#[derive(Deserialize)]
+pub(crate) struct SearchQuery {
+    #[serde(default)]
+    q: String,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    #[serde(rename = "type")]
+    pub resource_type: ResourceType,
+    pub id: String,
+    pub name: String,
+    pub score: u32,
+    /// Which field produced the score ("name", "description", "node_label", ...).
+    pub matched: &'static str,
+}
+
+/// Score `name` against a lowercased query.
+fn name_score(name: &str, query: &str) -> u32 {
+    let name = name.to_lowercase();
+    if name == query {
+        EXACT_NAME
+    } else if name.starts_with(query) {
+        NAME_PREFIX
+    } else if name.contains(query) {
+        NAME_CONTAINS
+    } else {
+        0
+    }
+}
+
+fn contains(text: &str, query: &str) -> bool {
+    text.to_lowercase().contains(query)
+}
+
+/// Best-scoring field out of the candidates, if any matched.
+fn best(candidates: impl IntoIterator<Item = (u32, &'static str)>) -> Option<(u32, &'static str)> {
+    candidates.into_iter().filter(|(score, _)| *score > 0).max_by_key(|(score, _)| *score)
+}
+
+fn hit(resource_type: ResourceType, id: &str, name: &str, matched: Option<(u32, &'static str)>) -> Option<SearchHit> {
+    matched.map(|(score, matched)| SearchHit {
+        resource_type,
+        id: id.to_string(),
+        name: name.to_string(),
+        score,
+        matched,
+    })
+}
+
+fn score_flow(flow: &Flow, query: &str) -> Option<SearchHit> {
+    let matched = best([
+        (name_score(&flow.name, query), "name"),
+        (
+            if flow.nodes.iter().any(|n| contains(&n.label, query)) { LABEL_OR_TAG } else { 0 },
+            "node_label",
+        ),
+        (if contains(&flow.description, query) { DESCRIPTION } else { 0 }, "description"),
+    ]);
+    hit(ResourceType::Flow, &flow.id, &flow.name, matched)
+}
+
+fn score_prompt(prompt: &SavedPrompt, query: &str) -> Option<SearchHit> {
+    let matched = best([
+        (name_score(&prompt.title, query), "title"),
+        (if prompt.tags.iter().any(|t| contains(t, query)) { LABEL_OR_TAG } else { 0 }, "tag"),
+        (if contains(&prompt.summary, query) { DESCRIPTION } else { 0 }, "summary"),
+    ]);
+    hit(ResourceType::Prompt, &prompt.id, &prompt.title, matched)
+}
+
+fn score_agent(agent: &Agent, query: &str) -> Option<SearchHit> {
+    let matched = best([
+        (name_score(&agent.name, query), "name"),
+        (if contains(&agent.description, query) { DESCRIPTION } else { 0 }, "description"),
+    ]);
+    hit(ResourceType::Agent, &agent.id, &agent.name, matched)
+}
+
+/// Rank flows, prompts and agents against `query`, best first.
+pub fn search(
+    flows: &[Flow],
+    prompts: &[SavedPrompt],
+    agents: &[Agent],
+    query: &str,
+    limit: usize,
+) -> Vec<SearchHit> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut hits: Vec<SearchHit> = flows
+        .iter()
+        .filter_map(|f| score_flow(f, &query))
+        .chain(prompts.iter().filter_map(|p| score_prompt(p, &query)))
+        .chain(agents.iter().filter_map(|a| score_agent(a, &query)))
+        .collect();
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+    hits.truncate(limit);
+    hits
+}
+
+/// GET /search?q=...&limit=... — ranked hits across flows, prompts and agents
+pub(crate) async fn search_all(
+    State(state): State<AppState>,
+    Query(params): Query<SearchQuery>,
+) -> Json<Value> {
+    let flows = state.flow_repo.list_flows().await;
+    let prompts = state.prompt_repo.list_prompts().await;
+    let agents = state.agent_repo.list().await;
+    let hits = search(&flows, &prompts, &agents, &params.q, params.limit.unwrap_or(DEFAULT_LIMIT));
+    Json(json!({ "query": params.q, "hits": hits }))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/search", get(search_all))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::{Node, NodeType, Position};
+
+    fn flow(id: &str, name: &str, node_label: &str) -> Flow {
+        let mut flow: Flow = serde_json::from_value(json!({
+            "id": id,
+            "name": name,
+            "description": "",
+            "enabled": true,
+            "nodes": [],
+            "edges": [],
+            "created_at": "2026-01-01T00:00:00Z",
+            "updated_at": "2026-01-01T00:00:00Z",
+        }))
+        .unwrap();
+        flow.nodes.push(Node {
+            id: "n1".to_string(),
+            node_type: NodeType::Sink,
+            kind: "slack".to_string(),
+            config: json!({}),
+            position: Position { x: 0.0, y: 0.0 },
+            label: node_label.to_string(),
+        });
+        flow
+    }
+
+    fn prompt(id: &str, title: &str, tags: &[&str]) -> SavedPrompt {
+        SavedPrompt {
+            id: id.to_string(),
+            title: title.to_string(),
+            summary: String::new(),
+            source_flow_name: String::new(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn agent(id: &str, name: &str, description: &str) -> Agent {
+        Agent::builder(id).name(name).description(description).build()
+    }
+
+    #[test]
+    fn test_search_spans_types_and_ranks_exact_names_first() {
+        let flows = vec![
+            flow("f1", "Daily digest", "Post to #eng"),
+            flow("f2", "Eng", "Slack"),
+        ];
+        let prompts = vec![prompt("p1", "Engineering brief", &[]), prompt("p2", "Misc", &["eng"])];
+        let agents = vec![
+            agent("a1", "Reviewer", "Reviews eng PRs"),
+            agent("a2", "Writer", "Drafts posts"),
+        ];
+
+        let hits = search(&flows, &prompts, &agents, "  ENG ", 50);
+        let ids: Vec<&str> = hits.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, vec!["f2", "p1", "f1", "p2", "a1"]);
+
+        assert_eq!(hits[0].matched, "name");
+        assert_eq!(hits[0].score, EXACT_NAME);
+        assert!(matches!(hits[4].resource_type, ResourceType::Agent));
+        assert_eq!(hits[4].matched, "description");
+
+        assert!(search(&flows, &prompts, &agents, "", 50).is_empty());
+        assert_eq!(search(&flows, &prompts, &agents, "eng", 2).len(), 2);
+    }
+}
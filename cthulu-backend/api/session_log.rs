@@ -0,0 +1,88 @@
+//! Append-only session logs at `<data_dir>/session_logs/{session_id}.jsonl`,
+//! shared by interactive chat turns and flow-run sessions.
+
+use std::path::{Path, PathBuf};
+
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+pub fn log_path(data_dir: &Path, session_id: &str) -> PathBuf {
+    data_dir.join("session_logs").join(format!("{session_id}.jsonl"))
+}
+
+/// Appends lines to a session log from a background task, so callers on the
+/// SSE relay path never wait on the disk.
+pub struct SessionLogWriter {
+    tx: mpsc::UnboundedSender<String>,
+    task: JoinHandle<()>,
+}
+
+impl SessionLogWriter {
+    /// Spawn the writer task for `session_id`'s log. Lines are written in
+    /// order and flushed whenever the queue runs dry.
+    pub fn spawn(data_dir: &Path, session_id: &str) -> Self {
+        let path = log_path(data_dir, session_id);
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        let task = tokio::spawn(async move {
+            if let Some(dir) = path.parent() {
+                let _ = tokio::fs::create_dir_all(dir).await;
+            }
+            let file = match tokio::fs::OpenOptions::new().create(true).append(true).open(&path).await {
+                Ok(file) => file,
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "failed to open session log");
+                    return;
+                }
+            };
+            let mut writer = tokio::io::BufWriter::new(file);
+            while let Some(first) = rx.recv().await {
+                let mut next = Some(first);
+                while let Some(mut line) = next {
+                    line.push('\n');
+                    if let Err(e) = writer.write_all(line.as_bytes()).await {
+                        tracing::warn!(path = %path.display(), error = %e, "failed to append to session log");
+                    }
+                    next = rx.try_recv().ok();
+                }
+                // Caught up: make the lines so far visible to log readers
+                let _ = writer.flush().await;
+            }
+        });
+        Self { tx, task }
+    }
+
+    /// Queue `line` for appending. Never blocks.
+    pub fn append(&self, line: &str) {
+        let _ = self.tx.send(line.to_string());
+    }
+
+    /// Wait until every queued line has been written.
+    pub async fn close(self) {
+        drop(self.tx);
+        let _ = self.task.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_appends_in_order_across_writers() {
+        let dir = tempdir().unwrap();
+        let writer = SessionLogWriter::spawn(dir.path(), "s1");
+        writer.append(r#"user:{"text":"hi"}"#);
+        writer.append(r#"text:{"text":"hello"}"#);
+        writer.close().await;
+
+        // A later turn appends to the same file
+        let writer = SessionLogWriter::spawn(dir.path(), "s1");
+        writer.append(r#"done:{}"#);
+        writer.close().await;
+
+        let content = std::fs::read_to_string(log_path(dir.path(), "s1")).unwrap();
+        assert_eq!(content, "user:{\"text\":\"hi\"}\ntext:{\"text\":\"hello\"}\ndone:{}\n");
+    }
+}
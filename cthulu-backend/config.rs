@@ -46,6 +46,340 @@ impl Config {
     }
 }
 
+/// Tuning for server-sent event streams, loaded from environment variables.
+#[derive(Debug, Clone, Copy)]
+pub struct SseConfig {
+    /// Interval between keep-alive comments on every SSE stream.
+    pub keep_alive: std::time::Duration,
+    /// How long a relay may wait without receiving an event before the
+    /// stream is closed and its subscriber released.
+    pub idle_timeout: std::time::Duration,
+    /// How long a finished chat turn's stream and replay buffer are kept
+    /// for reconnecting clients. They also outlive any still-connected
+    /// subscriber. `0` = only wait for subscribers.
+    pub done_grace: std::time::Duration,
+}
+
+impl SseConfig {
+    pub fn from_env() -> Self {
+        Self::from_raw_values(
+            std::env::var("SSE_KEEPALIVE_SECS").ok().as_deref(),
+            std::env::var("SSE_IDLE_TIMEOUT_SECS").ok().as_deref(),
+            std::env::var("SSE_DONE_GRACE_SECS").ok().as_deref(),
+        )
+    }
+
+    pub fn from_raw_values(
+        keep_alive_secs: Option<&str>,
+        idle_timeout_secs: Option<&str>,
+        done_grace_secs: Option<&str>,
+    ) -> Self {
+        let keep_alive = keep_alive_secs
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(15);
+        let idle_timeout = idle_timeout_secs
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(600);
+        let done_grace = done_grace_secs
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(5);
+
+        SseConfig {
+            keep_alive: std::time::Duration::from_secs(keep_alive),
+            idle_timeout: std::time::Duration::from_secs(idle_timeout),
+            done_grace: std::time::Duration::from_secs(done_grace),
+        }
+    }
+}
+
+impl Default for SseConfig {
+    fn default() -> Self {
+        Self::from_raw_values(None, None, None)
+    }
+}
+
+/// Rules for executor-node `working_dir` overrides, loaded from environment variables.
+#[derive(Debug, Clone, Default)]
+pub struct WorkingDirPolicy {
+    /// Directories a node's working dir must live under. Empty = anywhere.
+    pub allowed_roots: Vec<std::path::PathBuf>,
+    /// Reject an invalid working dir instead of falling back to the CWD.
+    pub strict: bool,
+}
+
+impl WorkingDirPolicy {
+    pub fn from_env() -> Self {
+        Self::from_raw_values(
+            std::env::var("NODE_WORKING_DIR_ALLOWLIST").ok().as_deref(),
+            std::env::var("NODE_WORKING_DIR_STRICT").ok().as_deref(),
+        )
+    }
+
+    pub fn from_raw_values(allowlist: Option<&str>, strict: Option<&str>) -> Self {
+        let allowed_roots = allowlist
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(std::path::PathBuf::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let strict = strict.map(|v| v == "true").unwrap_or(false);
+
+        WorkingDirPolicy { allowed_roots, strict }
+    }
+
+    /// Check that `dir` exists, is a directory and sits under an allowed root.
+    /// Returns the canonical path.
+    pub fn check(&self, dir: &std::path::Path) -> anyhow::Result<std::path::PathBuf> {
+        let canonical = dir
+            .canonicalize()
+            .map_err(|e| anyhow::anyhow!("working_dir '{}' does not exist: {e}", dir.display()))?;
+        if !canonical.is_dir() {
+            anyhow::bail!("working_dir '{}' is not a directory", dir.display());
+        }
+        if !self.allowed_roots.is_empty()
+            && !self.allowed_roots.iter().any(|root| {
+                root.canonicalize()
+                    .is_ok_and(|root| canonical.starts_with(root))
+            })
+        {
+            anyhow::bail!(
+                "working_dir '{}' is outside the allowed directories",
+                canonical.display()
+            );
+        }
+        Ok(canonical)
+    }
+
+    /// Create `dir` (and its parents) if missing, then [`check`](Self::check)
+    /// it. The path is vetted against the allowed roots before anything is
+    /// created, so a disallowed dir is never made.
+    pub fn create(&self, dir: &std::path::Path) -> anyhow::Result<std::path::PathBuf> {
+        if dir.components().any(|c| c == std::path::Component::ParentDir) {
+            anyhow::bail!("working_dir '{}' must not contain '..'", dir.display());
+        }
+        if !dir.exists()
+            && !self.allowed_roots.is_empty()
+            && !self.allowed_roots.iter().any(|root| {
+                dir.starts_with(root) || root.canonicalize().is_ok_and(|root| dir.starts_with(root))
+            })
+        {
+            anyhow::bail!("working_dir '{}' is outside the allowed directories", dir.display());
+        }
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow::anyhow!("failed to create working_dir '{}': {e}", dir.display()))?;
+        self.check(dir)
+    }
+
+    /// Resolve a configured working dir: the checked path if valid; otherwise
+    /// an error in strict mode, or the process CWD (with a warning).
+    pub fn resolve(&self, configured: Option<&str>) -> anyhow::Result<std::path::PathBuf> {
+        let cwd = || std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        let Some(configured) = configured.filter(|s| !s.is_empty()) else {
+            return Ok(cwd());
+        };
+        match self.check(std::path::Path::new(configured)) {
+            Ok(dir) => Ok(dir),
+            Err(e) if self.strict => Err(e),
+            Err(e) => {
+                tracing::warn!(error = %e, "invalid working_dir, falling back to current directory");
+                Ok(cwd())
+            }
+        }
+    }
+
+    /// Check a node's `additional_dirs` (a JSON array of paths). There's no
+    /// fallback as with `working_dir`: any missing or disallowed entry is an
+    /// error. Returns the canonical paths.
+    pub fn check_additional(&self, configured: &serde_json::Value) -> anyhow::Result<Vec<std::path::PathBuf>> {
+        let Some(entries) = configured.as_array() else {
+            if configured.is_null() {
+                return Ok(Vec::new());
+            }
+            anyhow::bail!("additional_dirs must be a list of paths");
+        };
+        entries
+            .iter()
+            .filter_map(|entry| match entry.as_str().map(str::trim) {
+                Some("") => None,
+                Some(dir) => Some(
+                    self.check(std::path::Path::new(dir))
+                        .map_err(|e| anyhow::anyhow!("additional_dirs: {e}")),
+                ),
+                None => Some(Err(anyhow::anyhow!("additional_dirs entries must be strings"))),
+            })
+            .collect()
+    }
+}
+
+/// Tool access for interactive chat when an agent sets neither a permission
+/// mode nor an allowlist, loaded from environment variables.
+#[derive(Debug, Clone)]
+pub struct ChatPermissionDefaults {
+    /// Give such agents `tools` instead of unrestricted access. Skipping all
+    /// permission checks then needs `permission_mode: bypass` on the agent.
+    pub safe_mode: bool,
+    pub tools: Vec<String>,
+}
+
+impl ChatPermissionDefaults {
+    pub fn from_env() -> Self {
+        Self::from_raw_values(
+            std::env::var("CHAT_SAFE_PERMISSIONS").ok().as_deref(),
+            std::env::var("CHAT_DEFAULT_PERMISSIONS").ok().as_deref(),
+        )
+    }
+
+    pub fn from_raw_values(safe_mode: Option<&str>, tools: Option<&str>) -> Self {
+        let safe_mode = safe_mode.map(|v| v != "false").unwrap_or(true);
+        let tools: Vec<String> = tools
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let tools = if tools.is_empty() {
+            ["Read", "Grep", "Glob"].map(String::from).to_vec()
+        } else {
+            tools
+        };
+
+        ChatPermissionDefaults { safe_mode, tools }
+    }
+
+    /// Allowlist for a chat turn. The agent's own mode or allowlist wins;
+    /// otherwise the node's `chat_permissions`, then the safe default.
+    pub fn resolve(
+        &self,
+        agent_permissions: &[String],
+        agent_mode: Option<crate::agents::PermissionMode>,
+        node_permissions: &serde_json::Value,
+    ) -> Vec<String> {
+        if agent_mode.is_some() || !agent_permissions.is_empty() {
+            return agent_permissions.to_vec();
+        }
+        let node_permissions: Vec<String> = node_permissions
+            .as_array()
+            .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+            .unwrap_or_default();
+        if !node_permissions.is_empty() {
+            node_permissions
+        } else if self.safe_mode {
+            self.tools.clone()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl Default for ChatPermissionDefaults {
+    fn default() -> Self {
+        Self::from_raw_values(None, None)
+    }
+}
+
+/// Size of the `output_preview` stored on each node run, loaded from
+/// environment variables.
+#[derive(Debug, Clone, Copy)]
+pub struct RunPreviewConfig {
+    /// Characters of node output kept in the preview.
+    pub chars: usize,
+}
+
+impl RunPreviewConfig {
+    pub fn from_env() -> Self {
+        Self::from_raw_value(std::env::var("RUN_PREVIEW_CHARS").ok().as_deref())
+    }
+
+    pub fn from_raw_value(chars: Option<&str>) -> Self {
+        let chars = chars
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(500);
+
+        RunPreviewConfig { chars }
+    }
+}
+
+impl Default for RunPreviewConfig {
+    fn default() -> Self {
+        Self::from_raw_value(None)
+    }
+}
+
+/// Tuning for the shared outbound HTTP client, loaded from environment variables.
+#[derive(Debug, Clone)]
+pub struct HttpClientConfig {
+    pub connect_timeout: std::time::Duration,
+    /// Total per-request timeout (connect + transfer).
+    pub request_timeout: std::time::Duration,
+    pub pool_max_idle_per_host: usize,
+    /// Proxy for all outbound requests, e.g. `http://proxy.internal:3128`.
+    pub proxy: Option<String>,
+}
+
+impl HttpClientConfig {
+    pub fn from_env() -> Self {
+        Self::from_raw_values(
+            std::env::var("HTTP_CONNECT_TIMEOUT_SECS").ok().as_deref(),
+            std::env::var("HTTP_REQUEST_TIMEOUT_SECS").ok().as_deref(),
+            std::env::var("HTTP_POOL_MAX_IDLE_PER_HOST").ok().as_deref(),
+            std::env::var("HTTP_CLIENT_PROXY").ok().as_deref(),
+        )
+    }
+
+    pub fn from_raw_values(
+        connect_timeout_secs: Option<&str>,
+        request_timeout_secs: Option<&str>,
+        pool_max_idle_per_host: Option<&str>,
+        proxy: Option<&str>,
+    ) -> Self {
+        let secs = |v: Option<&str>, default: u64| {
+            v.and_then(|v| v.parse::<u64>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        };
+        let pool_max_idle_per_host = pool_max_idle_per_host
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(usize::MAX);
+        let proxy = proxy.map(str::trim).filter(|s| !s.is_empty()).map(String::from);
+
+        HttpClientConfig {
+            connect_timeout: std::time::Duration::from_secs(secs(connect_timeout_secs, 10)),
+            request_timeout: std::time::Duration::from_secs(secs(request_timeout_secs, 30)),
+            pool_max_idle_per_host,
+            proxy,
+        }
+    }
+
+    /// A `reqwest` builder with these settings applied.
+    pub fn builder(&self) -> anyhow::Result<reqwest::ClientBuilder> {
+        let mut builder = reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host);
+        if let Some(proxy) = &self.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| anyhow::anyhow!("invalid HTTP_CLIENT_PROXY '{proxy}': {e}"))?;
+            builder = builder.proxy(proxy);
+        }
+        Ok(builder)
+    }
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self::from_raw_values(None, None, None, None)
+    }
+}
+
 // --- Source and Sink types used by flow runner ---
 
 #[derive(Debug, Clone, Deserialize)]
@@ -57,17 +391,33 @@ pub enum SourceConfig {
         limit: usize,
         #[serde(default)]
         keywords: Vec<String>,
+        /// Extra request headers; values may reference `${ENV_VAR}`.
+        #[serde(default)]
+        headers: std::collections::BTreeMap<String, String>,
     },
     WebScrape {
         url: String,
         #[serde(default)]
         keywords: Vec<String>,
+        #[serde(default)]
+        headers: std::collections::BTreeMap<String, String>,
     },
     GithubMergedPrs {
         repos: Vec<String>,
         #[serde(default = "default_since_days")]
         since_days: u64,
     },
+    GithubIssues {
+        repos: Vec<String>,
+        #[serde(default)]
+        state: crate::tasks::sources::github_issues::IssueState,
+        /// Issues must carry every label (filtered by GitHub).
+        #[serde(default)]
+        labels: Vec<String>,
+        /// Only issues updated within this many days.
+        #[serde(default = "default_since_days")]
+        since_days: u64,
+    },
     GoogleSheets {
         spreadsheet_id: String,
         #[serde(default)]
@@ -94,7 +444,113 @@ pub enum SourceConfig {
         date_format: Option<String>,
         #[serde(default = "default_rss_limit")]
         limit: usize,
+        #[serde(default)]
+        headers: std::collections::BTreeMap<String, String>,
+        /// CSS selector for the "next page" link; unset = first page only.
+        #[serde(default)]
+        next_page_selector: Option<String>,
+        #[serde(default = "default_max_pages")]
+        max_pages: usize,
+    },
+    #[serde(rename = "youtube")]
+    YouTube {
+        #[serde(default)]
+        channel_id: Option<String>,
+        #[serde(default)]
+        playlist_id: Option<String>,
+        #[serde(default = "default_rss_limit")]
+        limit: usize,
+        #[serde(default)]
+        keywords: Vec<String>,
+    },
+    Sitemap {
+        url: String,
+        #[serde(default = "default_rss_limit")]
+        limit: usize,
+        #[serde(default = "default_since_days")]
+        since_days: u64,
+        #[serde(default)]
+        keywords: Vec<String>,
+        /// Fetch each page's `<title>` instead of deriving it from the URL slug.
+        #[serde(default)]
+        fetch_titles: bool,
     },
+    #[serde(rename = "hackernews")]
+    HackerNews {
+        #[serde(default)]
+        story_type: crate::tasks::sources::hackernews::StoryType,
+        #[serde(default = "default_rss_limit")]
+        limit: usize,
+        #[serde(default)]
+        keywords: Vec<String>,
+    },
+    Reddit {
+        subreddit: String,
+        #[serde(default)]
+        sort: crate::tasks::sources::reddit::RedditSort,
+        #[serde(default = "default_rss_limit")]
+        limit: usize,
+        #[serde(default)]
+        keywords: Vec<String>,
+    },
+    Rest(RestSourceConfig),
+}
+
+/// A paginated JSON REST API. Paths are dot-separated (`data.items`, `links.next`)
+/// or JSONPath-style (`$.data.items[*]`, `$.links.next`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct RestSourceConfig {
+    pub url: String,
+    #[serde(default)]
+    pub query: std::collections::BTreeMap<String, String>,
+    /// Header values may reference env vars as `${VAR}`.
+    #[serde(default)]
+    pub headers: std::collections::BTreeMap<String, String>,
+    /// Path to the array of items in each response; empty = the response itself.
+    #[serde(default)]
+    pub items_path: String,
+    #[serde(default = "default_title_path")]
+    pub title_path: String,
+    #[serde(default = "default_url_path")]
+    pub url_path: String,
+    #[serde(default = "default_summary_path")]
+    pub summary_path: String,
+    #[serde(default)]
+    pub date_path: Option<String>,
+    #[serde(default)]
+    pub image_path: Option<String>,
+    /// Path to the next-page cursor (or next-page URL); unset = single page.
+    #[serde(default)]
+    pub cursor_path: Option<String>,
+    /// Query parameter the cursor is sent back in.
+    #[serde(default = "default_cursor_param")]
+    pub cursor_param: String,
+    #[serde(default = "default_max_pages")]
+    pub max_pages: usize,
+    #[serde(default = "default_rss_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+fn default_title_path() -> String {
+    "title".to_string()
+}
+
+fn default_url_path() -> String {
+    "url".to_string()
+}
+
+fn default_summary_path() -> String {
+    "summary".to_string()
+}
+
+fn default_cursor_param() -> String {
+    "cursor".to_string()
+}
+
+fn default_max_pages() -> usize {
+    5
 }
 
 fn default_rss_limit() -> usize {
@@ -105,6 +561,18 @@ fn default_since_days() -> u64 {
     7
 }
 
+fn default_sanitize_mentions() -> bool {
+    true
+}
+
+fn default_slack_max_blocks() -> usize {
+    crate::tasks::sinks::slack::blocks::MAX_BLOCKS_PER_MESSAGE
+}
+
+fn default_http_post_json() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum SinkConfig {
@@ -112,10 +580,60 @@ pub enum SinkConfig {
         webhook_url_env: Option<String>,
         bot_token_env: Option<String>,
         channel: Option<String>,
+        /// Let Slack expand link previews (off by default — digests carry many links).
+        #[serde(default)]
+        unfurl_links: bool,
+        #[serde(default)]
+        unfurl_media: bool,
+        /// Neutralize `@here` / `@channel` / `@everyone` in model output.
+        #[serde(default = "default_sanitize_mentions")]
+        sanitize_mentions: bool,
+        /// Blocks per Slack message before truncating (Slack allows at most 50).
+        #[serde(default = "default_slack_max_blocks")]
+        max_blocks: usize,
+        /// When a message is truncated, post the full output as thread replies.
+        #[serde(default)]
+        overflow_to_thread: bool,
+    },
+    Discord {
+        webhook_url_env: String,
+    },
+    Telegram {
+        bot_token_env: String,
+        /// Numeric chat id or `@channelname`.
+        chat_id: String,
+    },
+    File {
+        /// JSONL file to append to; `{flow_id}` and `{date}` are filled in.
+        path: String,
+    },
+    HttpPost {
+        url: String,
+        /// Request headers; values may reference `${ENV_VAR}`.
+        #[serde(default)]
+        headers: std::collections::BTreeMap<String, String>,
+        /// Send `{"text": ...}` as JSON; otherwise the raw text as `text/plain`.
+        #[serde(default = "default_http_post_json")]
+        json: bool,
     },
     Notion {
         token_env: String,
+        /// Database to create a page in; unused when `append_to_page` is set.
+        #[serde(default)]
         database_id: String,
+        /// Append the output as blocks to this existing page instead.
+        #[serde(default)]
+        append_to_page: Option<String>,
+    },
+    Email {
+        /// Env var holding the SMTP host, optionally `host:port` (default 587).
+        smtp_host_env: String,
+        smtp_user_env: String,
+        smtp_pass_env: String,
+        from: String,
+        to: Vec<String>,
+        /// `{flow_name}` and `{date}` are filled in.
+        subject: String,
     },
 }
 
@@ -170,4 +688,198 @@ mod tests {
         let config = Config::from_raw_values(None, None, None, None);
         assert!(!config.auth_enabled);
     }
+
+    #[test]
+    fn test_sse_config_defaults() {
+        let sse = SseConfig::from_raw_values(None, None, None);
+        assert_eq!(sse.keep_alive.as_secs(), 15);
+        assert_eq!(sse.idle_timeout.as_secs(), 600);
+        assert_eq!(sse.done_grace.as_secs(), 5);
+    }
+
+    #[test]
+    fn test_sse_config_custom_and_invalid_values() {
+        let sse = SseConfig::from_raw_values(Some("5"), Some("0"), Some("0"));
+        assert_eq!(sse.keep_alive.as_secs(), 5);
+        assert_eq!(sse.idle_timeout.as_secs(), 600);
+        assert!(sse.done_grace.is_zero());
+    }
+
+    #[test]
+    fn test_run_preview_config_from_raw_value() {
+        assert_eq!(RunPreviewConfig::from_raw_value(None).chars, 500);
+        assert_eq!(RunPreviewConfig::from_raw_value(Some("2000")).chars, 2000);
+        assert_eq!(RunPreviewConfig::from_raw_value(Some("0")).chars, 500);
+        assert_eq!(RunPreviewConfig::from_raw_value(Some("lots")).chars, 500);
+    }
+
+    #[test]
+    fn test_http_client_config_defaults() {
+        let config = HttpClientConfig::default();
+        assert_eq!(config.connect_timeout, std::time::Duration::from_secs(10));
+        assert_eq!(config.request_timeout, std::time::Duration::from_secs(30));
+        assert_eq!(config.pool_max_idle_per_host, usize::MAX);
+        assert!(config.proxy.is_none());
+        assert!(config.builder().unwrap().build().is_ok());
+    }
+
+    #[test]
+    fn test_http_client_config_custom_values() {
+        let config = HttpClientConfig::from_raw_values(
+            Some("3"),
+            Some("120"),
+            Some("4"),
+            Some("http://proxy.internal:3128"),
+        );
+        assert_eq!(config.connect_timeout, std::time::Duration::from_secs(3));
+        assert_eq!(config.request_timeout, std::time::Duration::from_secs(120));
+        assert_eq!(config.pool_max_idle_per_host, 4);
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy.internal:3128"));
+        assert!(config.builder().unwrap().build().is_ok());
+
+        // Zero / garbage timeouts fall back to the defaults.
+        let config = HttpClientConfig::from_raw_values(Some("0"), Some("soon"), None, Some(""));
+        assert_eq!(config.connect_timeout, std::time::Duration::from_secs(10));
+        assert_eq!(config.request_timeout, std::time::Duration::from_secs(30));
+        assert!(config.proxy.is_none());
+    }
+
+    #[test]
+    fn test_http_client_config_invalid_proxy() {
+        let config = HttpClientConfig::from_raw_values(None, None, None, Some("not a url"));
+        let err = config.builder().unwrap_err();
+        assert!(err.to_string().contains("HTTP_CLIENT_PROXY"));
+    }
+
+    #[test]
+    fn test_working_dir_policy_from_raw_values() {
+        let policy = WorkingDirPolicy::from_raw_values(Some("/srv/a, /srv/b,"), Some("true"));
+        assert_eq!(policy.allowed_roots.len(), 2);
+        assert!(policy.strict);
+
+        let policy = WorkingDirPolicy::from_raw_values(None, None);
+        assert!(policy.allowed_roots.is_empty());
+        assert!(!policy.strict);
+    }
+
+    #[test]
+    fn test_working_dir_policy_valid_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let policy = WorkingDirPolicy {
+            allowed_roots: vec![root.path().to_path_buf()],
+            strict: true,
+        };
+        let resolved = policy.resolve(project.to_str()).unwrap();
+        assert_eq!(resolved, project.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_working_dir_policy_missing_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let missing = root.path().join("nope");
+        let file = root.path().join("file.txt");
+        std::fs::write(&file, "x").unwrap();
+
+        let strict = WorkingDirPolicy { allowed_roots: vec![], strict: true };
+        let err = strict.resolve(missing.to_str()).unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+        let err = strict.resolve(file.to_str()).unwrap_err();
+        assert!(err.to_string().contains("not a directory"));
+
+        let lenient = WorkingDirPolicy::default();
+        assert_eq!(
+            lenient.resolve(missing.to_str()).unwrap(),
+            std::env::current_dir().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_working_dir_policy_unsafe_dir() {
+        let allowed = tempfile::tempdir().unwrap();
+        let elsewhere = tempfile::tempdir().unwrap();
+        let policy = WorkingDirPolicy {
+            allowed_roots: vec![allowed.path().to_path_buf()],
+            strict: true,
+        };
+        let err = policy.resolve(elsewhere.path().to_str()).unwrap_err();
+        assert!(err.to_string().contains("outside the allowed directories"));
+
+        // `..` can't be used to climb out of an allowed root
+        let sneaky = allowed.path().join("..");
+        assert!(policy.resolve(sneaky.to_str()).is_err());
+    }
+
+    #[test]
+    fn test_working_dir_policy_create() {
+        let allowed = tempfile::tempdir().unwrap();
+        let elsewhere = tempfile::tempdir().unwrap();
+        let policy = WorkingDirPolicy {
+            allowed_roots: vec![allowed.path().to_path_buf()],
+            strict: true,
+        };
+
+        let nested = allowed.path().join("runs/2024-01-10");
+        assert_eq!(policy.create(&nested).unwrap(), nested.canonicalize().unwrap());
+
+        // Nothing is created outside the allowlist
+        let outside = elsewhere.path().join("run");
+        let err = policy.create(&outside).unwrap_err();
+        assert!(err.to_string().contains("outside the allowed directories"));
+        assert!(!outside.exists());
+        assert!(policy.create(&allowed.path().join("../escape")).is_err());
+    }
+
+    #[test]
+    fn test_additional_dirs_checked_against_allowlist() {
+        let allowed = tempfile::tempdir().unwrap();
+        let prompts = allowed.path().join("prompts");
+        let data = allowed.path().join("data");
+        std::fs::create_dir(&prompts).unwrap();
+        std::fs::create_dir(&data).unwrap();
+        let elsewhere = tempfile::tempdir().unwrap();
+        let policy = WorkingDirPolicy {
+            allowed_roots: vec![allowed.path().to_path_buf()],
+            strict: false,
+        };
+
+        let dirs = policy
+            .check_additional(&serde_json::json!([prompts.to_str(), data.to_str(), ""]))
+            .unwrap();
+        assert_eq!(dirs, vec![prompts.canonicalize().unwrap(), data.canonicalize().unwrap()]);
+        assert!(policy.check_additional(&serde_json::Value::Null).unwrap().is_empty());
+
+        // Rejected even though the policy isn't strict.
+        let err = policy
+            .check_additional(&serde_json::json!([prompts.to_str(), elsewhere.path().to_str()]))
+            .unwrap_err();
+        assert!(err.to_string().contains("outside the allowed directories"));
+        assert!(policy.check_additional(&serde_json::json!("/tmp")).is_err());
+    }
+
+    #[test]
+    fn test_chat_permission_defaults_apply_to_unrestricted_agents() {
+        let defaults = ChatPermissionDefaults::default();
+        assert!(defaults.safe_mode);
+        assert_eq!(defaults.resolve(&[], None, &serde_json::Value::Null), vec!["Read", "Grep", "Glob"]);
+
+        // A node override replaces the default; an agent allowlist beats both.
+        let node = serde_json::json!(["Read", "Bash"]);
+        assert_eq!(defaults.resolve(&[], None, &node), vec!["Read", "Bash"]);
+        assert_eq!(defaults.resolve(&["Edit".to_string()], None, &node), vec!["Edit"]);
+
+        let custom = ChatPermissionDefaults::from_raw_values(None, Some("Read, WebFetch"));
+        assert_eq!(custom.resolve(&[], None, &serde_json::Value::Null), vec!["Read", "WebFetch"]);
+    }
+
+    #[test]
+    fn test_chat_permission_skip_all_is_explicit() {
+        use crate::agents::PermissionMode;
+        let defaults = ChatPermissionDefaults::default();
+        assert!(defaults.resolve(&[], Some(PermissionMode::Bypass), &serde_json::Value::Null).is_empty());
+
+        let legacy = ChatPermissionDefaults::from_raw_values(Some("false"), None);
+        assert!(legacy.resolve(&[], None, &serde_json::Value::Null).is_empty());
+    }
 }
@@ -0,0 +1,223 @@
+use serde::Serialize;
+
+use crate::flows::history::{FlowRun, NodeRun, RunStatus};
+
+/// Lines beyond this (per side) are left out of the output diff to keep the
+/// LCS table bounded.
+const MAX_DIFF_LINES: usize = 2000;
+
+/// Side-by-side comparison of two runs of the same flow.
+#[derive(Debug, Serialize)]
+pub struct RunComparison {
+    pub run_a: RunSummary,
+    pub run_b: RunSummary,
+    /// One entry per node that ran in either run, in run-A order then run-B extras.
+    pub nodes: Vec<NodeComparison>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub id: String,
+    pub status: RunStatus,
+    pub duration_ms: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NodeComparison {
+    pub node_id: String,
+    pub status_a: Option<RunStatus>,
+    pub status_b: Option<RunStatus>,
+    pub duration_ms_a: Option<i64>,
+    pub duration_ms_b: Option<i64>,
+    pub status_changed: bool,
+    pub output_changed: bool,
+    /// Line diff of the node output (`-` run A, `+` run B, ` ` unchanged);
+    /// empty when the outputs match.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub output_diff: Vec<String>,
+}
+
+fn duration_ms(started: chrono::DateTime<chrono::Utc>, finished: Option<chrono::DateTime<chrono::Utc>>) -> Option<i64> {
+    finished.map(|f| (f - started).num_milliseconds())
+}
+
+/// Full output when stored, otherwise the preview.
+fn node_output(node: &NodeRun) -> &str {
+    node.output
+        .as_deref()
+        .or(node.output_preview.as_deref())
+        .unwrap_or("")
+}
+
+fn summarize(run: &FlowRun) -> RunSummary {
+    RunSummary {
+        id: run.id.clone(),
+        status: run.status,
+        duration_ms: duration_ms(run.started_at, run.finished_at),
+        error: run.error.clone(),
+    }
+}
+
+/// Line-level diff via longest common subsequence.
+pub fn diff_lines(a: &str, b: &str) -> Vec<String> {
+    let a: Vec<&str> = a.lines().take(MAX_DIFF_LINES).collect();
+    let b: Vec<&str> = b.lines().take(MAX_DIFF_LINES).collect();
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            out.push(format!(" {}", a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(format!("-{}", a[i]));
+            i += 1;
+        } else {
+            out.push(format!("+{}", b[j]));
+            j += 1;
+        }
+    }
+    out.extend(a[i..].iter().map(|l| format!("-{l}")));
+    out.extend(b[j..].iter().map(|l| format!("+{l}")));
+    out
+}
+
+/// Compare two runs node by node.
+pub fn compare_runs(a: &FlowRun, b: &FlowRun) -> RunComparison {
+    let mut node_ids: Vec<&str> = a.node_runs.iter().map(|n| n.node_id.as_str()).collect();
+    for n in &b.node_runs {
+        if !node_ids.contains(&n.node_id.as_str()) {
+            node_ids.push(&n.node_id);
+        }
+    }
+
+    let nodes = node_ids
+        .into_iter()
+        .map(|node_id| {
+            let na = a.node_runs.iter().find(|n| n.node_id == node_id);
+            let nb = b.node_runs.iter().find(|n| n.node_id == node_id);
+            let out_a = na.map(node_output).unwrap_or("");
+            let out_b = nb.map(node_output).unwrap_or("");
+            let output_changed = out_a != out_b;
+
+            NodeComparison {
+                node_id: node_id.to_string(),
+                status_a: na.map(|n| n.status),
+                status_b: nb.map(|n| n.status),
+                duration_ms_a: na.and_then(|n| duration_ms(n.started_at, n.finished_at)),
+                duration_ms_b: nb.and_then(|n| duration_ms(n.started_at, n.finished_at)),
+                status_changed: na.map(|n| n.status) != nb.map(|n| n.status),
+                output_changed,
+                output_diff: if output_changed {
+                    diff_lines(out_a, out_b)
+                } else {
+                    Vec::new()
+                },
+            }
+        })
+        .collect();
+
+    RunComparison {
+        run_a: summarize(a),
+        run_b: summarize(b),
+        nodes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::history::RunTrigger;
+    use chrono::Utc;
+
+    fn node_run(node_id: &str, status: RunStatus, output: &str) -> NodeRun {
+        NodeRun {
+            node_id: node_id.to_string(),
+            status,
+            started_at: Utc::now(),
+            finished_at: Some(Utc::now()),
+            output_preview: Some(output.chars().take(20).collect()),
+            output: Some(output.to_string()),
+            prompt: None,
+            checkpoint: None,
+            cost_usd: None,
+        }
+    }
+
+    fn run(id: &str, node_runs: Vec<NodeRun>) -> FlowRun {
+        FlowRun {
+            id: id.to_string(),
+            flow_id: "f1".to_string(),
+            status: RunStatus::Success,
+            started_at: Utc::now(),
+            finished_at: Some(Utc::now()),
+            node_runs,
+            error: None,
+            trigger: RunTrigger::Manual,
+            tags: vec![],
+            note: None,
+            resumed_from: None,
+        }
+    }
+
+    #[test]
+    fn test_compare_highlights_changed_node() {
+        let a = run(
+            "a",
+            vec![
+                node_run("t1", RunStatus::Success, "trigger"),
+                node_run("e1", RunStatus::Success, "summary\nBTC up\nETH flat"),
+            ],
+        );
+        let b = run(
+            "b",
+            vec![
+                node_run("t1", RunStatus::Success, "trigger"),
+                node_run("e1", RunStatus::Failed, "summary\nBTC down\nETH flat"),
+                node_run("s1", RunStatus::Success, "sent"),
+            ],
+        );
+
+        let cmp = compare_runs(&a, &b);
+        assert_eq!(cmp.nodes.len(), 3);
+
+        let t1 = &cmp.nodes[0];
+        assert!(!t1.status_changed && !t1.output_changed);
+        assert!(t1.output_diff.is_empty());
+
+        let e1 = &cmp.nodes[1];
+        assert!(e1.status_changed);
+        assert!(e1.output_changed);
+        assert_eq!(
+            e1.output_diff,
+            vec![" summary", "-BTC up", "+BTC down", " ETH flat"]
+        );
+
+        let s1 = &cmp.nodes[2];
+        assert_eq!(s1.status_a, None);
+        assert_eq!(s1.status_b, Some(RunStatus::Success));
+        assert!(s1.status_changed);
+    }
+
+    #[test]
+    fn test_diff_lines_falls_back_to_preview() {
+        let mut node = node_run("e1", RunStatus::Success, "full output");
+        node.output = None;
+        assert_eq!(node_output(&node), "full output");
+        assert_eq!(diff_lines("", "x"), vec!["+x"]);
+    }
+}
@@ -0,0 +1,105 @@
+//! Rolling executor-cost cap for scheduled runs.
+//!
+//! Spend is summed from the flow's persisted run history, so the window
+//! survives restarts. Only scheduled fires are gated; manual runs always go.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::history::FlowRun;
+
+fn default_window_hours() -> u32 {
+    24
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostCap {
+    /// Most the flow may spend (USD) within the window.
+    pub max_usd: f64,
+    #[serde(default = "default_window_hours")]
+    pub window_hours: u32,
+    /// Disable the flow, rather than just skipping the fire, once over.
+    #[serde(default)]
+    pub disable_when_exceeded: bool,
+}
+
+impl CostCap {
+    pub fn window(&self) -> Duration {
+        Duration::hours(i64::from(self.window_hours.max(1)))
+    }
+
+    /// `Some(spend)` when the runs within the window have used up the cap.
+    pub fn exceeded(&self, runs: &[FlowRun], now: DateTime<Utc>) -> Option<f64> {
+        let spend = window_spend(runs, now, self.window());
+        (spend >= self.max_usd).then_some(spend)
+    }
+}
+
+/// Executor cost of the runs started within `window` before `now`.
+pub fn window_spend(runs: &[FlowRun], now: DateTime<Utc>, window: Duration) -> f64 {
+    let since = now - window;
+    runs.iter()
+        .filter(|r| r.started_at >= since)
+        .filter_map(FlowRun::total_cost_usd)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::history::{NodeRun, RunStatus, RunTrigger};
+
+    fn run(started_at: DateTime<Utc>, cost_usd: f64) -> FlowRun {
+        FlowRun {
+            id: uuid::Uuid::new_v4().to_string(),
+            flow_id: "f1".to_string(),
+            status: RunStatus::Success,
+            started_at,
+            finished_at: Some(started_at),
+            node_runs: vec![NodeRun {
+                node_id: "e1".to_string(),
+                status: RunStatus::Success,
+                started_at,
+                finished_at: Some(started_at),
+                output_preview: None,
+                output: None,
+                prompt: None,
+                checkpoint: None,
+                cost_usd: Some(cost_usd),
+            }],
+            error: None,
+            trigger: RunTrigger::Cron,
+            tags: vec![],
+            note: None,
+            resumed_from: None,
+        }
+    }
+
+    fn daily_cap(max_usd: f64) -> CostCap {
+        CostCap { max_usd, window_hours: 24, disable_when_exceeded: false }
+    }
+
+    #[test]
+    fn test_window_spend_ignores_old_runs() {
+        let now = Utc::now();
+        let runs = vec![
+            run(now - Duration::hours(30), 10.0),
+            run(now - Duration::hours(5), 1.5),
+            run(now - Duration::minutes(10), 0.25),
+        ];
+        assert_eq!(window_spend(&runs, now, Duration::hours(24)), 1.75);
+    }
+
+    #[test]
+    fn test_over_cap_is_skipped_under_cap_runs() {
+        let now = Utc::now();
+        let runs = vec![run(now - Duration::hours(2), 3.0), run(now - Duration::hours(1), 2.5)];
+
+        // $5.50 today: over a $5/day cap, under a $10/day one
+        assert_eq!(daily_cap(5.0).exceeded(&runs, now), Some(5.5));
+        assert_eq!(daily_cap(10.0).exceeded(&runs, now), None);
+
+        // Yesterday's spend has rolled out of the window
+        assert_eq!(daily_cap(5.0).exceeded(&runs, now + Duration::hours(24)), None);
+    }
+}
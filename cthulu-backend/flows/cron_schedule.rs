@@ -0,0 +1,127 @@
+//! Cron trigger schedules. A trigger's `schedule` is one expression or an
+//! array of them; the flow fires at whichever entry comes due first.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use croner::Cron;
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct CronEntry {
+    pub expression: String,
+    cron: Cron,
+}
+
+impl CronEntry {
+    pub fn next_after(&self, now: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.cron.find_next_occurrence(now, false).ok()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CronSchedules {
+    entries: Vec<CronEntry>,
+}
+
+impl CronSchedules {
+    /// Parse a cron node's `schedule` value. Errors for an array name the
+    /// offending entry's index.
+    pub fn from_config(schedule: &Value) -> Result<Self> {
+        let entries = match schedule {
+            Value::String(expr) => vec![parse_entry(expr).with_context(|| format!("invalid cron '{expr}'"))?],
+            Value::Array(items) => {
+                if items.is_empty() {
+                    bail!("cron trigger has no schedules");
+                }
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let expr = item.as_str().with_context(|| format!("schedule[{i}] is not a string"))?;
+                        parse_entry(expr).with_context(|| format!("schedule[{i}] '{expr}' is invalid"))
+                    })
+                    .collect::<Result<_>>()?
+            }
+            Value::Null => bail!("cron trigger missing 'schedule'"),
+            _ => bail!("cron 'schedule' must be a string or an array of strings"),
+        };
+        Ok(Self { entries })
+    }
+
+    pub fn entries(&self) -> &[CronEntry] {
+        &self.entries
+    }
+
+    /// Earliest next fire across all entries.
+    pub fn next_after(&self, now: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.entries.iter().filter_map(|e| e.next_after(now)).min()
+    }
+
+    /// The next `n` fires across all entries, in order. Entries due at the
+    /// same instant fire once.
+    pub fn upcoming(&self, now: &DateTime<Utc>, n: usize) -> Vec<DateTime<Utc>> {
+        let mut runs = Vec::with_capacity(n);
+        let mut cursor = *now;
+        while runs.len() < n {
+            let Some(next) = self.next_after(&cursor) else { break };
+            runs.push(next);
+            cursor = next + chrono::Duration::seconds(1);
+        }
+        runs
+    }
+
+    /// Expressions joined for logging.
+    pub fn describe(&self) -> String {
+        self.entries.iter().map(|e| e.expression.as_str()).collect::<Vec<_>>().join(" | ")
+    }
+}
+
+fn parse_entry(expr: &str) -> Result<CronEntry> {
+    let expr = expr.trim();
+    if expr.is_empty() {
+        bail!("empty expression");
+    }
+    let cron = Cron::new(expr).parse().map_err(|e| anyhow::anyhow!("{e}"))?;
+    Ok(CronEntry { expression: expr.to_string(), cron })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    #[test]
+    fn test_single_and_multiple_schedules() {
+        // Friday 2026-01-02 10:00 UTC
+        let now = Utc.with_ymd_and_hms(2026, 1, 2, 10, 0, 0).unwrap();
+
+        let single = CronSchedules::from_config(&json!("0 9 * * 1-5")).unwrap();
+        assert_eq!(single.next_after(&now), Some(Utc.with_ymd_and_hms(2026, 1, 5, 9, 0, 0).unwrap()));
+
+        // Weekdays at 9, weekends at noon: Saturday noon comes first
+        let both = CronSchedules::from_config(&json!(["0 9 * * 1-5", "0 12 * * 0,6"])).unwrap();
+        assert_eq!(both.entries().len(), 2);
+        assert_eq!(both.next_after(&now), Some(Utc.with_ymd_and_hms(2026, 1, 3, 12, 0, 0).unwrap()));
+        assert_eq!(
+            both.upcoming(&now, 3),
+            vec![
+                Utc.with_ymd_and_hms(2026, 1, 3, 12, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2026, 1, 4, 12, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2026, 1, 5, 9, 0, 0).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_entry_reports_its_index() {
+        let err = CronSchedules::from_config(&json!(["0 9 * * 1-5", "not a cron"])).unwrap_err();
+        assert!(format!("{err:#}").starts_with("schedule[1] 'not a cron' is invalid"), "{err:#}");
+
+        let err = CronSchedules::from_config(&json!(["0 9 * * *", 5])).unwrap_err();
+        assert_eq!(err.to_string(), "schedule[1] is not a string");
+
+        assert!(CronSchedules::from_config(&json!([])).is_err());
+        assert!(CronSchedules::from_config(&Value::Null).is_err());
+    }
+}
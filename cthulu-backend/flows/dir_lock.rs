@@ -0,0 +1,110 @@
+//! Advisory locks on executor working directories.
+//!
+//! A flow's executor and an interactive chat turn for the same flow can both
+//! point claude at one directory. Each takes the lock for `(flow_id, dir)`
+//! first, so they run one after the other instead of editing the tree at the
+//! same time.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// Held for the duration of one operation; dropping it releases the directory.
+pub type DirLockGuard = OwnedMutexGuard<()>;
+
+type DirKey = (String, PathBuf);
+
+#[derive(Clone, Default)]
+pub struct WorkingDirLocks {
+    locks: Arc<std::sync::Mutex<HashMap<DirKey, Arc<Mutex<()>>>>>,
+}
+
+impl WorkingDirLocks {
+    fn lock_for(&self, flow_id: &str, dir: &Path) -> Arc<Mutex<()>> {
+        // Canonicalize so `./repo` and `/abs/repo` share a lock.
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        // Drop entries nobody holds or waits on.
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks
+            .entry((flow_id.to_string(), dir))
+            .or_default()
+            .clone()
+    }
+
+    /// Take the lock if it's free.
+    pub fn try_acquire(&self, flow_id: &str, dir: &Path) -> Option<DirLockGuard> {
+        self.lock_for(flow_id, dir).try_lock_owned().ok()
+    }
+
+    /// Wait until the lock is free.
+    pub async fn acquire(&self, flow_id: &str, dir: &Path) -> DirLockGuard {
+        self.lock_for(flow_id, dir).lock_owned().await
+    }
+
+    /// Like [`acquire`](Self::acquire), but calls `on_wait` first if someone
+    /// else holds the lock.
+    pub async fn acquire_or_wait(&self, flow_id: &str, dir: &Path, on_wait: impl FnOnce()) -> DirLockGuard {
+        if let Some(guard) = self.try_acquire(flow_id, dir) {
+            return guard;
+        }
+        on_wait();
+        self.acquire(flow_id, dir).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_same_dir_operations_do_not_overlap() {
+        let dir = tempfile::tempdir().unwrap();
+        let locks = WorkingDirLocks::default();
+        let active = Arc::new(AtomicUsize::new(0));
+        let overlapped = Arc::new(AtomicBool::new(false));
+        let waited = Arc::new(AtomicBool::new(false));
+
+        let mut tasks = Vec::new();
+        for _ in 0..2 {
+            let (locks, active, overlapped, waited) =
+                (locks.clone(), active.clone(), overlapped.clone(), waited.clone());
+            let path = dir.path().to_path_buf();
+            tasks.push(tokio::spawn(async move {
+                let _guard = locks
+                    .acquire_or_wait("flow-1", &path, || waited.store(true, Ordering::SeqCst))
+                    .await;
+                if active.fetch_add(1, Ordering::SeqCst) > 0 {
+                    overlapped.store(true, Ordering::SeqCst);
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                active.fetch_sub(1, Ordering::SeqCst);
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert!(!overlapped.load(Ordering::SeqCst));
+        assert!(waited.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_locks_are_scoped_to_flow_and_dir() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        let locks = WorkingDirLocks::default();
+
+        let held = locks.try_acquire("flow-1", a.path()).unwrap();
+        assert!(locks.try_acquire("flow-1", a.path()).is_none());
+        assert!(locks.try_acquire("flow-1", b.path()).is_some());
+        assert!(locks.try_acquire("flow-2", a.path()).is_some());
+
+        drop(held);
+        assert!(locks.try_acquire("flow-1", a.path()).is_some());
+    }
+}
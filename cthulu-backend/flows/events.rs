@@ -18,6 +18,12 @@ pub enum RunEventType {
     NodeStarted,
     NodeCompleted,
     NodeFailed,
+    /// An executor is waiting for its working dir to be released.
+    WaitingForLock,
+    /// A scheduled fire was skipped: the flow is over its cost cap.
+    CostCapExceeded,
+    /// Dry run: an executor's full rendered prompt, in place of running it.
+    PromptRendered,
     RunCompleted,
     RunFailed,
     Log,
@@ -30,6 +36,9 @@ impl RunEventType {
             RunEventType::NodeStarted => "node_started",
             RunEventType::NodeCompleted => "node_completed",
             RunEventType::NodeFailed => "node_failed",
+            RunEventType::WaitingForLock => "waiting_for_lock",
+            RunEventType::CostCapExceeded => "cost_cap_exceeded",
+            RunEventType::PromptRendered => "prompt_rendered",
             RunEventType::RunCompleted => "run_completed",
             RunEventType::RunFailed => "run_failed",
             RunEventType::Log => "log",
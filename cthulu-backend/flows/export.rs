@@ -0,0 +1,223 @@
+//! Flat run-history export (CSV or JSON) for spreadsheets and reporting.
+//!
+//! One row per run: no node detail, just what a report needs.
+
+use std::borrow::Cow;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::history::{FlowRun, RunStatus, RunTrigger};
+
+/// Run-list filter shared by the runs API and exports.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct RunFilter {
+    /// Only runs carrying this tag.
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Only runs started at or after this time.
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl RunFilter {
+    pub fn matches(&self, run: &FlowRun) -> bool {
+        self.tag
+            .as_deref()
+            .filter(|t| !t.is_empty())
+            .is_none_or(|tag| run.has_tag(tag))
+            && self.since.is_none_or(|since| run.started_at >= since)
+    }
+
+    pub fn apply(&self, mut runs: Vec<FlowRun>) -> Vec<FlowRun> {
+        runs.retain(|r| self.matches(r));
+        runs
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// `None` (or empty) defaults to CSV.
+    pub fn parse(raw: Option<&str>) -> Option<Self> {
+        match raw.map(str::trim).unwrap_or_default() {
+            "" | "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Json => "application/json",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// One exported run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunRow {
+    pub flow_id: String,
+    pub flow_name: String,
+    pub run_id: String,
+    pub status: RunStatus,
+    pub trigger: RunTrigger,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_secs: Option<f64>,
+    pub cost_usd: Option<f64>,
+    pub error: Option<String>,
+}
+
+impl RunRow {
+    pub fn new(flow_name: &str, run: &FlowRun) -> Self {
+        Self {
+            flow_id: run.flow_id.clone(),
+            flow_name: flow_name.to_string(),
+            run_id: run.id.clone(),
+            status: run.status,
+            trigger: run.trigger,
+            started_at: run.started_at,
+            finished_at: run.finished_at,
+            duration_secs: run.duration_secs(),
+            cost_usd: run.total_cost_usd(),
+            error: run.error.clone(),
+        }
+    }
+
+    fn csv_fields(&self) -> [String; 10] {
+        let enum_str = |v: serde_json::Value| v.as_str().unwrap_or_default().to_string();
+        [
+            self.flow_id.clone(),
+            self.flow_name.clone(),
+            self.run_id.clone(),
+            enum_str(serde_json::json!(self.status)),
+            enum_str(serde_json::json!(self.trigger)),
+            self.started_at.to_rfc3339(),
+            self.finished_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            self.duration_secs.map(|d| format!("{d:.3}")).unwrap_or_default(),
+            self.cost_usd.map(|c| format!("{c:.4}")).unwrap_or_default(),
+            self.error.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+pub const CSV_HEADER: [&str; 10] = [
+    "flow_id",
+    "flow_name",
+    "run_id",
+    "status",
+    "trigger",
+    "started_at",
+    "finished_at",
+    "duration_secs",
+    "cost_usd",
+    "error",
+];
+
+/// Quote a CSV field (RFC 4180) if it holds a comma, quote or line break.
+pub fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+fn csv_line<S: AsRef<str>>(fields: &[S]) -> String {
+    let mut line = fields
+        .iter()
+        .map(|f| csv_field(f.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// CSV lines (header first), one chunk per row so the body can be streamed.
+pub fn csv_lines(rows: Vec<RunRow>) -> impl Iterator<Item = String> {
+    std::iter::once(csv_line(&CSV_HEADER)).chain(rows.into_iter().map(|row| csv_line(&row.csv_fields())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn run(id: &str, started_at: DateTime<Utc>, tags: &[&str], error: Option<&str>) -> FlowRun {
+        FlowRun {
+            id: id.to_string(),
+            flow_id: "f1".to_string(),
+            status: if error.is_some() { RunStatus::Failed } else { RunStatus::Success },
+            started_at,
+            finished_at: Some(started_at + Duration::milliseconds(1500)),
+            node_runs: vec![],
+            error: error.map(String::from),
+            trigger: RunTrigger::Cron,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            note: None,
+            resumed_from: None,
+        }
+    }
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line1\nline2"), "\"line1\nline2\"");
+        assert_eq!(csv_field("cr\r"), "\"cr\r\"");
+    }
+
+    #[test]
+    fn test_csv_export_escapes_error_messages() {
+        let now = Utc::now();
+        let failed = run("r1", now, &[], Some("sink \"slack\" failed: 500,\nretry later"));
+        let lines: Vec<String> = csv_lines(vec![RunRow::new("Daily, digest", &failed)]).collect();
+
+        assert_eq!(lines[0], format!("{}\r\n", CSV_HEADER.join(",")));
+        assert!(lines[1].starts_with("f1,\"Daily, digest\",r1,failed,cron,"));
+        assert!(lines[1].contains(",1.500,,"));
+        assert!(lines[1].ends_with(",\"sink \"\"slack\"\" failed: 500,\nretry later\"\r\n"));
+    }
+
+    #[test]
+    fn test_row_count_matches_filtered_runs() {
+        let now = Utc::now();
+        let runs = vec![
+            run("old", now - Duration::days(10), &["flaky"], None),
+            run("recent", now - Duration::hours(1), &[], Some("boom")),
+            run("recent-flaky", now, &["Flaky"], None),
+        ];
+
+        let filter = RunFilter { tag: None, since: Some(now - Duration::days(1)) };
+        let matched = filter.apply(runs.clone());
+        let rows: Vec<RunRow> = matched.iter().map(|r| RunRow::new("Flow", r)).collect();
+        assert_eq!(rows.len(), 2);
+        // Header plus one line per run
+        assert_eq!(csv_lines(rows).count(), matched.len() + 1);
+
+        let filter = RunFilter { tag: Some("flaky".to_string()), since: Some(now - Duration::days(1)) };
+        let ids: Vec<String> = filter.apply(runs).into_iter().map(|r| r.id).collect();
+        assert_eq!(ids, ["recent-flaky"]);
+    }
+
+    #[test]
+    fn test_export_format_parse() {
+        assert_eq!(ExportFormat::parse(None), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::parse(Some("json")), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::parse(Some("xlsx")), None);
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Instant;
 
 use anyhow::{Context, Result, bail};
@@ -8,16 +9,22 @@ use chrono::Utc;
 use tokio::sync::RwLock;
 
 use super::Flow;
-use super::history::{FlowRun, NodeRun, RunStatus, MAX_RUNS_PER_FLOW};
+use super::history::{FlowRun, LastRun, NodeCheckpoint, NodeRun, RunStatus, MAX_RUNS_PER_FLOW};
 use super::repository::FlowRepository;
+use crate::secrets::{self, SecretCipher};
 
 pub struct FileFlowRepository {
     base_dir: PathBuf,
     flows: RwLock<HashMap<String, Flow>>,
     runs: RwLock<HashMap<String, VecDeque<FlowRun>>>,
+    /// Latest finished run per flow, so flow lists needn't scan run history.
+    /// Rebuilt from the run files on load.
+    last_runs: RwLock<HashMap<String, LastRun>>,
     /// Filenames written by this process — used to skip fs-watcher events for our own writes.
     /// Maps filename -> write timestamp for time-based expiry.
     self_writes: std::sync::Mutex<HashMap<String, Instant>>,
+    /// Encrypts secret-typed node config fields on disk (see `crate::secrets`).
+    cipher: Option<Arc<SecretCipher>>,
 }
 
 impl FileFlowRepository {
@@ -26,10 +33,57 @@ impl FileFlowRepository {
             base_dir,
             flows: RwLock::new(HashMap::new()),
             runs: RwLock::new(HashMap::new()),
+            last_runs: RwLock::new(HashMap::new()),
             self_writes: std::sync::Mutex::new(HashMap::new()),
+            cipher: None,
         }
     }
 
+    pub fn with_cipher(mut self, cipher: Option<SecretCipher>) -> Self {
+        self.cipher = cipher.map(Arc::new);
+        self
+    }
+
+    /// Encrypt secret fields for writing. Refuses to persist secrets in plaintext.
+    fn seal_flow(&self, flow: &Flow) -> Result<Flow> {
+        let mut sealed = flow.clone();
+        for node in &mut sealed.nodes {
+            if !secrets::has_secrets(&node.config) {
+                continue;
+            }
+            let Some(cipher) = &self.cipher else {
+                bail!(
+                    "node '{}' has a secret field but {} is not set",
+                    node.label,
+                    secrets::SECRET_KEY_ENV
+                );
+            };
+            secrets::seal_config(&mut node.config, cipher)?;
+        }
+        Ok(sealed)
+    }
+
+    /// Decrypt secret fields read from disk. Plaintext secrets are kept (and
+    /// encrypted on the next save), but any secret requires a configured key.
+    fn open_flow(&self, flow: &mut Flow) -> Result<()> {
+        for node in &mut flow.nodes {
+            if !secrets::has_secrets(&node.config) {
+                continue;
+            }
+            let Some(cipher) = &self.cipher else {
+                bail!(
+                    "flow '{}' node '{}' has a secret field but {} is not set",
+                    flow.id,
+                    node.label,
+                    secrets::SECRET_KEY_ENV
+                );
+            };
+            secrets::open_config(&mut node.config, cipher)
+                .with_context(|| format!("flow '{}' node '{}'", flow.id, node.label))?;
+        }
+        Ok(())
+    }
+
     fn flows_dir(&self) -> PathBuf {
         self.base_dir.join("flows")
     }
@@ -83,7 +137,11 @@ impl FileFlowRepository {
         let path = self.flows_dir().join(filename);
         for attempt in 0..2 {
             if let Ok(content) = std::fs::read_to_string(&path) {
-                if let Ok(flow) = serde_json::from_str::<Flow>(&content) {
+                if let Ok(mut flow) = serde_json::from_str::<Flow>(&content) {
+                    if let Err(e) = self.open_flow(&mut flow) {
+                        tracing::warn!(filename, error = %e, "skipping flow reload: cannot decrypt secrets");
+                        return None;
+                    }
                     let id = flow.id.clone();
                     self.flows.write().await.insert(id.clone(), flow);
                     tracing::debug!(flow_id = %id, filename, "reloaded flow from disk");
@@ -139,11 +197,11 @@ impl FlowRepository for FileFlowRepository {
         std::fs::create_dir_all(&dir)
             .with_context(|| format!("failed to create flows dir: {}", dir.display()))?;
 
+        let content = serde_json::to_string_pretty(&self.seal_flow(&flow)?)
+            .context("failed to serialize flow")?;
         let filename = format!("{}.json", flow.id);
         self.mark_self_write(&filename);
         let path = dir.join(&filename);
-        let content = serde_json::to_string_pretty(&flow)
-            .context("failed to serialize flow")?;
         let tmp_path = path.with_extension("json.tmp");
         std::fs::write(&tmp_path, &content)
             .with_context(|| format!("failed to write flow temp file: {}", tmp_path.display()))?;
@@ -167,6 +225,7 @@ impl FlowRepository for FileFlowRepository {
 
         // Clean up runs for this flow
         self.runs.write().await.remove(id);
+        self.last_runs.write().await.remove(id);
         let runs_path = self.runs_dir().join(id);
         if runs_path.exists() {
             std::fs::remove_dir_all(&runs_path)
@@ -201,6 +260,12 @@ impl FlowRepository for FileFlowRepository {
             .unwrap_or_default()
     }
 
+    async fn get_run(&self, flow_id: &str, run_id: &str) -> Option<FlowRun> {
+        let runs = self.runs.read().await;
+        runs.get(flow_id)
+            .and_then(|q| q.iter().find(|r| r.id == run_id).cloned())
+    }
+
     async fn complete_run(
         &self,
         flow_id: &str,
@@ -208,12 +273,18 @@ impl FlowRepository for FileFlowRepository {
         status: RunStatus,
         error: Option<String>,
     ) -> Result<()> {
+        let mut last_run = None;
         self.mutate_run(flow_id, run_id, |r| {
             r.status = status;
             r.finished_at = Some(Utc::now());
             r.error = error;
+            last_run = LastRun::of(r);
         })
-        .await
+        .await?;
+        if let Some(last_run) = last_run {
+            self.last_runs.write().await.insert(flow_id.to_string(), last_run);
+        }
+        Ok(())
     }
 
     async fn push_node_run(
@@ -247,6 +318,90 @@ impl FlowRepository for FileFlowRepository {
         .await
     }
 
+    async fn set_node_output(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        output: String,
+    ) -> Result<()> {
+        let node_id = node_id.to_string();
+        self.mutate_run(flow_id, run_id, |r| {
+            if let Some(nr) = r.node_runs.iter_mut().find(|nr| nr.node_id == node_id) {
+                nr.output = Some(output);
+            }
+        })
+        .await
+    }
+
+    async fn set_node_prompt(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        prompt: String,
+    ) -> Result<()> {
+        let node_id = node_id.to_string();
+        self.mutate_run(flow_id, run_id, |r| {
+            if let Some(nr) = r.node_runs.iter_mut().find(|nr| nr.node_id == node_id) {
+                nr.prompt = Some(prompt);
+            }
+        })
+        .await
+    }
+
+    async fn set_node_cost(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        cost_usd: f64,
+    ) -> Result<()> {
+        let node_id = node_id.to_string();
+        self.mutate_run(flow_id, run_id, |r| {
+            if let Some(nr) = r.node_runs.iter_mut().find(|nr| nr.node_id == node_id) {
+                nr.cost_usd = Some(cost_usd);
+            }
+        })
+        .await
+    }
+
+    async fn set_node_checkpoint(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        checkpoint: NodeCheckpoint,
+    ) -> Result<()> {
+        let node_id = node_id.to_string();
+        self.mutate_run(flow_id, run_id, |r| {
+            if let Some(nr) = r.node_runs.iter_mut().find(|nr| nr.node_id == node_id) {
+                nr.checkpoint = Some(checkpoint);
+            }
+        })
+        .await
+    }
+
+    async fn annotate_run(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        tags: Vec<String>,
+        note: Option<String>,
+    ) -> Result<()> {
+        self.mutate_run(flow_id, run_id, |r| {
+            for tag in tags {
+                if !r.tags.contains(&tag) {
+                    r.tags.push(tag);
+                }
+            }
+            if note.is_some() {
+                r.note = note;
+            }
+        })
+        .await
+    }
+
     async fn load_all(&self) -> Result<()> {
         // Load flows
         let flows_dir = self.flows_dir();
@@ -265,8 +420,9 @@ impl FlowRepository for FileFlowRepository {
             }
             let content = std::fs::read_to_string(&path)
                 .with_context(|| format!("failed to read flow file: {}", path.display()))?;
-            let flow: Flow = serde_json::from_str(&content)
+            let mut flow: Flow = serde_json::from_str(&content)
                 .with_context(|| format!("failed to parse flow file: {}", path.display()))?;
+            self.open_flow(&mut flow)?;
             tracing::info!(flow_id = %flow.id, name = %flow.name, "Loaded flow");
             loaded_flows.insert(flow.id.clone(), flow);
         }
@@ -332,15 +488,28 @@ impl FlowRepository for FileFlowRepository {
             loaded_runs.insert(flow_id, VecDeque::from(flow_runs));
         }
 
+        let last_runs = loaded_runs
+            .iter()
+            .filter_map(|(flow_id, runs)| {
+                let latest = runs.iter().filter_map(LastRun::of).max_by_key(|r| r.finished_at)?;
+                Some((flow_id.clone(), latest))
+            })
+            .collect();
         *self.runs.write().await = loaded_runs;
+        *self.last_runs.write().await = last_runs;
 
         Ok(())
     }
+
+    async fn last_runs(&self) -> HashMap<String, LastRun> {
+        self.last_runs.read().await.clone()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::flows::history::RunTrigger;
     use crate::flows::{Node, NodeType, Position};
     use tempfile::tempdir;
 
@@ -360,11 +529,28 @@ mod tests {
             }],
             edges: vec![],
             version: 0,
+            max_runtime_secs: None,
+            capture_prompts: true,
+            sandbox_backend: None,
+            cost_cap: None,
+            default_session_budget_usd: None,
+            output_transform: vec![],
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
     }
 
+    fn webhook_flow(id: &str, secret: &str) -> Flow {
+        let mut flow = test_flow(id, "Webhook Flow");
+        flow.nodes[0].kind = "webhook".to_string();
+        flow.nodes[0].config = serde_json::json!({ "path": "github", "secret": secret });
+        flow
+    }
+
+    fn cipher() -> Option<crate::secrets::SecretCipher> {
+        Some(crate::secrets::SecretCipher::new("test-key").unwrap())
+    }
+
     fn test_run(flow_id: &str, run_id: &str) -> FlowRun {
         FlowRun {
             id: run_id.to_string(),
@@ -374,6 +560,10 @@ mod tests {
             finished_at: None,
             node_runs: vec![],
             error: None,
+            trigger: RunTrigger::Manual,
+            tags: vec![],
+            note: None,
+            resumed_from: None,
         }
     }
 
@@ -396,6 +586,50 @@ mod tests {
         assert_eq!(loaded.name, "Test Flow");
     }
 
+    #[tokio::test]
+    async fn test_flow_secrets_encrypted_on_disk() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf()).with_cipher(cipher());
+        repo.load_all().await.unwrap();
+        repo.save_flow(webhook_flow("f1", "whsec_123")).await.unwrap();
+
+        let on_disk = std::fs::read_to_string(dir.path().join("flows/f1.json")).unwrap();
+        assert!(!on_disk.contains("whsec_123"));
+        assert!(on_disk.contains("enc:v1:"));
+        // The in-memory copy stays usable.
+        assert_eq!(repo.get_flow("f1").await.unwrap().nodes[0].config["secret"], "whsec_123");
+
+        let repo2 = FileFlowRepository::new(dir.path().to_path_buf()).with_cipher(cipher());
+        repo2.load_all().await.unwrap();
+        let loaded = repo2.get_flow("f1").await.unwrap();
+        assert_eq!(loaded.nodes[0].config["secret"], "whsec_123");
+    }
+
+    #[tokio::test]
+    async fn test_flow_plaintext_secret_migrated_on_write() {
+        let dir = tempdir().unwrap();
+        let flows_dir = dir.path().join("flows");
+        std::fs::create_dir_all(&flows_dir).unwrap();
+        let legacy = serde_json::to_string(&webhook_flow("f1", "plain")).unwrap();
+        std::fs::write(flows_dir.join("f1.json"), legacy).unwrap();
+
+        // Secrets present but no key: refuse to start.
+        let err = FileFlowRepository::new(dir.path().to_path_buf())
+            .load_all()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("CTHULU_SECRET_KEY"));
+
+        let repo = FileFlowRepository::new(dir.path().to_path_buf()).with_cipher(cipher());
+        repo.load_all().await.unwrap();
+        let flow = repo.get_flow("f1").await.unwrap();
+        assert_eq!(flow.nodes[0].config["secret"], "plain");
+
+        repo.save_flow(flow).await.unwrap();
+        let on_disk = std::fs::read_to_string(flows_dir.join("f1.json")).unwrap();
+        assert!(!on_disk.contains("\"plain\""));
+    }
+
     #[tokio::test]
     async fn test_flow_list() {
         let dir = tempdir().unwrap();
@@ -526,6 +760,52 @@ mod tests {
         assert_eq!(runs[0].status, RunStatus::Success);
     }
 
+    #[tokio::test]
+    async fn test_last_run_tracks_completion_and_survives_restart() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+
+        repo.add_run(test_run("f1", "r1")).await.unwrap();
+        assert!(repo.last_runs().await.is_empty(), "a running run isn't a last run");
+
+        repo.push_node_run("f1", "r1", NodeRun {
+            node_id: "e1".to_string(),
+            status: RunStatus::Running,
+            started_at: Utc::now(),
+            finished_at: None,
+            output_preview: None,
+            output: None,
+            prompt: None,
+            checkpoint: None,
+            cost_usd: None,
+        })
+        .await
+        .unwrap();
+        repo.set_node_cost("f1", "r1", "e1", 0.25).await.unwrap();
+        repo.complete_run("f1", "r1", RunStatus::Success, None).await.unwrap();
+
+        let last = repo.last_runs().await["f1"].clone();
+        assert_eq!(last.run_id, "r1");
+        assert_eq!(last.status, RunStatus::Success);
+        assert_eq!(last.cost_usd, Some(0.25));
+
+        // A newer run replaces it, whatever its outcome
+        repo.add_run(test_run("f1", "r2")).await.unwrap();
+        repo.complete_run("f1", "r2", RunStatus::Failed, Some("boom".into())).await.unwrap();
+        let last = repo.last_runs().await["f1"].clone();
+        assert_eq!((last.run_id.as_str(), last.status), ("r2", RunStatus::Failed));
+
+        // Backfilled from run history on load
+        drop(repo);
+        let repo2 = FileFlowRepository::new(dir.path().to_path_buf());
+        repo2.load_all().await.unwrap();
+        assert_eq!(repo2.last_runs().await["f1"], last);
+
+        repo2.delete_flow("f1").await.unwrap();
+        assert!(repo2.last_runs().await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_push_node_run() {
         let dir = tempdir().unwrap();
@@ -540,6 +820,10 @@ mod tests {
             started_at: Utc::now(),
             finished_at: None,
             output_preview: None,
+            output: None,
+            prompt: None,
+            checkpoint: None,
+            cost_usd: None,
         };
         repo.push_node_run("f1", "r1", nr).await.unwrap();
 
@@ -562,6 +846,10 @@ mod tests {
             started_at: Utc::now(),
             finished_at: None,
             output_preview: None,
+            output: None,
+            prompt: None,
+            checkpoint: None,
+            cost_usd: None,
         };
         repo.push_node_run("f1", "r1", nr).await.unwrap();
         repo
@@ -601,4 +889,50 @@ mod tests {
         assert_eq!(runs[0].status, RunStatus::Failed);
         assert_eq!(runs[0].error.as_deref(), Some("boom"));
     }
+
+    #[tokio::test]
+    async fn test_annotate_run_and_filter_by_tag() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+
+        repo.add_run(test_run("f1", "r1")).await.unwrap();
+        repo.add_run(test_run("f1", "r2")).await.unwrap();
+        repo
+            .annotate_run("f1", "r1", vec!["flaky".to_string()], Some("timeout upstream".to_string()))
+            .await
+            .unwrap();
+        // Re-adding an existing tag doesn't duplicate it, and a missing note keeps the old one
+        repo
+            .annotate_run("f1", "r1", vec!["flaky".to_string()], None)
+            .await
+            .unwrap();
+
+        // Verify persistence
+        drop(repo);
+        let repo2 = FileFlowRepository::new(dir.path().to_path_buf());
+        repo2.load_all().await.unwrap();
+
+        let r1 = repo2.get_run("f1", "r1").await.unwrap();
+        assert_eq!(r1.tags, vec!["flaky".to_string()]);
+        assert_eq!(r1.note.as_deref(), Some("timeout upstream"));
+
+        let flaky: Vec<_> = repo2
+            .get_runs("f1", 10)
+            .await
+            .into_iter()
+            .filter(|r| r.has_tag("FLAKY"))
+            .collect();
+        assert_eq!(flaky.len(), 1);
+        assert_eq!(flaky[0].id, "r1");
+    }
+
+    #[tokio::test]
+    async fn test_annotate_missing_run_errors() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+
+        assert!(repo.annotate_run("f1", "nope", vec![], None).await.is_err());
+    }
 }
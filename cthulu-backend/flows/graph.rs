@@ -140,6 +140,27 @@ pub fn build_adjacency(
     (children, parents)
 }
 
+/// Executor nodes with no incoming edges in a flow that has sources.
+///
+/// Such executors run with empty input — almost always a wiring mistake.
+/// Flows without sources (e.g. trigger-context only) are not flagged.
+pub fn orphaned_executors<'a>(
+    nodes: &'a [crate::flows::Node],
+    edges: &[crate::flows::Edge],
+) -> Vec<&'a crate::flows::Node> {
+    use crate::flows::NodeType;
+
+    if !nodes.iter().any(|n| n.node_type == NodeType::Source) {
+        return vec![];
+    }
+    let (_, parents) = build_adjacency(nodes, edges);
+    nodes
+        .iter()
+        .filter(|n| n.node_type == NodeType::Executor)
+        .filter(|n| parents.get(&n.id).is_none_or(|p| p.is_empty()))
+        .collect()
+}
+
 /// Topological sort using Kahn's algorithm over ALL nodes.
 /// Returns node IDs in execution order. Returns Err if the graph has a cycle.
 pub fn topo_sort(
@@ -185,6 +206,53 @@ pub fn topo_sort(
     Ok(sorted)
 }
 
+/// Structural problems that would break a run, checked when a flow is saved:
+/// duplicate node ids, edges to missing nodes, and cycles (the runner
+/// topo-sorts every node, so a cycle anywhere stalls it). A flow with no
+/// nodes yet is a draft and passes. A flow being edited may lack an executor
+/// for a while; `require_executor` is checked when it's enabled or run.
+pub fn validate_flow(flow: &crate::flows::Flow) -> Result<(), Vec<String>> {
+    if flow.nodes.is_empty() {
+        return Ok(());
+    }
+    let mut problems = Vec::new();
+
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    for node in &flow.nodes {
+        if !seen.insert(node.id.as_str()) && !duplicates.contains(&node.id.as_str()) {
+            duplicates.push(node.id.as_str());
+        }
+    }
+    for id in &duplicates {
+        problems.push(format!("duplicate node id '{id}'"));
+    }
+
+    for edge in &flow.edges {
+        for (end, id) in [("source", &edge.source), ("target", &edge.target)] {
+            if !seen.contains(id.as_str()) {
+                problems.push(format!("edge '{}' {end} '{id}' references a missing node", edge.id));
+            }
+        }
+    }
+
+    // Adjacency is keyed by id, so duplicates would make cycle results meaningless
+    if duplicates.is_empty() && topo_sort(&flow.nodes, &flow.edges).is_err() {
+        problems.push("flow graph has a cycle".to_string());
+    }
+
+    if problems.is_empty() { Ok(()) } else { Err(problems) }
+}
+
+/// A flow can only be enabled or run once it has an executor.
+pub fn require_executor(flow: &crate::flows::Flow) -> Result<(), String> {
+    if flow.nodes.iter().any(|n| n.node_type == crate::flows::NodeType::Executor) {
+        Ok(())
+    } else {
+        Err("flow has no executor node".to_string())
+    }
+}
+
 /// Group topologically-sorted nodes into levels for parallel execution.
 /// Level 0 = roots (no parents), level N = max(parent levels) + 1.
 /// Returns Vec<Vec<node_id>> where each inner vec is one level.
@@ -222,6 +290,103 @@ pub fn compute_levels(
     levels
 }
 
+// ── Text export ────────────────────────────────────────────────────────
+
+fn node_type_name(node_type: crate::flows::NodeType) -> &'static str {
+    use crate::flows::NodeType;
+    match node_type {
+        NodeType::Trigger => "trigger",
+        NodeType::Source => "source",
+        NodeType::Executor => "executor",
+        NodeType::Sink => "sink",
+    }
+}
+
+fn node_type_color(node_type: crate::flows::NodeType) -> &'static str {
+    use crate::flows::NodeType;
+    match node_type {
+        NodeType::Trigger => "#f59e0b",
+        NodeType::Source => "#3b82f6",
+        NodeType::Executor => "#8b5cf6",
+        NodeType::Sink => "#10b981",
+    }
+}
+
+/// Only edges whose endpoints both exist — same rule as `build_adjacency`.
+fn valid_edges<'a>(
+    nodes: &[crate::flows::Node],
+    edges: &'a [crate::flows::Edge],
+) -> impl Iterator<Item = &'a crate::flows::Edge> {
+    let node_ids: HashSet<String> = nodes.iter().map(|n| n.id.clone()).collect();
+    edges
+        .iter()
+        .filter(move |e| node_ids.contains(&e.source) && node_ids.contains(&e.target))
+}
+
+/// Render a flow graph as Graphviz DOT, one filled box per node colored by type.
+pub fn to_dot(flow_name: &str, nodes: &[crate::flows::Node], edges: &[crate::flows::Edge]) -> String {
+    let esc = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut out = format!("digraph \"{}\" {{\n", esc(flow_name));
+    out.push_str("  rankdir=LR;\n  node [shape=box, style=\"rounded,filled\", fontcolor=white];\n");
+    for node in nodes {
+        out.push_str(&format!(
+            "  \"{}\" [label=\"{}\\n({}: {})\", fillcolor=\"{}\"];\n",
+            esc(&node.id),
+            esc(&node.label),
+            node_type_name(node.node_type),
+            esc(&node.kind),
+            node_type_color(node.node_type),
+        ));
+    }
+    for edge in valid_edges(nodes, edges) {
+        out.push_str(&format!("  \"{}\" -> \"{}\";\n", esc(&edge.source), esc(&edge.target)));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Render a flow graph as a Mermaid flowchart with a class per node type.
+///
+/// Node ids are replaced with positional ids (`n0`, `n1`, …) because Mermaid
+/// is picky about characters in identifiers.
+pub fn to_mermaid(nodes: &[crate::flows::Node], edges: &[crate::flows::Edge]) -> String {
+    let esc = |s: &str| s.replace('"', "#quot;");
+    let ids: HashMap<&str, String> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.id.as_str(), format!("n{i}")))
+        .collect();
+
+    let mut out = String::from("flowchart LR\n");
+    for node in nodes {
+        out.push_str(&format!(
+            "  {}[\"{}<br/>({}: {})\"]:::{}\n",
+            ids[node.id.as_str()],
+            esc(&node.label),
+            node_type_name(node.node_type),
+            esc(&node.kind),
+            node_type_name(node.node_type),
+        ));
+    }
+    for edge in valid_edges(nodes, edges) {
+        out.push_str(&format!(
+            "  {} --> {}\n",
+            ids[edge.source.as_str()],
+            ids[edge.target.as_str()]
+        ));
+    }
+    for node_type in ["trigger", "source", "executor", "sink"] {
+        let color = nodes
+            .iter()
+            .find(|n| node_type_name(n.node_type) == node_type)
+            .map(|n| node_type_color(n.node_type));
+        if let Some(color) = color {
+            out.push_str(&format!("  classDef {node_type} fill:{color},color:#fff;\n"));
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,6 +478,82 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("cycle"));
     }
 
+    fn make_flow(nodes: Vec<Node>, edges: Vec<Edge>) -> crate::flows::Flow {
+        crate::flows::Flow {
+            id: "f".into(),
+            name: "f".into(),
+            description: String::new(),
+            enabled: true,
+            nodes,
+            edges,
+            version: 0,
+            max_runtime_secs: None,
+            capture_prompts: true,
+            sandbox_backend: None,
+            cost_cap: None,
+            default_session_budget_usd: None,
+            output_transform: vec![],
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_validate_flow_accepts_valid_and_empty_flows() {
+        let flow = make_flow(
+            vec![make_node("s1", NodeType::Source), make_node("e1", NodeType::Executor)],
+            vec![make_edge("s1", "e1")],
+        );
+        assert_eq!(validate_flow(&flow), Ok(()));
+        assert_eq!(validate_flow(&make_flow(vec![], vec![])), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_flow_dangling_edge() {
+        let flow = make_flow(
+            vec![make_node("s1", NodeType::Source), make_node("e1", NodeType::Executor)],
+            vec![make_edge("s1", "e1"), make_edge("e1", "gone")],
+        );
+        assert_eq!(
+            validate_flow(&flow),
+            Err(vec!["edge 'e1->gone' target 'gone' references a missing node".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_validate_flow_no_executor() {
+        // Saving a half-built flow is fine; enabling or running it is not
+        let flow = make_flow(vec![make_node("s1", NodeType::Source)], vec![]);
+        assert_eq!(validate_flow(&flow), Ok(()));
+        assert_eq!(require_executor(&flow), Err("flow has no executor node".to_string()));
+    }
+
+    #[test]
+    fn test_validate_flow_duplicate_ids() {
+        let flow = make_flow(
+            vec![
+                make_node("e1", NodeType::Executor),
+                make_node("e1", NodeType::Executor),
+                make_node("e1", NodeType::Sink),
+            ],
+            vec![],
+        );
+        assert_eq!(validate_flow(&flow), Err(vec!["duplicate node id 'e1'".to_string()]));
+    }
+
+    #[test]
+    fn test_validate_flow_executor_cycle() {
+        let flow = make_flow(
+            vec![
+                make_node("s1", NodeType::Source),
+                make_node("e1", NodeType::Executor),
+                make_node("e2", NodeType::Executor),
+            ],
+            vec![make_edge("s1", "e1"), make_edge("e1", "e2"), make_edge("e2", "e1")],
+        );
+        assert_eq!(validate_flow(&flow), Err(vec!["flow graph has a cycle".to_string()]));
+    }
+
     #[test]
     fn test_compute_levels_linear() {
         let nodes = vec![
@@ -457,6 +698,72 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_orphaned_executors() {
+        let nodes = vec![
+            make_node("t1", NodeType::Trigger),
+            make_node("s1", NodeType::Source),
+            make_node("e1", NodeType::Executor),
+            make_node("e2", NodeType::Executor),
+        ];
+        let edges = vec![make_edge("t1", "s1"), make_edge("s1", "e1")];
+
+        let orphans = orphaned_executors(&nodes, &edges);
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].id, "e2");
+    }
+
+    #[test]
+    fn test_orphaned_executors_ignored_without_sources() {
+        let nodes = vec![
+            make_node("t1", NodeType::Trigger),
+            make_node("e1", NodeType::Executor),
+        ];
+
+        assert!(orphaned_executors(&nodes, &[]).is_empty());
+    }
+
+    fn export_fixture() -> (Vec<Node>, Vec<Edge>) {
+        let mut nodes = vec![
+            make_node("t1", NodeType::Trigger),
+            make_node("s1", NodeType::Source),
+            make_node("e1", NodeType::Executor),
+            make_node("k1", NodeType::Sink),
+        ];
+        nodes[2].label = "Summarize \"news\"".to_string();
+        let edges = vec![
+            make_edge("t1", "s1"),
+            make_edge("s1", "e1"),
+            make_edge("e1", "k1"),
+            make_edge("e1", "ghost"),
+        ];
+        (nodes, edges)
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let (nodes, edges) = export_fixture();
+        let dot = to_dot("Daily", &nodes, &edges);
+
+        assert!(dot.starts_with("digraph \"Daily\""));
+        assert!(dot.contains("Summarize \\\"news\\\""));
+        assert!(dot.contains("(executor: test)"));
+        assert!(dot.contains("fillcolor=\"#8b5cf6\""));
+        // Edge to a missing node is dropped
+        assert_eq!(dot.matches(" -> ").count(), 3);
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let (nodes, edges) = export_fixture();
+        let mermaid = to_mermaid(&nodes, &edges);
+
+        assert!(mermaid.starts_with("flowchart LR"));
+        assert!(mermaid.contains("n2[\"Summarize #quot;news#quot;<br/>(executor: test)\"]:::executor"));
+        assert!(mermaid.contains("classDef sink"));
+        assert_eq!(mermaid.matches(" --> ").count(), 3);
+    }
+
     #[test]
     fn test_build_adjacency() {
         let nodes = vec![
@@ -1,6 +1,10 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::tasks::sources::ContentItem;
+
 pub const MAX_RUNS_PER_FLOW: usize = 100;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -11,6 +15,19 @@ pub enum RunStatus {
     Failed,
 }
 
+/// What caused a flow run to start.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    Manual,
+    Cron,
+    GithubPr,
+    Webhook,
+    /// Runs recorded before provenance was tracked.
+    #[default]
+    Unknown,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlowRun {
     pub id: String,
@@ -20,6 +37,130 @@ pub struct FlowRun {
     pub finished_at: Option<DateTime<Utc>>,
     pub node_runs: Vec<NodeRun>,
     pub error: Option<String>,
+    #[serde(default)]
+    pub trigger: RunTrigger,
+    /// Free-form triage labels (e.g. "flaky", "real-bug").
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Free-form triage note.
+    #[serde(default)]
+    pub note: Option<String>,
+    /// The failed run this one retried; its successful nodes were reused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resumed_from: Option<String>,
+}
+
+impl FlowRun {
+    /// Case-insensitive tag membership check used for run-list filtering.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Summed executor cost, or `None` if no node reported one.
+    pub fn total_cost_usd(&self) -> Option<f64> {
+        self.node_runs
+            .iter()
+            .filter_map(|nr| nr.cost_usd)
+            .reduce(|a, b| a + b)
+    }
+
+    /// Wall-clock duration, once the run has finished.
+    pub fn duration_secs(&self) -> Option<f64> {
+        self.finished_at
+            .map(|end| (end - self.started_at).num_milliseconds() as f64 / 1000.0)
+    }
+
+    /// Start-to-finish milliseconds from the stored timestamps; `None` while
+    /// the run is in flight.
+    pub fn total_duration_ms(&self) -> Option<i64> {
+        elapsed_ms(self.started_at, self.finished_at)
+    }
+}
+
+/// Outcome of a flow's most recently finished run, shown in flow lists.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LastRun {
+    pub run_id: String,
+    pub status: RunStatus,
+    pub finished_at: DateTime<Utc>,
+    pub cost_usd: Option<f64>,
+}
+
+impl LastRun {
+    /// `None` for a run that hasn't finished.
+    pub fn of(run: &FlowRun) -> Option<Self> {
+        Some(Self {
+            run_id: run.id.clone(),
+            status: run.status,
+            finished_at: run.finished_at?,
+            cost_usd: run.total_cost_usd(),
+        })
+    }
+}
+
+impl NodeRun {
+    /// Start-to-finish milliseconds from the stored timestamps; `None` while
+    /// the node is in flight.
+    pub fn duration_ms(&self) -> Option<i64> {
+        elapsed_ms(self.started_at, self.finished_at)
+    }
+}
+
+fn elapsed_ms(started_at: DateTime<Utc>, finished_at: Option<DateTime<Utc>>) -> Option<i64> {
+    // Clamp: a clock step between start and finish mustn't read as negative
+    finished_at.map(|end| (end - started_at).num_milliseconds().max(0))
+}
+
+/// A run as the API returns it: the stored run plus durations derived from
+/// its timestamps, which aren't persisted.
+#[derive(Debug, Serialize)]
+pub struct FlowRunResponse<'a> {
+    pub id: &'a str,
+    pub flow_id: &'a str,
+    pub status: RunStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub total_duration_ms: Option<i64>,
+    pub node_runs: Vec<NodeRunResponse<'a>>,
+    pub error: Option<&'a str>,
+    pub trigger: RunTrigger,
+    pub tags: &'a [String],
+    pub note: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resumed_from: Option<&'a str>,
+}
+
+impl<'a> From<&'a FlowRun> for FlowRunResponse<'a> {
+    fn from(run: &'a FlowRun) -> Self {
+        Self {
+            id: &run.id,
+            flow_id: &run.flow_id,
+            status: run.status,
+            started_at: run.started_at,
+            finished_at: run.finished_at,
+            total_duration_ms: run.total_duration_ms(),
+            node_runs: run.node_runs.iter().map(NodeRunResponse::from).collect(),
+            error: run.error.as_deref(),
+            trigger: run.trigger,
+            tags: &run.tags,
+            note: run.note.as_deref(),
+            resumed_from: run.resumed_from.as_deref(),
+        }
+    }
+}
+
+/// A node run as the API returns it, with its derived `duration_ms`.
+#[derive(Debug, Serialize)]
+pub struct NodeRunResponse<'a> {
+    #[serde(flatten)]
+    pub node_run: &'a NodeRun,
+    pub duration_ms: Option<i64>,
+}
+
+impl<'a> From<&'a NodeRun> for NodeRunResponse<'a> {
+    fn from(node_run: &'a NodeRun) -> Self {
+        Self { node_run, duration_ms: node_run.duration_ms() }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,4 +170,101 @@ pub struct NodeRun {
     pub started_at: DateTime<Utc>,
     pub finished_at: Option<DateTime<Utc>>,
     pub output_preview: Option<String>,
+    /// Full (size-capped) node output, shown in run detail only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Rendered executor prompt (size-capped), shown in run detail only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Full output of a successful node, kept so a retry can skip it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<NodeCheckpoint>,
+    /// What an executor's claude session reported it cost.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+/// Restorable output of a node (see `NodeOutput`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NodeCheckpoint {
+    Items { items: Vec<ContentItem> },
+    Text { text: String },
+    Context { vars: HashMap<String, String> },
+    Empty,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_run_without_trigger_defaults_to_unknown() {
+        let json = r#"{
+            "id": "r1",
+            "flow_id": "f1",
+            "status": "success",
+            "started_at": "2026-01-01T00:00:00Z",
+            "finished_at": null,
+            "node_runs": [],
+            "error": null
+        }"#;
+        let run: FlowRun = serde_json::from_str(json).unwrap();
+        assert_eq!(run.trigger, RunTrigger::Unknown);
+    }
+
+    fn node_run(id: &str, started_at: DateTime<Utc>, finished_at: Option<DateTime<Utc>>) -> NodeRun {
+        NodeRun {
+            node_id: id.to_string(),
+            status: if finished_at.is_some() { RunStatus::Success } else { RunStatus::Running },
+            started_at,
+            finished_at,
+            output_preview: None,
+            output: None,
+            prompt: None,
+            checkpoint: None,
+            cost_usd: Some(0.5),
+        }
+    }
+
+    #[test]
+    fn responses_carry_durations_from_stored_timestamps() {
+        let start: DateTime<Utc> = "2026-01-01T00:00:00Z".parse().unwrap();
+        let ms = chrono::Duration::milliseconds;
+        let mut run = FlowRun {
+            id: "r1".to_string(),
+            flow_id: "f1".to_string(),
+            status: RunStatus::Running,
+            started_at: start,
+            finished_at: None,
+            node_runs: vec![
+                node_run("s1", start, Some(start + ms(1500))),
+                node_run("e1", start + ms(1500), None),
+            ],
+            error: None,
+            trigger: RunTrigger::Manual,
+            tags: vec![],
+            note: None,
+            resumed_from: None,
+        };
+
+        // In flight: null, not a negative or wall-clock-based number
+        let json = serde_json::to_value(FlowRunResponse::from(&run)).unwrap();
+        assert_eq!(json["total_duration_ms"], serde_json::Value::Null);
+        assert_eq!(json["node_runs"][0]["duration_ms"], 1500);
+        assert_eq!(json["node_runs"][1]["duration_ms"], serde_json::Value::Null);
+        assert!(json.get("resumed_from").is_none());
+        assert!(json["node_runs"][0].get("output").is_none());
+
+        assert_eq!(json["node_runs"][0]["cost_usd"], 0.5);
+
+        run.finished_at = Some(start + ms(4250));
+        let json = serde_json::to_value(FlowRunResponse::from(&run)).unwrap();
+        assert_eq!(json["total_duration_ms"], 4250);
+
+        // Stored runs don't carry the derived fields
+        let stored = serde_json::to_value(&run).unwrap();
+        assert!(stored.get("total_duration_ms").is_none());
+        assert!(stored["node_runs"][0].get("duration_ms").is_none());
+    }
 }
@@ -0,0 +1,403 @@
+//! Import a standalone flow YAML from a URL (a raw GitHub file, a gist, ...)
+//! or an uploaded body, and export flows in the same format.
+//!
+//! Only https URLs are fetched, optionally limited to `FLOW_IMPORT_ALLOWED_HOSTS`,
+//! and hosts that are or resolve to private/loopback addresses are refused, on
+//! every redirect hop, so the endpoint can't be pointed at internal services.
+//! The imported flow gets fresh ids and starts disabled, like template imports;
+//! its sandbox backend and cost cap are dropped for the importer to set.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use reqwest::Url;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::output_transform::OutputTransform;
+use super::{with_fresh_ids, Edge, Flow, Node, NodeType};
+use crate::flows::{graph, processors};
+
+/// Largest flow YAML accepted.
+pub const MAX_IMPORT_BYTES: usize = 256 * 1024;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_REDIRECTS: usize = 5;
+
+/// Hosts flows may be imported from, from `FLOW_IMPORT_ALLOWED_HOSTS`
+/// (comma-separated). Empty = any public https host.
+pub fn allowed_hosts_from_env() -> Vec<String> {
+    std::env::var("FLOW_IMPORT_ALLOWED_HOSTS")
+        .map(|v| {
+            v.split(',')
+                .map(|h| h.trim().to_ascii_lowercase())
+                .filter(|h| !h.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                // 0.0.0.0/8 ("this network") and 100.64.0.0/10 (carrier-grade NAT)
+                || a == 0
+                || (a == 100 && (b & 0xc0) == 64)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_private(IpAddr::V4(v4));
+            }
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // fc00::/7 (unique local) and fe80::/10 (link local)
+                || (ip.segments()[0] & 0xfe00) == 0xfc00
+                || (ip.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// Check that `url` is https, names a host, and is allowed.
+pub fn check_url(url: &str, allowed_hosts: &[String]) -> Result<Url> {
+    let parsed = Url::parse(url.trim()).context("invalid URL")?;
+    if parsed.scheme() != "https" {
+        bail!("only https URLs can be imported");
+    }
+    let host = parsed
+        .host_str()
+        .context("URL has no host")?
+        .trim_matches(|c| c == '[' || c == ']')
+        .to_ascii_lowercase();
+    if host == "localhost" || host.parse::<IpAddr>().is_ok_and(is_private) {
+        bail!("URL points at a private address");
+    }
+    if !allowed_hosts.is_empty() && !allowed_hosts.contains(&host) {
+        bail!("host '{host}' is not in the import allowlist");
+    }
+    Ok(parsed)
+}
+
+/// Read a response body, failing once it passes `max` bytes.
+async fn read_capped(mut resp: reqwest::Response, max: usize) -> Result<String> {
+    if resp.content_length().is_some_and(|len| len as usize > max) {
+        bail!("flow YAML is larger than {max} bytes");
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await.context("failed to read flow YAML")? {
+        body.extend_from_slice(&chunk);
+        if body.len() > max {
+            bail!("flow YAML is larger than {max} bytes");
+        }
+    }
+    String::from_utf8(body).context("flow YAML is not valid UTF-8")
+}
+
+/// Resolves hosts for import fetches, refusing a name if any of its
+/// addresses is private. Literal IPs never reach it; `check_url` covers those.
+struct PublicOnlyResolver;
+
+impl reqwest::dns::Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if let Some(addr) = addrs.iter().find(|addr| is_private(addr.ip())) {
+                return Err(format!("host '{host}' resolves to private address {}", addr.ip()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// A client for import fetches: every redirect hop is checked like the
+/// original URL, and every host must resolve to public addresses only.
+fn import_client(allowed_hosts: &[String]) -> Result<reqwest::Client> {
+    let allowed_hosts = allowed_hosts.to_vec();
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                return attempt.error("too many redirects");
+            }
+            match check_url(attempt.url().as_str(), &allowed_hosts) {
+                Ok(_) => attempt.follow(),
+                Err(e) => attempt.error(format!("redirected to a disallowed URL: {e:#}")),
+            }
+        }))
+        .dns_resolver(Arc::new(PublicOnlyResolver))
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .context("failed to build import HTTP client")
+}
+
+/// Fetch the YAML at `url`.
+pub async fn fetch_flow_yaml(url: &str, allowed_hosts: &[String]) -> Result<String> {
+    let url = check_url(url, allowed_hosts)?;
+    let resp = import_client(allowed_hosts)?
+        .get(url)
+        .send()
+        .await
+        .context("failed to fetch flow YAML")?
+        .error_for_status()
+        .context("flow URL returned an error status")?;
+    read_capped(resp, MAX_IMPORT_BYTES).await
+}
+
+/// The parts of a flow document an import keeps; ids and timestamps are
+/// ignored and regenerated.
+#[derive(Deserialize)]
+struct FlowDocument {
+    name: String,
+    #[serde(default)]
+    description: String,
+    nodes: Vec<Node>,
+    #[serde(default)]
+    edges: Vec<Edge>,
+    #[serde(default)]
+    max_runtime_secs: Option<u64>,
+    #[serde(default)]
+    capture_prompts: Option<bool>,
+    #[serde(default, alias = "session_budget_usd")]
+    default_session_budget_usd: Option<f64>,
+    #[serde(default)]
+    output_transform: Vec<OutputTransform>,
+}
+
+/// Every problem that kept a flow document from importing.
+#[derive(Debug, thiserror::Error)]
+#[error("{}", .problems.join("; "))]
+pub struct InvalidFlow {
+    pub problems: Vec<String>,
+}
+
+/// `graph::validate_flow`'s structural checks, plus: the flow has nodes, an
+/// executor, and source/sink configs that parse.
+fn validate(flow: &Flow) -> std::result::Result<(), InvalidFlow> {
+    if flow.nodes.is_empty() {
+        return Err(InvalidFlow { problems: vec!["flow has no nodes".to_string()] });
+    }
+    let mut problems = graph::validate_flow(flow).err().unwrap_or_default();
+    if let Err(e) = graph::require_executor(flow) {
+        problems.push(e);
+    }
+
+    let of_type = |t: NodeType| flow.nodes.iter().filter(|n| n.node_type == t).collect::<Vec<_>>();
+    if let Err(e) = processors::parse_source_configs(&of_type(NodeType::Source)) {
+        problems.push(format!("{e:#}"));
+    }
+    if let Err(e) = processors::parse_sink_configs(&of_type(NodeType::Sink)) {
+        problems.push(format!("{e:#}"));
+    }
+    if problems.is_empty() { Ok(()) } else { Err(InvalidFlow { problems }) }
+}
+
+/// Parse and validate a flow YAML, returning a new disabled flow with fresh
+/// flow, node and edge ids. A document that parses but fails validation
+/// errors with `InvalidFlow`.
+pub fn parse_flow_yaml(yaml: &str) -> Result<Flow> {
+    let doc: FlowDocument = serde_yaml::from_str(yaml).context("failed to parse flow YAML")?;
+
+    let now = Utc::now();
+    let flow = Flow {
+        id: Uuid::new_v4().to_string(),
+        name: doc.name,
+        description: doc.description,
+        enabled: false,
+        nodes: doc.nodes,
+        edges: doc.edges,
+        version: 0,
+        max_runtime_secs: doc.max_runtime_secs.filter(|secs| *secs > 0),
+        capture_prompts: doc.capture_prompts.unwrap_or(true),
+        // Where it runs and what it may spend are the importer's call, not the
+        // document's
+        sandbox_backend: None,
+        cost_cap: None,
+        default_session_budget_usd: doc.default_session_budget_usd.filter(|usd| *usd > 0.0),
+        output_transform: doc.output_transform,
+        created_at: now,
+        updated_at: now,
+    };
+    // Checked before renaming so problems name the document's own ids
+    validate(&flow)?;
+
+    let (nodes, edges) = with_fresh_ids(flow.nodes, flow.edges);
+    Ok(Flow { nodes, edges, ..flow })
+}
+
+/// Serialize a flow as a YAML document `parse_flow_yaml` reads back.
+pub fn flow_to_yaml(flow: &Flow) -> Result<String> {
+    serde_yaml::to_string(flow).context("failed to serialize flow")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOW_YAML: &str = r#"
+id: original-id
+name: Imported digest
+nodes:
+  - id: t1
+    node_type: trigger
+    kind: manual
+    config: {}
+    position: { x: 0, y: 0 }
+    label: Manual
+  - id: s1
+    node_type: source
+    kind: rss
+    config: { url: "https://example.com/feed.xml" }
+    position: { x: 200, y: 0 }
+    label: Feed
+  - id: e1
+    node_type: executor
+    kind: claude-code
+    config: { prompt: "Summarize" }
+    position: { x: 400, y: 0 }
+    label: Summarize
+edges:
+  - { id: a, source: t1, target: s1 }
+  - { id: b, source: s1, target: e1 }
+"#;
+
+    #[test]
+    fn test_parse_flow_yaml_assigns_fresh_ids() {
+        let flow = parse_flow_yaml(FLOW_YAML).unwrap();
+        assert_eq!(flow.name, "Imported digest");
+        assert_ne!(flow.id, "original-id");
+        assert!(!flow.enabled);
+        assert_eq!(flow.nodes.len(), 3);
+        assert!(flow.nodes.iter().all(|n| !["t1", "s1", "e1"].contains(&n.id.as_str())));
+
+        // Edges follow the renamed nodes.
+        let source = flow.nodes.iter().find(|n| n.kind == "rss").unwrap();
+        let executor = flow.nodes.iter().find(|n| n.kind == "claude-code").unwrap();
+        assert!(flow.edges.iter().any(|e| e.source == source.id && e.target == executor.id));
+    }
+
+    #[test]
+    fn test_parse_flow_yaml_rejects_invalid_flows() {
+        let dangling = FLOW_YAML.replace("target: e1", "target: missing");
+        assert!(parse_flow_yaml(&dangling).unwrap_err().to_string().contains("missing node"));
+
+        let bad_source = FLOW_YAML.replace("kind: rss", "kind: nonsense");
+        assert!(parse_flow_yaml(&bad_source).is_err());
+
+        assert!(parse_flow_yaml("not: [a flow").is_err());
+    }
+
+    #[test]
+    fn test_parse_flow_yaml_reads_old_session_budget_name() {
+        let yaml = format!("session_budget_usd: 2.5\n{FLOW_YAML}");
+        assert_eq!(parse_flow_yaml(&yaml).unwrap().default_session_budget_usd, Some(2.5));
+    }
+
+    #[test]
+    fn test_invalid_flow_lists_every_problem() {
+        let yaml = FLOW_YAML
+            .replace("target: e1", "target: missing")
+            .replace("kind: rss", "kind: nonsense");
+        let err = parse_flow_yaml(&yaml).unwrap_err();
+        let invalid = err.downcast_ref::<InvalidFlow>().unwrap();
+        assert_eq!(invalid.problems.len(), 2, "{:?}", invalid.problems);
+
+        // Missing required fields fail to parse at all
+        let err = parse_flow_yaml("nodes: []").unwrap_err();
+        assert!(err.downcast_ref::<InvalidFlow>().is_none());
+        assert!(format!("{err:#}").contains("name"));
+    }
+
+    #[test]
+    fn test_export_import_round_trip_preserves_configs() {
+        let yaml = FLOW_YAML.replace(
+            r#"config: { prompt: "Summarize" }"#,
+            r#"config: { prompt: "Summarize:\n  {{content}}", max_turns: 3, temperature: 0.5, filters: [{ kind: limit, count: 5 }], model: "", extra: null, "1": "01" }"#,
+        );
+        let original = parse_flow_yaml(&yaml).unwrap();
+        let exported = flow_to_yaml(&original).unwrap();
+        let imported = parse_flow_yaml(&exported).unwrap();
+
+        assert_ne!(imported.id, original.id);
+        assert_eq!(imported.name, original.name);
+        assert_eq!(imported.nodes.len(), original.nodes.len());
+        for node in &original.nodes {
+            let copy = imported.nodes.iter().find(|n| n.label == node.label).unwrap();
+            assert_eq!(copy.config, node.config, "config of {}", node.label);
+            assert_eq!(copy.kind, node.kind);
+        }
+    }
+
+    #[test]
+    fn test_check_url() {
+        assert!(check_url("https://raw.githubusercontent.com/o/r/main/flow.yaml", &[]).is_ok());
+        assert!(check_url("http://raw.githubusercontent.com/o/r/main/flow.yaml", &[]).is_err());
+        assert!(check_url("file:///etc/passwd", &[]).is_err());
+        assert!(check_url("https://127.0.0.1/flow.yaml", &[]).is_err());
+        assert!(check_url("https://10.0.0.5/flow.yaml", &[]).is_err());
+        assert!(check_url("https://[::1]/flow.yaml", &[]).is_err());
+        assert!(check_url("https://[::ffff:127.0.0.1]/flow.yaml", &[]).is_err());
+        assert!(check_url("https://100.64.0.1/flow.yaml", &[]).is_err());
+        assert!(check_url("https://169.254.169.254/latest/meta-data", &[]).is_err());
+        assert!(check_url("https://100.128.0.1/flow.yaml", &[]).is_ok());
+
+        let allowed = vec!["gist.githubusercontent.com".to_string()];
+        assert!(check_url("https://gist.githubusercontent.com/u/abc/raw/flow.yaml", &allowed).is_ok());
+        assert!(check_url("https://evil.example.com/flow.yaml", &allowed).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolver_refuses_names_with_private_addresses() {
+        use reqwest::dns::Resolve;
+
+        let err = PublicOnlyResolver.resolve("localhost".parse().unwrap()).await.err().unwrap();
+        assert!(err.to_string().contains("private address"), "{err}");
+    }
+
+    #[test]
+    fn test_import_drops_sandbox_backend_and_cost_cap() {
+        let yaml = format!("sandbox_backend: firecracker\ncost_cap: {{ max_usd: 1000 }}\n{FLOW_YAML}");
+        let flow = parse_flow_yaml(&yaml).unwrap();
+        assert_eq!(flow.sandbox_backend, None);
+        assert!(flow.cost_cap.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_is_rejected() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Streams a body past the cap without a Content-Length
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = sock.read(&mut buf).await;
+            let _ = sock
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/yaml\r\nConnection: close\r\n\r\n")
+                .await;
+            let chunk = vec![b'a'; 16 * 1024];
+            for _ in 0..(MAX_IMPORT_BYTES / chunk.len() + 2) {
+                if sock.write_all(&chunk).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let resp = reqwest::Client::new()
+            .get(format!("http://{addr}/flow.yaml"))
+            .send()
+            .await
+            .unwrap();
+        let err = read_capped(resp, MAX_IMPORT_BYTES).await.unwrap_err();
+        assert!(err.to_string().contains("larger than"));
+    }
+}
@@ -1,12 +1,22 @@
+pub mod bundle;
+pub mod compare;
+pub mod cost_cap;
+pub mod cron_schedule;
+pub mod dir_lock;
 pub mod events;
+pub mod export;
 pub mod file_repository;
 pub mod graph;
 pub mod history;
+pub mod import;
+pub mod output_transform;
 pub mod processors;
 pub mod repository;
 pub mod runner;
 pub mod scheduler;
 pub mod session_bridge;
+pub mod shutdown;
+pub mod webhook;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -23,6 +33,30 @@ pub struct Flow {
     pub edges: Vec<Edge>,
     #[serde(default)]
     pub version: u64,
+    /// Wall-clock budget for a whole run. The run is cancelled and marked
+    /// failed once exceeded. `None` = no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_runtime_secs: Option<u64>,
+    /// Keep each executor's rendered prompt on the run record. Turn off for
+    /// flows whose prompts carry sensitive data.
+    #[serde(default = "default_true")]
+    pub capture_prompts: bool,
+    /// Sandbox backend (`dangerous`, `firecracker`, ...) for this flow's
+    /// sandboxed executors. `None` = the server default; an executor's own
+    /// `sandbox_backend` config wins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox_backend: Option<String>,
+    /// Rolling cost cap on scheduled runs; `None` = unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_cap: Option<cost_cap::CostCap>,
+    /// Cost budget (USD) applied to each chat session on this flow's executor
+    /// nodes that has no `cost_budget_usd` of its own. Per session, not a cap
+    /// on the flow's combined spend (see `cost_cap` for that). `None` = unlimited.
+    #[serde(default, alias = "session_budget_usd", skip_serializing_if = "Option::is_none")]
+    pub default_session_budget_usd: Option<f64>,
+    /// Steps applied, in order, to executor output before sinks deliver it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output_transform: Vec<output_transform::OutputTransform>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -31,6 +65,52 @@ fn default_true() -> bool {
     true
 }
 
+impl Flow {
+    /// A disabled copy named "<name> (copy)" with fresh flow, node and edge
+    /// ids and a reset version.
+    pub fn duplicate(&self) -> Flow {
+        let (nodes, edges) = with_fresh_ids(self.nodes.clone(), self.edges.clone());
+        let now = Utc::now();
+        Flow {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: format!("{} (copy)", self.name),
+            enabled: false,
+            nodes,
+            edges,
+            version: 0,
+            created_at: now,
+            updated_at: now,
+            ..self.clone()
+        }
+    }
+}
+
+/// Give every node and edge a new id, pointing edges at the renamed nodes.
+/// An edge end that names no node is left as it was.
+pub fn with_fresh_ids(nodes: Vec<Node>, edges: Vec<Edge>) -> (Vec<Node>, Vec<Edge>) {
+    let new_ids: std::collections::HashMap<String, String> = nodes
+        .iter()
+        .map(|n| (n.id.clone(), uuid::Uuid::new_v4().to_string()))
+        .collect();
+    let renamed = |id: String| new_ids.get(&id).cloned().unwrap_or(id);
+    let edges = edges
+        .into_iter()
+        .map(|edge| Edge {
+            id: uuid::Uuid::new_v4().to_string(),
+            source: renamed(edge.source),
+            target: renamed(edge.target),
+        })
+        .collect();
+    let nodes = nodes
+        .into_iter()
+        .map(|node| Node {
+            id: new_ids[&node.id].clone(),
+            ..node
+        })
+        .collect();
+    (nodes, edges)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Node {
     pub id: String,
@@ -84,6 +164,12 @@ mod tests {
             }],
             edges: vec![],
             version: 0,
+            max_runtime_secs: None,
+            capture_prompts: true,
+            sandbox_backend: None,
+            cost_cap: None,
+            default_session_budget_usd: None,
+            output_transform: vec![],
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -95,6 +181,75 @@ mod tests {
         assert_eq!(parsed.nodes[0].node_type, NodeType::Trigger);
     }
 
+    #[test]
+    fn test_duplicate_remaps_ids_consistently() {
+        let node = |id: &str, node_type| Node {
+            id: id.to_string(),
+            node_type,
+            kind: "test".to_string(),
+            config: serde_json::json!({ "label_of": id }),
+            position: Position { x: 0.0, y: 0.0 },
+            label: id.to_string(),
+        };
+        let edge = |id: &str, source: &str, target: &str| Edge {
+            id: id.to_string(),
+            source: source.to_string(),
+            target: target.to_string(),
+        };
+        let original = Flow {
+            id: "flow-1".to_string(),
+            name: "Digest".to_string(),
+            description: String::new(),
+            enabled: true,
+            nodes: vec![
+                node("t1", NodeType::Trigger),
+                node("s1", NodeType::Source),
+                node("s2", NodeType::Source),
+                node("e1", NodeType::Executor),
+                node("k1", NodeType::Sink),
+            ],
+            edges: vec![
+                edge("a", "t1", "s1"),
+                edge("b", "t1", "s2"),
+                edge("c", "s1", "e1"),
+                edge("d", "s2", "e1"),
+                edge("e", "e1", "k1"),
+            ],
+            version: 7,
+            max_runtime_secs: Some(60),
+            capture_prompts: true,
+            sandbox_backend: None,
+            cost_cap: None,
+            default_session_budget_usd: None,
+            output_transform: vec![],
+            created_at: Utc::now() - chrono::Duration::days(3),
+            updated_at: Utc::now() - chrono::Duration::days(1),
+        };
+
+        let copy = original.duplicate();
+        assert_ne!(copy.id, original.id);
+        assert_eq!(copy.name, "Digest (copy)");
+        assert!(!copy.enabled);
+        assert_eq!(copy.version, 0);
+        assert!(copy.created_at > original.created_at);
+        assert_eq!(copy.max_runtime_secs, Some(60));
+
+        // Every node and edge id is new, and each edge joins the copies of
+        // the nodes its original joined
+        let old_of: std::collections::HashMap<&str, &str> = copy
+            .nodes
+            .iter()
+            .map(|n| (n.id.as_str(), n.config["label_of"].as_str().unwrap()))
+            .collect();
+        assert_eq!(old_of.len(), 5);
+        assert!(copy.nodes.iter().all(|n| n.id != n.label));
+        for (copied, orig) in copy.edges.iter().zip(&original.edges) {
+            assert_ne!(copied.id, orig.id);
+            assert_eq!(old_of[copied.source.as_str()], orig.source);
+            assert_eq!(old_of[copied.target.as_str()], orig.target);
+        }
+    }
+
     #[test]
     fn test_node_type_serialization() {
         assert_eq!(
@@ -0,0 +1,117 @@
+//! Post-processing applied to executor output before it reaches a sink.
+//!
+//! A flow's `output_transform` is an ordered list; each step sees the
+//! previous step's result.
+
+use serde::{Deserialize, Serialize};
+
+/// Longest first line `strip_preamble` treats as a lead-in.
+const MAX_PREAMBLE_CHARS: usize = 120;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputTransform {
+    /// Drop a short lead-in line ending in `:` ("Here's your report:").
+    StripPreamble,
+    /// Cut to at most `max` characters, ending in `…` when cut.
+    MaxChars { max: usize },
+    Prepend { text: String },
+    Append { text: String },
+}
+
+impl OutputTransform {
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            OutputTransform::StripPreamble => strip_preamble(text),
+            OutputTransform::MaxChars { max } => max_chars(text, *max),
+            OutputTransform::Prepend { text: prefix } => format!("{prefix}{text}"),
+            OutputTransform::Append { text: suffix } => format!("{text}{suffix}"),
+        }
+    }
+}
+
+/// Run `transforms` over `text` in order.
+pub fn apply_all(transforms: &[OutputTransform], text: &str) -> String {
+    transforms
+        .iter()
+        .fold(text.to_string(), |text, transform| transform.apply(&text))
+}
+
+fn strip_preamble(text: &str) -> String {
+    let trimmed = text.trim_start();
+    let Some((first, rest)) = trimmed.split_once('\n') else {
+        return text.to_string();
+    };
+    let line = first.trim();
+    let is_preamble = line.ends_with(':')
+        && line.chars().count() <= MAX_PREAMBLE_CHARS
+        // Headings, list items, tables and quotes are content
+        && !line.starts_with(['#', '-', '*', '|', '>', '`']);
+    if is_preamble && !rest.trim().is_empty() {
+        rest.trim_start_matches(['\r', '\n']).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+fn max_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+    let mut cut: String = text.chars().take(max - 1).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_preamble() {
+        let t = OutputTransform::StripPreamble;
+        assert_eq!(t.apply("Here's your report:\n\n# Daily\n- item"), "# Daily\n- item");
+        assert_eq!(t.apply("  Sure! Here is the summary:\r\n\r\nBTC up"), "BTC up");
+        // Content that merely starts with a heading or list is kept
+        assert_eq!(t.apply("# Report:\nbody"), "# Report:\nbody");
+        assert_eq!(t.apply("- note:\nbody"), "- note:\nbody");
+        // No colon, or nothing after the lead-in: unchanged
+        assert_eq!(t.apply("BTC up 5%\nETH flat"), "BTC up 5%\nETH flat");
+        assert_eq!(t.apply("Here's your report:\n\n"), "Here's your report:\n\n");
+    }
+
+    #[test]
+    fn test_max_chars() {
+        let t = OutputTransform::MaxChars { max: 5 };
+        assert_eq!(t.apply("hello"), "hello");
+        assert_eq!(t.apply("hello world"), "hell…");
+        assert_eq!(t.apply("ééééééé"), "éééé…");
+        assert_eq!(OutputTransform::MaxChars { max: 0 }.apply("x"), "");
+    }
+
+    #[test]
+    fn test_prepend_and_append() {
+        assert_eq!(OutputTransform::Prepend { text: "📈 ".into() }.apply("BTC"), "📈 BTC");
+        assert_eq!(OutputTransform::Append { text: "\n— bot".into() }.apply("BTC"), "BTC\n— bot");
+    }
+
+    #[test]
+    fn test_transforms_compose_in_order() {
+        let transforms: Vec<OutputTransform> = serde_json::from_value(serde_json::json!([
+            { "type": "strip_preamble" },
+            { "type": "prepend", "text": ">> " },
+            { "type": "max_chars", "max": 10 },
+            { "type": "append", "text": " [end]" },
+        ]))
+        .unwrap();
+        assert_eq!(apply_all(&transforms, "Here you go:\nBitcoin rallied today"), ">> Bitcoi… [end]");
+
+        // Same steps, different order, different result
+        let reordered = [transforms[3].clone(), transforms[2].clone()];
+        assert_eq!(apply_all(&reordered, "Bitcoin rallied"), "Bitcoin r…");
+        assert_eq!(apply_all(&[], "as is"), "as is");
+    }
+}
@@ -1,13 +1,14 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{Context, Result, bail};
 use chrono::Utc;
 
+use crate::agents::{node_max_turns, node_model, node_timeout};
 use crate::agents::repository::AgentRepository;
 use crate::api::{FlowSessions, InteractSession};
-use crate::config::{SinkConfig, SourceConfig};
+use crate::config::{RestSourceConfig, SinkConfig, SourceConfig, WorkingDirPolicy};
 use crate::flows::graph::NodeOutput;
 use crate::flows::session_bridge::{FlowRunMeta, SessionBridge};
 use crate::flows::{Node, NodeType};
@@ -17,8 +18,18 @@ use crate::tasks::context::render_prompt;
 use crate::tasks::executors::{Executor, LineSink};
 use crate::tasks::executors::claude_code::ClaudeCodeExecutor;
 use crate::tasks::executors::sandbox::SandboxExecutor;
-use crate::tasks::pipeline::{format_items, resolve_sinks};
+use crate::tasks::filters::{self, Filter};
+use crate::tasks::filters::date_window::DateWindowFilter;
+use crate::tasks::filters::dedup::{DedupFilter, DedupKey, PendingDedup, DEFAULT_MAX_REMEMBERED};
+use crate::tasks::filters::limit::{LimitFilter, LimitStrategy};
+use crate::tasks::pipeline::{format_items_with, format_items_within, resolve_sinks, FormatOptions};
+use crate::tasks::rank::{self, RankMode};
+use crate::tasks::sinks::retry::{deliver_with_retry, RetryPolicy, DEFAULT_MAX_RETRIES};
+use crate::tasks::sinks::slack::blocks::MAX_BLOCKS_PER_MESSAGE;
 use crate::tasks::sources;
+use crate::tasks::sources::github_issues::IssueState;
+use crate::tasks::sources::hackernews::StoryType;
+use crate::tasks::sources::reddit::{self, RedditSort};
 
 /// Dependencies needed by node processors.
 /// Cloneable so it can be shared across parallel tasks.
@@ -35,8 +46,33 @@ pub struct NodeDeps {
     pub run_id: Option<String>,
     /// Flow name (for flow-run session metadata).
     pub flow_name: Option<String>,
+    /// Receives each executor's rendered prompt for the run record.
+    /// `None` when the flow opted out of prompt capture.
+    pub prompt_sink: Option<PromptSink>,
+    /// Told when an executor has to wait for its working dir to be released.
+    pub lock_wait_sink: Option<LockWaitSink>,
+    /// Receives what each sink node delivered, for the run record.
+    pub delivery_sink: Option<DeliverySink>,
+    /// Validates executor `additional_dirs`.
+    pub dir_policy: WorkingDirPolicy,
+    /// Dry runs only render executor prompts; stateful filters must not
+    /// record anything.
+    pub dry_run: bool,
+    /// Dedup keys this run let through, recorded once it succeeds.
+    pub dedup_pending: PendingDedup,
 }
 
+/// Callback receiving `(node_id, rendered_prompt)`.
+pub type PromptSink = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+/// Callback receiving `(node_id, working_dir)`.
+pub type LockWaitSink = Arc<dyn Fn(&str, &std::path::Path) + Send + Sync>;
+
+/// Callback receiving `(node_id, delivered_text, receipts, attempts)`, where
+/// receipts are the identifiers the sink's destinations returned (Slack `ts`,
+/// ...) and attempts counts deliveries including retries.
+pub type DeliverySink = Arc<dyn Fn(&str, &str, &[String], u32) + Send + Sync>;
+
 /// Process a single node, dispatching by type.
 /// Returns (NodeOutput, Option<ExecutionResult>) — the execution result is only
 /// populated for executor nodes.
@@ -80,6 +116,9 @@ async fn process_source(node: &Node, deps: &NodeDeps) -> Result<NodeOutput> {
 
 // ── Executor Processing ────────────────────────────────────────────────
 
+/// Slack past an executor's own timeout before the node is abandoned.
+const EXECUTOR_TIMEOUT_GRACE: std::time::Duration = std::time::Duration::from_secs(30);
+
 async fn process_executor(
     node: &Node,
     input: NodeOutput,
@@ -87,9 +126,14 @@ async fn process_executor(
 ) -> Result<NodeOutput> {
     // Build prompt from input
     let rendered = render_executor_prompt(node, &input, deps).await?;
+    if let Some(sink) = &deps.prompt_sink {
+        sink(&node.id, &rendered);
+    }
 
     // Resolve agent config
-    let (permissions, append_system_prompt) = resolve_agent_config(node, deps).await?;
+    let agent = resolve_agent_config(node, deps).await?;
+    let permissions = agent.permissions.clone();
+    let append_system_prompt = agent.append_system_prompt.clone();
 
     // Resolve working dir
     let working_dir = node.config["working_dir"]
@@ -102,36 +146,72 @@ async fn process_executor(
         .as_str()
         .unwrap_or(node.kind.as_str());
 
+    let additional_dirs = deps
+        .dir_policy
+        .check_additional(&node.config["additional_dirs"])
+        .with_context(|| format!("executor node '{}' has invalid additional_dirs", node.label))?;
+
+    let model = node_model(&node.config);
+    let max_turns = node_max_turns(&node.config);
+    let timeout = node_timeout(&node.config);
+
     let executor: Box<dyn Executor> = match runtime {
         "sandbox" => {
+            if !additional_dirs.is_empty() {
+                tracing::warn!(node = %node.label, "additional_dirs is ignored by the sandbox runtime");
+            }
             let provider = deps
                 .sandbox_provider
                 .as_ref()
                 .context("sandbox executor requested but no sandbox provider configured")?;
-            Box::new(SandboxExecutor::new(
-                provider.clone(),
-                permissions.clone(),
-                append_system_prompt,
-            ))
+            Box::new(
+                SandboxExecutor::new(provider.clone(), permissions.clone(), append_system_prompt)
+                    .with_permission_mode(agent.permission_mode, agent.disallowed_tools.clone())
+                    .with_model(model.clone())
+                    .with_max_turns(max_turns)
+                    .with_timeout(timeout),
+            )
         }
-        _ => Box::new(ClaudeCodeExecutor::new(
-            permissions.clone(),
-            append_system_prompt,
-        )),
+        _ => Box::new(
+            ClaudeCodeExecutor::new(permissions.clone(), append_system_prompt)
+                .with_permission_mode(agent.permission_mode, agent.disallowed_tools.clone())
+                .with_additional_dirs(additional_dirs)
+                .with_model(model.clone())
+                .with_max_turns(max_turns)
+                .with_timeout(timeout),
+        ),
     };
 
-    let perms_display = if permissions.is_empty() {
-        "ALL".to_string()
-    } else {
-        permissions.join(", ")
+    let perms_display = match agent.permission_mode {
+        Some(mode) if permissions.is_empty() => mode.as_cli().to_string(),
+        Some(mode) => format!("{} ({})", permissions.join(", "), mode.as_cli()),
+        None if permissions.is_empty() => "ALL".to_string(),
+        None => permissions.join(", "),
     };
     tracing::info!(
         executor = %node.kind,
+        model = model.as_deref().unwrap_or("default"),
         permissions = %perms_display,
         input_chars = rendered.len(),
         "Executing",
     );
 
+    // Don't run while an interactive turn for this flow is using the same dir
+    let _dir_lock = match &deps.session_bridge {
+        Some(bridge) => Some(
+            bridge
+                .dir_locks
+                .acquire_or_wait(&deps.flow_id, &working_dir, || {
+                    tracing::info!(dir = %working_dir.display(), "Waiting for working dir lock");
+                    if let Some(sink) = &deps.lock_wait_sink {
+                        sink(&node.id, &working_dir);
+                    }
+                })
+                .await,
+        ),
+        None => None,
+    };
+
     // Set up session bridge for streaming into agent workspace
     let agent_id = node.config["agent_id"]
         .as_str()
@@ -149,10 +229,19 @@ async fn process_executor(
     )
     .await;
 
-    let exec_result = executor
-        .execute_streaming(&rendered, &working_dir, line_sink.clone())
-        .await
-        .with_context(|| format!("executor '{}' failed", node.label));
+    // The executor kills claude (and tears down its sandbox) at `timeout`;
+    // this backstop catches hangs outside the process, e.g. provisioning.
+    // Dropping the future kills a local claude via `kill_on_drop`.
+    let exec_result = match tokio::time::timeout(
+        timeout + EXECUTOR_TIMEOUT_GRACE,
+        executor.execute_streaming(&rendered, &working_dir, line_sink.clone()),
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(_elapsed) => Err(anyhow::anyhow!("timed out after {}s", timeout.as_secs())),
+    }
+    .with_context(|| format!("executor '{}' failed", node.label));
 
     // Finalize session regardless of success/failure
     finalize_flow_run_session(
@@ -177,29 +266,28 @@ async fn process_executor(
 }
 
 /// Render the prompt for an executor node from its upstream input.
-async fn render_executor_prompt(
+pub async fn render_executor_prompt(
     node: &Node,
     input: &NodeOutput,
     deps: &NodeDeps,
 ) -> Result<String> {
+    let timestamp = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
+
     // If input is Context (e.g. from GitHub PR trigger), use it as template vars
-    let vars = if let Some(ctx) = input.as_context() {
+    let mut items = Vec::new();
+    let mut vars = if let Some(ctx) = input.as_context() {
         let mut vars = ctx.clone();
-        let timestamp = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
         vars.entry("timestamp".to_string()).or_insert(timestamp);
         vars
     } else {
-        // Build template vars from items/text
-        let items = input.as_items();
-        let content = if items.is_empty() {
-            input.as_text()
-        } else {
-            format_items(&items)
-        };
-        let timestamp = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
+        // Build template vars from items/text; `content` is filled in below
+        let data_dir = deps.session_bridge.as_ref().map(|b| b.data_dir.as_path());
+        let pending = (!deps.dry_run).then_some(&deps.dedup_pending);
+        let filters = parse_filter_config(node, &deps.flow_id, data_dir, pending).await?;
+        items = filters::apply_all(&filters, input.as_items());
+        rank_items(node, &mut items);
 
         let mut vars = HashMap::new();
-        vars.insert("content".to_string(), content);
         vars.insert("item_count".to_string(), items.len().to_string());
         vars.insert("timestamp".to_string(), timestamp);
         vars
@@ -212,7 +300,6 @@ async fn render_executor_prompt(
     let prompt_template = load_prompt_template(prompt_path)?;
 
     // Fetch market data if needed
-    let mut vars = vars;
     if prompt_template.contains("{{market_data}}") {
         let market_data = match tokio::time::timeout(
             std::time::Duration::from_secs(15),
@@ -226,28 +313,36 @@ async fn render_executor_prompt(
         vars.insert("market_data".to_string(), market_data);
     }
 
-    let rendered = render_prompt(&prompt_template, &vars);
+    let limit = prompt_limit(node);
+    if input.as_context().is_some() {
+        let rendered = render_prompt(&prompt_template, &vars);
+        return limit.check(rendered, "context");
+    }
 
     // If we have items but the template doesn't use {{content}}, append them
-    let items = input.as_items();
-    let rendered = if !items.is_empty() && !prompt_template.contains("{{content}}") {
-        format!(
-            "{rendered}\n\n<<<\n{}\n>>>",
-            vars.get("content").cloned().unwrap_or_default()
-        )
-    } else {
-        rendered
-    };
-
-    Ok(rendered)
+    let append_content = !items.is_empty() && !prompt_template.contains("{{content}}");
+    let text = if items.is_empty() { Some(input.as_text()) } else { None };
+    render_within_limit(
+        |content| {
+            let mut vars = vars.clone();
+            vars.insert("content".to_string(), content.to_string());
+            let rendered = render_prompt(&prompt_template, &vars);
+            if append_content {
+                format!("{rendered}\n\n<<<\n{content}\n>>>")
+            } else {
+                rendered
+            }
+        },
+        &items,
+        text.as_deref(),
+        format_options(node),
+        limit,
+    )
 }
 
 /// Resolve permissions and system prompt from the agent referenced by `agent_id`.
 /// Returns an error if `agent_id` is missing or the agent cannot be found.
-async fn resolve_agent_config(
-    node: &Node,
-    deps: &NodeDeps,
-) -> Result<(Vec<String>, Option<String>)> {
+async fn resolve_agent_config(node: &Node, deps: &NodeDeps) -> Result<crate::agents::Agent> {
     let agent_id = node.config["agent_id"]
         .as_str()
         .filter(|s| !s.is_empty())
@@ -263,7 +358,7 @@ async fn resolve_agent_config(
         .await
         .with_context(|| format!("agent '{}' not found (referenced by node '{}')", agent_id, node.label))?;
 
-    Ok((agent.permissions.clone(), agent.append_system_prompt.clone()))
+    Ok(agent)
 }
 
 // ── Sink Processing ────────────────────────────────────────────────────
@@ -276,15 +371,32 @@ async fn process_sink(node: &Node, input: NodeOutput, deps: &NodeDeps) -> Result
     }
 
     let configs = parse_sink_configs(&[node])?;
-    let resolved = resolve_sinks(&configs, &deps.http_client)?;
+    let resolved = resolve_sinks(
+        &configs,
+        &deps.http_client,
+        &deps.flow_id,
+        deps.flow_name.as_deref().unwrap_or("Unknown"),
+        &deps.dir_policy,
+        deps.session_bridge.as_ref().map(|b| b.data_dir.as_path()),
+    )?;
+    let policy = RetryPolicy::with_max_retries(
+        node.config["max_retries"].as_u64().map_or(DEFAULT_MAX_RETRIES, |n| n.min(u32::MAX as u64) as u32),
+    );
 
+    let mut receipts = Vec::new();
+    let mut attempts = 0;
     for sink in &resolved {
-        sink.deliver(&text)
+        let delivered = deliver_with_retry(sink.as_ref(), &text, &policy)
             .await
             .with_context(|| format!("sink '{}' delivery failed", node.label))?;
+        receipts.extend(delivered.receipt);
+        attempts = attempts.max(delivered.attempts);
     }
 
-    tracing::info!(node = %node.label, "Sink delivered");
+    if let Some(delivery_sink) = &deps.delivery_sink {
+        delivery_sink(&node.id, &text, &receipts, attempts);
+    }
+    tracing::info!(node = %node.label, receipts = ?receipts, attempts, "Sink delivered");
     Ok(NodeOutput::Empty)
 }
 
@@ -327,6 +439,7 @@ async fn setup_flow_run_session(
         busy_since: Some(chrono::Utc::now()),
         message_count: 0,
         total_cost: 0.0,
+        cost_budget_usd: None,
         created_at: chrono::Utc::now().to_rfc3339(),
         skills_dir: None,
         kind: "flow_run".to_string(),
@@ -440,6 +553,195 @@ async fn finalize_flow_run_session(
 
 // ── Config Parsing Helpers (moved from runner.rs) ──────────────────────
 
+/// Parse an executor's optional `filters` list, applied to its input items
+/// before ranking. `dedup` keeps its history under `data_dir` and leaves new
+/// keys in `pending` (`None` records nothing).
+pub async fn parse_filter_config(
+    node: &Node,
+    flow_id: &str,
+    data_dir: Option<&Path>,
+    pending: Option<&PendingDedup>,
+) -> Result<Vec<Box<dyn Filter>>> {
+    let Some(entries) = node.config["filters"].as_array() else {
+        return Ok(Vec::new());
+    };
+    let mut filters: Vec<Box<dyn Filter>> = Vec::with_capacity(entries.len());
+    for (i, entry) in entries.iter().enumerate() {
+        let filter: Box<dyn Filter> = match entry["kind"].as_str() {
+            Some("date-window") => {
+                let max_age_hours = entry["max_age_hours"]
+                    .as_u64()
+                    .with_context(|| format!("filters[{i}] (date-window) needs 'max_age_hours' (hours, a whole number)"))?;
+                Box::new(DateWindowFilter {
+                    max_age_hours,
+                    drop_undated: entry["drop_undated"].as_bool().unwrap_or(false),
+                })
+            }
+            Some("dedup") => {
+                let data_dir = data_dir.with_context(|| format!("filters[{i}] (dedup) needs a data dir"))?;
+                let key = match entry["key"].as_str() {
+                    None => DedupKey::Url,
+                    Some(k) => DedupKey::parse(k)
+                        .with_context(|| format!("filters[{i}] (dedup) key must be 'url' or 'title', got '{k}'"))?,
+                };
+                let max_remembered = entry["max_remembered"]
+                    .as_u64()
+                    .map_or(DEFAULT_MAX_REMEMBERED, |n| n as usize);
+                let path = DedupFilter::path_for(data_dir, flow_id, &node.id);
+                Box::new(DedupFilter::load(path, key, max_remembered, pending.cloned()).await)
+            }
+            Some("limit") => {
+                let count = entry["count"]
+                    .as_u64()
+                    .with_context(|| format!("filters[{i}] (limit) needs 'count'"))?;
+                let strategy = match entry["strategy"].as_str() {
+                    None => LimitStrategy::Head,
+                    Some(s) => LimitStrategy::parse(s).with_context(|| {
+                        format!("filters[{i}] (limit) strategy must be head, tail or newest, got '{s}'")
+                    })?,
+                };
+                Box::new(LimitFilter { count: count as usize, strategy })
+            }
+            Some(other) => bail!("filters[{i}] has unknown kind '{other}'"),
+            None => bail!("filters[{i}] missing 'kind'"),
+        };
+        filters.push(filter);
+    }
+    Ok(filters)
+}
+
+/// Apply the executor's optional `rank` mode to its input items.
+/// `source_priority` is handled when upstream outputs are merged (see runner).
+fn rank_items(node: &Node, items: &mut [sources::ContentItem]) {
+    let Some(mode) = node.config["rank"].as_str().and_then(RankMode::parse) else {
+        return;
+    };
+    match mode {
+        RankMode::Recency => rank::rank_by_recency(items),
+        RankMode::KeywordScore => {
+            let keywords: Vec<String> = node.config["rank_keywords"]
+                .as_array()
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default();
+            rank::rank_by_keyword_score(items, &keywords);
+        }
+        RankMode::SourcePriority => {}
+    }
+}
+
+/// Item formatting switches from the executor's config (`include_images`).
+pub fn format_options(node: &Node) -> FormatOptions {
+    FormatOptions {
+        include_images: node.config["include_images"].as_bool().unwrap_or(false),
+    }
+}
+
+/// Default cap on a rendered executor prompt (roughly 125k tokens).
+pub const DEFAULT_MAX_PROMPT_CHARS: usize = 500_000;
+
+/// Size cap on a rendered executor prompt, from `max_prompt_chars` /
+/// `max_prompt_strict` in the executor's config.
+#[derive(Debug, Clone, Copy)]
+pub struct PromptLimit {
+    pub max_chars: usize,
+    /// Fail instead of trimming `{{content}}` when the prompt is too large.
+    pub strict: bool,
+}
+
+impl PromptLimit {
+    /// Pass `prompt` through if it fits, otherwise fail in strict mode or
+    /// warn and send it as-is (for prompts with nothing safe to trim).
+    fn check(&self, prompt: String, what: &str) -> Result<String> {
+        let chars = prompt.chars().count();
+        if chars <= self.max_chars {
+            return Ok(prompt);
+        }
+        if self.strict {
+            bail!(
+                "rendered prompt is {chars} chars, over max_prompt_chars ({})",
+                self.max_chars
+            );
+        }
+        tracing::warn!(chars, max = self.max_chars, what, "rendered prompt exceeds max_prompt_chars");
+        Ok(prompt)
+    }
+}
+
+pub fn prompt_limit(node: &Node) -> PromptLimit {
+    PromptLimit {
+        max_chars: node.config["max_prompt_chars"]
+            .as_u64()
+            .filter(|n| *n > 0)
+            .map(|n| n as usize)
+            .unwrap_or(DEFAULT_MAX_PROMPT_CHARS),
+        strict: node.config["max_prompt_strict"].as_bool().unwrap_or(false),
+    }
+}
+
+/// Render a prompt via `render(content)`, trimming the content to keep the
+/// result under `limit`. Items (already ranked) are dropped from the end with
+/// an "[N items omitted]" marker; plain `text` content is cut with a
+/// "[N chars omitted]" marker. Strict limits fail instead of trimming.
+pub fn render_within_limit(
+    render: impl Fn(&str) -> String,
+    items: &[sources::ContentItem],
+    text: Option<&str>,
+    options: FormatOptions,
+    limit: PromptLimit,
+) -> Result<String> {
+    let content = match text {
+        Some(text) => text.to_string(),
+        None => format_items_with(items, options),
+    };
+    let prompt = render(&content);
+    let chars = prompt.chars().count();
+    if chars <= limit.max_chars || limit.strict {
+        return limit.check(prompt, "content");
+    }
+
+    let budget = limit.max_chars.saturating_sub(render("").chars().count());
+    let trimmed = match text {
+        Some(text) => {
+            let total = text.chars().count();
+            let marker_room = format!("\n[{total} chars omitted]").len();
+            let keep = budget.saturating_sub(marker_room);
+            if keep >= total {
+                // The content isn't what makes the prompt too large.
+                return limit.check(prompt, "content");
+            }
+            tracing::warn!(omitted_chars = total - keep, max = limit.max_chars, "trimmed executor input to fit max_prompt_chars");
+            let kept: String = text.chars().take(keep).collect();
+            format!("{kept}\n[{} chars omitted]", total - keep)
+        }
+        None => {
+            let (content, omitted) = format_items_within(items, options, budget);
+            tracing::warn!(omitted, max = limit.max_chars, "trimmed executor input to fit max_prompt_chars");
+            content
+        }
+    };
+    limit.check(render(&trimmed), "trimmed content")
+}
+
+/// Read a string map (e.g. request headers) from node config, coercing
+/// scalar values to strings.
+fn string_map(value: &serde_json::Value) -> std::collections::BTreeMap<String, String> {
+    value
+        .as_object()
+        .map(|map| {
+            map.iter()
+                .map(|(k, v)| {
+                    let v = v.as_str().map(String::from).unwrap_or_else(|| v.to_string());
+                    (k.clone(), v)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 pub fn parse_source_configs(nodes: &[&Node]) -> Result<Vec<SourceConfig>> {
     let mut configs = Vec::new();
     for node in nodes {
@@ -462,6 +764,7 @@ pub fn parse_source_configs(nodes: &[&Node]) -> Result<Vec<SourceConfig>> {
                     url,
                     limit,
                     keywords,
+                    headers: string_map(&node.config["headers"]),
                 }
             }
             "web-scrape" => {
@@ -477,7 +780,11 @@ pub fn parse_source_configs(nodes: &[&Node]) -> Result<Vec<SourceConfig>> {
                             .collect()
                     })
                     .unwrap_or_default();
-                SourceConfig::WebScrape { url, keywords }
+                SourceConfig::WebScrape {
+                    url,
+                    keywords,
+                    headers: string_map(&node.config["headers"]),
+                }
             }
             "github-merged-prs" => {
                 let repos = node.config["repos"]
@@ -489,6 +796,29 @@ pub fn parse_source_configs(nodes: &[&Node]) -> Result<Vec<SourceConfig>> {
                 let since_days = node.config["since_days"].as_u64().unwrap_or(7);
                 SourceConfig::GithubMergedPrs { repos, since_days }
             }
+            "github-issues" => {
+                let repos = node.config["repos"]
+                    .as_array()
+                    .context("github-issues node missing 'repos'")?
+                    .iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect();
+                let state = node.config["state"]
+                    .as_str()
+                    .map(IssueState::parse)
+                    .transpose()?
+                    .unwrap_or_default();
+                let labels = node.config["labels"]
+                    .as_array()
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let since_days = node.config["since_days"].as_u64().unwrap_or(7);
+                SourceConfig::GithubIssues { repos, state, labels, since_days }
+            }
             "web-scraper" => {
                 let url = node.config["url"]
                     .as_str()
@@ -505,6 +835,11 @@ pub fn parse_source_configs(nodes: &[&Node]) -> Result<Vec<SourceConfig>> {
                 let date_selector = node.config["date_selector"].as_str().map(String::from);
                 let date_format = node.config["date_format"].as_str().map(String::from);
                 let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                let next_page_selector = node.config["next_page_selector"]
+                    .as_str()
+                    .filter(|s| !s.trim().is_empty())
+                    .map(String::from);
+                let max_pages = node.config["max_pages"].as_u64().unwrap_or(5) as usize;
                 SourceConfig::WebScraper {
                     url,
                     base_url,
@@ -515,6 +850,9 @@ pub fn parse_source_configs(nodes: &[&Node]) -> Result<Vec<SourceConfig>> {
                     date_selector,
                     date_format,
                     limit,
+                    headers: string_map(&node.config["headers"]),
+                    next_page_selector,
+                    max_pages,
                 }
             }
             "google-sheets" => {
@@ -534,6 +872,119 @@ pub fn parse_source_configs(nodes: &[&Node]) -> Result<Vec<SourceConfig>> {
                     limit,
                 }
             }
+            "youtube" => {
+                let channel_id = node.config["channel_id"].as_str().map(String::from);
+                let playlist_id = node.config["playlist_id"].as_str().map(String::from);
+                if channel_id.is_none() && playlist_id.is_none() {
+                    bail!("youtube node requires 'channel_id' or 'playlist_id'");
+                }
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                let keywords = node.config["keywords"]
+                    .as_array()
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                SourceConfig::YouTube {
+                    channel_id,
+                    playlist_id,
+                    limit,
+                    keywords,
+                }
+            }
+            "sitemap" => {
+                let url = node.config["url"]
+                    .as_str()
+                    .context("sitemap node missing 'url'")?
+                    .to_string();
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                let since_days = node.config["since_days"].as_u64().unwrap_or(7);
+                let keywords = node.config["keywords"]
+                    .as_array()
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let fetch_titles = node.config["fetch_titles"].as_bool().unwrap_or(false);
+                SourceConfig::Sitemap {
+                    url,
+                    limit,
+                    since_days,
+                    keywords,
+                    fetch_titles,
+                }
+            }
+            "hackernews" => {
+                let story_type = node.config["story_type"]
+                    .as_str()
+                    .map(StoryType::parse)
+                    .transpose()?
+                    .unwrap_or_default();
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                let keywords = node.config["keywords"]
+                    .as_array()
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                SourceConfig::HackerNews {
+                    story_type,
+                    limit,
+                    keywords,
+                }
+            }
+            "reddit" => {
+                let subreddit = reddit::normalize_subreddit(
+                    node.config["subreddit"]
+                        .as_str()
+                        .context("reddit node missing 'subreddit'")?,
+                )?;
+                let sort = node.config["sort"]
+                    .as_str()
+                    .map(RedditSort::parse)
+                    .transpose()?
+                    .unwrap_or_default();
+                let limit = node.config["limit"].as_u64().unwrap_or(10) as usize;
+                let keywords = node.config["keywords"]
+                    .as_array()
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                SourceConfig::Reddit {
+                    subreddit,
+                    sort,
+                    limit,
+                    keywords,
+                }
+            }
+            "rest" => {
+                if node.config["url"].as_str().is_none() {
+                    bail!("rest node missing 'url'");
+                }
+                // Query params and headers are string maps; coerce scalars for convenience.
+                let mut config = node.config.clone();
+                for key in ["query", "headers"] {
+                    if let Some(map) = config.get_mut(key).and_then(|v| v.as_object_mut()) {
+                        for value in map.values_mut() {
+                            if !value.is_string() {
+                                *value = serde_json::Value::String(value.to_string());
+                            }
+                        }
+                    }
+                }
+                let rest_config: RestSourceConfig =
+                    serde_json::from_value(config).context("invalid rest source config")?;
+                SourceConfig::Rest(rest_config)
+            }
             "market-data" => {
                 // Market data is handled specially via template variable
                 continue;
@@ -553,17 +1004,108 @@ pub fn parse_sink_configs(nodes: &[&Node]) -> Result<Vec<SinkConfig>> {
                 webhook_url_env: node.config["webhook_url_env"].as_str().map(String::from),
                 bot_token_env: node.config["bot_token_env"].as_str().map(String::from),
                 channel: node.config["channel"].as_str().map(String::from),
+                unfurl_links: node.config["unfurl_links"].as_bool().unwrap_or(false),
+                unfurl_media: node.config["unfurl_media"].as_bool().unwrap_or(false),
+                sanitize_mentions: node.config["sanitize_mentions"].as_bool().unwrap_or(true),
+                max_blocks: node.config["max_blocks"]
+                    .as_u64()
+                    .map(|n| n as usize)
+                    .unwrap_or(MAX_BLOCKS_PER_MESSAGE),
+                overflow_to_thread: node.config["overflow_to_thread"].as_bool().unwrap_or(false),
             },
-            "notion" => SinkConfig::Notion {
-                token_env: node.config["token_env"]
+            "discord" => SinkConfig::Discord {
+                webhook_url_env: node.config["webhook_url_env"]
                     .as_str()
-                    .context("notion node missing 'token_env'")?
+                    .context("discord node missing 'webhook_url_env'")?
                     .to_string(),
-                database_id: node.config["database_id"]
+            },
+            "telegram" => SinkConfig::Telegram {
+                bot_token_env: node.config["bot_token_env"]
+                    .as_str()
+                    .context("telegram node missing 'bot_token_env'")?
+                    .to_string(),
+                // Numeric ids are often written unquoted
+                chat_id: match &node.config["chat_id"] {
+                    serde_json::Value::String(id) => id.clone(),
+                    serde_json::Value::Number(id) => id.to_string(),
+                    _ => bail!("telegram node missing 'chat_id'"),
+                },
+            },
+            "file" => SinkConfig::File {
+                path: node.config["path"]
                     .as_str()
-                    .context("notion node missing 'database_id'")?
+                    .filter(|p| !p.trim().is_empty())
+                    .context("file node missing 'path'")?
                     .to_string(),
             },
+            "http-post" => SinkConfig::HttpPost {
+                url: node.config["url"]
+                    .as_str()
+                    .filter(|u| !u.trim().is_empty())
+                    .context("http-post node missing 'url'")?
+                    .to_string(),
+                headers: string_map(&node.config["headers"]),
+                json: match node.config["content_type"].as_str() {
+                    None | Some("application/json") => true,
+                    Some("text/plain") => false,
+                    Some(other) => bail!(
+                        "http-post content_type must be application/json or text/plain, got '{other}'"
+                    ),
+                },
+            },
+            "notion" => {
+                let append_to_page = node.config["append_to_page"]
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|p| !p.is_empty())
+                    .map(String::from);
+                let database_id = match node.config["database_id"].as_str() {
+                    Some(id) => id.to_string(),
+                    None if append_to_page.is_some() => String::new(),
+                    None => bail!("notion node missing 'database_id' (or 'append_to_page')"),
+                };
+                SinkConfig::Notion {
+                    token_env: node.config["token_env"]
+                        .as_str()
+                        .context("notion node missing 'token_env'")?
+                        .to_string(),
+                    database_id,
+                    append_to_page,
+                }
+            }
+            "email" => {
+                let field = |key: &str| -> Result<String> {
+                    node.config[key]
+                        .as_str()
+                        .filter(|v| !v.trim().is_empty())
+                        .map(String::from)
+                        .with_context(|| format!("email node missing '{key}'"))
+                };
+                // A single comma-separated string or a list
+                let to: Vec<String> = match &node.config["to"] {
+                    serde_json::Value::String(s) => s.split(',').map(|a| a.trim().to_string()).collect(),
+                    serde_json::Value::Array(list) => {
+                        list.iter().filter_map(|a| a.as_str()).map(|a| a.trim().to_string()).collect()
+                    }
+                    _ => Vec::new(),
+                };
+                let to: Vec<String> = to.into_iter().filter(|a| !a.is_empty()).collect();
+                if to.is_empty() {
+                    bail!("email node missing 'to'");
+                }
+                SinkConfig::Email {
+                    smtp_host_env: field("smtp_host_env")?,
+                    smtp_user_env: field("smtp_user_env")?,
+                    smtp_pass_env: field("smtp_pass_env")?,
+                    from: field("from")?,
+                    to,
+                    subject: node.config["subject"]
+                        .as_str()
+                        .filter(|s| !s.trim().is_empty())
+                        .unwrap_or("{flow_name} - {date}")
+                        .to_string(),
+                }
+            }
             other => bail!("unknown sink kind: {other}"),
         };
         configs.push(config);
@@ -2,7 +2,9 @@ use anyhow::Result;
 use async_trait::async_trait;
 
 use super::Flow;
-use super::history::{FlowRun, NodeRun, RunStatus};
+use std::collections::HashMap;
+
+use super::history::{FlowRun, LastRun, NodeCheckpoint, NodeRun, RunStatus};
 
 #[async_trait]
 pub trait FlowRepository: Send + Sync {
@@ -15,6 +17,7 @@ pub trait FlowRepository: Send + Sync {
     // Runs (tightly coupled to flows)
     async fn add_run(&self, run: FlowRun) -> Result<()>;
     async fn get_runs(&self, flow_id: &str, limit: usize) -> Vec<FlowRun>;
+    async fn get_run(&self, flow_id: &str, run_id: &str) -> Option<FlowRun>;
     async fn complete_run(
         &self,
         flow_id: &str,
@@ -36,6 +39,48 @@ pub trait FlowRepository: Send + Sync {
         status: RunStatus,
         output_preview: Option<String>,
     ) -> Result<()>;
+    /// Store the full (already size-capped) output of a node run.
+    async fn set_node_output(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        output: String,
+    ) -> Result<()>;
+    /// Store the rendered (already size-capped) prompt of an executor node run.
+    async fn set_node_prompt(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        prompt: String,
+    ) -> Result<()>;
+    /// Store what an executor node run cost.
+    async fn set_node_cost(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        cost_usd: f64,
+    ) -> Result<()>;
+    /// Store a successful node's restorable output for retries.
+    async fn set_node_checkpoint(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        node_id: &str,
+        checkpoint: NodeCheckpoint,
+    ) -> Result<()>;
+    /// Add tags (deduplicated) and optionally replace the note on a run.
+    async fn annotate_run(
+        &self,
+        flow_id: &str,
+        run_id: &str,
+        tags: Vec<String>,
+        note: Option<String>,
+    ) -> Result<()>;
+    /// Most recently finished run per flow id, kept current as runs complete.
+    async fn last_runs(&self) -> HashMap<String, LastRun>;
 
     // Lifecycle
     async fn load_all(&self) -> Result<()>;
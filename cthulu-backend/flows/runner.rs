@@ -1,26 +1,32 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use chrono::Utc;
 use tracing::Instrument;
 use uuid::Uuid;
 
 use tokio::sync::broadcast;
 
+use crate::agents::{node_max_turns, node_model};
 use crate::agents::repository::AgentRepository;
+use crate::config::{RunPreviewConfig, WorkingDirPolicy};
 use crate::flows::events::{RunEvent, RunEventType};
 use crate::flows::graph::{self, NodeOutput};
-use crate::flows::history::{FlowRun, NodeRun, RunStatus};
-use crate::flows::processors::{self, NodeDeps};
+use crate::flows::output_transform;
+use crate::flows::history::{FlowRun, NodeCheckpoint, NodeRun, RunStatus, RunTrigger};
+use crate::flows::bundle::Redactor;
+use crate::flows::processors::{self, DeliverySink, LockWaitSink, NodeDeps, PromptSink};
 use crate::flows::repository::FlowRepository;
 use crate::flows::session_bridge::SessionBridge;
 use crate::flows::{Flow, NodeType};
 use crate::github::client::GithubClient;
-use crate::sandbox::provider::SandboxProvider;
+use crate::sandbox::provider::{SandboxProvider, SandboxProviders};
 use crate::tasks::context::render_prompt;
-use crate::tasks::pipeline::format_items;
+use crate::tasks::filters::dedup::PendingDedup;
+use crate::tasks::rank::RankMode;
 use crate::tasks::sources::{self, ContentItem};
 
 /// Data returned by `prepare_session()` — everything needed to start
@@ -33,19 +39,55 @@ pub struct SessionInfo {
     pub permissions: Vec<String>,
     pub append_system_prompt: Option<String>,
     pub working_dir: String,
+    /// Extra roots for `--add-dir`, from the executor's `additional_dirs`.
+    pub additional_dirs: Vec<PathBuf>,
+    /// The executor's `model` override, passed as `--model`.
+    pub model: Option<String>,
+    /// The executor's `max_turns` cap, passed as `--max-turns`.
+    pub max_turns: Option<u32>,
     pub sources_summary: String,
     pub sinks_summary: String,
 }
 
+/// Rendered prompt size for one executor node, from `estimate_prompts()`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PromptEstimate {
+    pub node_id: String,
+    pub label: String,
+    pub chars: usize,
+    /// Rough token count (~4 chars per token).
+    pub approx_tokens: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PromptEstimate {
+    fn from_rendered(node: &crate::flows::Node, rendered: &str) -> Self {
+        let chars = rendered.chars().count();
+        Self {
+            node_id: node.id.clone(),
+            label: node.label.clone(),
+            chars,
+            approx_tokens: chars.div_ceil(4),
+            error: None,
+        }
+    }
+}
+
 pub struct FlowRunner {
     pub http_client: Arc<reqwest::Client>,
     pub github_client: Option<Arc<dyn GithubClient>>,
     pub events_tx: Option<broadcast::Sender<RunEvent>>,
-    pub sandbox_provider: Option<Arc<dyn SandboxProvider>>,
+    /// Sandbox backends executors can run in; flows pick one by name.
+    pub sandbox_providers: Option<SandboxProviders>,
     /// Agent repository for resolving `agent_id` on executor nodes.
     pub agent_repo: Option<Arc<dyn AgentRepository>>,
     /// Session bridge for routing executor output to agent workspaces.
     pub session_bridge: Option<SessionBridge>,
+    /// Where executor `working_dir` overrides and file sinks may point.
+    pub working_dir_policy: WorkingDirPolicy,
+    /// How much of each node's output its run record previews.
+    pub run_preview: RunPreviewConfig,
 }
 
 impl FlowRunner {
@@ -114,26 +156,100 @@ impl FlowRunner {
             format!("{} sink(s): {}", sink_nodes.len(), parts.join(", "))
         };
 
-        // 1. Fetch sources
-        let source_configs = processors::parse_source_configs(&source_nodes)?;
+        let items = self.fetch_flow_items(&source_nodes).await?;
+        let rendered = self.render_dry(executor_node, &items).await?;
+
+        // Resolve permissions and system prompt from the referenced agent
+        let (permissions, append_system_prompt) = if let Some(agent_id) =
+            executor_node.config["agent_id"].as_str().filter(|s| !s.is_empty())
+        {
+            if let Some(repo) = &self.agent_repo {
+                if let Some(agent) = repo.get(agent_id).await {
+                    (agent.permissions.clone(), agent.append_system_prompt.clone())
+                } else {
+                    tracing::warn!(agent_id, node = %executor_node.label, "agent not found, using empty config");
+                    (vec![], None)
+                }
+            } else {
+                (vec![], None)
+            }
+        } else {
+            (vec![], None)
+        };
+
+        let working_dir = executor_node.config["working_dir"]
+            .as_str()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+
+        Ok(SessionInfo {
+            flow_id: flow.id.clone(),
+            flow_name: flow.name.clone(),
+            prompt: rendered,
+            permissions,
+            append_system_prompt,
+            working_dir: working_dir.to_string_lossy().to_string(),
+            additional_dirs: self
+                .working_dir_policy
+                .check_additional(&executor_node.config["additional_dirs"])?,
+            model: node_model(&executor_node.config),
+            max_turns: node_max_turns(&executor_node.config),
+            sources_summary,
+            sinks_summary,
+        })
+    }
+
+    /// Estimate the size of each executor's rendered prompt without running it.
+    ///
+    /// Fetches the flow's sources once and renders every executor the same way
+    /// `prepare_session` does. A failed render is reported on its entry rather
+    /// than failing the whole estimate.
+    pub async fn estimate_prompts(&self, flow: &Flow) -> Result<Vec<PromptEstimate>> {
+        let source_nodes: Vec<_> = flow
+            .nodes
+            .iter()
+            .filter(|n| n.node_type == NodeType::Source)
+            .collect();
+        let items = self.fetch_flow_items(&source_nodes).await?;
+
+        let mut estimates = Vec::new();
+        for node in flow.nodes.iter().filter(|n| n.node_type == NodeType::Executor) {
+            let estimate = match self.render_dry(node, &items).await {
+                Ok(rendered) => PromptEstimate::from_rendered(node, &rendered),
+                Err(e) => PromptEstimate {
+                    node_id: node.id.clone(),
+                    label: node.label.clone(),
+                    chars: 0,
+                    approx_tokens: 0,
+                    error: Some(format!("{e:#}")),
+                },
+            };
+            estimates.push(estimate);
+        }
+        Ok(estimates)
+    }
+
+    /// Fetch items from all of a flow's source nodes.
+    async fn fetch_flow_items(&self, source_nodes: &[&crate::flows::Node]) -> Result<Vec<ContentItem>> {
+        let source_configs = processors::parse_source_configs(source_nodes)?;
         let github_token = self
             .github_client
             .as_ref()
             .and_then(|_| std::env::var("GITHUB_TOKEN").ok());
 
-        let items: Vec<ContentItem> = if !source_configs.is_empty() {
+        Ok(if !source_configs.is_empty() {
             sources::fetch_all(&source_configs, &self.http_client, github_token.as_deref())
                 .await
         } else {
             vec![]
-        };
+        })
+    }
 
-        // 2. Render prompt
-        let content = format_items(&items);
+    /// Render an executor's prompt template against fetched items, outside a run.
+    async fn render_dry(&self, executor_node: &crate::flows::Node, items: &[ContentItem]) -> Result<String> {
         let timestamp = Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
 
         let mut vars = HashMap::new();
-        vars.insert("content".to_string(), content.clone());
         vars.insert("item_count".to_string(), items.len().to_string());
         vars.insert("timestamp".to_string(), timestamp);
 
@@ -157,60 +273,35 @@ impl FlowRunner {
             vars.insert("market_data".to_string(), market_data);
         }
 
-        let rendered = render_prompt(&prompt_template, &vars);
-
-        let rendered = if !items.is_empty() && !prompt_template.contains("{{content}}") {
-            format!(
-                "{rendered}\n\n<<<\n{}\n>>>",
-                vars.get("content").cloned().unwrap_or_default()
-            )
-        } else {
-            rendered
-        };
-
-        // Resolve permissions and system prompt from the referenced agent
-        let (permissions, append_system_prompt) = if let Some(agent_id) =
-            executor_node.config["agent_id"].as_str().filter(|s| !s.is_empty())
-        {
-            if let Some(repo) = &self.agent_repo {
-                if let Some(agent) = repo.get(agent_id).await {
-                    (agent.permissions.clone(), agent.append_system_prompt.clone())
+        let append_content = !items.is_empty() && !prompt_template.contains("{{content}}");
+        processors::render_within_limit(
+            |content| {
+                let mut vars = vars.clone();
+                vars.insert("content".to_string(), content.to_string());
+                let rendered = render_prompt(&prompt_template, &vars);
+                if append_content {
+                    format!("{rendered}\n\n<<<\n{content}\n>>>")
                 } else {
-                    tracing::warn!(agent_id, node = %executor_node.label, "agent not found, using empty config");
-                    (vec![], None)
+                    rendered
                 }
-            } else {
-                (vec![], None)
-            }
-        } else {
-            (vec![], None)
-        };
-
-        let working_dir = executor_node.config["working_dir"]
-            .as_str()
-            .map(PathBuf::from)
-            .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
-
-        Ok(SessionInfo {
-            flow_id: flow.id.clone(),
-            flow_name: flow.name.clone(),
-            prompt: rendered,
-            permissions,
-            append_system_prompt,
-            working_dir: working_dir.to_string_lossy().to_string(),
-            sources_summary,
-            sinks_summary,
-        })
+            },
+            items,
+            None,
+            processors::format_options(executor_node),
+            processors::prompt_limit(executor_node),
+        )
     }
 
     /// Prepare a session for a specific executor node (node-level chat).
     /// Does NOT run sources — just resolves the node's own config
     /// (prompt, permissions, working_dir, system_prompt).
     /// Permissions and system prompt are resolved from the referenced agent.
+    /// The node's `working_dir` is validated against `dir_policy` up front.
     pub async fn prepare_node_session(
         flow: &Flow,
         node_id: &str,
         agent_repo: Option<&Arc<dyn AgentRepository>>,
+        dir_policy: &WorkingDirPolicy,
     ) -> Result<SessionInfo> {
         let executor_node = flow
             .nodes
@@ -236,10 +327,9 @@ impl FlowRunner {
             (vec![], None)
         };
 
-        let working_dir = executor_node.config["working_dir"]
-            .as_str()
-            .map(PathBuf::from)
-            .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+        let working_dir = dir_policy
+            .resolve(executor_node.config["working_dir"].as_str())
+            .with_context(|| format!("executor node '{}' has an invalid working_dir", executor_node.label))?;
 
         // For node-level chat the prompt is informational only — the user types
         // their own messages. We still resolve it so the UI can show it.
@@ -255,6 +345,11 @@ impl FlowRunner {
             permissions,
             append_system_prompt,
             working_dir: working_dir.to_string_lossy().to_string(),
+            additional_dirs: dir_policy
+                .check_additional(&executor_node.config["additional_dirs"])
+                .with_context(|| format!("executor node '{}' has invalid additional_dirs", executor_node.label))?,
+            model: node_model(&executor_node.config),
+            max_turns: node_max_turns(&executor_node.config),
             sources_summary: "N/A (node-level chat)".into(),
             sinks_summary: "N/A (node-level chat)".into(),
         })
@@ -263,15 +358,54 @@ impl FlowRunner {
     /// Execute a flow. If `context` is `Some`, skips source fetching
     /// and uses the provided variables for prompt rendering (e.g. PR diff).
     /// If `context` is `None`, runs the full source → render pipeline.
+    /// `trigger` records what started the run in its history entry.
     pub async fn execute(
         &self,
         flow: &Flow,
         repo: &dyn FlowRepository,
         context: Option<HashMap<String, String>>,
+        trigger: RunTrigger,
+    ) -> Result<FlowRun> {
+        self.start_run(flow, repo, context, trigger, None, false).await
+    }
+
+    /// Run sources, filters and prompt rendering, then stop before the
+    /// executors: each executor's rendered prompt is emitted and previewed on
+    /// its node run, and neither executors downstream of it nor sinks run.
+    pub async fn execute_dry_run(&self, flow: &Flow, repo: &dyn FlowRepository) -> Result<FlowRun> {
+        self.start_run(flow, repo, None, RunTrigger::Manual, None, true).await
+    }
+
+    /// Retry a failed run as a new run linked by `resumed_from`. Nodes that
+    /// succeeded in `original` are restored from their checkpoints, so only
+    /// the failed nodes and everything downstream of them execute again.
+    pub async fn retry(&self, flow: &Flow, repo: &dyn FlowRepository, original: &FlowRun) -> Result<FlowRun> {
+        check_retryable(original)?;
+        self.start_run(flow, repo, None, original.trigger, Some(original), false).await
+    }
+
+    async fn start_run(
+        &self,
+        flow: &Flow,
+        repo: &dyn FlowRepository,
+        context: Option<HashMap<String, String>>,
+        trigger: RunTrigger,
+        resume: Option<&FlowRun>,
+        dry_run: bool,
     ) -> Result<FlowRun> {
         let has_context = context.is_some();
         let run_id = Uuid::new_v4().to_string();
         let short_id = &run_id[..8];
+        let restored: HashMap<String, NodeRun> = resume
+            .map(|original| {
+                original
+                    .node_runs
+                    .iter()
+                    .filter(|nr| nr.status == RunStatus::Success && nr.checkpoint.is_some())
+                    .map(|nr| (nr.node_id.clone(), nr.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
         let run = FlowRun {
             id: run_id.clone(),
             flow_id: flow.id.clone(),
@@ -280,17 +414,40 @@ impl FlowRunner {
             finished_at: None,
             node_runs: vec![],
             error: None,
+            trigger,
+            tags: if dry_run { vec![DRY_RUN_TAG.to_string()] } else { vec![] },
+            note: None,
+            resumed_from: resume.map(|original| original.id.clone()),
         };
         repo.add_run(run.clone()).await?;
 
-        let ctx_label = if has_context { " (with context)" } else { "" };
+        let ctx_label = match resume {
+            Some(original) => format!(" (retrying {})", &original.id[..original.id.len().min(8)]),
+            None if dry_run => " (dry run)".to_string(),
+            None if has_context => " (with context)".to_string(),
+            None => String::new(),
+        };
         self.emit(&flow.id, &run_id, None, RunEventType::RunStarted, format!("Flow execution started{ctx_label}"));
 
         let span = tracing::info_span!("flow_run", flow = %flow.name, run = %short_id);
         tracing::info!(parent: &span, nodes = flow.nodes.len(), edges = flow.edges.len(), "▶ Started{ctx_label}");
 
         let start = std::time::Instant::now();
-        let result = self.execute_inner(flow, &run_id, repo, context).instrument(span.clone()).await;
+        let inner = self
+            .execute_inner(flow, &run_id, repo, context, restored, dry_run)
+            .instrument(span.clone());
+        let result = match flow.max_runtime_secs.filter(|secs| *secs > 0) {
+            Some(secs) => match tokio::time::timeout(Duration::from_secs(secs), inner).await {
+                Ok(result) => result,
+                // Dropping `inner` aborts in-flight node tasks (and kills their processes)
+                Err(_) => {
+                    let msg = format!("flow exceeded max runtime of {secs}s");
+                    self.fail_running_nodes(flow, &run_id, repo, &msg).await;
+                    Err(anyhow::anyhow!(msg))
+                }
+            },
+            None => inner.await,
+        };
         let elapsed = start.elapsed();
 
         // Determine final status: if execute_inner returned Ok but any node failed, mark as Failed
@@ -334,6 +491,43 @@ impl FlowRunner {
         Ok(run)
     }
 
+    /// Mark node runs still `Running` as failed, e.g. after the run was cancelled.
+    async fn fail_running_nodes(&self, flow: &Flow, run_id: &str, repo: &dyn FlowRepository, reason: &str) {
+        let Some(run) = repo.get_run(&flow.id, run_id).await else {
+            return;
+        };
+        for nr in run.node_runs.iter().filter(|nr| nr.status == RunStatus::Running) {
+            self.emit(&flow.id, run_id, Some(&nr.node_id), RunEventType::NodeFailed, reason);
+            if let Err(e) = repo
+                .complete_node_run(&flow.id, run_id, &nr.node_id, RunStatus::Failed, Some(reason.to_string()))
+                .await
+            {
+                tracing::warn!(node = %nr.node_id, error = %e, "failed to mark node run as failed");
+            }
+        }
+    }
+
+    /// Sandbox provider for `node`: its own `sandbox_backend`, else the
+    /// flow's, else the default. Errors on a backend that isn't configured.
+    fn sandbox_provider_for(&self, flow: &Flow, node: &crate::flows::Node) -> Result<Option<Arc<dyn SandboxProvider>>> {
+        let Some(providers) = &self.sandbox_providers else {
+            return Ok(None);
+        };
+        let backend = node.config["sandbox_backend"]
+            .as_str()
+            .filter(|b| !b.is_empty())
+            .or(flow.sandbox_backend.as_deref());
+        match providers.get(backend) {
+            Some(provider) => Ok(Some(provider.clone())),
+            None => bail!(
+                "node '{}' uses sandbox backend '{}', which isn't configured (available: {})",
+                node.label,
+                backend.unwrap_or_default(),
+                providers.names().join(", ")
+            ),
+        }
+    }
+
     /// Core DAG execution engine.
     ///
     /// Topologically sorts all nodes, groups them by level (distance from roots),
@@ -348,18 +542,44 @@ impl FlowRunner {
         run_id: &str,
         repo: &dyn FlowRepository,
         context: Option<HashMap<String, String>>,
+        mut restored: HashMap<String, NodeRun>,
+        dry_run: bool,
     ) -> Result<bool> {
+        let dir_policy = self.working_dir_policy.clone();
+        let rendered_flow = render_working_dirs(flow, run_id, &dir_policy)?;
+        let flow = rendered_flow.as_ref().unwrap_or(flow);
+
         // Topo sort all nodes
         let sorted = graph::topo_sort(&flow.nodes, &flow.edges)?;
         let (_, parents) = graph::build_adjacency(&flow.nodes, &flow.edges);
         let levels = graph::compute_levels(&sorted, &parents);
 
+        // Warn (but keep going) about executors that won't receive any input
+        for orphan in graph::orphaned_executors(&flow.nodes, &flow.edges) {
+            let msg = format!(
+                "Executor '{}' has no incoming edges and will run with empty input",
+                orphan.label
+            );
+            tracing::warn!(node = %orphan.label, "{msg}");
+            self.emit(&flow.id, run_id, Some(&orphan.id), RunEventType::Log, msg);
+        }
+
+        // Resolve each executor's sandbox backend up front so a typo fails the
+        // run before anything executes
+        let mut sandboxes: HashMap<&str, Option<Arc<dyn SandboxProvider>>> = HashMap::new();
+        for node in flow.nodes.iter().filter(|n| n.node_type == NodeType::Executor) {
+            sandboxes.insert(node.id.as_str(), self.sandbox_provider_for(flow, node)?);
+        }
+
         // Build node lookup
         let node_map: HashMap<&str, &crate::flows::Node> =
             flow.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
 
         // Per-node output storage
         let mut outputs: HashMap<String, NodeOutput> = HashMap::new();
+        // For executors: the non-executor content (sources / trigger context)
+        // at the head of their executor chain, used by `input_mode`.
+        let mut origins: HashMap<String, NodeOutput> = HashMap::new();
 
         // Inject context as trigger output if provided (GitHub PR path)
         if let Some(ctx) = context {
@@ -368,24 +588,75 @@ impl FlowRunner {
             }
         }
 
+        // Executors report their rendered prompt here; persisted once the node finishes
+        let captured_prompts: Arc<std::sync::Mutex<HashMap<String, String>>> = Arc::default();
+        let prompt_sink: Option<PromptSink> = flow.capture_prompts.then(|| {
+            let captured = Arc::clone(&captured_prompts);
+            Arc::new(move |node_id: &str, prompt: &str| {
+                if let Ok(mut map) = captured.lock() {
+                    map.insert(node_id.to_string(), truncate(prompt, MAX_STORED_PROMPT_BYTES));
+                }
+            }) as PromptSink
+        });
+
+        let lock_wait_sink: LockWaitSink = {
+            let events_tx = self.events_tx.clone();
+            let (flow_id, run_id) = (flow.id.clone(), run_id.to_string());
+            Arc::new(move |node_id: &str, dir: &std::path::Path| {
+                if let Some(tx) = &events_tx {
+                    let _ = tx.send(RunEvent {
+                        flow_id: flow_id.clone(),
+                        run_id: run_id.clone(),
+                        timestamp: Utc::now(),
+                        node_id: Some(node_id.to_string()),
+                        event_type: RunEventType::WaitingForLock,
+                        message: format!("Waiting for lock on {}", dir.display()),
+                    });
+                }
+            })
+        };
+
+        let preview_chars = self.run_preview.chars;
+
+        // Sinks report what they delivered; it becomes the node run's preview
+        let captured_deliveries: Arc<std::sync::Mutex<HashMap<String, String>>> = Arc::default();
+        let delivery_sink: DeliverySink = {
+            let captured = Arc::clone(&captured_deliveries);
+            let redactor = Redactor::from_env();
+            Arc::new(move |node_id: &str, text: &str, receipts: &[String], attempts: u32| {
+                if let Ok(mut map) = captured.lock() {
+                    let preview = delivery_preview(&redactor.redact(text), receipts, attempts, preview_chars);
+                    map.insert(node_id.to_string(), preview);
+                }
+            })
+        };
+
         let deps = NodeDeps {
             http_client: Arc::clone(&self.http_client),
             github_client: self.github_client.clone(),
-            sandbox_provider: self.sandbox_provider.clone(),
+            sandbox_provider: self.sandbox_providers.as_ref().map(|p| p.default_provider().clone()),
             agent_repo: self.agent_repo.clone(),
             flow_id: flow.id.clone(),
             session_bridge: self.session_bridge.clone(),
             run_id: Some(run_id.to_string()),
             flow_name: Some(flow.name.clone()),
+            prompt_sink,
+            lock_wait_sink: Some(lock_wait_sink),
+            delivery_sink: Some(delivery_sink),
+            dir_policy,
+            dry_run,
+            dedup_pending: PendingDedup::default(),
         };
 
         let mut any_failed = false;
+        // Dry run: executors rendered so far, and everything skipped below them
+        let mut dry_stopped: HashSet<String> = HashSet::new();
 
         for level in &levels {
             // For nodes within a level that can run in parallel, we collect futures
             // However, since nodes in the same level are independent (no edges between them),
             // we can process them concurrently
-            let mut handles: Vec<(String, tokio::task::JoinHandle<Result<NodeOutput>>)> = Vec::new();
+            let mut handles: Vec<(String, AbortOnDrop<Result<NodeOutput>>)> = Vec::new();
 
             for node_id in level {
                 let node = match node_map.get(node_id.as_str()) {
@@ -400,15 +671,57 @@ impl FlowRunner {
                 }
 
                 // Collect & merge parent outputs
-                let parent_outputs: Vec<NodeOutput> = parents
+                let mut parent_ids: Vec<&String> = parents
                     .get(node_id.as_str())
-                    .map(|pids| {
-                        pids.iter()
-                            .filter_map(|p| outputs.get(p).cloned())
-                            .collect()
-                    })
+                    .map(|pids| pids.iter().collect())
                     .unwrap_or_default();
-                let input = NodeOutput::merge(parent_outputs);
+                if node.node_type == NodeType::Executor
+                    && node.config["rank"].as_str().and_then(RankMode::parse)
+                        == Some(RankMode::SourcePriority)
+                {
+                    // Stable: equal priorities keep their edge order
+                    parent_ids.sort_by_key(|pid| {
+                        std::cmp::Reverse(
+                            node_map
+                                .get(pid.as_str())
+                                .and_then(|n| n.config["priority"].as_i64())
+                                .unwrap_or(0),
+                        )
+                    });
+                }
+                if dry_run
+                    && (node.node_type == NodeType::Sink
+                        || parent_ids.iter().any(|p| dry_stopped.contains(p.as_str())))
+                {
+                    dry_stopped.insert(node_id.clone());
+                    continue;
+                }
+
+                let parent_outputs: Vec<NodeOutput> = parent_ids
+                    .iter()
+                    .filter_map(|p| outputs.get(*p).cloned())
+                    .collect();
+                let mut input = NodeOutput::merge(parent_outputs);
+
+                // Post-process executor output on its way into a sink
+                if node.node_type == NodeType::Sink
+                    && !flow.output_transform.is_empty()
+                    && let NodeOutput::Text(text, result) = input
+                {
+                    input = NodeOutput::Text(output_transform::apply_all(&flow.output_transform, &text), result);
+                }
+
+                if node.node_type == NodeType::Executor && !matches!(input, NodeOutput::Failed) {
+                    let origin = NodeOutput::merge(
+                        parent_ids
+                            .iter()
+                            .filter_map(|p| origins.get(*p).or_else(|| outputs.get(*p)).cloned())
+                            .collect(),
+                    );
+                    let mode = InputMode::parse(node.config["input_mode"].as_str());
+                    input = executor_input(mode, input, origin.clone());
+                    origins.insert(node_id.clone(), origin);
+                }
 
                 // Skip if any parent failed (propagate failure sentinel)
                 if matches!(input, NodeOutput::Failed) {
@@ -418,6 +731,25 @@ impl FlowRunner {
                     continue;
                 }
 
+                // Retry: reuse the output this node produced in the original run
+                if let Some(prior) = restored.remove(node_id.as_str())
+                    && let Some(checkpoint) = prior.checkpoint.clone()
+                {
+                    // Reused output cost nothing this time
+                    let reused = NodeRun { cost_usd: None, ..prior };
+                    repo.push_node_run(&flow.id, run_id, reused).await?;
+                    self.emit(
+                        &flow.id,
+                        run_id,
+                        Some(node_id),
+                        RunEventType::NodeCompleted,
+                        "Reused output from the original run",
+                    );
+                    tracing::info!(node = %node.label, "↺ Node output reused");
+                    outputs.insert(node_id.clone(), restore_checkpoint(checkpoint));
+                    continue;
+                }
+
                 // Record node run start
                 let node_run = NodeRun {
                     node_id: node_id.clone(),
@@ -425,6 +757,10 @@ impl FlowRunner {
                     started_at: Utc::now(),
                     finished_at: None,
                     output_preview: None,
+                    output: None,
+                    prompt: None,
+                    checkpoint: None,
+                    cost_usd: None,
                 };
                 repo.push_node_run(&flow.id, run_id, node_run).await?;
                 self.emit(
@@ -437,22 +773,69 @@ impl FlowRunner {
 
                 // Spawn task for parallel execution within the level
                 let node_clone = node.clone();
-                let deps_clone = deps.clone();
+                let mut deps_clone = deps.clone();
+                if let Some(provider) = sandboxes.get(node_id.as_str()) {
+                    deps_clone.sandbox_provider = provider.clone();
+                }
+                let render_only = dry_run && node.node_type == NodeType::Executor;
+                if render_only {
+                    dry_stopped.insert(node_id.clone());
+                }
                 let handle = tokio::spawn(async move {
-                    processors::process_node(&node_clone, input, &deps_clone).await
+                    if render_only {
+                        processors::render_executor_prompt(&node_clone, &input, &deps_clone)
+                            .await
+                            .map(|prompt| NodeOutput::Text(prompt, None))
+                    } else {
+                        processors::process_node(&node_clone, input, &deps_clone).await
+                    }
                 });
-                handles.push((node_id.clone(), handle));
+                handles.push((node_id.clone(), AbortOnDrop(handle)));
             }
 
             // Await all parallel tasks in this level
-            for (node_id, handle) in handles {
+            for (node_id, mut handle) in handles {
                 let node = node_map[node_id.as_str()];
-                match handle.await {
+                let joined = (&mut handle.0).await;
+                let prompt = captured_prompts.lock().ok().and_then(|mut map| map.remove(&node_id));
+                if let Some(prompt) = prompt {
+                    repo.set_node_prompt(&flow.id, run_id, &node_id, prompt).await?;
+                }
+                let delivery = captured_deliveries.lock().ok().and_then(|mut map| map.remove(&node_id));
+                if dry_stopped.contains(&node_id)
+                    && let Ok(Ok(NodeOutput::Text(prompt, _))) = &joined
+                {
+                    self.emit(&flow.id, run_id, Some(&node_id), RunEventType::PromptRendered, prompt);
+                    if flow.capture_prompts {
+                        repo.set_node_prompt(&flow.id, run_id, &node_id, truncate(prompt, MAX_STORED_PROMPT_BYTES))
+                            .await?;
+                    }
+                    tracing::info!(node = %node.label, chars = prompt.len(), "✓ Prompt rendered (dry run)");
+                    repo.complete_node_run(
+                        &flow.id,
+                        run_id,
+                        &node_id,
+                        RunStatus::Success,
+                        Some(dry_run_preview(prompt)),
+                    )
+                    .await?;
+                    continue;
+                }
+                match joined {
                     Ok(Ok(output)) => {
                         // Build preview for node run
                         let preview = match &output {
                             NodeOutput::Items(items) => format!("{} items", items.len()),
                             NodeOutput::Text(t, exec_result) => {
+                                if let Some(er) = exec_result.as_ref().filter(|er| er.max_turns_reached) {
+                                    self.emit(
+                                        &flow.id,
+                                        run_id,
+                                        Some(&node_id),
+                                        RunEventType::Log,
+                                        format!("{} stopped at its max_turns cap ({} turns)", node.label, er.num_turns),
+                                    );
+                                }
                                 if let Some(er) = exec_result {
                                     self.emit(
                                         &flow.id,
@@ -465,9 +848,14 @@ impl FlowRunner {
                                         ),
                                     );
                                 }
-                                truncate(t, 500)
+                                match exec_result {
+                                    Some(er) if er.max_turns_reached => {
+                                        format!("[max turns reached: {}] {}", er.num_turns, truncate_chars(t, preview_chars))
+                                    }
+                                    _ => truncate_chars(t, preview_chars),
+                                }
                             }
-                            NodeOutput::Empty => "Done".to_string(),
+                            NodeOutput::Empty => delivery.unwrap_or_else(|| "Done".to_string()),
                             _ => "Done".to_string(),
                         };
 
@@ -490,6 +878,21 @@ impl FlowRunner {
                             Some(preview),
                         )
                         .await?;
+                        if let NodeOutput::Text(t, _) = &output {
+                            repo.set_node_output(
+                                &flow.id,
+                                run_id,
+                                &node_id,
+                                truncate(t, MAX_STORED_OUTPUT_BYTES),
+                            )
+                            .await?;
+                        }
+                        if let NodeOutput::Text(_, Some(er)) = &output {
+                            repo.set_node_cost(&flow.id, run_id, &node_id, er.cost_usd).await?;
+                        }
+                        if let Some(checkpoint) = checkpoint_of(&output) {
+                            repo.set_node_checkpoint(&flow.id, run_id, &node_id, checkpoint).await?;
+                        }
                         outputs.insert(node_id, output);
                     }
                     Ok(Err(e)) => {
@@ -538,10 +941,194 @@ impl FlowRunner {
             }
         }
 
+        // Only a run whose executors and sinks all succeeded marks its items seen
+        if !any_failed && !dry_run {
+            deps.dedup_pending.commit().await;
+        }
+
         Ok(any_failed)
     }
 }
 
+/// What an executor downstream of another executor is fed (`input_mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum InputMode {
+    /// The previous executor's output only.
+    #[default]
+    Replace,
+    /// The original content followed by the previous executor's output.
+    Append,
+    /// Always the original content, ignoring upstream executors.
+    Original,
+}
+
+impl InputMode {
+    fn parse(s: Option<&str>) -> Self {
+        match s {
+            Some("append") => Self::Append,
+            Some("original") => Self::Original,
+            _ => Self::Replace,
+        }
+    }
+}
+
+/// Pick an executor's input. `original` is the non-executor content at the
+/// head of its executor chain; when the executor reads sources directly the
+/// two are the same and every mode yields `input`.
+fn executor_input(mode: InputMode, input: NodeOutput, original: NodeOutput) -> NodeOutput {
+    if matches!(original, NodeOutput::Empty | NodeOutput::Failed) {
+        return input;
+    }
+    match mode {
+        InputMode::Replace => input,
+        InputMode::Original => original,
+        InputMode::Append => {
+            let original_text = original.as_text();
+            let input_text = input.as_text();
+            if input_text == original_text {
+                input
+            } else {
+                NodeOutput::Text(format!("{original_text}\n\n{input_text}"), None)
+            }
+        }
+    }
+}
+
+/// Aborts the wrapped node task when dropped, so a cancelled run (e.g. one
+/// that hit `max_runtime_secs`) doesn't leave nodes running in the background.
+struct AbortOnDrop<T>(tokio::task::JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Cap on the full node output kept in the run record.
+const MAX_STORED_OUTPUT_BYTES: usize = 256 * 1024;
+
+/// Cap on the rendered executor prompt kept in the run record.
+const MAX_STORED_PROMPT_BYTES: usize = 256 * 1024;
+
+/// Tag on runs started by `execute_dry_run`.
+pub const DRY_RUN_TAG: &str = "dry-run";
+
+/// Characters of the rendered prompt shown on a dry-run executor's node run.
+const DRY_RUN_PREVIEW_CHARS: usize = 500;
+
+/// Node-run preview for a dry-run executor: the prompt's length and start.
+fn dry_run_preview(prompt: &str) -> String {
+    let head: String = prompt.chars().take(DRY_RUN_PREVIEW_CHARS).collect();
+    format!("dry run — {} chars rendered\n{head}", prompt.chars().count())
+}
+
+/// Why `run` can't be retried, if it can't.
+pub fn check_retryable(run: &FlowRun) -> Result<()> {
+    if run.status != RunStatus::Failed {
+        bail!("only failed runs can be retried");
+    }
+    // Trigger context (PR diff, webhook body) isn't stored with the run.
+    if matches!(run.trigger, RunTrigger::GithubPr | RunTrigger::Webhook) {
+        bail!("runs started by a PR or webhook trigger can't be retried");
+    }
+    Ok(())
+}
+
+/// Snapshot of a successful node's output for `NodeRun::checkpoint`.
+fn checkpoint_of(output: &NodeOutput) -> Option<NodeCheckpoint> {
+    match output {
+        NodeOutput::Items(items) => Some(NodeCheckpoint::Items { items: items.clone() }),
+        NodeOutput::Text(text, _) => Some(NodeCheckpoint::Text { text: text.clone() }),
+        NodeOutput::Context(vars) => Some(NodeCheckpoint::Context { vars: vars.clone() }),
+        NodeOutput::Empty => Some(NodeCheckpoint::Empty),
+        NodeOutput::Failed => None,
+    }
+}
+
+fn restore_checkpoint(checkpoint: NodeCheckpoint) -> NodeOutput {
+    match checkpoint {
+        NodeCheckpoint::Items { items } => NodeOutput::Items(items),
+        NodeCheckpoint::Text { text } => NodeOutput::Text(text, None),
+        NodeCheckpoint::Context { vars } => NodeOutput::Context(vars),
+        NodeCheckpoint::Empty => NodeOutput::Empty,
+    }
+}
+
+/// Copy of `flow` with executor prompts replaced for a single run, keyed by
+/// node id. Errors on an unknown node or one that isn't an executor.
+pub fn apply_prompt_overrides(flow: &Flow, overrides: &HashMap<String, String>) -> Result<Flow> {
+    let mut flow = flow.clone();
+    for (node_id, prompt) in overrides {
+        let node = flow
+            .nodes
+            .iter_mut()
+            .find(|n| &n.id == node_id)
+            .with_context(|| format!("prompt override for unknown node '{node_id}'"))?;
+        if node.node_type != NodeType::Executor {
+            bail!("prompt override for node '{}', which isn't an executor", node.label);
+        }
+        node.config["prompt"] = serde_json::json!(prompt);
+    }
+    Ok(flow)
+}
+
+/// Render templated node `working_dir`s (`{{flow}}`, `{{date}}`, `{{run_id}}`)
+/// for this run, creating each directory and checking it against `policy`.
+/// `None` if no node's working dir is templated.
+fn render_working_dirs(flow: &Flow, run_id: &str, policy: &WorkingDirPolicy) -> Result<Option<Flow>> {
+    let is_templated = |n: &crate::flows::Node| n.config["working_dir"].as_str().is_some_and(|d| d.contains("{{"));
+    if !flow.nodes.iter().any(is_templated) {
+        return Ok(None);
+    }
+
+    // Flow names can hold spaces and slashes; keep the rendered path to one segment
+    let flow_segment: String = flow
+        .name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    let vars = HashMap::from([
+        ("flow".to_string(), flow_segment),
+        ("date".to_string(), Utc::now().format("%Y-%m-%d").to_string()),
+        ("run_id".to_string(), run_id.to_string()),
+    ]);
+
+    let mut rendered = flow.clone();
+    for node in rendered.nodes.iter_mut().filter(|n| is_templated(n)) {
+        let template = node.config["working_dir"].as_str().unwrap_or_default();
+        let dir = policy
+            .create(&PathBuf::from(render_prompt(template, &vars)))
+            .with_context(|| format!("node '{}' has an invalid working_dir", node.label))?;
+        node.config["working_dir"] = serde_json::json!(dir.to_string_lossy());
+    }
+    Ok(Some(rendered))
+}
+
+/// Node-run preview for a sink: what its destinations returned, how many
+/// attempts it took when retried, and the start of the (already redacted)
+/// text it sent.
+fn delivery_preview(text: &str, receipts: &[String], attempts: u32, max: usize) -> String {
+    let delivered = if attempts > 1 {
+        format!("delivered after {attempts} attempts")
+    } else {
+        "delivered".to_string()
+    };
+    if receipts.is_empty() {
+        format!("{delivered} — {}", truncate_chars(text, max))
+    } else {
+        format!("{delivered}: {} — {}", receipts.join(", "), truncate_chars(text, max))
+    }
+}
+
+/// Cut `s` to at most `max` characters, for previews sized in characters.
+fn truncate_chars(s: &str, max: usize) -> String {
+    match s.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", &s[..end]),
+        None => s.to_string(),
+    }
+}
+
+/// Cut `s` to at most `max` bytes, backing off to a char boundary.
 fn truncate(s: &str, max: usize) -> String {
     if s.len() <= max {
         s.to_string()
@@ -553,3 +1140,596 @@ fn truncate(s: &str, max: usize) -> String {
         format!("{}…", &s[..end])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::file_repository::FileFlowRepository;
+    use crate::flows::{Node, Position};
+    use crate::sandbox::{
+        SandboxBackendKind, SandboxError, SandboxHandle, SandboxProviderInfo, SandboxSpec, SandboxSummary,
+    };
+    use tempfile::tempdir;
+
+    fn test_runner() -> FlowRunner {
+        FlowRunner {
+            http_client: Arc::new(reqwest::Client::new()),
+            github_client: None,
+            events_tx: None,
+            sandbox_providers: None,
+            agent_repo: None,
+            session_bridge: None,
+            working_dir_policy: WorkingDirPolicy::default(),
+            run_preview: RunPreviewConfig::default(),
+        }
+    }
+
+    fn trigger_only_flow(id: &str) -> Flow {
+        Flow {
+            id: id.to_string(),
+            name: "Trigger only".to_string(),
+            description: String::new(),
+            enabled: true,
+            nodes: vec![Node {
+                id: "t1".to_string(),
+                node_type: NodeType::Trigger,
+                kind: "manual".to_string(),
+                config: serde_json::json!({}),
+                position: Position { x: 0.0, y: 0.0 },
+                label: "Manual".to_string(),
+            }],
+            edges: vec![],
+            version: 0,
+            max_runtime_secs: None,
+            capture_prompts: true,
+            sandbox_backend: None,
+            cost_cap: None,
+            default_session_budget_usd: None,
+            output_transform: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    struct StubSandbox(SandboxBackendKind);
+
+    #[async_trait::async_trait]
+    impl SandboxProvider for StubSandbox {
+        fn info(&self) -> SandboxProviderInfo {
+            SandboxProviderInfo {
+                kind: self.0,
+                supports_persistent_state: false,
+                supports_checkpoint: false,
+                supports_public_http: false,
+                supports_sleep_resume: false,
+            }
+        }
+
+        async fn provision(&self, _spec: SandboxSpec) -> std::result::Result<Box<dyn SandboxHandle>, SandboxError> {
+            Err(SandboxError::Unsupported("stub"))
+        }
+
+        async fn attach(&self, _id: &str) -> std::result::Result<Box<dyn SandboxHandle>, SandboxError> {
+            Err(SandboxError::Unsupported("stub"))
+        }
+
+        async fn list(&self) -> std::result::Result<Vec<SandboxSummary>, SandboxError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_sandbox_backend_selects_provider() {
+        let mut runner = test_runner();
+        runner.sandbox_providers = Some(
+            SandboxProviders::new(Arc::new(StubSandbox(SandboxBackendKind::Firecracker)))
+                .with(Arc::new(StubSandbox(SandboxBackendKind::DangerousHost))),
+        );
+        let mut flow = trigger_only_flow("sandboxed");
+        let mut executor = Node {
+            id: "e1".to_string(),
+            node_type: NodeType::Executor,
+            kind: "claude-code".to_string(),
+            config: serde_json::json!({ "runtime": "sandbox" }),
+            position: Position { x: 0.0, y: 0.0 },
+            label: "Sandboxed".to_string(),
+        };
+        let dispatched = |flow: &Flow, node: &Node| {
+            runner.sandbox_provider_for(flow, node).unwrap().unwrap().info().kind
+        };
+
+        // Unpinned flows get the default
+        assert_eq!(dispatched(&flow, &executor), SandboxBackendKind::Firecracker);
+
+        flow.sandbox_backend = Some("dangerous".to_string());
+        assert_eq!(dispatched(&flow, &executor), SandboxBackendKind::DangerousHost);
+
+        // The executor's own setting wins over the flow's
+        executor.config["sandbox_backend"] = serde_json::json!("firecracker");
+        assert_eq!(dispatched(&flow, &executor), SandboxBackendKind::Firecracker);
+
+        executor.config["sandbox_backend"] = serde_json::json!("sprite");
+        let err = runner.sandbox_provider_for(&flow, &executor).err().expect("sprite is not registered").to_string();
+        assert!(err.contains("'sprite'") && err.contains("available: dangerous, firecracker"), "{err}");
+    }
+
+    #[test]
+    fn test_delivery_preview() {
+        let receipts = vec!["1712345678.000100".to_string()];
+        assert_eq!(
+            delivery_preview("# Daily digest\nBTC up", &receipts, 1, 14),
+            "delivered: 1712345678.000100 — # Daily digest…"
+        );
+        assert_eq!(delivery_preview("hi", &[], 1, 100), "delivered — hi");
+        assert_eq!(
+            delivery_preview("hi", &receipts, 3, 100),
+            "delivered after 3 attempts: 1712345678.000100 — hi"
+        );
+    }
+
+    fn source_items() -> NodeOutput {
+        NodeOutput::Items(vec![ContentItem {
+            title: "BTC rallies".to_string(),
+            url: "https://example.com/btc".to_string(),
+            summary: "Up 5%.".to_string(),
+            published: None,
+            image_url: None,
+        }])
+    }
+
+    #[test]
+    fn test_executor_input_modes() {
+        let previous = || NodeOutput::Text("Synthesis: bullish".to_string(), None);
+
+        let replaced = executor_input(InputMode::Replace, previous(), source_items());
+        assert_eq!(replaced.as_text(), "Synthesis: bullish");
+
+        let original = executor_input(InputMode::Original, previous(), source_items());
+        assert!(matches!(original, NodeOutput::Items(_)));
+        assert!(original.as_text().contains("BTC rallies"));
+        assert!(!original.as_text().contains("Synthesis"));
+
+        let appended = executor_input(InputMode::Append, previous(), source_items()).as_text();
+        let source_pos = appended.find("BTC rallies").unwrap();
+        let synthesis_pos = appended.find("Synthesis: bullish").unwrap();
+        assert!(source_pos < synthesis_pos);
+    }
+
+    #[test]
+    fn test_executor_input_reading_sources_directly() {
+        // No upstream executor: input and origin coincide, so nothing is duplicated.
+        for mode in [InputMode::Replace, InputMode::Append, InputMode::Original] {
+            let fed = executor_input(mode, source_items(), source_items());
+            assert!(matches!(fed, NodeOutput::Items(_)));
+            assert_eq!(fed.as_text().matches("BTC rallies").count(), 1);
+        }
+        assert_eq!(InputMode::parse(None), InputMode::Replace);
+        assert_eq!(InputMode::parse(Some("append")), InputMode::Append);
+        assert_eq!(InputMode::parse(Some("bogus")), InputMode::Replace);
+    }
+
+    #[test]
+    fn test_truncate_respects_limit_and_char_boundaries() {
+        assert_eq!(truncate("hello", 10), "hello");
+        assert_eq!(truncate("hello world", 5), "hello…");
+        // "é" is two bytes; cutting at byte 3 would split it
+        assert_eq!(truncate("aéé", 2), "a…");
+        assert_eq!(truncate("aéé", 3), "aé…");
+    }
+
+    #[test]
+    fn test_truncate_chars_counts_characters() {
+        assert_eq!(truncate_chars("hello", 5), "hello");
+        assert_eq!(truncate_chars("hello world", 5), "hello…");
+        // Three two-byte chars fit a three-char preview
+        assert_eq!(truncate_chars("ééé", 3), "ééé");
+        assert_eq!(truncate_chars("éééé", 3), "ééé…");
+    }
+
+    #[tokio::test]
+    async fn test_orphaned_executor_warns_but_run_proceeds() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+
+        // A feed on a port nobody listens on: fails fast, never leaves the host
+        let closed_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut flow = trigger_only_flow("f1");
+        flow.nodes.push(Node {
+            id: "s1".to_string(),
+            node_type: NodeType::Source,
+            kind: "rss".to_string(),
+            config: serde_json::json!({ "url": format!("http://{closed_addr}/feed.xml") }),
+            position: Position { x: 0.0, y: 0.0 },
+            label: "Feed".to_string(),
+        });
+        flow.nodes.push(Node {
+            id: "e1".to_string(),
+            node_type: NodeType::Executor,
+            kind: "claude-code".to_string(),
+            config: serde_json::json!({}),
+            position: Position { x: 0.0, y: 0.0 },
+            label: "Lonely Executor".to_string(),
+        });
+        flow.edges.push(crate::flows::Edge {
+            id: "t1->s1".to_string(),
+            source: "t1".to_string(),
+            target: "s1".to_string(),
+        });
+
+        let (tx, mut rx) = broadcast::channel(64);
+        let mut runner = test_runner();
+        runner.events_tx = Some(tx);
+
+        let run = runner.execute(&flow, &repo, None, RunTrigger::Manual).await.unwrap();
+        // The executor still ran (and failed on its missing prompt) — it was not skipped
+        assert!(run.node_runs.iter().any(|nr| nr.node_id == "e1"));
+
+        let mut warned = false;
+        while let Ok(event) = rx.try_recv() {
+            if matches!(event.event_type, RunEventType::Log) && event.message.contains("Lonely Executor") {
+                warned = true;
+            }
+        }
+        assert!(warned, "expected an orphaned-executor log event");
+    }
+
+    #[tokio::test]
+    async fn test_retry_skips_successful_executors() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+
+        // t1 -> e1 -> e2. Neither executor has a prompt, so re-running either fails.
+        let mut flow = trigger_only_flow("f1");
+        for (id, label) in [("e1", "Summarize"), ("e2", "Publish")] {
+            flow.nodes.push(Node {
+                id: id.to_string(),
+                node_type: NodeType::Executor,
+                kind: "claude-code".to_string(),
+                config: serde_json::json!({}),
+                position: Position { x: 0.0, y: 0.0 },
+                label: label.to_string(),
+            });
+        }
+        for (source, target) in [("t1", "e1"), ("e1", "e2")] {
+            flow.edges.push(crate::flows::Edge {
+                id: format!("{source}->{target}"),
+                source: source.to_string(),
+                target: target.to_string(),
+            });
+        }
+
+        let node_run = |id: &str, status, checkpoint| NodeRun {
+            node_id: id.to_string(),
+            status,
+            started_at: Utc::now(),
+            finished_at: Some(Utc::now()),
+            output_preview: None,
+            output: None,
+            prompt: None,
+            checkpoint,
+            cost_usd: None,
+        };
+        let original = FlowRun {
+            id: "original-run".to_string(),
+            flow_id: "f1".to_string(),
+            status: RunStatus::Failed,
+            started_at: Utc::now(),
+            finished_at: Some(Utc::now()),
+            node_runs: vec![
+                node_run(
+                    "e1",
+                    RunStatus::Success,
+                    Some(NodeCheckpoint::Text { text: "expensive summary".to_string() }),
+                ),
+                node_run("e2", RunStatus::Failed, None),
+            ],
+            error: Some("one or more nodes failed".to_string()),
+            trigger: RunTrigger::Manual,
+            tags: vec![],
+            note: None,
+            resumed_from: None,
+        };
+        repo.add_run(original.clone()).await.unwrap();
+
+        let retried = test_runner().retry(&flow, &repo, &original).await.unwrap();
+        assert_eq!(retried.resumed_from.as_deref(), Some("original-run"));
+        assert_ne!(retried.id, original.id);
+
+        let e1 = retried.node_runs.iter().find(|nr| nr.node_id == "e1").unwrap();
+        assert_eq!(e1.status, RunStatus::Success, "e1 should be reused, not re-run");
+        assert!(matches!(&e1.checkpoint, Some(NodeCheckpoint::Text { text }) if text == "expensive summary"));
+        let e2 = retried.node_runs.iter().find(|nr| nr.node_id == "e2").unwrap();
+        assert_eq!(e2.status, RunStatus::Failed, "e2 should have executed again");
+
+        // Only failed runs qualify.
+        let mut succeeded = original.clone();
+        succeeded.status = RunStatus::Success;
+        assert!(test_runner().retry(&flow, &repo, &succeeded).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_records_trigger_provenance() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+        let flow = trigger_only_flow("f1");
+        let runner = test_runner();
+
+        let manual = runner.execute(&flow, &repo, None, RunTrigger::Manual).await.unwrap();
+        let cron = runner.execute(&flow, &repo, None, RunTrigger::Cron).await.unwrap();
+
+        assert_eq!(manual.trigger, RunTrigger::Manual);
+        assert_eq!(cron.trigger, RunTrigger::Cron);
+
+        let runs = repo.get_runs("f1", 10).await;
+        let stored = runs.iter().find(|r| r.id == manual.id).unwrap();
+        assert_eq!(stored.trigger, RunTrigger::Manual);
+    }
+
+    #[tokio::test]
+    async fn test_flow_max_runtime_cancels_run() {
+        use tokio::io::AsyncReadExt;
+
+        // A feed server that accepts the request but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (closed_tx, closed_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            // Returns once the client hangs up
+            while let Ok(n) = sock.read(&mut buf).await {
+                if n == 0 {
+                    break;
+                }
+            }
+            let _ = closed_tx.send(());
+        });
+
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+
+        let mut flow = trigger_only_flow("f1");
+        flow.max_runtime_secs = Some(1);
+        flow.nodes.push(Node {
+            id: "s1".to_string(),
+            node_type: NodeType::Source,
+            kind: "rss".to_string(),
+            config: serde_json::json!({ "url": format!("http://{addr}/feed.xml") }),
+            position: Position { x: 0.0, y: 0.0 },
+            label: "Slow Feed".to_string(),
+        });
+        flow.edges.push(crate::flows::Edge {
+            id: "t1->s1".to_string(),
+            source: "t1".to_string(),
+            target: "s1".to_string(),
+        });
+
+        let started = std::time::Instant::now();
+        let err = test_runner().execute(&flow, &repo, None, RunTrigger::Manual).await.unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(err.to_string().contains("exceeded max runtime"));
+
+        let run = repo.get_runs("f1", 10).await.into_iter().next().unwrap();
+        assert_eq!(run.status, RunStatus::Failed);
+        assert!(run.error.as_deref().unwrap().contains("exceeded max runtime"));
+        let node = run.node_runs.iter().find(|nr| nr.node_id == "s1").unwrap();
+        assert_eq!(node.status, RunStatus::Failed);
+
+        // The in-flight node task was aborted, dropping its connection
+        tokio::time::timeout(Duration::from_secs(5), closed_rx)
+            .await
+            .expect("node task kept running after the run was cancelled")
+            .unwrap();
+    }
+
+    fn prompt_capture_flow(id: &str) -> Flow {
+        let mut flow = trigger_only_flow(id);
+        flow.nodes.push(Node {
+            id: "e1".to_string(),
+            node_type: NodeType::Executor,
+            kind: "claude-code".to_string(),
+            config: serde_json::json!({ "prompt": "Review PR: {{title}}" }),
+            position: Position { x: 0.0, y: 0.0 },
+            label: "Reviewer".to_string(),
+        });
+        flow.edges.push(crate::flows::Edge {
+            id: "t1->e1".to_string(),
+            source: "t1".to_string(),
+            target: "e1".to_string(),
+        });
+        flow
+    }
+
+    #[tokio::test]
+    async fn test_rendered_prompt_is_captured_on_run() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+        let flow = prompt_capture_flow("f1");
+        let context = HashMap::from([("title".to_string(), "Fix login".to_string())]);
+
+        // The executor itself fails (no agent configured), after rendering its prompt
+        let run = test_runner().execute(&flow, &repo, Some(context), RunTrigger::Manual).await.unwrap();
+
+        let stored = repo.get_run("f1", &run.id).await.unwrap();
+        let node = stored.node_runs.iter().find(|nr| nr.node_id == "e1").unwrap();
+        assert_eq!(node.prompt.as_deref(), Some("Review PR: Fix login"));
+    }
+
+    #[tokio::test]
+    async fn test_rendered_prompt_capture_can_be_disabled() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+        let mut flow = prompt_capture_flow("f1");
+        flow.capture_prompts = false;
+        let context = HashMap::from([("title".to_string(), "Secret".to_string())]);
+
+        let run = test_runner().execute(&flow, &repo, Some(context), RunTrigger::Manual).await.unwrap();
+
+        let stored = repo.get_run("f1", &run.id).await.unwrap();
+        let node = stored.node_runs.iter().find(|nr| nr.node_id == "e1").unwrap();
+        assert!(node.prompt.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_stops_before_executors_and_sinks() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+        // t1 -> e1 -> k1, where running e1 would fail (no agent) and k1 has no env
+        let mut flow = prompt_capture_flow("f1");
+        flow.nodes[1].config["prompt"] = serde_json::json!("Summarize {{item_count}} items");
+        flow.nodes.push(Node {
+            id: "k1".to_string(),
+            node_type: NodeType::Sink,
+            kind: "slack".to_string(),
+            config: serde_json::json!({ "webhook_url_env": "CTHULU_TEST_UNSET_WEBHOOK" }),
+            position: Position { x: 0.0, y: 0.0 },
+            label: "Slack".to_string(),
+        });
+        flow.edges.push(crate::flows::Edge {
+            id: "e1->k1".to_string(),
+            source: "e1".to_string(),
+            target: "k1".to_string(),
+        });
+
+        let (tx, mut rx) = broadcast::channel(64);
+        let mut runner = test_runner();
+        runner.events_tx = Some(tx);
+        let run = runner.execute_dry_run(&flow, &repo).await.unwrap();
+
+        let run = repo.get_run("f1", &run.id).await.unwrap();
+        assert_eq!(run.status, RunStatus::Success);
+        assert_eq!(run.tags, vec![DRY_RUN_TAG.to_string()]);
+        let e1 = run.node_runs.iter().find(|nr| nr.node_id == "e1").unwrap();
+        assert_eq!(e1.status, RunStatus::Success);
+        assert_eq!(e1.output_preview.as_deref(), Some("dry run — 17 chars rendered\nSummarize 0 items"));
+        assert!(run.node_runs.iter().all(|nr| nr.node_id != "k1"));
+
+        let mut rendered = None;
+        while let Ok(event) = rx.try_recv() {
+            if matches!(event.event_type, RunEventType::PromptRendered) {
+                rendered = Some(event.message);
+            }
+        }
+        assert_eq!(rendered.as_deref(), Some("Summarize 0 items"));
+    }
+
+    #[tokio::test]
+    async fn test_prompt_override_applies_to_one_run() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+        repo.save_flow(prompt_capture_flow("f1")).await.unwrap();
+        let stored = repo.get_flow("f1").await.unwrap();
+        let overrides = HashMap::from([("e1".to_string(), "Nitpick PR: {{title}}".to_string())]);
+        let context = HashMap::from([("title".to_string(), "Fix login".to_string())]);
+
+        let flow = apply_prompt_overrides(&stored, &overrides).unwrap();
+        let run = test_runner().execute(&flow, &repo, Some(context), RunTrigger::Manual).await.unwrap();
+
+        let run = repo.get_run("f1", &run.id).await.unwrap();
+        let node = run.node_runs.iter().find(|nr| nr.node_id == "e1").unwrap();
+        assert_eq!(node.prompt.as_deref(), Some("Nitpick PR: Fix login"));
+        let after = repo.get_flow("f1").await.unwrap();
+        assert_eq!(after.nodes[1].config["prompt"], "Review PR: {{title}}");
+        assert_eq!(after.version, stored.version);
+
+        // Only executors that exist can be overridden
+        let unknown = HashMap::from([("nope".to_string(), "x".to_string())]);
+        assert!(apply_prompt_overrides(&stored, &unknown).unwrap_err().to_string().contains("unknown node"));
+        let trigger = HashMap::from([("t1".to_string(), "x".to_string())]);
+        assert!(apply_prompt_overrides(&stored, &trigger).unwrap_err().to_string().contains("isn't an executor"));
+    }
+
+    #[tokio::test]
+    async fn test_templated_working_dir_is_rendered_and_created() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().join("data"));
+        repo.load_all().await.unwrap();
+        let runs_root = dir.path().join("runs");
+        let mut flow = prompt_capture_flow("f1");
+        flow.nodes[1].config["working_dir"] =
+            serde_json::json!(format!("{}/{{{{flow}}}}/{{{{date}}}}/{{{{run_id}}}}", runs_root.display()));
+        let context = HashMap::from([("title".to_string(), "Fix login".to_string())]);
+
+        let run = test_runner().execute(&flow, &repo, Some(context), RunTrigger::Manual).await.unwrap();
+
+        let date = run.started_at.format("%Y-%m-%d").to_string();
+        assert!(runs_root.join("Trigger-only").join(date).join(&run.id).is_dir());
+        // Only the run saw the rendered path
+        assert!(flow.nodes[1].config["working_dir"].as_str().unwrap().ends_with("{{run_id}}"));
+    }
+
+    #[test]
+    fn test_templated_working_dir_outside_allowlist_fails() {
+        let allowed = tempdir().unwrap();
+        let elsewhere = tempdir().unwrap();
+        let policy = WorkingDirPolicy {
+            allowed_roots: vec![allowed.path().to_path_buf()],
+            strict: false,
+        };
+        let mut flow = prompt_capture_flow("f1");
+        flow.nodes[1].config["working_dir"] = serde_json::json!(format!("{}/{{{{run_id}}}}", elsewhere.path().display()));
+
+        let err = render_working_dirs(&flow, "r1", &policy).unwrap_err();
+        assert!(format!("{err:#}").contains("outside the allowed directories"));
+        assert!(!elsewhere.path().join("r1").exists());
+
+        // Static dirs are left to the executor
+        flow.nodes[1].config["working_dir"] = serde_json::json!(elsewhere.path().to_str());
+        assert!(render_working_dirs(&flow, "r1", &policy).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_prompts_per_executor() {
+        let mut flow = prompt_capture_flow("f1");
+        flow.nodes.push(Node {
+            id: "e2".to_string(),
+            node_type: NodeType::Executor,
+            kind: "claude-code".to_string(),
+            config: serde_json::json!({}),
+            position: Position { x: 0.0, y: 0.0 },
+            label: "No Prompt".to_string(),
+        });
+
+        let estimates = test_runner().estimate_prompts(&flow).await.unwrap();
+        assert_eq!(estimates.len(), 2);
+
+        let e1 = estimates.iter().find(|e| e.node_id == "e1").unwrap();
+        // "Review PR: {{title}}" renders with the unknown var left in place
+        let expected = render_prompt("Review PR: {{title}}", &HashMap::new());
+        assert_eq!(e1.chars, expected.chars().count());
+        assert_eq!(e1.approx_tokens, expected.chars().count().div_ceil(4));
+        assert!(e1.error.is_none());
+
+        let e2 = estimates.iter().find(|e| e.node_id == "e2").unwrap();
+        assert!(e2.error.as_deref().unwrap().contains("missing 'prompt'"));
+    }
+
+    #[tokio::test]
+    async fn test_prepare_node_session_validates_working_dir() {
+        let dir = tempdir().unwrap();
+        let mut flow = prompt_capture_flow("f1");
+        let strict = WorkingDirPolicy { allowed_roots: vec![dir.path().to_path_buf()], strict: true };
+
+        flow.nodes[1].config["working_dir"] = serde_json::json!(dir.path().to_str().unwrap());
+        let info = FlowRunner::prepare_node_session(&flow, "e1", None, &strict).await.unwrap();
+        assert_eq!(info.working_dir, dir.path().canonicalize().unwrap().to_string_lossy());
+
+        let missing = dir.path().join("missing");
+        flow.nodes[1].config["working_dir"] = serde_json::json!(missing.to_str().unwrap());
+        let err = FlowRunner::prepare_node_session(&flow, "e1", None, &strict).await.unwrap_err();
+        assert!(format!("{err:#}").contains("invalid working_dir"));
+
+        // Lenient mode falls back to the CWD instead
+        let lenient = WorkingDirPolicy::default();
+        let info = FlowRunner::prepare_node_session(&flow, "e1", None, &lenient).await.unwrap();
+        assert_eq!(info.working_dir, std::env::current_dir().unwrap().to_string_lossy());
+    }
+}
@@ -1,24 +1,31 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use chrono::Utc;
-use croner::Cron;
 use tokio::sync::Mutex;
 use tokio::sync::broadcast;
-use tokio::task::JoinHandle;
+use tokio::task::{JoinHandle, JoinSet};
 
 use crate::agents::repository::AgentRepository;
+use crate::api::changes::{ChangeType, ResourceChangeEvent, ResourceType};
 use crate::api::FlowSessions;
-use crate::flows::events::RunEvent;
+use crate::config::{RunPreviewConfig, WorkingDirPolicy};
+use crate::flows::cost_cap::CostCap;
+use crate::flows::cron_schedule::CronSchedules;
+use crate::flows::events::{RunEvent, RunEventType};
+use crate::flows::history::{RunTrigger, MAX_RUNS_PER_FLOW};
 use crate::flows::repository::FlowRepository;
 use crate::flows::runner::FlowRunner;
+use crate::flows::dir_lock::WorkingDirLocks;
 use crate::flows::session_bridge::SessionBridge;
-use crate::flows::NodeType;
+use crate::flows::{Flow, NodeType};
 use crate::github::client::GithubClient;
 use crate::github::models::RepoConfig;
-use crate::sandbox::provider::SandboxProvider;
+use crate::sandbox::provider::SandboxProviders;
 use crate::tasks::diff;
 
 pub struct FlowScheduler {
@@ -26,15 +33,22 @@ pub struct FlowScheduler {
     http_client: Arc<reqwest::Client>,
     github_client: Option<Arc<dyn GithubClient>>,
     events_tx: broadcast::Sender<RunEvent>,
+    /// Resource change feed, for flows the scheduler itself updates.
+    changes_tx: broadcast::Sender<ResourceChangeEvent>,
     handles: Mutex<HashMap<String, JoinHandle<()>>>,
+    /// Times each flow's trigger task has been restarted since it was last started.
+    restart_counts: RestartCounts,
     seen_prs: Arc<Mutex<HashMap<String, HashMap<u64, String>>>>,
-    sandbox_provider: Arc<dyn SandboxProvider>,
+    sandbox_providers: SandboxProviders,
     agent_repo: Arc<dyn AgentRepository>,
     /// Fields needed to construct SessionBridge for flow runs.
     interact_sessions: Arc<tokio::sync::RwLock<HashMap<String, FlowSessions>>>,
     sessions_path: std::path::PathBuf,
     data_dir: std::path::PathBuf,
     session_streams: Arc<Mutex<HashMap<String, broadcast::Sender<String>>>>,
+    dir_locks: WorkingDirLocks,
+    working_dir_policy: WorkingDirPolicy,
+    run_preview: RunPreviewConfig,
 }
 
 impl FlowScheduler {
@@ -43,26 +57,35 @@ impl FlowScheduler {
         http_client: Arc<reqwest::Client>,
         github_client: Option<Arc<dyn GithubClient>>,
         events_tx: broadcast::Sender<RunEvent>,
-        sandbox_provider: Arc<dyn SandboxProvider>,
+        changes_tx: broadcast::Sender<ResourceChangeEvent>,
+        sandbox_providers: SandboxProviders,
         agent_repo: Arc<dyn AgentRepository>,
         interact_sessions: Arc<tokio::sync::RwLock<HashMap<String, FlowSessions>>>,
         sessions_path: std::path::PathBuf,
         data_dir: std::path::PathBuf,
         session_streams: Arc<Mutex<HashMap<String, broadcast::Sender<String>>>>,
+        dir_locks: WorkingDirLocks,
+        working_dir_policy: WorkingDirPolicy,
+    run_preview: RunPreviewConfig,
     ) -> Self {
         Self {
             flow_repo,
             http_client,
             github_client,
             events_tx,
+            changes_tx,
             handles: Mutex::new(HashMap::new()),
+            restart_counts: Arc::new(std::sync::Mutex::new(HashMap::new())),
             seen_prs: Arc::new(Mutex::new(HashMap::new())),
-            sandbox_provider,
+            sandbox_providers,
             agent_repo,
             interact_sessions,
             sessions_path,
             data_dir,
             session_streams,
+            dir_locks,
+            working_dir_policy,
+            run_preview,
         }
     }
 
@@ -72,9 +95,35 @@ impl FlowScheduler {
             sessions_path: self.sessions_path.clone(),
             data_dir: self.data_dir.clone(),
             session_streams: self.session_streams.clone(),
+            dir_locks: self.dir_locks.clone(),
         }
     }
 
+    /// Spawn a trigger loop under a supervisor that restarts it if it dies
+    /// while the flow is still enabled.
+    fn spawn_supervised<T, Fut>(&self, flow_id: &str, flow_name: &str, trigger: T) -> JoinHandle<()>
+    where
+        T: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.restart_counts.lock().unwrap().remove(flow_id);
+        let flow_repo = self.flow_repo.clone();
+        let id = flow_id.to_string();
+        let still_enabled = move || {
+            let flow_repo = flow_repo.clone();
+            let id = id.clone();
+            async move { flow_repo.get_flow(&id).await.is_some_and(|f| f.enabled) }
+        };
+        tokio::spawn(supervise(
+            flow_id.to_string(),
+            flow_name.to_string(),
+            self.restart_counts.clone(),
+            RestartPolicy::DEFAULT,
+            trigger,
+            still_enabled,
+        ))
+    }
+
     pub async fn start_all(&self) {
         let flows = self.flow_repo.list_flows().await;
         for flow in flows {
@@ -108,10 +157,7 @@ impl FlowScheduler {
 
         match trigger_node.kind.as_str() {
             "cron" => {
-                let schedule = trigger_node.config["schedule"]
-                    .as_str()
-                    .context("cron trigger missing 'schedule'")?
-                    .to_string();
+                let schedules = CronSchedules::from_config(&trigger_node.config["schedule"])?;
 
                 let flow_id = flow.id.clone();
                 let flow_name = flow.name.clone();
@@ -119,27 +165,48 @@ impl FlowScheduler {
                 let http_client = self.http_client.clone();
                 let github_client = self.github_client.clone();
                 let events_tx = self.events_tx.clone();
+                let changes_tx = self.changes_tx.clone();
 
-                tracing::info!(flow = %flow.name, schedule = %schedule, "Started cron trigger");
+                tracing::info!(flow = %flow.name, schedule = %schedules.describe(), "Started cron trigger");
 
-                let sandbox_provider = self.sandbox_provider.clone();
+                let sandbox_providers = self.sandbox_providers.clone();
                 let agent_repo = self.agent_repo.clone();
                 let session_bridge = self.build_session_bridge();
-                let handle = tokio::spawn(async move {
-                    cron_loop(
-                        &flow_id,
-                        &flow_name,
-                        &schedule,
-                        flow_repo,
-                        http_client,
-                        github_client,
-                        events_tx,
-                        sandbox_provider,
-                        agent_repo,
-                        session_bridge,
-                    )
-                    .await;
-                });
+                let working_dir_policy = self.working_dir_policy.clone();
+                let run_preview = self.run_preview;
+                let trigger = move || {
+                    let flow_id = flow_id.clone();
+                    let flow_name = flow_name.clone();
+                    let schedules = schedules.clone();
+                    let flow_repo = flow_repo.clone();
+                    let http_client = http_client.clone();
+                    let github_client = github_client.clone();
+                    let events_tx = events_tx.clone();
+                    let changes_tx = changes_tx.clone();
+                    let sandbox_providers = sandbox_providers.clone();
+                    let agent_repo = agent_repo.clone();
+                    let session_bridge = session_bridge.clone();
+                    let working_dir_policy = working_dir_policy.clone();
+                    async move {
+                        cron_loop(
+                            &flow_id,
+                            &flow_name,
+                            &schedules,
+                            flow_repo,
+                            http_client,
+                            github_client,
+                            events_tx,
+                            changes_tx,
+                            sandbox_providers,
+                            agent_repo,
+                            session_bridge,
+                            working_dir_policy,
+                            run_preview,
+                        )
+                        .await;
+                    }
+                };
+                let handle = self.spawn_supervised(&flow.id, &flow.name, trigger);
                 self.handles.lock().await.insert(flow.id.clone(), handle);
             }
             "github-pr" => {
@@ -155,26 +222,48 @@ impl FlowScheduler {
                 let seen_prs = self.seen_prs.clone();
                 let trigger_config = trigger_node.config.clone();
                 let events_tx = self.events_tx.clone();
+                let changes_tx = self.changes_tx.clone();
 
-                let sandbox_provider = self.sandbox_provider.clone();
+                let sandbox_providers = self.sandbox_providers.clone();
                 let agent_repo = self.agent_repo.clone();
                 let session_bridge = self.build_session_bridge();
-                let handle = tokio::spawn(async move {
-                    github_pr_loop(
-                        &flow_id,
-                        &flow_name,
-                        trigger_config,
-                        flow_repo,
-                        http_client,
-                        github_client,
-                        seen_prs,
-                        events_tx,
-                        sandbox_provider,
-                        agent_repo,
-                        session_bridge,
-                    )
-                    .await;
-                });
+                let working_dir_policy = self.working_dir_policy.clone();
+                let run_preview = self.run_preview;
+                let trigger = move || {
+                    let flow_id = flow_id.clone();
+                    let flow_name = flow_name.clone();
+                    let trigger_config = trigger_config.clone();
+                    let flow_repo = flow_repo.clone();
+                    let http_client = http_client.clone();
+                    let github_client = github_client.clone();
+                    let seen_prs = seen_prs.clone();
+                    let events_tx = events_tx.clone();
+                    let changes_tx = changes_tx.clone();
+                    let sandbox_providers = sandbox_providers.clone();
+                    let agent_repo = agent_repo.clone();
+                    let session_bridge = session_bridge.clone();
+                    let working_dir_policy = working_dir_policy.clone();
+                    async move {
+                        github_pr_loop(
+                            &flow_id,
+                            &flow_name,
+                            trigger_config,
+                            flow_repo,
+                            http_client,
+                            github_client,
+                            seen_prs,
+                            events_tx,
+                            changes_tx,
+                            sandbox_providers,
+                            agent_repo,
+                            session_bridge,
+                            working_dir_policy,
+                            run_preview,
+                        )
+                        .await;
+                    }
+                };
+                let handle = self.spawn_supervised(&flow.id, &flow.name, trigger);
 
                 tracing::info!(flow = %flow.name, "Started GitHub PR trigger");
                 self.handles.lock().await.insert(flow.id.clone(), handle);
@@ -202,6 +291,16 @@ impl FlowScheduler {
         }
     }
 
+    /// Stop every flow's trigger (server shutdown). Runs already started
+    /// are separate tasks and keep going.
+    pub async fn stop_all(&self) {
+        let mut handles = self.handles.lock().await;
+        for (_, handle) in handles.drain() {
+            handle.abort();
+        }
+        tracing::info!("Stopped all flow triggers");
+    }
+
     pub async fn restart_flow(&self, flow_id: &str) -> Result<()> {
         self.stop_flow(flow_id).await;
         self.start_flow(flow_id).await
@@ -210,7 +309,50 @@ impl FlowScheduler {
     /// Return the set of flow IDs that currently have active scheduler tasks.
     pub async fn active_flow_ids(&self) -> Vec<String> {
         let handles = self.handles.lock().await;
-        handles.keys().cloned().collect()
+        handles
+            .iter()
+            .filter(|(_, h)| !h.is_finished())
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Restart counts per flow, for flows whose trigger has been restarted.
+    pub fn restart_counts(&self) -> HashMap<String, u32> {
+        self.restart_counts.lock().unwrap().clone()
+    }
+
+    /// Enabled flows whose github-pr trigger watches `repo_slug` and wants
+    /// a review of this PR event, honouring `skip_drafts` and
+    /// `review_on_push` like the polling loop. Each comes with the repo slug
+    /// as the trigger configures it, which may differ in case from the event's.
+    pub async fn pr_review_flows(&self, repo_slug: &str, draft: bool, new_commits: bool) -> Vec<(String, String)> {
+        self.flow_repo
+            .list_flows()
+            .await
+            .into_iter()
+            .filter(|flow| flow.enabled)
+            .filter_map(|flow| {
+                let repo = flow.nodes.iter().find_map(|n| {
+                    (n.node_type == NodeType::Trigger
+                        && n.kind == "github-pr"
+                        && wants_pr_event(&n.config, repo_slug, draft, new_commits))
+                    .then(|| configured_repo(&n.config, repo_slug))
+                    .flatten()
+                })?;
+                Some((flow.id, repo.full_name()))
+            })
+            .collect()
+    }
+
+    /// Whether a flow about to be fired by a webhook is under its cost cap;
+    /// see `cost_cap_allows`. A missing flow is left to the trigger to report.
+    pub async fn cost_cap_allows(&self, flow_id: &str) -> bool {
+        match self.flow_repo.get_flow(flow_id).await {
+            Some(flow) => {
+                cost_cap_allows(&flow, &*self.flow_repo, &self.events_tx, &self.changes_tx).await
+            }
+            None => true,
+        }
     }
 
     /// Execute a specific PR review through a flow with github-pr trigger.
@@ -242,25 +384,17 @@ impl FlowScheduler {
             .as_u64()
             .unwrap_or(50_000) as usize;
 
-        // Parse repo slug
+        // GitHub slugs are case-insensitive: use the trigger's own spelling,
+        // as the polling loop does, for local_path and the seen_prs key
+        let configured = configured_repo(&trigger_node.config, repo_slug);
+        let repo_slug = configured
+            .as_ref()
+            .map_or_else(|| repo_slug.to_string(), |repo| repo.full_name());
+        let repo_slug = repo_slug.as_str();
         let (owner, repo_name) = repo_slug
             .split_once('/')
             .context("invalid repo slug, expected 'owner/repo'")?;
-
-        // Find local_path from trigger config repos
-        let local_path = trigger_node.config["repos"]
-            .as_array()
-            .and_then(|repos| {
-                repos.iter().find_map(|r| {
-                    let slug = r["slug"].as_str()?;
-                    if slug == repo_slug {
-                        Some(PathBuf::from(r["path"].as_str().unwrap_or(".")))
-                    } else {
-                        None
-                    }
-                })
-            })
-            .unwrap_or_else(|| PathBuf::from("."));
+        let local_path = configured.map_or_else(|| PathBuf::from("."), |repo| repo.local_path);
 
         let pr = github_client
             .fetch_single_pr(owner, repo_name, pr_number)
@@ -296,13 +430,15 @@ impl FlowScheduler {
             http_client: self.http_client.clone(),
             github_client: self.github_client.clone(),
             events_tx: Some(self.events_tx.clone()),
-            sandbox_provider: Some(self.sandbox_provider.clone()),
+            sandbox_providers: Some(self.sandbox_providers.clone()),
             agent_repo: Some(self.agent_repo.clone()),
             session_bridge: Some(self.build_session_bridge()),
+            working_dir_policy: self.working_dir_policy.clone(),
+            run_preview: self.run_preview,
         };
 
         runner
-            .execute(&flow, &*self.flow_repo, Some(context))
+            .execute(&flow, &*self.flow_repo, Some(context), RunTrigger::GithubPr)
             .await?;
 
         diff::cleanup(&diff_ctx);
@@ -310,39 +446,116 @@ impl FlowScheduler {
     }
 }
 
+// ── Trigger supervision ──────────────────────────────────────────
+
+type RestartCounts = Arc<std::sync::Mutex<HashMap<String, u32>>>;
+
+/// How a dead trigger task is restarted.
+#[derive(Debug, Clone, Copy)]
+struct RestartPolicy {
+    max_restarts: u32,
+    base_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RestartPolicy {
+    const DEFAULT: Self = Self {
+        max_restarts: 5,
+        base_backoff: Duration::from_secs(5),
+        max_backoff: Duration::from_secs(300),
+    };
+
+    /// Exponential backoff before restart number `attempt` (0-based).
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.base_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// Run a trigger loop and restart it with backoff whenever it ends (returns
+/// or panics), as long as the flow is still enabled and the restart budget
+/// isn't spent. Aborting the supervisor also aborts the running trigger.
+async fn supervise<T, Fut, K, KFut>(
+    flow_id: String,
+    flow_name: String,
+    restart_counts: RestartCounts,
+    policy: RestartPolicy,
+    mut trigger: T,
+    still_enabled: K,
+) where
+    T: FnMut() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+    K: Fn() -> KFut,
+    KFut: Future<Output = bool>,
+{
+    let mut restarts = 0u32;
+    loop {
+        // The JoinSet owns the trigger task, so dropping it (stop_flow aborting
+        // this supervisor) cancels the trigger too.
+        let mut task = JoinSet::new();
+        task.spawn(trigger());
+        match task.join_next().await {
+            Some(Err(e)) if e.is_panic() => {
+                tracing::error!(flow = %flow_name, error = %e, "Trigger task panicked");
+            }
+            Some(Err(_)) => return,
+            _ => tracing::warn!(flow = %flow_name, "Trigger task exited"),
+        }
+
+        if !still_enabled().await {
+            tracing::info!(flow = %flow_name, "Flow disabled or deleted, not restarting trigger");
+            return;
+        }
+        if restarts >= policy.max_restarts {
+            tracing::error!(
+                flow = %flow_name,
+                restarts,
+                "Trigger task keeps dying, giving up on restarts"
+            );
+            return;
+        }
+
+        let delay = policy.backoff(restarts);
+        tracing::warn!(
+            flow = %flow_name,
+            attempt = restarts + 1,
+            delay_secs = delay.as_secs(),
+            "Restarting trigger task"
+        );
+        tokio::time::sleep(delay).await;
+
+        restarts += 1;
+        restart_counts.lock().unwrap().insert(flow_id.clone(), restarts);
+    }
+}
+
 // ── Cron loop ────────────────────────────────────────────────────
 
 async fn cron_loop(
     flow_id: &str,
     flow_name: &str,
-    schedule: &str,
+    schedules: &CronSchedules,
     flow_repo: Arc<dyn FlowRepository>,
     http_client: Arc<reqwest::Client>,
     github_client: Option<Arc<dyn GithubClient>>,
     events_tx: broadcast::Sender<RunEvent>,
-    sandbox_provider: Arc<dyn SandboxProvider>,
+    changes_tx: broadcast::Sender<ResourceChangeEvent>,
+    sandbox_providers: SandboxProviders,
     agent_repo: Arc<dyn AgentRepository>,
     session_bridge: SessionBridge,
+    working_dir_policy: WorkingDirPolicy,
+    run_preview: RunPreviewConfig,
 ) {
-    let cron = match Cron::new(schedule).parse() {
-        Ok(c) => c,
-        Err(e) => {
-            tracing::error!(flow = %flow_name, error = %e, "Invalid cron expression '{schedule}'");
-            return;
-        }
-    };
-
-    tracing::info!(flow = %flow_name, schedule = %schedule, "Cron loop started");
+    tracing::info!(flow = %flow_name, schedule = %schedules.describe(), "Cron loop started");
 
     loop {
         let now = Utc::now();
-        let next = match cron.find_next_occurrence(&now, false) {
-            Ok(next) => next,
-            Err(e) => {
-                tracing::error!(flow = %flow_name, error = %e, "Failed to compute next cron occurrence");
-                tokio::time::sleep(std::time::Duration::from_secs(60)).await;
-                continue;
-            }
+        // Whichever schedule comes due first
+        let Some(next) = schedules.next_after(&now) else {
+            tracing::error!(flow = %flow_name, "Failed to compute next cron occurrence");
+            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            continue;
         };
 
         let duration = (next - now).to_std().unwrap_or(std::time::Duration::from_secs(1));
@@ -373,21 +586,95 @@ async fn cron_loop(
             }
         };
 
+        if !cost_cap_allows(&flow, &*flow_repo, &events_tx, &changes_tx).await {
+            continue;
+        }
+
         let runner = FlowRunner {
             http_client: http_client.clone(),
             github_client: github_client.clone(),
             events_tx: Some(events_tx.clone()),
-            sandbox_provider: Some(sandbox_provider.clone()),
+            sandbox_providers: Some(sandbox_providers.clone()),
             agent_repo: Some(agent_repo.clone()),
             session_bridge: Some(session_bridge.clone()),
+            working_dir_policy: working_dir_policy.clone(),
+            run_preview,
         };
 
-        if let Err(e) = runner.execute(&flow, &*flow_repo, None).await {
+        if let Err(e) = runner.execute(&flow, &*flow_repo, None, RunTrigger::Cron).await {
             tracing::error!(flow = %flow_name, error = %e, "Cron flow execution failed");
         }
     }
 }
 
+/// Pre-fire check shared by every automatic trigger path (cron, PR polling,
+/// webhooks). Returns whether the flow may fire; a flow over its cost cap
+/// has the fire skipped through `skip_over_cap` instead.
+pub(crate) async fn cost_cap_allows(
+    flow: &Flow,
+    flow_repo: &dyn FlowRepository,
+    events_tx: &broadcast::Sender<RunEvent>,
+    changes_tx: &broadcast::Sender<ResourceChangeEvent>,
+) -> bool {
+    let Some(cap) = &flow.cost_cap else {
+        return true;
+    };
+    let runs = flow_repo.get_runs(&flow.id, MAX_RUNS_PER_FLOW).await;
+    let Some(spend) = cap.exceeded(&runs, Utc::now()) else {
+        return true;
+    };
+    skip_over_cap(flow, cap, spend, flow_repo, events_tx, changes_tx).await;
+    false
+}
+
+/// Skip an automatic fire of a flow over its cost cap: warn, emit an event,
+/// and disable the flow if the cap says to. Returns whether it was disabled.
+async fn skip_over_cap(
+    flow: &Flow,
+    cap: &CostCap,
+    spend: f64,
+    flow_repo: &dyn FlowRepository,
+    events_tx: &broadcast::Sender<RunEvent>,
+    changes_tx: &broadcast::Sender<ResourceChangeEvent>,
+) -> bool {
+    let mut message = format!(
+        "Skipped automatic run: ${spend:.2} spent in the last {}h (cap ${:.2})",
+        cap.window_hours, cap.max_usd
+    );
+    tracing::warn!(flow = %flow.name, spend, max_usd = cap.max_usd, "Flow over cost cap, skipping automatic fire");
+
+    let mut disabled = false;
+    if cap.disable_when_exceeded {
+        let mut updated = flow.clone();
+        updated.enabled = false;
+        updated.version += 1;
+        updated.updated_at = Utc::now();
+        match flow_repo.save_flow(updated).await {
+            Ok(()) => {
+                message.push_str("; flow disabled");
+                disabled = true;
+                let _ = changes_tx.send(ResourceChangeEvent {
+                    resource_type: ResourceType::Flow,
+                    change_type: ChangeType::Updated,
+                    resource_id: flow.id.clone(),
+                    timestamp: Utc::now(),
+                });
+            }
+            Err(e) => tracing::error!(flow = %flow.name, error = %e, "Failed to disable flow over cost cap"),
+        }
+    }
+
+    let _ = events_tx.send(RunEvent {
+        flow_id: flow.id.clone(),
+        run_id: String::new(),
+        timestamp: Utc::now(),
+        node_id: None,
+        event_type: RunEventType::CostCapExceeded,
+        message,
+    });
+    disabled
+}
+
 // ── GitHub PR loop ───────────────────────────────────────────────
 
 async fn github_pr_loop(
@@ -399,9 +686,12 @@ async fn github_pr_loop(
     github_client: Arc<dyn GithubClient>,
     seen_prs: Arc<Mutex<HashMap<String, HashMap<u64, String>>>>,
     events_tx: broadcast::Sender<RunEvent>,
-    sandbox_provider: Arc<dyn SandboxProvider>,
+    changes_tx: broadcast::Sender<ResourceChangeEvent>,
+    sandbox_providers: SandboxProviders,
     agent_repo: Arc<dyn AgentRepository>,
     session_bridge: SessionBridge,
+    working_dir_policy: WorkingDirPolicy,
+    run_preview: RunPreviewConfig,
 ) {
     let poll_interval = trigger_config["poll_interval"].as_u64().unwrap_or(60);
     let skip_drafts = trigger_config["skip_drafts"].as_bool().unwrap_or(true);
@@ -515,6 +805,12 @@ async fn github_pr_loop(
             }
         };
 
+        // Skip the whole poll while over the cap, so new PRs stay unseen
+        // and get reviewed once spend drops back under it
+        if !cost_cap_allows(&flow, &*flow_repo, &events_tx, &changes_tx).await {
+            continue;
+        }
+
         for repo in &seeded_repos {
             let prs = match github_client
                 .fetch_open_prs(&repo.owner, &repo.repo)
@@ -632,13 +928,15 @@ async fn github_pr_loop(
                     http_client: http_client.clone(),
                     github_client: Some(github_client.clone()),
                     events_tx: Some(events_tx.clone()),
-                    sandbox_provider: Some(sandbox_provider.clone()),
+                    sandbox_providers: Some(sandbox_providers.clone()),
                     agent_repo: Some(agent_repo.clone()),
                     session_bridge: Some(session_bridge.clone()),
+                    working_dir_policy: working_dir_policy.clone(),
+                    run_preview,
                 };
 
                 match runner
-                    .execute(&flow, &*flow_repo, Some(context))
+                    .execute(&flow, &*flow_repo, Some(context), RunTrigger::GithubPr)
                     .await
                 {
                     Ok(run) => {
@@ -669,6 +967,21 @@ async fn github_pr_loop(
 
 // ── Helpers ──────────────────────────────────────────────────────
 
+fn wants_pr_event(trigger_config: &serde_json::Value, repo_slug: &str, draft: bool, new_commits: bool) -> bool {
+    let skip_drafts = trigger_config["skip_drafts"].as_bool().unwrap_or(true);
+    let review_on_push = trigger_config["review_on_push"].as_bool().unwrap_or(false);
+    !(draft && skip_drafts)
+        && (!new_commits || review_on_push)
+        && configured_repo(trigger_config, repo_slug).is_some()
+}
+
+/// The trigger's repo entry for `repo_slug`, matched case-insensitively.
+fn configured_repo(trigger_config: &serde_json::Value, repo_slug: &str) -> Option<RepoConfig> {
+    parse_repo_configs(trigger_config)
+        .into_iter()
+        .find(|r| r.full_name().eq_ignore_ascii_case(repo_slug))
+}
+
 fn parse_repo_configs(trigger_config: &serde_json::Value) -> Vec<RepoConfig> {
     trigger_config["repos"]
         .as_array()
@@ -715,6 +1028,7 @@ mod tests {
     #[allow(dead_code)]
     struct MockGithubClient {
         prs: StdMutex<Vec<PullRequest>>,
+        prs_fetched: StdMutex<u32>,
         comments_posted: StdMutex<Vec<(String, u64, String)>>,
         diff: String,
     }
@@ -724,6 +1038,7 @@ mod tests {
         fn new(prs: Vec<PullRequest>, diff: &str) -> Self {
             Self {
                 prs: StdMutex::new(prs),
+                prs_fetched: StdMutex::new(0),
                 comments_posted: StdMutex::new(Vec::new()),
                 diff: diff.to_string(),
             }
@@ -733,6 +1048,7 @@ mod tests {
     #[async_trait::async_trait]
     impl GithubClient for MockGithubClient {
         async fn fetch_open_prs(&self, _owner: &str, _repo: &str) -> anyhow::Result<Vec<PullRequest>> {
+            *self.prs_fetched.lock().unwrap() += 1;
             Ok(self.prs.lock().unwrap().clone())
         }
         async fn fetch_single_pr(&self, _owner: &str, _repo: &str, _pr: u64) -> anyhow::Result<PullRequest> {
@@ -751,6 +1067,70 @@ mod tests {
         }
     }
 
+    fn fast_policy(max_restarts: u32) -> RestartPolicy {
+        RestartPolicy {
+            max_restarts,
+            base_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_supervise_restarts_dead_trigger() {
+        let starts = Arc::new(StdMutex::new(0u32));
+        let counts: RestartCounts = Arc::new(StdMutex::new(HashMap::new()));
+
+        let s = starts.clone();
+        supervise(
+            "f1".to_string(),
+            "Flow".to_string(),
+            counts.clone(),
+            fast_policy(3),
+            move || {
+                *s.lock().unwrap() += 1;
+                // Trigger dies right away.
+                async {}
+            },
+            || async { true },
+        )
+        .await;
+
+        // Initial start plus the bounded number of restarts.
+        assert_eq!(*starts.lock().unwrap(), 4);
+        assert_eq!(counts.lock().unwrap().get("f1"), Some(&3));
+    }
+
+    #[tokio::test]
+    async fn test_supervise_does_not_restart_disabled_flow() {
+        let starts = Arc::new(StdMutex::new(0u32));
+        let counts: RestartCounts = Arc::new(StdMutex::new(HashMap::new()));
+
+        let s = starts.clone();
+        supervise(
+            "f1".to_string(),
+            "Flow".to_string(),
+            counts.clone(),
+            fast_policy(3),
+            move || {
+                *s.lock().unwrap() += 1;
+                async { panic!("trigger blew up") }
+            },
+            || async { false },
+        )
+        .await;
+
+        assert_eq!(*starts.lock().unwrap(), 1);
+        assert!(counts.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restart_backoff_is_capped() {
+        let policy = RestartPolicy::DEFAULT;
+        assert_eq!(policy.backoff(0), Duration::from_secs(5));
+        assert_eq!(policy.backoff(2), Duration::from_secs(20));
+        assert_eq!(policy.backoff(10), Duration::from_secs(300));
+    }
+
     fn make_pr(number: u64, title: &str) -> PullRequest {
         PullRequest {
             number,
@@ -823,6 +1203,33 @@ mod tests {
         assert!(draft.draft);
     }
 
+    #[test]
+    fn test_wants_pr_event() {
+        let config = serde_json::json!({ "repos": [{ "slug": "acme/widgets", "path": "." }] });
+        assert!(wants_pr_event(&config, "acme/widgets", false, false));
+        assert!(wants_pr_event(&config, "Acme/Widgets", false, false));
+        assert!(!wants_pr_event(&config, "acme/other", false, false));
+        // Drafts are skipped and pushes ignored unless configured otherwise
+        assert!(!wants_pr_event(&config, "acme/widgets", true, false));
+        assert!(!wants_pr_event(&config, "acme/widgets", false, true));
+
+        let eager = serde_json::json!({
+            "repos": [{ "slug": "acme/widgets" }],
+            "skip_drafts": false,
+            "review_on_push": true,
+        });
+        assert!(wants_pr_event(&eager, "acme/widgets", true, true));
+    }
+
+    #[test]
+    fn test_configured_repo_keeps_trigger_spelling() {
+        let config = serde_json::json!({ "repos": [{ "slug": "Acme/Widgets", "path": "/src/widgets" }] });
+        let repo = configured_repo(&config, "acme/widgets").unwrap();
+        assert_eq!(repo.full_name(), "Acme/Widgets");
+        assert_eq!(repo.local_path, PathBuf::from("/src/widgets"));
+        assert!(configured_repo(&config, "acme/other").is_none());
+    }
+
     #[test]
     fn test_make_pr_with_sha() {
         let pr = make_pr_with_sha(5, "Test", "custom-sha-123");
@@ -891,4 +1298,166 @@ mod tests {
         }
         assert_eq!(seen["owner/repo"][&42], "abc123def456");
     }
+
+    #[tokio::test]
+    async fn test_skip_over_cap_disables_flow_when_configured() {
+        use crate::flows::file_repository::FileFlowRepository;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        let now = Utc::now();
+        let cap = CostCap { max_usd: 5.0, window_hours: 24, disable_when_exceeded: true };
+        let flow = Flow {
+            id: "f1".to_string(),
+            name: "Capped".to_string(),
+            description: String::new(),
+            enabled: true,
+            nodes: vec![],
+            edges: vec![],
+            version: 0,
+            max_runtime_secs: None,
+            capture_prompts: true,
+            sandbox_backend: None,
+            cost_cap: Some(cap.clone()),
+            default_session_budget_usd: None,
+            output_transform: vec![],
+            created_at: now,
+            updated_at: now,
+        };
+        repo.save_flow(flow.clone()).await.unwrap();
+        let (events_tx, mut events_rx) = broadcast::channel(4);
+        let (changes_tx, mut changes_rx) = broadcast::channel(4);
+
+        assert!(skip_over_cap(&flow, &cap, 6.0, &repo, &events_tx, &changes_tx).await);
+        assert!(!repo.get_flow("f1").await.unwrap().enabled);
+        let event = events_rx.try_recv().unwrap();
+        assert!(matches!(event.event_type, RunEventType::CostCapExceeded));
+        assert!(event.message.contains("flow disabled"));
+        let change = changes_rx.try_recv().unwrap();
+        assert_eq!(change.resource_id, "f1");
+        assert_eq!(change.change_type, ChangeType::Updated);
+
+        // Without the flag the fire is just skipped
+        let cap = CostCap { disable_when_exceeded: false, ..cap };
+        repo.save_flow(flow.clone()).await.unwrap();
+        assert!(!skip_over_cap(&flow, &cap, 6.0, &repo, &events_tx, &changes_tx).await);
+        assert!(repo.get_flow("f1").await.unwrap().enabled);
+        assert!(changes_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_github_pr_loop_skips_polls_over_cost_cap() {
+        use crate::agents::file_repository::FileAgentRepository;
+        use crate::flows::file_repository::FileFlowRepository;
+        use crate::flows::history::{FlowRun, NodeRun, RunStatus};
+        use crate::flows::{Node, Position};
+        use crate::sandbox::backends::dangerous::DangerousHostProvider;
+        use crate::sandbox::DangerousConfig;
+
+        let dir = tempfile::tempdir().unwrap();
+        let flow_repo = Arc::new(FileFlowRepository::new(dir.path().join("flows")));
+        let now = Utc::now();
+        let trigger_config = serde_json::json!({
+            "repos": [{ "slug": "owner/repo", "path": "." }],
+            "poll_interval": 1,
+        });
+        let flow = Flow {
+            id: "f1".to_string(),
+            name: "Capped".to_string(),
+            description: String::new(),
+            enabled: true,
+            nodes: vec![Node {
+                id: "t1".to_string(),
+                node_type: NodeType::Trigger,
+                kind: "github-pr".to_string(),
+                config: trigger_config.clone(),
+                position: Position { x: 0.0, y: 0.0 },
+                label: "PRs".to_string(),
+            }],
+            edges: vec![],
+            version: 0,
+            max_runtime_secs: None,
+            capture_prompts: true,
+            sandbox_backend: None,
+            cost_cap: Some(CostCap { max_usd: 5.0, window_hours: 24, disable_when_exceeded: true }),
+            default_session_budget_usd: None,
+            output_transform: vec![],
+            created_at: now,
+            updated_at: now,
+        };
+        flow_repo.save_flow(flow).await.unwrap();
+        flow_repo
+            .add_run(FlowRun {
+                id: "r1".to_string(),
+                flow_id: "f1".to_string(),
+                status: RunStatus::Success,
+                started_at: now,
+                finished_at: Some(now),
+                node_runs: vec![NodeRun {
+                    node_id: "e1".to_string(),
+                    status: RunStatus::Success,
+                    started_at: now,
+                    finished_at: Some(now),
+                    output_preview: None,
+                    output: None,
+                    prompt: None,
+                    checkpoint: None,
+                    cost_usd: Some(6.0),
+                }],
+                error: None,
+                trigger: RunTrigger::GithubPr,
+                tags: vec![],
+                note: None,
+                resumed_from: None,
+            })
+            .await
+            .unwrap();
+
+        let github = Arc::new(MockGithubClient::new(vec![make_pr(1, "Add feature")], "diff"));
+        let (events_tx, mut events_rx) = broadcast::channel(16);
+        let (changes_tx, _changes_rx) = broadcast::channel(16);
+        let sandbox = DangerousHostProvider::new(DangerousConfig {
+            root_dir: dir.path().join("sandboxes"),
+            ..DangerousConfig::default()
+        })
+        .unwrap();
+        let session_bridge = SessionBridge {
+            sessions: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            sessions_path: dir.path().join("sessions.yaml"),
+            data_dir: dir.path().to_path_buf(),
+            session_streams: Arc::new(Mutex::new(HashMap::new())),
+            dir_locks: WorkingDirLocks::default(),
+        };
+
+        // The first poll is skipped and disables the flow; the next one
+        // sees it disabled and stops the loop
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            github_pr_loop(
+                "f1",
+                "Capped",
+                trigger_config,
+                flow_repo.clone(),
+                Arc::new(reqwest::Client::new()),
+                github.clone(),
+                Arc::new(Mutex::new(HashMap::new())),
+                events_tx,
+                changes_tx,
+                SandboxProviders::new(Arc::new(sandbox)),
+                Arc::new(FileAgentRepository::new(dir.path().join("agents"))),
+                session_bridge,
+                WorkingDirPolicy::default(),
+                RunPreviewConfig::default(),
+            ),
+        )
+        .await
+        .expect("PR loop should stop once the flow is disabled");
+
+        // Only the seed fetched PRs
+        assert_eq!(*github.prs_fetched.lock().unwrap(), 1);
+        assert!(github.comments_posted.lock().unwrap().is_empty());
+        assert!(!flow_repo.get_flow("f1").await.unwrap().enabled);
+        let event = events_rx.try_recv().unwrap();
+        assert!(matches!(event.event_type, RunEventType::CostCapExceeded));
+    }
 }
@@ -5,6 +5,7 @@ use std::sync::Arc;
 use tokio::sync::{broadcast, Mutex, RwLock};
 
 use crate::api::FlowSessions;
+use crate::flows::dir_lock::WorkingDirLocks;
 
 /// Metadata linking a session to a flow run.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,4 +31,6 @@ pub struct SessionBridge {
     /// Live broadcast channels for flow-run session streaming.
     /// Key: session_id, Value: sender that broadcasts JSONL lines.
     pub session_streams: Arc<Mutex<HashMap<String, broadcast::Sender<String>>>>,
+    /// Working-dir locks shared with interactive chat turns.
+    pub dir_locks: WorkingDirLocks,
 }
@@ -0,0 +1,152 @@
+//! Server shutdown: let in-flight runs finish, then close out the rest so
+//! they don't stay `Running` forever.
+
+use std::time::Duration;
+
+use super::history::{RunStatus, MAX_RUNS_PER_FLOW};
+use super::repository::FlowRepository;
+
+/// Error recorded on runs (and their running nodes) cut off by shutdown.
+pub const SHUTDOWN_ERROR: &str = "server shutdown";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// `(flow_id, run_id)` of every run still marked running.
+pub async fn running_runs(repo: &dyn FlowRepository) -> Vec<(String, String)> {
+    let mut running = Vec::new();
+    for flow in repo.list_flows().await {
+        for run in repo.get_runs(&flow.id, MAX_RUNS_PER_FLOW).await {
+            if run.status == RunStatus::Running {
+                running.push((flow.id.clone(), run.id));
+            }
+        }
+    }
+    running
+}
+
+/// Wait until no run is running, or until `grace` elapses.
+/// Returns true if every run finished in time.
+pub async fn wait_for_runs(repo: &dyn FlowRepository, grace: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + grace;
+    loop {
+        if running_runs(repo).await.is_empty() {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Mark every running run, and its running nodes, failed with
+/// `SHUTDOWN_ERROR`. Returns how many runs were marked.
+pub async fn fail_running_runs(repo: &dyn FlowRepository) -> usize {
+    let running = running_runs(repo).await;
+    for (flow_id, run_id) in &running {
+        let nodes = repo
+            .get_run(flow_id, run_id)
+            .await
+            .map(|run| run.node_runs)
+            .unwrap_or_default();
+        for node in nodes.iter().filter(|n| n.status == RunStatus::Running) {
+            if let Err(e) = repo
+                .complete_node_run(flow_id, run_id, &node.node_id, RunStatus::Failed, Some(SHUTDOWN_ERROR.to_string()))
+                .await
+            {
+                tracing::warn!(flow_id = %flow_id, run_id = %run_id, error = %e, "failed to close node run on shutdown");
+            }
+        }
+        if let Err(e) = repo
+            .complete_run(flow_id, run_id, RunStatus::Failed, Some(SHUTDOWN_ERROR.to_string()))
+            .await
+        {
+            tracing::warn!(flow_id = %flow_id, run_id = %run_id, error = %e, "failed to close run on shutdown");
+        }
+    }
+    running.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flows::file_repository::FileFlowRepository;
+    use crate::flows::history::{FlowRun, NodeRun, RunTrigger};
+    use crate::flows::Flow;
+    use chrono::Utc;
+    use tempfile::tempdir;
+
+    fn flow(id: &str) -> Flow {
+        Flow {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            enabled: true,
+            nodes: vec![],
+            edges: vec![],
+            version: 0,
+            max_runtime_secs: None,
+            capture_prompts: true,
+            sandbox_backend: None,
+            cost_cap: None,
+            default_session_budget_usd: None,
+            output_transform: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn run(flow_id: &str, run_id: &str) -> FlowRun {
+        FlowRun {
+            id: run_id.to_string(),
+            flow_id: flow_id.to_string(),
+            status: RunStatus::Running,
+            started_at: Utc::now(),
+            finished_at: None,
+            node_runs: vec![],
+            error: None,
+            trigger: RunTrigger::Manual,
+            tags: vec![],
+            note: None,
+            resumed_from: None,
+        }
+    }
+
+    fn node_run(node_id: &str) -> NodeRun {
+        NodeRun {
+            node_id: node_id.to_string(),
+            status: RunStatus::Running,
+            started_at: Utc::now(),
+            finished_at: None,
+            output_preview: None,
+            output: None,
+            prompt: None,
+            checkpoint: None,
+            cost_usd: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fail_running_runs_closes_runs_and_nodes() {
+        let dir = tempdir().unwrap();
+        let repo = FileFlowRepository::new(dir.path().to_path_buf());
+        repo.load_all().await.unwrap();
+        repo.save_flow(flow("f1")).await.unwrap();
+
+        repo.add_run(run("f1", "done")).await.unwrap();
+        repo.complete_run("f1", "done", RunStatus::Success, None).await.unwrap();
+        repo.add_run(run("f1", "stuck")).await.unwrap();
+        repo.push_node_run("f1", "stuck", node_run("e1")).await.unwrap();
+
+        assert!(!wait_for_runs(&repo, Duration::ZERO).await);
+        assert_eq!(fail_running_runs(&repo).await, 1);
+        assert!(wait_for_runs(&repo, Duration::ZERO).await);
+
+        let stuck = repo.get_run("f1", "stuck").await.unwrap();
+        assert_eq!(stuck.status, RunStatus::Failed);
+        assert_eq!(stuck.error.as_deref(), Some(SHUTDOWN_ERROR));
+        assert!(stuck.finished_at.is_some());
+        assert_eq!(stuck.node_runs[0].status, RunStatus::Failed);
+        assert_eq!(repo.get_run("f1", "done").await.unwrap().status, RunStatus::Success);
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::flows::{Flow, Node, NodeType};
+
+/// Normalize a webhook path so `/github/issues`, `github/issues/` and
+/// `github/issues` all match.
+pub fn normalize_path(path: &str) -> &str {
+    path.trim_matches('/')
+}
+
+/// The flow's webhook trigger node listening on `path`, if any.
+pub fn find_trigger<'a>(flow: &'a Flow, path: &str) -> Option<&'a Node> {
+    flow.nodes.iter().find(|n| {
+        n.node_type == NodeType::Trigger
+            && n.kind == "webhook"
+            && n.config["path"]
+                .as_str()
+                .is_some_and(|p| normalize_path(p) == normalize_path(path))
+    })
+}
+
+/// Header carrying the shared secret for triggers with a `secret` config.
+pub const SECRET_HEADER: &str = "X-Webhook-Secret";
+
+/// Whether a delivery may fire `trigger`: true when the trigger has no
+/// (or an empty) `secret`, else `provided` must equal it.
+pub fn secret_matches(config: &Value, provided: Option<&str>) -> bool {
+    match config["secret"].as_str().filter(|s| !s.is_empty()) {
+        None => true,
+        Some(secret) => provided.is_some_and(|p| constant_time_eq(p.as_bytes(), secret.as_bytes())),
+    }
+}
+
+/// Compare without returning early on the first differing byte. Only the
+/// length leaks.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Evaluate a JSONPath expression against `root`.
+///
+/// Supports the subset senders' payloads actually need: `$`, `.key`,
+/// `['key']` / `["key"]` and `[index]`, e.g. `$.issue.labels[0].name`.
+/// Returns `None` if the path is malformed or doesn't resolve.
+pub fn json_path<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
+    let rest = path.trim().strip_prefix('$')?;
+    let mut current = root;
+    let mut chars = rest.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let mut key = String::new();
+                while let Some(&next) = chars.peek() {
+                    if next == '.' || next == '[' {
+                        break;
+                    }
+                    key.push(next);
+                    chars.next();
+                }
+                if key.is_empty() {
+                    return None;
+                }
+                current = current.get(key.as_str())?;
+            }
+            '[' => {
+                let mut inner = String::new();
+                for next in chars.by_ref() {
+                    if next == ']' {
+                        break;
+                    }
+                    inner.push(next);
+                }
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+                current = match quoted {
+                    Some(key) => current.get(key)?,
+                    None => current.get(inner.trim().parse::<usize>().ok()?)?,
+                };
+            }
+            _ => return None,
+        }
+    }
+
+    Some(current)
+}
+
+/// Build the run context from a trigger's `var_map` (`{ "var": "$.json.path" }`).
+///
+/// Strings are used as-is; other values are rendered as JSON. Paths that
+/// don't resolve render as empty strings and are returned so the caller
+/// can warn about them.
+pub fn build_context(var_map: &Value, body: &Value) -> (HashMap<String, String>, Vec<String>) {
+    let mut context = HashMap::new();
+    let mut unresolved = Vec::new();
+
+    let Some(map) = var_map.as_object() else {
+        return (context, unresolved);
+    };
+
+    for (var, path) in map {
+        let resolved = path.as_str().and_then(|p| json_path(body, p));
+        let rendered = match resolved {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Null) | None => {
+                unresolved.push(var.clone());
+                String::new()
+            }
+            Some(other) => other.to_string(),
+        };
+        context.insert(var.clone(), rendered);
+    }
+
+    (context, unresolved)
+}
+
+/// How long a delivery's idempotency key is remembered by default.
+const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A trigger's idempotency key for this delivery: the `idempotency_header`
+/// value if configured (looked up via `header`), else the string or number at
+/// `idempotency_path` in the body. `None` when neither is configured or set.
+pub fn idempotency_key<'h>(
+    config: &Value,
+    header: impl Fn(&str) -> Option<&'h str>,
+    body: &Value,
+) -> Option<String> {
+    if let Some(name) = config["idempotency_header"].as_str().filter(|h| !h.is_empty()) {
+        return header(name).map(str::trim).filter(|k| !k.is_empty()).map(String::from);
+    }
+    let path = config["idempotency_path"].as_str().filter(|p| !p.is_empty())?;
+    match json_path(body, path)? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// How long a trigger remembers keys (`idempotency_ttl_secs`, default 24h).
+pub fn idempotency_ttl(config: &Value) -> Duration {
+    config["idempotency_ttl_secs"]
+        .as_u64()
+        .filter(|s| *s > 0)
+        .map_or(DEFAULT_IDEMPOTENCY_TTL, Duration::from_secs)
+}
+
+/// Idempotency keys seen recently, per trigger, so retried deliveries from
+/// at-least-once senders don't start a second run. In memory only.
+#[derive(Clone, Default)]
+pub struct SeenDeliveries {
+    seen: Arc<Mutex<HashMap<(String, String), Instant>>>,
+}
+
+impl SeenDeliveries {
+    /// Record `key` for `trigger` and return whether it's a first delivery,
+    /// i.e. not already seen within `ttl`.
+    pub fn first_delivery(&self, trigger: &str, key: &str, ttl: Duration, now: Instant) -> bool {
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        seen.retain(|_, expires| *expires > now);
+        let entry = (trigger.to_string(), key.to_string());
+        if seen.contains_key(&entry) {
+            return false;
+        }
+        seen.insert(entry, now + ttl);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn github_issue_payload() -> Value {
+        json!({
+            "action": "opened",
+            "issue": {
+                "title": "Crash on startup",
+                "number": 42,
+                "labels": [{ "name": "bug" }, { "name": "p1" }],
+                "user": { "login": "octocat" }
+            },
+            "repository": { "full_name": "acme/widgets" }
+        })
+    }
+
+    #[test]
+    fn test_json_path_nested_extraction() {
+        let body = github_issue_payload();
+        assert_eq!(json_path(&body, "$.issue.title"), Some(&json!("Crash on startup")));
+        assert_eq!(json_path(&body, "$.issue.labels[1].name"), Some(&json!("p1")));
+        assert_eq!(json_path(&body, "$['repository']['full_name']"), Some(&json!("acme/widgets")));
+        assert_eq!(json_path(&body, "$"), Some(&body));
+    }
+
+    #[test]
+    fn test_json_path_missing_or_malformed() {
+        let body = github_issue_payload();
+        assert_eq!(json_path(&body, "$.issue.assignee"), None);
+        assert_eq!(json_path(&body, "$.issue.labels[5]"), None);
+        assert_eq!(json_path(&body, "issue.title"), None);
+        assert_eq!(json_path(&body, "$..title"), None);
+    }
+
+    #[test]
+    fn test_build_context_maps_vars() {
+        let body = github_issue_payload();
+        let var_map = json!({
+            "issue_title": "$.issue.title",
+            "issue_number": "$.issue.number",
+            "author": "$.issue.user.login",
+        });
+        let (context, unresolved) = build_context(&var_map, &body);
+        assert_eq!(context["issue_title"], "Crash on startup");
+        assert_eq!(context["issue_number"], "42");
+        assert_eq!(context["author"], "octocat");
+        assert!(unresolved.is_empty());
+    }
+
+    #[test]
+    fn test_build_context_missing_paths_render_empty() {
+        let body = github_issue_payload();
+        let var_map = json!({
+            "title": "$.issue.title",
+            "amount": "$.data.object.amount",
+        });
+        let (context, unresolved) = build_context(&var_map, &body);
+        assert_eq!(context["title"], "Crash on startup");
+        assert_eq!(context["amount"], "");
+        assert_eq!(unresolved, vec!["amount".to_string()]);
+    }
+
+    #[test]
+    fn test_idempotency_key_from_header_or_body() {
+        let body = json!({ "id": "evt_123", "seq": 7 });
+        let headers = HashMap::from([("x-delivery-id", " abc ")]);
+        let header = |name: &str| headers.get(name.to_ascii_lowercase().as_str()).copied();
+
+        let by_header = json!({ "idempotency_header": "X-Delivery-Id", "idempotency_path": "$.id" });
+        assert_eq!(idempotency_key(&by_header, header, &body).as_deref(), Some("abc"));
+        assert_eq!(idempotency_key(&json!({ "idempotency_path": "$.id" }), header, &body).as_deref(), Some("evt_123"));
+        assert_eq!(idempotency_key(&json!({ "idempotency_path": "$.seq" }), header, &body).as_deref(), Some("7"));
+        assert_eq!(idempotency_key(&json!({ "idempotency_path": "$.nope" }), header, &body), None);
+        assert_eq!(idempotency_key(&json!({}), header, &body), None);
+    }
+
+    #[test]
+    fn test_repeated_key_within_ttl_is_a_duplicate() {
+        let seen = SeenDeliveries::default();
+        let ttl = Duration::from_secs(60);
+        let now = Instant::now();
+
+        assert!(seen.first_delivery("f1:t1", "evt_1", ttl, now));
+        assert!(!seen.first_delivery("f1:t1", "evt_1", ttl, now + Duration::from_secs(30)));
+        // Other keys and other triggers are independent
+        assert!(seen.first_delivery("f1:t1", "evt_2", ttl, now));
+        assert!(seen.first_delivery("f2:t1", "evt_1", ttl, now));
+        // Once the TTL lapses the key counts as new again
+        assert!(seen.first_delivery("f1:t1", "evt_1", ttl, now + Duration::from_secs(61)));
+    }
+
+    #[test]
+    fn test_secret_matches() {
+        assert!(secret_matches(&json!({ "path": "x" }), None));
+        assert!(secret_matches(&json!({ "secret": "" }), None));
+
+        let config = json!({ "secret": "s3cret" });
+        assert!(secret_matches(&config, Some("s3cret")));
+        assert!(!secret_matches(&config, Some("s3creT")));
+        assert!(!secret_matches(&config, Some("s3cret-and-more")));
+        assert!(!secret_matches(&config, None));
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path("/github/issues/"), "github/issues");
+        assert_eq!(normalize_path("stripe"), "stripe");
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use ring::hmac;
+use serde::Deserialize;
+
+use crate::flows::webhook::constant_time_eq;
+
+/// Env var holding the secret GitHub signs webhook deliveries with.
+pub const WEBHOOK_SECRET_ENV: &str = "GITHUB_WEBHOOK_SECRET";
+
+pub const SIGNATURE_HEADER: &str = "X-Hub-Signature-256";
+pub const EVENT_HEADER: &str = "X-GitHub-Event";
+
+/// Whether `signature` (`sha256=<hex>`, as sent in `X-Hub-Signature-256`) is
+/// the HMAC-SHA256 of `body` under `secret`.
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(provided) = signature.trim().strip_prefix("sha256=") else {
+        return false;
+    };
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let expected: String = hmac::sign(&key, body)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    constant_time_eq(provided.to_ascii_lowercase().as_bytes(), expected.as_bytes())
+}
+
+/// A pull request that was opened or received new commits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrPushed {
+    pub repo: String,
+    pub number: u64,
+    pub draft: bool,
+    /// `synchronize`: commits pushed to an already-open PR.
+    pub new_commits: bool,
+}
+
+#[derive(Deserialize)]
+struct PullRequestEvent {
+    action: String,
+    pull_request: EventPr,
+    repository: EventRepo,
+}
+
+#[derive(Deserialize)]
+struct EventPr {
+    number: u64,
+    #[serde(default)]
+    draft: bool,
+}
+
+#[derive(Deserialize)]
+struct EventRepo {
+    full_name: String,
+}
+
+/// Parse a delivery of event type `event`. `None` for anything other than a
+/// `pull_request` opened/synchronize event.
+pub fn parse_event(event: &str, body: &[u8]) -> Result<Option<PrPushed>> {
+    if event != "pull_request" {
+        return Ok(None);
+    }
+    let parsed: PullRequestEvent =
+        serde_json::from_slice(body).context("invalid pull_request event payload")?;
+    if !matches!(parsed.action.as_str(), "opened" | "synchronize") {
+        return Ok(None);
+    }
+    Ok(Some(PrPushed {
+        repo: parsed.repository.full_name,
+        number: parsed.pull_request.number,
+        draft: parsed.pull_request.draft,
+        new_commits: parsed.action == "synchronize",
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_signature() {
+        // Example from GitHub's "Validating webhook deliveries" docs
+        let secret = "It's a Secret to Everybody";
+        let body = b"Hello, World!";
+        let signature = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+        assert!(verify_signature(secret, body, signature));
+        assert!(!verify_signature(secret, b"Hello, World?", signature));
+        assert!(!verify_signature("other", body, signature));
+        assert!(!verify_signature(secret, body, signature.trim_start_matches("sha256=")));
+    }
+
+    #[test]
+    fn test_parse_event() {
+        let body = |action: &str| {
+            format!(
+                r#"{{"action": "{action}", "pull_request": {{"number": 7, "draft": false}}, "repository": {{"full_name": "acme/widgets"}}}}"#
+            )
+        };
+        assert_eq!(
+            parse_event("pull_request", body("synchronize").as_bytes()).unwrap(),
+            Some(PrPushed { repo: "acme/widgets".into(), number: 7, draft: false, new_commits: true })
+        );
+        assert_eq!(parse_event("pull_request", body("closed").as_bytes()).unwrap(), None);
+        assert_eq!(parse_event("push", b"{}").unwrap(), None);
+        assert!(parse_event("pull_request", b"{}").is_err());
+    }
+}
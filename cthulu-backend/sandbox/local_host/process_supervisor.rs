@@ -64,6 +64,8 @@ impl ProcessSupervisor {
         cmd.stdin(if req.stdin.is_some() { Stdio::piped() } else { Stdio::null() });
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
+        // A caller that stops waiting (e.g. a cancelled flow run) shouldn't orphan the process
+        cmd.kill_on_drop(!req.detach);
 
         let mut child = cmd.spawn().map_err(|e| SandboxError::Exec(format!("spawn failed: {e}")))?;
 
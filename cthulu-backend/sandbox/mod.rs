@@ -12,7 +12,7 @@ mod sprite;
 
 pub use error::SandboxError;
 pub use handle::{ExecStream, SandboxHandle};
-pub use provider::SandboxProvider;
+pub use provider::{SandboxProvider, SandboxProviders};
 pub use types::*;
 
 use backends::dangerous::DangerousHostProvider;
@@ -1,3 +1,6 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
 use async_trait::async_trait;
 
 use super::error::SandboxError;
@@ -22,3 +25,42 @@ pub trait SandboxProvider: Send + Sync {
     /// List active/known sandboxes for this provider.
     async fn list(&self) -> Result<Vec<SandboxSummary>, SandboxError>;
 }
+
+/// The configured providers, keyed by backend name (see
+/// [`SandboxBackendKind::name`](super::types::SandboxBackendKind::name)),
+/// plus the default used when a flow doesn't pick one.
+#[derive(Clone)]
+pub struct SandboxProviders {
+    default: Arc<dyn SandboxProvider>,
+    by_name: BTreeMap<&'static str, Arc<dyn SandboxProvider>>,
+}
+
+impl SandboxProviders {
+    pub fn new(default: Arc<dyn SandboxProvider>) -> Self {
+        let by_name = BTreeMap::from([(default.info().kind.name(), default.clone())]);
+        Self { default, by_name }
+    }
+
+    /// Also offer `provider`, without changing the default.
+    pub fn with(mut self, provider: Arc<dyn SandboxProvider>) -> Self {
+        self.by_name.entry(provider.info().kind.name()).or_insert(provider);
+        self
+    }
+
+    pub fn default_provider(&self) -> &Arc<dyn SandboxProvider> {
+        &self.default
+    }
+
+    /// The provider for `name`, or the default when `None`. `None` back
+    /// means no provider by that name is configured.
+    pub fn get(&self, name: Option<&str>) -> Option<&Arc<dyn SandboxProvider>> {
+        match name {
+            Some(name) => self.by_name.get(name),
+            None => Some(&self.default),
+        }
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.by_name.keys().copied().collect()
+    }
+}
@@ -16,6 +16,17 @@ pub enum SandboxBackendKind {
     FlySprite,
 }
 
+impl SandboxBackendKind {
+    /// Name flows use to pick this backend (`sandbox_backend`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DangerousHost => "dangerous",
+            Self::Firecracker => "firecracker",
+            Self::FlySprite => "sprite",
+        }
+    }
+}
+
 // ── Provider info ───────────────────────────────────────────────────
 
 #[derive(Debug, Clone)]
@@ -293,6 +304,10 @@ pub struct DangerousConfig {
     pub read_only_host_mounts: Vec<PathBuf>,
     pub enable_rlimits: bool,
     pub max_output_bytes: usize,
+    /// Programs that may be executed. Empty = no restriction.
+    pub command_allowlist: Vec<String>,
+    /// Programs that may never be executed, checked after the allowlist.
+    pub command_denylist: Vec<String>,
 }
 
 impl Default for DangerousConfig {
@@ -305,6 +320,8 @@ impl Default for DangerousConfig {
             read_only_host_mounts: vec![],
             enable_rlimits: false,
             max_output_bytes: 10 * 1024 * 1024, // 10 MB
+            command_allowlist: vec![],
+            command_denylist: vec![],
         }
     }
 }
@@ -454,6 +471,8 @@ mod tests {
         assert!(!dc.use_dedicated_user);
         assert!(!dc.enable_rlimits);
         assert_eq!(dc.max_output_bytes, 10 * 1024 * 1024);
+        assert!(dc.command_allowlist.is_empty());
+        assert!(dc.command_denylist.is_empty());
     }
 
     #[test]
@@ -0,0 +1,178 @@
+//! Encryption at rest for secret-typed config fields.
+//!
+//! Values are sealed with AES-256-GCM under a key derived from
+//! `CTHULU_SECRET_KEY` and stored as `enc:v1:<base64(nonce || ciphertext)>`.
+//! Plaintext values are still accepted on load and get encrypted the next
+//! time their owner is saved.
+
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::digest::{SHA256, digest};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_json::Value;
+
+/// Env var holding the secret-encryption passphrase.
+pub const SECRET_KEY_ENV: &str = "CTHULU_SECRET_KEY";
+
+/// Node config keys whose values are treated as secrets.
+pub const SECRET_CONFIG_KEYS: &[&str] = &["secret", "webhook_secret", "hmac_key", "signing_secret"];
+
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// Whether a stored value is already ciphertext.
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+pub struct SecretCipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl SecretCipher {
+    /// Derive the AEAD key from a passphrase (SHA-256).
+    pub fn new(passphrase: &str) -> Result<Self> {
+        if passphrase.trim().is_empty() {
+            bail!("{SECRET_KEY_ENV} must not be empty");
+        }
+        let key_bytes = digest(&SHA256, passphrase.as_bytes());
+        let key = UnboundKey::new(&AES_256_GCM, key_bytes.as_ref())
+            .map_err(|_| anyhow!("failed to initialise secret key"))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Cipher from `CTHULU_SECRET_KEY`, or `None` when it isn't set.
+    pub fn from_env() -> Result<Option<Self>> {
+        match std::env::var(SECRET_KEY_ENV) {
+            Ok(v) if !v.is_empty() => Self::new(&v).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| anyhow!("failed to generate nonce"))?;
+
+        let mut sealed = plaintext.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut sealed)
+            .map_err(|_| anyhow!("failed to encrypt secret"))?;
+
+        let mut blob = nonce.to_vec();
+        blob.extend_from_slice(&sealed);
+        Ok(format!(
+            "{ENCRYPTED_PREFIX}{}",
+            base64::engine::general_purpose::STANDARD.encode(blob)
+        ))
+    }
+
+    pub fn decrypt(&self, value: &str) -> Result<String> {
+        let encoded = value
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .context("value is not an encrypted secret")?;
+        let blob = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .context("encrypted secret is not valid base64")?;
+        if blob.len() < NONCE_LEN {
+            bail!("encrypted secret is truncated");
+        }
+        let (nonce, sealed) = blob.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| anyhow!("encrypted secret has a bad nonce"))?;
+
+        let mut buf = sealed.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut buf)
+            .map_err(|_| anyhow!("failed to decrypt secret (wrong {SECRET_KEY_ENV}?)"))?;
+        String::from_utf8(plaintext.to_vec()).context("decrypted secret is not UTF-8")
+    }
+}
+
+/// Non-empty string values under secret keys in a node config object.
+fn secret_values(config: &mut Value) -> impl Iterator<Item = &mut String> {
+    config
+        .as_object_mut()
+        .into_iter()
+        .flat_map(|map| map.iter_mut())
+        .filter(|(k, _)| SECRET_CONFIG_KEYS.contains(&k.as_str()))
+        .filter_map(|(_, v)| match v {
+            Value::String(s) if !s.is_empty() => Some(s),
+            _ => None,
+        })
+}
+
+/// Whether a node config carries any secret-typed value (plaintext or encrypted).
+pub fn has_secrets(config: &Value) -> bool {
+    config.as_object().is_some_and(|map| {
+        map.iter().any(|(k, v)| {
+            SECRET_CONFIG_KEYS.contains(&k.as_str()) && v.as_str().is_some_and(|s| !s.is_empty())
+        })
+    })
+}
+
+/// Encrypt every plaintext secret in a node config.
+pub fn seal_config(config: &mut Value, cipher: &SecretCipher) -> Result<()> {
+    for value in secret_values(config) {
+        if !is_encrypted(value) {
+            *value = cipher.encrypt(value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decrypt every encrypted secret in a node config; plaintext is left as-is.
+pub fn open_config(config: &mut Value, cipher: &SecretCipher) -> Result<()> {
+    for value in secret_values(config) {
+        if is_encrypted(value) {
+            *value = cipher.decrypt(value)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip() {
+        let cipher = SecretCipher::new("correct horse battery staple").unwrap();
+        let sealed = cipher.encrypt("whsec_123").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.contains("whsec_123"));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "whsec_123");
+        // Fresh nonce each time.
+        assert_ne!(sealed, cipher.encrypt("whsec_123").unwrap());
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let sealed = SecretCipher::new("key-a").unwrap().encrypt("s3cret").unwrap();
+        let err = SecretCipher::new("key-b").unwrap().decrypt(&sealed).unwrap_err();
+        assert!(err.to_string().contains("failed to decrypt"));
+    }
+
+    #[test]
+    fn test_seal_and_open_config() {
+        let cipher = SecretCipher::new("k").unwrap();
+        let mut config = json!({ "path": "github", "secret": "whsec_123", "hmac_key": "" });
+        assert!(has_secrets(&config));
+
+        seal_config(&mut config, &cipher).unwrap();
+        assert_eq!(config["path"], "github");
+        assert!(is_encrypted(config["secret"].as_str().unwrap()));
+        assert_eq!(config["hmac_key"], "");
+
+        open_config(&mut config, &cipher).unwrap();
+        assert_eq!(config["secret"], "whsec_123");
+        assert!(!has_secrets(&json!({ "path": "github" })));
+    }
+}
@@ -1,24 +1,81 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 use tokio::time::timeout;
 
-use super::{ExecutionResult, Executor, LineSink};
+use super::json_lines::{JsonLineAssembler, ParsedLine};
+use super::{ExecutionResult, Executor, LineSink, MAX_TURNS_SUBTYPE};
+use crate::agents::{
+    add_dir_args, max_turns_args, model_args, permission_args, PermissionMode,
+    DEFAULT_EXECUTOR_TIMEOUT_SECS,
+};
 
-const PROCESS_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+/// Trailing stderr lines kept for the error when claude fails.
+const STDERR_TAIL_LINES: usize = 20;
 
 pub struct ClaudeCodeExecutor {
     permissions: Vec<String>,
     append_system_prompt: Option<String>,
+    permission_mode: Option<PermissionMode>,
+    disallowed_tools: Vec<String>,
+    additional_dirs: Vec<PathBuf>,
+    model: Option<String>,
+    max_turns: Option<u32>,
+    timeout: Duration,
 }
 
 impl ClaudeCodeExecutor {
     pub fn new(permissions: Vec<String>, append_system_prompt: Option<String>) -> Self {
-        Self { permissions, append_system_prompt }
+        Self {
+            permissions,
+            append_system_prompt,
+            permission_mode: None,
+            disallowed_tools: Vec::new(),
+            additional_dirs: Vec::new(),
+            model: None,
+            max_turns: None,
+            timeout: Duration::from_secs(DEFAULT_EXECUTOR_TIMEOUT_SECS),
+        }
+    }
+
+    /// Use an explicit permission mode and deny-list instead of the
+    /// allowlist-or-bypass default.
+    pub fn with_permission_mode(
+        mut self,
+        mode: Option<PermissionMode>,
+        disallowed_tools: Vec<String>,
+    ) -> Self {
+        self.permission_mode = mode;
+        self.disallowed_tools = disallowed_tools;
+        self
+    }
+
+    /// Extra roots passed as `--add-dir`, already validated by the caller.
+    pub fn with_additional_dirs(mut self, dirs: Vec<PathBuf>) -> Self {
+        self.additional_dirs = dirs;
+        self
+    }
+
+    /// Model passed as `--model`; `None` uses claude's configured default.
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Turn cap passed as `--max-turns`; `None` leaves claude uncapped.
+    pub fn with_max_turns(mut self, max_turns: Option<u32>) -> Self {
+        self.max_turns = max_turns;
+        self
+    }
+
+    /// Wall-clock limit after which the claude process is killed.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
     }
 
     pub fn build_args(&self) -> Vec<String> {
@@ -34,12 +91,15 @@ impl ClaudeCodeExecutor {
             args.push(prompt.clone());
         }
 
-        if self.permissions.is_empty() {
-            args.push("--dangerously-skip-permissions".to_string());
-        } else {
-            args.push("--allowedTools".to_string());
-            args.push(self.permissions.join(","));
-        }
+        args.extend(permission_args(
+            &self.permissions,
+            self.permission_mode,
+            &self.disallowed_tools,
+            true,
+        ));
+        args.extend(add_dir_args(&self.additional_dirs));
+        args.extend(model_args(self.model.as_deref()));
+        args.extend(max_turns_args(self.max_turns));
 
         args.push("-".to_string()); // read from stdin
         args
@@ -68,6 +128,8 @@ impl Executor for ClaudeCodeExecutor {
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            // Killed if the run is cancelled (e.g. flow max runtime exceeded)
+            .kill_on_drop(true)
             .spawn()
             .context("failed to spawn claude process")?;
 
@@ -81,16 +143,22 @@ impl Executor for ClaudeCodeExecutor {
             }
         }
 
-        // Stream stderr to tracing
+        // Stream stderr to tracing, keeping the tail for error reports
         let stderr = child.stderr.take().expect("stderr piped");
         let stderr_handle = tokio::spawn(async move {
             let reader = BufReader::new(stderr);
             let mut lines = reader.lines();
+            let mut tail = std::collections::VecDeque::with_capacity(STDERR_TAIL_LINES);
             while let Ok(Some(line)) = lines.next_line().await {
                 if !line.is_empty() {
                     tracing::debug!(source = "claude-stderr", "{}", line);
+                    if tail.len() == STDERR_TAIL_LINES {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line);
                 }
             }
+            Vec::from(tail).join("\n")
         });
 
         // Stream stdout JSON events to tracing, capture result
@@ -99,9 +167,11 @@ impl Executor for ClaudeCodeExecutor {
             let mut result_text: Option<String> = None;
             let mut total_cost: f64 = 0.0;
             let mut total_turns: u64 = 0;
+            let mut max_turns_reached = false;
 
             let reader = BufReader::new(stdout);
             let mut lines = reader.lines();
+            let mut assembler = JsonLineAssembler::new();
             while let Ok(Some(line)) = lines.next_line().await {
                 if line.is_empty() {
                     continue;
@@ -112,7 +182,19 @@ impl Executor for ClaudeCodeExecutor {
                     sink(line.clone());
                 }
 
-                if let Ok(event) = serde_json::from_str::<serde_json::Value>(&line) {
+                let event = match assembler.push(&line) {
+                    ParsedLine::Json(event) => Some(event),
+                    ParsedLine::Discarded(raw) => {
+                        tracing::warn!(
+                            source = "claude",
+                            len = raw.len(),
+                            "Dropping malformed JSON line from claude stdout",
+                        );
+                        None
+                    }
+                    ParsedLine::Text(_) | ParsedLine::Pending => None,
+                };
+                if let Some(event) = event {
                     let event_type = event
                         .get("type")
                         .and_then(|v| v.as_str())
@@ -172,6 +254,8 @@ impl Executor for ClaudeCodeExecutor {
                                 .get("num_turns")
                                 .and_then(|v| v.as_u64())
                                 .unwrap_or(0);
+                            max_turns_reached = event.get("subtype").and_then(|v| v.as_str())
+                                == Some(MAX_TURNS_SUBTYPE);
                             result_text = event
                                 .get("result")
                                 .and_then(|v| v.as_str())
@@ -187,36 +271,53 @@ impl Executor for ClaudeCodeExecutor {
                     }
                 }
             }
+            if let Some(raw) = assembler.finish() {
+                tracing::warn!(
+                    source = "claude",
+                    len = raw.len(),
+                    "claude stdout ended mid-JSON, dropping partial line",
+                );
+            }
 
-            (result_text, total_cost, total_turns)
+            (result_text, total_cost, total_turns, max_turns_reached)
         });
 
-        let status = match timeout(PROCESS_TIMEOUT, child.wait()).await {
+        let status = match timeout(self.timeout, child.wait()).await {
             Ok(result) => result.context("failed to wait on claude")?,
             Err(_elapsed) => {
                 tracing::error!(
                     "claude process timed out after {}s, killing",
-                    PROCESS_TIMEOUT.as_secs()
+                    self.timeout.as_secs()
                 );
                 let _ = child.kill().await;
                 stderr_handle.abort();
                 stdout_handle.abort();
-                anyhow::bail!("claude process timed out after {}s", PROCESS_TIMEOUT.as_secs());
+                anyhow::bail!("claude process timed out after {}s", self.timeout.as_secs());
             }
         };
-        let _ = stderr_handle.await;
-        let (result_text, cost_usd, num_turns) = stdout_handle
+        let stderr_tail = stderr_handle.await.unwrap_or_default();
+        let (result_text, cost_usd, num_turns, max_turns_reached) = stdout_handle
             .await
-            .unwrap_or((None, 0.0, 0));
+            .unwrap_or((None, 0.0, 0, false));
 
-        if !status.success() {
-            anyhow::bail!("claude exited with {}", status);
+        // Hitting the cap exits non-zero but still produced a (partial) run
+        if !status.success() && !max_turns_reached {
+            // Claude's own message (e.g. an unknown --model) beats a bare exit code
+            let detail = result_text
+                .as_deref()
+                .filter(|t| !t.trim().is_empty())
+                .unwrap_or(stderr_tail.trim());
+            if detail.is_empty() {
+                anyhow::bail!("claude exited with {}", status);
+            }
+            anyhow::bail!("claude exited with {}: {}", status, detail);
         }
 
         Ok(ExecutionResult {
             text: result_text.unwrap_or_default(),
             cost_usd,
             num_turns,
+            max_turns_reached,
         })
     }
 }
@@ -256,6 +357,86 @@ mod tests {
         assert!(args.contains(&"Read".to_string()));
     }
 
+    fn mode_args(mode: PermissionMode) -> Vec<String> {
+        ClaudeCodeExecutor::new(vec!["Read".to_string()], None)
+            .with_permission_mode(Some(mode), vec![])
+            .build_args()
+    }
+
+    #[test]
+    fn test_build_args_permission_modes() {
+        for (mode, flag) in [
+            (PermissionMode::AcceptEdits, "acceptEdits"),
+            (PermissionMode::Plan, "plan"),
+            (PermissionMode::Default, "default"),
+        ] {
+            let args = mode_args(mode);
+            let pos = args.iter().position(|a| a == "--permission-mode").unwrap();
+            assert_eq!(args[pos + 1], flag);
+            assert!(!args.contains(&"--dangerously-skip-permissions".to_string()));
+            assert!(args.contains(&"--allowedTools".to_string()));
+        }
+
+        let args = mode_args(PermissionMode::Bypass);
+        assert!(args.contains(&"--dangerously-skip-permissions".to_string()));
+        assert!(!args.contains(&"--permission-mode".to_string()));
+    }
+
+    #[test]
+    fn test_build_args_disallowed_tools() {
+        let executor = ClaudeCodeExecutor::new(vec![], None).with_permission_mode(
+            Some(PermissionMode::AcceptEdits),
+            vec!["Bash".to_string(), "WebFetch".to_string()],
+        );
+        let args = executor.build_args();
+        let pos = args.iter().position(|a| a == "--disallowedTools").unwrap();
+        assert_eq!(args[pos + 1], "Bash,WebFetch");
+        // An explicit mode replaces the empty-allowlist bypass.
+        assert!(!args.contains(&"--dangerously-skip-permissions".to_string()));
+        assert!(!args.contains(&"--allowedTools".to_string()));
+    }
+
+    #[test]
+    fn test_build_args_additional_dirs() {
+        let executor = ClaudeCodeExecutor::new(vec![], None).with_additional_dirs(vec![
+            PathBuf::from("/srv/prompts"),
+            PathBuf::from("/srv/data"),
+        ]);
+        let args = executor.build_args();
+        let pos = args.iter().position(|a| a == "--add-dir").unwrap();
+        assert_eq!(
+            args[pos..pos + 4],
+            ["--add-dir", "/srv/prompts", "--add-dir", "/srv/data"]
+        );
+        assert_eq!(args.last().unwrap(), "-");
+
+        let args = ClaudeCodeExecutor::new(vec![], None).build_args();
+        assert!(!args.contains(&"--add-dir".to_string()));
+    }
+
+    #[test]
+    fn test_build_args_model() {
+        let args = ClaudeCodeExecutor::new(vec![], None)
+            .with_model(Some("claude-sonnet-4-5".to_string()))
+            .build_args();
+        let pos = args.iter().position(|a| a == "--model").unwrap();
+        assert_eq!(args[pos + 1], "claude-sonnet-4-5");
+        assert_eq!(args.last().unwrap(), "-");
+
+        let args = ClaudeCodeExecutor::new(vec![], None).build_args();
+        assert!(!args.contains(&"--model".to_string()));
+    }
+
+    #[test]
+    fn test_build_args_max_turns() {
+        let args = ClaudeCodeExecutor::new(vec![], None).with_max_turns(Some(12)).build_args();
+        let pos = args.iter().position(|a| a == "--max-turns").unwrap();
+        assert_eq!(args[pos + 1], "12");
+
+        let args = ClaudeCodeExecutor::new(vec![], None).with_max_turns(None).build_args();
+        assert!(!args.contains(&"--max-turns".to_string()));
+    }
+
     #[test]
     fn test_build_args_always_reads_stdin() {
         let executor = ClaudeCodeExecutor::new(vec![], None);
@@ -0,0 +1,168 @@
+//! Tolerant parsing of claude's `stream-json` stdout.
+//!
+//! Each stdout line is normally one complete JSON object, but a line can
+//! arrive truncated (split writes, very large tool inputs). Rather than
+//! surfacing half an object to users as plain text, truncated JSON is
+//! buffered until the rest arrives, and JSON that is genuinely broken is
+//! dropped (and logged) instead of being shown.
+
+/// Upper bound on buffered partial JSON before it is given up on.
+const MAX_PENDING_BYTES: usize = 16 * 1024 * 1024;
+
+/// Outcome of feeding one stdout line to a [`JsonLineAssembler`].
+#[derive(Debug)]
+pub enum ParsedLine {
+    /// A complete JSON value.
+    Json(serde_json::Value),
+    /// Plain, non-JSON output — safe to show as text.
+    Text(String),
+    /// Looks like the start of a JSON object; waiting for more input.
+    Pending,
+    /// JSON-looking content that could not be parsed. Never user-visible.
+    Discarded(String),
+}
+
+#[derive(Debug, Default)]
+pub struct JsonLineAssembler {
+    pending: String,
+}
+
+fn looks_like_json(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with('{') || trimmed.starts_with('[')
+}
+
+impl JsonLineAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, line: &str) -> ParsedLine {
+        if self.pending.is_empty() {
+            return self.start(line);
+        }
+
+        let buffered = self.pending.len();
+        self.pending.push_str(line);
+        match self.parse_pending() {
+            // The buffered prefix was truncated (or grew too large) and this
+            // line doesn't complete it: drop the prefix, not the new line
+            ParsedLine::Discarded(mut stale) if looks_like_json(line) => {
+                stale.truncate(buffered);
+                tracing::warn!(bytes = stale.len(), "dropping incomplete JSON from claude output");
+                self.start(line)
+            }
+            parsed => parsed,
+        }
+    }
+
+    fn start(&mut self, line: &str) -> ParsedLine {
+        if !looks_like_json(line) {
+            return ParsedLine::Text(line.to_string());
+        }
+        self.pending.push_str(line);
+        self.parse_pending()
+    }
+
+    fn parse_pending(&mut self) -> ParsedLine {
+        match serde_json::from_str::<serde_json::Value>(&self.pending) {
+            Ok(value) => {
+                self.pending.clear();
+                ParsedLine::Json(value)
+            }
+            Err(e) if e.is_eof() && self.pending.len() <= MAX_PENDING_BYTES => ParsedLine::Pending,
+            Err(_) => ParsedLine::Discarded(std::mem::take(&mut self.pending)),
+        }
+    }
+
+    /// Anything still buffered when the stream ends is incomplete JSON.
+    pub fn finish(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            None
+        } else {
+            Some(std::mem::take(&mut self.pending))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete_line_parses() {
+        let mut asm = JsonLineAssembler::new();
+        match asm.push(r#"{"type":"result","result":"ok"}"#) {
+            ParsedLine::Json(v) => assert_eq!(v["result"], "ok"),
+            other => panic!("expected Json, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn plain_text_passes_through() {
+        let mut asm = JsonLineAssembler::new();
+        assert!(matches!(asm.push("Warning: something"), ParsedLine::Text(t) if t == "Warning: something"));
+    }
+
+    #[test]
+    fn split_json_is_buffered_until_complete() {
+        let mut asm = JsonLineAssembler::new();
+        assert!(matches!(asm.push(r#"{"type":"assistant","message":"#), ParsedLine::Pending));
+        match asm.push(r#"{"content":[]}}"#) {
+            ParsedLine::Json(v) => assert_eq!(v["type"], "assistant"),
+            other => panic!("expected Json, got {other:?}"),
+        }
+        assert!(asm.finish().is_none());
+    }
+
+    #[test]
+    fn malformed_jsonish_line_is_discarded_not_text() {
+        let mut asm = JsonLineAssembler::new();
+        assert!(matches!(asm.push(r#"{"type": "result", oops}"#), ParsedLine::Discarded(_)));
+        // The assembler recovers for the next line
+        assert!(matches!(asm.push(r#"{"type":"system"}"#), ParsedLine::Json(_)));
+    }
+
+    #[test]
+    fn truncated_line_does_not_swallow_the_next_one() {
+        let mut asm = JsonLineAssembler::new();
+        assert!(matches!(asm.push(r#"{"type":"assistant","message":{"con"#), ParsedLine::Pending));
+        match asm.push(r#"{"type":"system"}"#) {
+            ParsedLine::Json(v) => assert_eq!(v["type"], "system"),
+            other => panic!("expected Json, got {other:?}"),
+        }
+        assert!(asm.finish().is_none());
+    }
+
+    #[test]
+    fn oversized_line_does_not_swallow_the_next_one() {
+        let mut asm = JsonLineAssembler::new();
+        let huge = format!(r#"{{"input":"{}"#, "x".repeat(MAX_PENDING_BYTES));
+        assert!(matches!(asm.push(&huge), ParsedLine::Discarded(_)));
+        assert!(matches!(asm.push(r#"{"type":"result"}"#), ParsedLine::Json(_)));
+
+        // Over the limit only once the next line is appended
+        let mut asm = JsonLineAssembler::new();
+        let almost = format!(r#"{{"input":"{}"#, "x".repeat(MAX_PENDING_BYTES - 16));
+        assert!(matches!(asm.push(&almost), ParsedLine::Pending));
+        assert!(matches!(asm.push(r#"{"type":"result","result":"done"}"#), ParsedLine::Json(v) if v["result"] == "done"));
+    }
+
+    #[test]
+    fn very_long_valid_line_parses() {
+        let big = "x".repeat(2 * 1024 * 1024);
+        let line = serde_json::json!({"type": "assistant", "input": big}).to_string();
+        let mut asm = JsonLineAssembler::new();
+        match asm.push(&line) {
+            ParsedLine::Json(v) => assert_eq!(v["input"].as_str().unwrap().len(), 2 * 1024 * 1024),
+            other => panic!("expected Json, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn unterminated_json_is_reported_on_finish() {
+        let mut asm = JsonLineAssembler::new();
+        assert!(matches!(asm.push(r#"{"type":"#), ParsedLine::Pending));
+        assert_eq!(asm.finish().as_deref(), Some(r#"{"type":"#));
+    }
+}
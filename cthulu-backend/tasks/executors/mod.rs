@@ -1,4 +1,5 @@
 pub mod claude_code;
+pub mod json_lines;
 pub mod sandbox;
 
 use anyhow::Result;
@@ -11,8 +12,13 @@ pub struct ExecutionResult {
     pub text: String,
     pub cost_usd: f64,
     pub num_turns: u64,
+    /// Claude stopped at the executor's `max_turns` cap.
+    pub max_turns_reached: bool,
 }
 
+/// `subtype` of the `result` event when claude hits `--max-turns`.
+pub(crate) const MAX_TURNS_SUBTYPE: &str = "error_max_turns";
+
 /// Callback that receives each stdout line from the executor process.
 pub type LineSink = Arc<dyn Fn(String) + Send + Sync>;
 
@@ -6,12 +6,15 @@ use std::time::Duration;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 
-use super::{ExecutionResult, Executor};
+use super::json_lines::{JsonLineAssembler, ParsedLine};
+use super::{ExecutionResult, Executor, MAX_TURNS_SUBTYPE};
+use crate::agents::{
+    max_turns_args, model_args, permission_args, PermissionMode, DEFAULT_EXECUTOR_TIMEOUT_SECS,
+};
+use crate::sandbox::handle::SandboxHandle;
 use crate::sandbox::provider::SandboxProvider;
 use crate::sandbox::types::*;
 
-const PROCESS_TIMEOUT: Duration = Duration::from_secs(15 * 60);
-
 /// Executor that runs Claude CLI inside a sandbox.
 ///
 /// Bridges the existing `Executor` trait (used by `FlowRunner`) to the
@@ -24,6 +27,11 @@ pub struct SandboxExecutor {
     provider: Arc<dyn SandboxProvider>,
     permissions: Vec<String>,
     append_system_prompt: Option<String>,
+    permission_mode: Option<PermissionMode>,
+    disallowed_tools: Vec<String>,
+    model: Option<String>,
+    max_turns: Option<u32>,
+    timeout: Duration,
 }
 
 impl SandboxExecutor {
@@ -36,9 +44,43 @@ impl SandboxExecutor {
             provider,
             permissions,
             append_system_prompt,
+            permission_mode: None,
+            disallowed_tools: Vec::new(),
+            model: None,
+            max_turns: None,
+            timeout: Duration::from_secs(DEFAULT_EXECUTOR_TIMEOUT_SECS),
         }
     }
 
+    /// See `ClaudeCodeExecutor::with_permission_mode`.
+    pub fn with_permission_mode(
+        mut self,
+        mode: Option<PermissionMode>,
+        disallowed_tools: Vec<String>,
+    ) -> Self {
+        self.permission_mode = mode;
+        self.disallowed_tools = disallowed_tools;
+        self
+    }
+
+    /// See `ClaudeCodeExecutor::with_model`.
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// See `ClaudeCodeExecutor::with_max_turns`.
+    pub fn with_max_turns(mut self, max_turns: Option<u32>) -> Self {
+        self.max_turns = max_turns;
+        self
+    }
+
+    /// See `ClaudeCodeExecutor::with_timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     fn build_claude_args(&self) -> Vec<String> {
         let mut args = vec![
             "claude".to_string(),
@@ -53,44 +95,78 @@ impl SandboxExecutor {
             args.push(prompt.clone());
         }
 
-        if self.permissions.is_empty() {
-            args.push("--dangerously-skip-permissions".to_string());
-        } else {
-            args.push("--allowedTools".to_string());
-            args.push(self.permissions.join(","));
-        }
+        args.extend(permission_args(
+            &self.permissions,
+            self.permission_mode,
+            &self.disallowed_tools,
+            true,
+        ));
+        args.extend(model_args(self.model.as_deref()));
+        args.extend(max_turns_args(self.max_turns));
 
         args.push("-".to_string()); // read from stdin
         args
     }
 }
 
+/// Spec for an executor's sandbox, keyed by `working_dir`'s last component.
+fn sandbox_spec(working_dir: &Path) -> SandboxSpec {
+    let workspace_id = working_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "default".to_string());
+
+    SandboxSpec {
+        workspace_id,
+        profile: SandboxProfile::Base,
+        filesystem: FilesystemSpec::default(),
+        resources: ResourceHints::default(),
+        env: BTreeMap::new(),
+        mounts: vec![],
+        network: NetworkPolicy::default_safe(),
+        lifecycle: LifecyclePolicy::default(),
+        labels: BTreeMap::from([("executor".into(), "sandbox".into())]),
+    }
+}
+
+/// Owns a provisioned sandbox and destroys it when dropped, so a caller that
+/// gives up on `execute()` (e.g. the node's outer timeout) doesn't leak it.
+struct SandboxGuard(Option<Box<dyn SandboxHandle>>);
+
+impl SandboxGuard {
+    fn handle(&self) -> &dyn SandboxHandle {
+        self.0.as_deref().expect("sandbox already destroyed")
+    }
+
+    async fn destroy(mut self) {
+        if let Some(handle) = self.0.take() {
+            let _ = handle.destroy().await;
+        }
+    }
+}
+
+impl Drop for SandboxGuard {
+    fn drop(&mut self) {
+        let Some(handle) = self.0.take() else { return };
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                if let Err(e) = handle.destroy().await {
+                    tracing::warn!(sandbox = handle.id(), error = %e, "failed to destroy abandoned sandbox");
+                }
+            });
+        }
+    }
+}
+
 #[async_trait]
 impl Executor for SandboxExecutor {
     async fn execute(&self, prompt: &str, working_dir: &Path) -> Result<ExecutionResult> {
-        // Use working_dir's last component as workspace_id
-        let workspace_id = working_dir
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| "default".to_string());
-
-        let spec = SandboxSpec {
-            workspace_id,
-            profile: SandboxProfile::Base,
-            filesystem: FilesystemSpec::default(),
-            resources: ResourceHints::default(),
-            env: BTreeMap::new(),
-            mounts: vec![],
-            network: NetworkPolicy::default_safe(),
-            lifecycle: LifecyclePolicy::default(),
-            labels: BTreeMap::from([("executor".into(), "sandbox".into())]),
-        };
-
-        let handle = self
-            .provider
-            .provision(spec)
-            .await
-            .map_err(|e| anyhow::anyhow!("sandbox provision failed: {e}"))?;
+        let handle = SandboxGuard(Some(
+            self.provider
+                .provision(sandbox_spec(working_dir))
+                .await
+                .map_err(|e| anyhow::anyhow!("sandbox provision failed: {e}"))?,
+        ));
 
         let args = self.build_claude_args();
 
@@ -99,21 +175,22 @@ impl Executor for SandboxExecutor {
             cwd: None,
             env: BTreeMap::new(),
             stdin: Some(prompt.as_bytes().to_vec()),
-            timeout: Some(PROCESS_TIMEOUT),
+            timeout: Some(self.timeout),
             tty: false,
             detach: false,
         };
 
         let result = handle
+            .handle()
             .exec(exec_req)
             .await
             .map_err(|e| anyhow::anyhow!("sandbox exec failed: {e}"))?;
 
         if result.timed_out {
-            let _ = handle.destroy().await;
+            handle.destroy().await;
             anyhow::bail!(
                 "claude process timed out after {}s",
-                PROCESS_TIMEOUT.as_secs()
+                self.timeout.as_secs()
             );
         }
 
@@ -122,12 +199,14 @@ impl Executor for SandboxExecutor {
         let mut result_text: Option<String> = None;
         let mut total_cost: f64 = 0.0;
         let mut total_turns: u64 = 0;
+        let mut max_turns_reached = false;
 
+        let mut assembler = JsonLineAssembler::new();
         for line in stdout_str.lines() {
             if line.is_empty() {
                 continue;
             }
-            if let Ok(event) = serde_json::from_str::<serde_json::Value>(line) {
+            if let ParsedLine::Json(event) = assembler.push(line) {
                 let event_type = event
                     .get("type")
                     .and_then(|v| v.as_str())
@@ -142,6 +221,8 @@ impl Executor for SandboxExecutor {
                         .get("num_turns")
                         .and_then(|v| v.as_u64())
                         .unwrap_or(0);
+                    max_turns_reached =
+                        event.get("subtype").and_then(|v| v.as_str()) == Some(MAX_TURNS_SUBTYPE);
                     result_text = event
                         .get("result")
                         .and_then(|v| v.as_str())
@@ -151,21 +232,22 @@ impl Executor for SandboxExecutor {
         }
 
         if let Some(code) = result.exit_code {
-            if code != 0 {
+            if code != 0 && !max_turns_reached {
                 let stderr_str = String::from_utf8_lossy(&result.stderr);
-                let _ = handle.destroy().await;
+                handle.destroy().await;
                 anyhow::bail!("claude exited with code {code}: {stderr_str}");
             }
         }
 
         // Destroy the sandbox to release resources (workspace dirs, VM state, TAP devices).
         // Each execute() provisions a fresh sandbox, so there's nothing to preserve.
-        let _ = handle.destroy().await;
+        handle.destroy().await;
 
         Ok(ExecutionResult {
             text: result_text.unwrap_or_default(),
             cost_usd: total_cost,
             num_turns: total_turns,
+            max_turns_reached,
         })
     }
 }
@@ -227,4 +309,27 @@ mod tests {
         let args = executor.build_claude_args();
         assert_eq!(args.last().unwrap(), "-");
     }
+
+    #[tokio::test]
+    async fn dropping_the_guard_destroys_the_sandbox() {
+        let tmp = tempfile::tempdir().unwrap();
+        let provider = crate::sandbox::backends::dangerous::DangerousHostProvider::new(DangerousConfig {
+            root_dir: tmp.path().to_path_buf(),
+            ..DangerousConfig::default()
+        })
+        .unwrap();
+        let handle = provider.provision(sandbox_spec(Path::new("/work/abandoned"))).await.unwrap();
+        let workspace = tmp.path().join("abandoned");
+        assert!(workspace.exists());
+
+        // As when the node's outer timeout drops execute() mid-run
+        drop(SandboxGuard(Some(handle)));
+        for _ in 0..50 {
+            if !workspace.exists() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("sandbox workspace was not destroyed");
+    }
 }
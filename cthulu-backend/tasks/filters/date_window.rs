@@ -0,0 +1,73 @@
+use chrono::{DateTime, Duration, Utc};
+
+use super::Filter;
+use crate::tasks::sources::ContentItem;
+
+/// Keeps items published within the last `max_age_hours`. Future-dated
+/// items (clock skew between us and the source) are kept.
+#[derive(Debug, Clone)]
+pub struct DateWindowFilter {
+    pub max_age_hours: u64,
+    /// Drop items with no `published` date instead of keeping them.
+    pub drop_undated: bool,
+}
+
+impl DateWindowFilter {
+    pub fn keep_at(&self, item: &ContentItem, now: DateTime<Utc>) -> bool {
+        let Some(published) = item.published else {
+            return !self.drop_undated;
+        };
+        // Capped so absurd windows can't overflow chrono's Duration
+        let max_age = Duration::hours(self.max_age_hours.min(u32::MAX as u64) as i64);
+        now.signed_duration_since(published) <= max_age
+    }
+}
+
+impl Filter for DateWindowFilter {
+    fn apply(&self, items: Vec<ContentItem>) -> Vec<ContentItem> {
+        let now = Utc::now();
+        items.into_iter().filter(|item| self.keep_at(item, now)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::filters::apply_all;
+    use chrono::TimeZone;
+
+    fn item(published: Option<DateTime<Utc>>) -> ContentItem {
+        ContentItem {
+            title: "t".into(),
+            url: "https://example.com".into(),
+            summary: String::new(),
+            published,
+            image_url: None,
+        }
+    }
+
+    #[test]
+    fn test_keeps_recent_and_future_items() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 2, 12, 0, 0).unwrap();
+        let filter = DateWindowFilter { max_age_hours: 24, drop_undated: false };
+
+        assert!(filter.keep_at(&item(Some(now - Duration::hours(3))), now));
+        assert!(filter.keep_at(&item(Some(now - Duration::hours(24))), now));
+        assert!(!filter.keep_at(&item(Some(now - Duration::hours(25))), now));
+        // Clock skew: a few minutes "ahead" of us is still fresh
+        assert!(filter.keep_at(&item(Some(now + Duration::minutes(10))), now));
+    }
+
+    #[test]
+    fn test_undated_items_follow_drop_undated() {
+        let now = Utc::now();
+        let keep = DateWindowFilter { max_age_hours: 24, drop_undated: false };
+        let drop = DateWindowFilter { max_age_hours: 24, drop_undated: true };
+        assert!(keep.keep_at(&item(None), now));
+        assert!(!drop.keep_at(&item(None), now));
+
+        let filters: Vec<Box<dyn Filter>> = vec![Box::new(drop)];
+        let kept = apply_all(&filters, vec![item(None), item(Some(now)), item(None)]);
+        assert_eq!(kept.len(), 1);
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use ring::digest::{digest, SHA256};
+
+use super::Filter;
+use crate::tasks::sources::ContentItem;
+
+pub const DEFAULT_MAX_REMEMBERED: usize = 1000;
+
+/// What identifies an item as already seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupKey {
+    Url,
+    /// Hash of title + URL, for sources whose items share a page URL.
+    Title,
+}
+
+impl DedupKey {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "url" => Some(Self::Url),
+            "title" => Some(Self::Title),
+            _ => None,
+        }
+    }
+
+    fn of(self, item: &ContentItem) -> String {
+        match self {
+            DedupKey::Url => item.url.clone(),
+            DedupKey::Title => {
+                let input = format!("{}\n{}", item.title.trim().to_lowercase(), item.url);
+                digest(&SHA256, input.as_bytes())
+                    .as_ref()
+                    .iter()
+                    .map(|b| format!("{b:02x}"))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Serializes read-modify-write of history files across concurrent runs.
+static HISTORY_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Drops items seen in earlier successful runs of the executor. Seen keys
+/// live in `<data_dir>/dedup/{flow_id}/{node_id}.json`, oldest first, capped
+/// at `max_remembered`. The keys an item passes with are only recorded once
+/// the run has succeeded, through `PendingDedup::commit`.
+#[derive(Debug, Clone)]
+pub struct DedupFilter {
+    pub path: PathBuf,
+    pub key: DedupKey,
+    pub max_remembered: usize,
+    /// Keys recorded before this run.
+    seen: HashSet<String>,
+    /// Where this run's new keys wait for the run to finish; `None` for dry
+    /// runs, which record nothing.
+    pending: Option<PendingDedup>,
+}
+
+impl DedupFilter {
+    pub fn path_for(data_dir: &Path, flow_id: &str, node_id: &str) -> PathBuf {
+        data_dir.join("dedup").join(flow_id).join(format!("{node_id}.json"))
+    }
+
+    /// Load the history at `path`. An unreadable history starts fresh.
+    pub async fn load(
+        path: PathBuf,
+        key: DedupKey,
+        max_remembered: usize,
+        pending: Option<PendingDedup>,
+    ) -> Self {
+        let seen = read_history(&path).await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Dedup history unreadable, starting fresh");
+            Vec::new()
+        });
+        Self { path, key, max_remembered, seen: seen.into_iter().collect(), pending }
+    }
+}
+
+impl Filter for DedupFilter {
+    fn apply(&self, items: Vec<ContentItem>) -> Vec<ContentItem> {
+        let mut known = self.seen.clone();
+        let mut fresh = Vec::with_capacity(items.len());
+        let mut fresh_keys = Vec::new();
+        for item in items {
+            let key = self.key.of(&item);
+            // `insert` also drops repeats within this run
+            if known.insert(key.clone()) {
+                fresh_keys.push(key);
+                fresh.push(item);
+            }
+        }
+        if let Some(pending) = &self.pending {
+            pending.record(&self.path, fresh_keys, self.max_remembered);
+        }
+        fresh
+    }
+}
+
+/// The keys a run's dedup filters let through, held until the run succeeds
+/// so a failed or retried run sees the same items again.
+#[derive(Debug, Clone, Default)]
+pub struct PendingDedup(Arc<Mutex<Vec<PendingKeys>>>);
+
+#[derive(Debug)]
+struct PendingKeys {
+    path: PathBuf,
+    keys: Vec<String>,
+    max_remembered: usize,
+}
+
+impl PendingDedup {
+    fn record(&self, path: &Path, keys: Vec<String>, max_remembered: usize) {
+        if let Ok(mut pending) = self.0.lock() {
+            pending.push(PendingKeys { path: path.to_path_buf(), keys, max_remembered });
+        }
+    }
+
+    /// Append every recorded key to its history. Call once the run succeeded.
+    pub async fn commit(&self) {
+        let pending = self.0.lock().map(|mut p| std::mem::take(&mut *p)).unwrap_or_default();
+        for PendingKeys { path, keys, max_remembered } in pending {
+            if let Err(e) = append_history(&path, keys, max_remembered).await {
+                tracing::warn!(error = %e, "Failed to save dedup history");
+            }
+        }
+    }
+}
+
+async fn read_history(path: &Path) -> Result<Vec<String>> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("invalid dedup file: {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// Add `keys` to the history at `path`, skipping ones a concurrent run of the
+/// same executor already recorded, then forget the oldest past `max_remembered`.
+async fn append_history(path: &Path, keys: Vec<String>, max_remembered: usize) -> Result<()> {
+    let _guard = HISTORY_LOCK.lock().await;
+    let mut seen = read_history(path).await.unwrap_or_default();
+    let mut known: HashSet<String> = seen.iter().cloned().collect();
+    seen.extend(keys.into_iter().filter(|key| known.insert(key.clone())));
+    let excess = seen.len().saturating_sub(max_remembered);
+    seen.drain(..excess);
+
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("failed to create dedup dir: {}", dir.display()))?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, serde_json::to_string(&seen)?)
+        .await
+        .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+    tokio::fs::rename(&tmp_path, path)
+        .await
+        .with_context(|| format!("failed to rename {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn item(title: &str, url: &str) -> ContentItem {
+        ContentItem {
+            title: title.into(),
+            url: url.into(),
+            summary: String::new(),
+            published: None,
+            image_url: None,
+        }
+    }
+
+    fn titles(items: &[ContentItem]) -> Vec<&str> {
+        items.iter().map(|i| i.title.as_str()).collect()
+    }
+
+    async fn filter(path: &Path, key: DedupKey, max_remembered: usize, pending: &PendingDedup) -> DedupFilter {
+        DedupFilter::load(path.to_path_buf(), key, max_remembered, Some(pending.clone())).await
+    }
+
+    #[tokio::test]
+    async fn test_cold_start_then_drops_seen_items() {
+        let dir = tempdir().unwrap();
+        let path = DedupFilter::path_for(dir.path(), "flow-1", "exec-1");
+        let pending = PendingDedup::default();
+
+        // No file yet: everything passes (minus in-run repeats)
+        let first = filter(&path, DedupKey::Url, DEFAULT_MAX_REMEMBERED, &pending)
+            .await
+            .apply(vec![item("a", "https://e.com/a"), item("b", "https://e.com/b"), item("a2", "https://e.com/a")]);
+        assert_eq!(titles(&first), vec!["a", "b"]);
+        // Nothing is recorded until the run succeeds
+        assert!(!path.exists());
+        pending.commit().await;
+        assert!(path.exists());
+
+        let second = filter(&path, DedupKey::Url, DEFAULT_MAX_REMEMBERED, &pending)
+            .await
+            .apply(vec![item("b", "https://e.com/b"), item("c", "https://e.com/c")]);
+        assert_eq!(titles(&second), vec!["c"]);
+
+        // Another executor in the same flow keeps its own history
+        let other = DedupFilter::path_for(dir.path(), "flow-1", "exec-2");
+        let third = filter(&other, DedupKey::Url, DEFAULT_MAX_REMEMBERED, &pending)
+            .await
+            .apply(vec![item("b", "https://e.com/b")]);
+        assert_eq!(titles(&third), vec!["b"]);
+    }
+
+    #[tokio::test]
+    async fn test_uncommitted_runs_and_dry_runs_record_nothing() {
+        let dir = tempdir().unwrap();
+        let path = DedupFilter::path_for(dir.path(), "flow-1", "exec-1");
+
+        // A failed run never commits: the retry sees the same items
+        let failed = PendingDedup::default();
+        filter(&path, DedupKey::Url, DEFAULT_MAX_REMEMBERED, &failed).await.apply(vec![item("a", "https://e.com/a")]);
+        let retry = PendingDedup::default();
+        let again = filter(&path, DedupKey::Url, DEFAULT_MAX_REMEMBERED, &retry).await.apply(vec![item("a", "https://e.com/a")]);
+        assert_eq!(titles(&again), vec!["a"]);
+
+        let dry = DedupFilter::load(path.clone(), DedupKey::Url, DEFAULT_MAX_REMEMBERED, None).await;
+        assert_eq!(titles(&dry.apply(vec![item("z", "https://e.com/z")])), vec!["z"]);
+        retry.commit().await;
+        assert_eq!(read_history(&path).await.unwrap(), vec!["https://e.com/a"]);
+    }
+
+    #[tokio::test]
+    async fn test_cap_forgets_oldest_and_concurrent_runs_do_not_duplicate() {
+        let dir = tempdir().unwrap();
+        let path = DedupFilter::path_for(dir.path(), "flow-1", "exec-1");
+
+        // Two overlapping runs both let "b" through
+        let (run1, run2) = (PendingDedup::default(), PendingDedup::default());
+        filter(&path, DedupKey::Title, 2, &run1).await.apply(vec![item("a", "https://e.com"), item("b", "https://e.com")]);
+        filter(&path, DedupKey::Title, 2, &run2).await.apply(vec![item("b", "https://e.com"), item("c", "https://e.com")]);
+        run1.commit().await;
+        run2.commit().await;
+        assert_eq!(read_history(&path).await.unwrap().len(), 2);
+
+        // Same URL, different titles: the title key keeps them apart; "a" was forgotten
+        let again = filter(&path, DedupKey::Title, 2, &PendingDedup::default())
+            .await
+            .apply(vec![item("a", "https://e.com"), item("b", "https://e.com"), item("c", "https://e.com")]);
+        assert_eq!(titles(&again), vec!["a"]);
+    }
+}
@@ -0,0 +1,95 @@
+use super::Filter;
+use crate::tasks::rank;
+use crate::tasks::sources::ContentItem;
+
+/// Which items a `LimitFilter` keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitStrategy {
+    Head,
+    Tail,
+    /// Newest `published` first, undated last; ties keep input order.
+    Newest,
+}
+
+impl LimitStrategy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "head" => Some(Self::Head),
+            "tail" => Some(Self::Tail),
+            "newest" => Some(Self::Newest),
+            _ => None,
+        }
+    }
+}
+
+/// Caps the item list at `count`.
+#[derive(Debug, Clone)]
+pub struct LimitFilter {
+    pub count: usize,
+    pub strategy: LimitStrategy,
+}
+
+impl Filter for LimitFilter {
+    fn apply(&self, mut items: Vec<ContentItem>) -> Vec<ContentItem> {
+        match self.strategy {
+            LimitStrategy::Head => items.truncate(self.count),
+            LimitStrategy::Tail => {
+                let excess = items.len().saturating_sub(self.count);
+                items.drain(..excess);
+            }
+            LimitStrategy::Newest => {
+                // Stable sort, so same-timestamp items come out the same every run
+                rank::rank_by_recency(&mut items);
+                items.truncate(self.count);
+            }
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn item(title: &str, published: Option<DateTime<Utc>>) -> ContentItem {
+        ContentItem {
+            title: title.into(),
+            url: String::new(),
+            summary: String::new(),
+            published,
+            image_url: None,
+        }
+    }
+
+    fn titles(items: &[ContentItem]) -> Vec<&str> {
+        items.iter().map(|i| i.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_head_and_tail() {
+        let items = || vec![item("a", None), item("b", None), item("c", None)];
+        let head = LimitFilter { count: 2, strategy: LimitStrategy::Head };
+        let tail = LimitFilter { count: 2, strategy: LimitStrategy::Tail };
+        assert_eq!(titles(&head.apply(items())), vec!["a", "b"]);
+        assert_eq!(titles(&tail.apply(items())), vec!["b", "c"]);
+        assert_eq!(titles(&LimitFilter { count: 5, strategy: LimitStrategy::Tail }.apply(items())), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_newest_is_stable_and_puts_undated_last() {
+        let t = |h| Some(Utc.with_ymd_and_hms(2026, 1, 2, h, 0, 0).unwrap());
+        let items = vec![
+            item("undated", None),
+            item("old", t(1)),
+            item("tie-1", t(5)),
+            item("newest", t(9)),
+            item("tie-2", t(5)),
+        ];
+        let newest = LimitFilter { count: 3, strategy: LimitStrategy::Newest };
+        assert_eq!(titles(&newest.apply(items.clone())), vec!["newest", "tie-1", "tie-2"]);
+
+        let all = LimitFilter { count: 10, strategy: LimitStrategy::Newest };
+        assert_eq!(titles(&all.apply(items)), vec!["newest", "tie-1", "tie-2", "old", "undated"]);
+    }
+}
@@ -0,0 +1,16 @@
+pub mod date_window;
+pub mod dedup;
+pub mod limit;
+
+use crate::tasks::sources::ContentItem;
+
+/// A step over content items, applied to an executor's input before
+/// ranking and formatting.
+pub trait Filter: Send + Sync {
+    fn apply(&self, items: Vec<ContentItem>) -> Vec<ContentItem>;
+}
+
+/// Run `filters` over `items` in order.
+pub fn apply_all(filters: &[Box<dyn Filter>], items: Vec<ContentItem>) -> Vec<ContentItem> {
+    filters.iter().fold(items, |items, filter| filter.apply(items))
+}
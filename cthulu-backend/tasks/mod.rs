@@ -1,6 +1,9 @@
 pub mod context;
 pub mod diff;
 pub mod executors;
+pub mod filters;
+pub mod notion;
 pub mod pipeline;
+pub mod rank;
 pub mod sinks;
 pub mod sources;
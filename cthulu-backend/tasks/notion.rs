@@ -0,0 +1,235 @@
+//! Shared Notion API client used by the Notion sink (and any Notion source),
+//! so the API version, auth headers, pagination and error mapping live in one place.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde_json::{json, Value};
+
+pub const NOTION_API_BASE: &str = "https://api.notion.com/v1";
+pub const NOTION_API_VERSION: &str = "2022-06-28";
+
+/// Notion caps `page_size` at 100.
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Typed view of a failed Notion API call.
+#[derive(Debug, thiserror::Error)]
+pub enum NotionError {
+    #[error("Notion rejected the integration token ({status}): {message}")]
+    Unauthorized { status: u16, message: String },
+    #[error("Notion object not found or not shared with the integration: {message}")]
+    NotFound { message: String },
+    #[error("Notion rate limit exceeded (retry after {retry_after_secs:?}s)")]
+    RateLimited { retry_after_secs: Option<u64> },
+    #[error("Notion rejected the request ({code}): {message}")]
+    InvalidRequest { code: String, message: String },
+    #[error("Notion API returned {status}: {message}")]
+    Api { status: u16, message: String },
+}
+
+/// Map a non-success status and Notion's error body to a `NotionError`.
+pub fn map_error(status: StatusCode, body: &Value, retry_after_secs: Option<u64>) -> NotionError {
+    let message = body["message"].as_str().unwrap_or("unknown error").to_string();
+    let code = body["code"].as_str().unwrap_or_default().to_string();
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => NotionError::Unauthorized {
+            status: status.as_u16(),
+            message,
+        },
+        StatusCode::NOT_FOUND => NotionError::NotFound { message },
+        StatusCode::TOO_MANY_REQUESTS => NotionError::RateLimited { retry_after_secs },
+        StatusCode::BAD_REQUEST | StatusCode::CONFLICT => NotionError::InvalidRequest {
+            code: if code.is_empty() { "validation_error".to_string() } else { code },
+            message,
+        },
+        _ => NotionError::Api {
+            status: status.as_u16(),
+            message,
+        },
+    }
+}
+
+/// Drive Notion's cursor pagination (`has_more` / `next_cursor`), collecting
+/// `results` from each page until exhausted or `max_results` is reached.
+pub async fn paginate<F, Fut>(mut fetch_page: F, max_results: usize) -> Result<Vec<Value>>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Value>>,
+{
+    let mut results = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let page = fetch_page(cursor.take()).await?;
+        if let Some(items) = page["results"].as_array() {
+            results.extend(items.iter().cloned());
+        }
+        if results.len() >= max_results {
+            results.truncate(max_results);
+            break;
+        }
+        match page["next_cursor"].as_str() {
+            Some(next) if page["has_more"].as_bool().unwrap_or(false) => {
+                cursor = Some(next.to_string());
+            }
+            _ => break,
+        }
+    }
+
+    Ok(results)
+}
+
+pub struct NotionClient {
+    http_client: Arc<reqwest::Client>,
+    token: String,
+    base_url: String,
+}
+
+impl NotionClient {
+    pub fn new(http_client: Arc<reqwest::Client>, token: String) -> Self {
+        Self {
+            http_client,
+            token,
+            base_url: NOTION_API_BASE.to_string(),
+        }
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http_client
+            .request(method, format!("{}/{}", self.base_url, path.trim_start_matches('/')))
+            .header("Authorization", format!("Bearer {}", self.token))
+            .header("Notion-Version", NOTION_API_VERSION)
+    }
+
+    /// Send a JSON request and return the parsed body, mapping API errors.
+    pub async fn send(&self, method: Method, path: &str, body: &Value) -> Result<Value> {
+        let response = self
+            .request(method, path)
+            .json(body)
+            .send()
+            .await
+            .with_context(|| format!("Notion request to {path} failed"))?;
+
+        let status = response.status();
+        let retry_after_secs = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+        let body: Value = response.json().await.unwrap_or_default();
+
+        if !status.is_success() {
+            return Err(map_error(status, &body, retry_after_secs).into());
+        }
+        Ok(body)
+    }
+
+    pub async fn post(&self, path: &str, body: &Value) -> Result<Value> {
+        self.send(Method::POST, path, body).await
+    }
+
+    pub async fn patch(&self, path: &str, body: &Value) -> Result<Value> {
+        self.send(Method::PATCH, path, body).await
+    }
+
+    /// Query a database, following pagination up to `max_results` rows.
+    #[allow(dead_code)] // for database-backed Notion sources
+    pub async fn query_database(
+        &self,
+        database_id: &str,
+        filter: Option<&Value>,
+        max_results: usize,
+    ) -> Result<Vec<Value>> {
+        let path = format!("databases/{database_id}/query");
+        paginate(
+            |cursor| {
+                let mut body = json!({ "page_size": max_results.min(MAX_PAGE_SIZE) });
+                if let Some(filter) = filter {
+                    body["filter"] = filter.clone();
+                }
+                if let Some(cursor) = cursor {
+                    body["start_cursor"] = json!(cursor);
+                }
+                let path = path.clone();
+                async move { self.post(&path, &body).await }
+            },
+            max_results,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_paginate_follows_cursor() {
+        let pages = vec![
+            json!({ "results": [{"id": "a"}, {"id": "b"}], "has_more": true, "next_cursor": "c1" }),
+            json!({ "results": [{"id": "c"}], "has_more": true, "next_cursor": "c2" }),
+            json!({ "results": [{"id": "d"}], "has_more": false, "next_cursor": null }),
+        ];
+        let seen = Mutex::new(Vec::new());
+        let results = paginate(
+            |cursor| {
+                seen.lock().unwrap().push(cursor.clone());
+                let idx = seen.lock().unwrap().len() - 1;
+                let page = pages[idx].clone();
+                async move { Ok(page) }
+            },
+            100,
+        )
+        .await
+        .unwrap();
+
+        let ids: Vec<&str> = results.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, vec!["a", "b", "c", "d"]);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![None, Some("c1".to_string()), Some("c2".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_paginate_stops_at_max_results() {
+        let calls = Mutex::new(0);
+        let results = paginate(
+            |_| {
+                *calls.lock().unwrap() += 1;
+                async { Ok(json!({ "results": [{"id": 1}, {"id": 2}], "has_more": true, "next_cursor": "x" })) }
+            },
+            3,
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(*calls.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_map_error() {
+        let body = json!({ "object": "error", "code": "validation_error", "message": "bad filter" });
+        assert!(matches!(
+            map_error(StatusCode::BAD_REQUEST, &body, None),
+            NotionError::InvalidRequest { ref code, ref message } if code == "validation_error" && message == "bad filter"
+        ));
+        assert!(matches!(
+            map_error(StatusCode::UNAUTHORIZED, &json!({}), None),
+            NotionError::Unauthorized { status: 401, .. }
+        ));
+        assert!(matches!(
+            map_error(StatusCode::NOT_FOUND, &json!({ "message": "gone" }), None),
+            NotionError::NotFound { .. }
+        ));
+        assert!(matches!(
+            map_error(StatusCode::TOO_MANY_REQUESTS, &json!({}), Some(7)),
+            NotionError::RateLimited { retry_after_secs: Some(7) }
+        ));
+        let err = map_error(StatusCode::BAD_GATEWAY, &json!({}), None);
+        assert_eq!(err.to_string(), "Notion API returned 502: unknown error");
+    }
+}
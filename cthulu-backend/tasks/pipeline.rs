@@ -2,15 +2,28 @@ use std::sync::Arc;
 
 use anyhow::{Context, Result};
 
-use crate::config::SinkConfig;
+use crate::config::{SinkConfig, WorkingDirPolicy};
 use crate::tasks::sinks::Sink;
+use crate::tasks::sinks::discord::DiscordWebhookSink;
+use crate::tasks::sinks::email::{EmailSink, SmtpSettings};
+use crate::tasks::sinks::file::{self, FileSink};
+use crate::tasks::sinks::http_post::HttpPostSink;
 use crate::tasks::sinks::notion::NotionSink;
-use crate::tasks::sinks::slack::{SlackApiSink, SlackWebhookSink};
+use crate::tasks::sinks::slack::{SlackApiSink, SlackMessageOptions, SlackWebhookSink};
+use crate::tasks::sinks::telegram::TelegramSink;
 use crate::tasks::sources::ContentItem;
+use crate::tasks::sources::rest::resolve_headers;
 
+/// `flow_id`, `dir_policy` and `data_dir` are for file sinks: the path
+/// template, the directories it may write to, and where it is confined when
+/// the policy has no allowlist. `flow_name` fills email subjects.
 pub fn resolve_sinks(
     configs: &[SinkConfig],
     http_client: &Arc<reqwest::Client>,
+    flow_id: &str,
+    flow_name: &str,
+    dir_policy: &WorkingDirPolicy,
+    data_dir: Option<&std::path::Path>,
 ) -> Result<Vec<Arc<dyn Sink>>> {
     let mut sinks: Vec<Arc<dyn Sink>> = Vec::with_capacity(configs.len());
 
@@ -20,7 +33,19 @@ pub fn resolve_sinks(
                 webhook_url_env,
                 bot_token_env,
                 channel,
+                unfurl_links,
+                unfurl_media,
+                sanitize_mentions,
+                max_blocks,
+                overflow_to_thread,
             } => {
+                let options = SlackMessageOptions {
+                    unfurl_links: *unfurl_links,
+                    unfurl_media: *unfurl_media,
+                    sanitize_mentions: *sanitize_mentions,
+                    max_blocks: *max_blocks,
+                    overflow_to_thread: *overflow_to_thread,
+                };
                 if let Some(token_env) = bot_token_env {
                     let bot_token = std::env::var(token_env).with_context(|| {
                         format!("sink requires env var {token_env} but it is not set")
@@ -32,6 +57,7 @@ pub fn resolve_sinks(
                         Arc::clone(http_client),
                         bot_token,
                         channel.clone(),
+                        options,
                     )));
                 } else if let Some(webhook_env) = webhook_url_env {
                     let webhook_url = std::env::var(webhook_env).with_context(|| {
@@ -40,14 +66,49 @@ pub fn resolve_sinks(
                     sinks.push(Arc::new(SlackWebhookSink::new(
                         Arc::clone(http_client),
                         webhook_url,
+                        options,
                     )));
                 } else {
                     anyhow::bail!("slack sink requires either webhook_url_env or bot_token_env");
                 }
             }
+            SinkConfig::Discord { webhook_url_env } => {
+                let webhook_url = std::env::var(webhook_url_env).with_context(|| {
+                    format!("sink requires env var {webhook_url_env} but it is not set")
+                })?;
+                sinks.push(Arc::new(DiscordWebhookSink::new(Arc::clone(http_client), webhook_url)));
+            }
+            SinkConfig::Telegram { bot_token_env, chat_id } => {
+                let bot_token = std::env::var(bot_token_env).with_context(|| {
+                    format!("sink requires env var {bot_token_env} but it is not set")
+                })?;
+                sinks.push(Arc::new(TelegramSink::new(
+                    Arc::clone(http_client),
+                    bot_token,
+                    chat_id.clone(),
+                )));
+            }
+            SinkConfig::File { path } => {
+                sinks.push(Arc::new(FileSink::new(
+                    path.clone(),
+                    flow_id.to_string(),
+                    dir_policy.clone(),
+                    data_dir.map(|dir| dir.join(file::DEFAULT_ROOT_SUBDIR)),
+                )));
+            }
+            SinkConfig::HttpPost { url, headers, json } => {
+                let headers = resolve_headers(headers, |var| std::env::var(var).ok())?;
+                sinks.push(Arc::new(HttpPostSink::new(
+                    Arc::clone(http_client),
+                    url.clone(),
+                    headers,
+                    *json,
+                )));
+            }
             SinkConfig::Notion {
                 token_env,
                 database_id,
+                append_to_page,
             } => {
                 let token = std::env::var(token_env).with_context(|| {
                     format!("sink requires env var {token_env} but it is not set")
@@ -56,6 +117,35 @@ pub fn resolve_sinks(
                     Arc::clone(http_client),
                     token,
                     database_id.clone(),
+                )
+                .with_append_to_page(append_to_page.clone())));
+            }
+            SinkConfig::Email {
+                smtp_host_env,
+                smtp_user_env,
+                smtp_pass_env,
+                from,
+                to,
+                subject,
+            } => {
+                let env = |var: &str| {
+                    std::env::var(var).with_context(|| {
+                        format!("sink requires env var {var} but it is not set")
+                    })
+                };
+                let (host, port) = SmtpSettings::parse_host(&env(smtp_host_env)?)?;
+                let smtp = SmtpSettings {
+                    host,
+                    port,
+                    username: env(smtp_user_env)?,
+                    password: env(smtp_pass_env)?,
+                };
+                sinks.push(Arc::new(EmailSink::new(
+                    smtp,
+                    from.clone(),
+                    to.clone(),
+                    subject.clone(),
+                    flow_name.to_string(),
                 )));
             }
         }
@@ -64,7 +154,19 @@ pub fn resolve_sinks(
     Ok(sinks)
 }
 
+/// Rendering switches for `format_items_with`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FormatOptions {
+    /// Render `image_url` as markdown image syntax (`![title](url)`) so
+    /// image-aware sinks can turn it into a thumbnail.
+    pub include_images: bool,
+}
+
 pub fn format_items(items: &[ContentItem]) -> String {
+    format_items_with(items, FormatOptions::default())
+}
+
+pub fn format_items_with(items: &[ContentItem], options: FormatOptions) -> String {
     if items.is_empty() {
         return "No items fetched.".to_string();
     }
@@ -88,11 +190,11 @@ pub fn format_items(items: &[ContentItem]) -> String {
                 item.summary.clone()
             };
 
-            let image_line = item
-                .image_url
-                .as_deref()
-                .map(|u| format!("\n   Image: {u}"))
-                .unwrap_or_default();
+            let image_line = match item.image_url.as_deref() {
+                Some(u) if options.include_images => format!("\n   ![{}]({u})", item.title),
+                Some(u) => format!("\n   Image: {u}"),
+                None => String::new(),
+            };
 
             format!(
                 "{}. [{}]({})\n   Published: {}{}\n   {}\n",
@@ -108,6 +210,40 @@ pub fn format_items(items: &[ContentItem]) -> String {
         .join("\n")
 }
 
+/// Format items so the result fits in `max_chars`, dropping whole items from
+/// the end (the lowest-ranked ones) and noting how many were left out.
+/// Returns the content and the number of omitted items.
+pub fn format_items_within(
+    items: &[ContentItem],
+    options: FormatOptions,
+    max_chars: usize,
+) -> (String, usize) {
+    let render = |keep: usize| {
+        let omitted = items.len() - keep;
+        match (keep, omitted) {
+            (_, 0) => format_items_with(items, options),
+            (0, _) => format!("[{omitted} items omitted]"),
+            _ => format!(
+                "{}\n[{omitted} items omitted]",
+                format_items_with(&items[..keep], options)
+            ),
+        }
+    };
+
+    // Content length grows with the number of items kept, so binary-search
+    // for the largest prefix that fits.
+    let (mut lo, mut hi) = (0, items.len());
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        if render(mid).chars().count() <= max_chars {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    (render(lo), items.len() - lo)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,4 +279,55 @@ mod tests {
         assert!(!result.contains("Image: https://example.com/1"));
         assert!(result.contains("Image: https://example.com/eth.jpg"));
     }
+
+    #[test]
+    fn test_format_items_with_images_as_markdown() {
+        let items = vec![
+            ContentItem {
+                title: "ETH Update".to_string(),
+                url: "https://example.com/2".to_string(),
+                summary: "Ethereum ships a major update.".to_string(),
+                published: None,
+                image_url: Some("https://example.com/eth.jpg".to_string()),
+            },
+            ContentItem {
+                title: "No Picture".to_string(),
+                url: "https://example.com/3".to_string(),
+                summary: "Text only.".to_string(),
+                published: None,
+                image_url: None,
+            },
+        ];
+        let result = format_items_with(&items, FormatOptions { include_images: true });
+        assert!(result.contains("![ETH Update](https://example.com/eth.jpg)"));
+        assert!(!result.contains("Image: "));
+        assert_eq!(result.matches("![").count(), 1);
+    }
+
+    #[test]
+    fn test_format_items_within_truncates_at_item_boundaries() {
+        let items: Vec<ContentItem> = (1..=5)
+            .map(|i| ContentItem {
+                title: format!("Item {i}"),
+                url: format!("https://example.com/{i}"),
+                summary: "x".repeat(100),
+                published: None,
+                image_url: None,
+            })
+            .collect();
+        let full = format_items(&items);
+
+        let (content, omitted) = format_items_within(&items, FormatOptions::default(), full.len());
+        assert_eq!((content.as_str(), omitted), (full.as_str(), 0));
+
+        let two = format_items(&items[..2]);
+        let (content, omitted) =
+            format_items_within(&items, FormatOptions::default(), two.len() + 40);
+        assert_eq!(omitted, 3);
+        assert_eq!(content, format!("{two}\n[3 items omitted]"));
+        assert!(content.contains("Item 2") && !content.contains("Item 3"));
+
+        let (content, omitted) = format_items_within(&items, FormatOptions::default(), 10);
+        assert_eq!((content.as_str(), omitted), ("[5 items omitted]", 5));
+    }
 }
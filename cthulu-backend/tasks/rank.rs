@@ -0,0 +1,117 @@
+use std::cmp::Reverse;
+
+use crate::tasks::sources::ContentItem;
+
+/// Optional reordering applied to content items before they are formatted
+/// into an executor prompt, so the most relevant items come first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankMode {
+    /// Newest `published` first; undated items last.
+    Recency,
+    /// Items from higher-`priority` source nodes first (applied when merging
+    /// upstream outputs, since items don't carry their source).
+    SourcePriority,
+    /// Items matching more of the configured keywords first.
+    KeywordScore,
+}
+
+impl RankMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "recency" => Some(Self::Recency),
+            "source_priority" | "source-priority" => Some(Self::SourcePriority),
+            "keyword_score" | "keyword-score" => Some(Self::KeywordScore),
+            _ => None,
+        }
+    }
+}
+
+/// Stable sort by `published` descending, undated items last.
+pub fn rank_by_recency(items: &mut [ContentItem]) {
+    items.sort_by_key(|item| (item.published.is_none(), Reverse(item.published)));
+}
+
+/// Number of keyword hits for an item. Title matches count double.
+pub fn keyword_score(item: &ContentItem, keywords: &[String]) -> usize {
+    let title = item.title.to_lowercase();
+    let summary = item.summary.to_lowercase();
+    keywords
+        .iter()
+        .map(|kw| kw.to_lowercase())
+        .filter(|kw| !kw.is_empty())
+        .map(|kw| 2 * title.matches(&kw).count() + summary.matches(&kw).count())
+        .sum()
+}
+
+/// Stable sort by keyword score descending.
+pub fn rank_by_keyword_score(items: &mut [ContentItem], keywords: &[String]) {
+    items.sort_by_cached_key(|item| Reverse(keyword_score(item, keywords)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn item(title: &str, summary: &str, day: Option<u32>) -> ContentItem {
+        ContentItem {
+            title: title.to_string(),
+            url: String::new(),
+            summary: summary.to_string(),
+            published: day.map(|d| Utc.with_ymd_and_hms(2026, 1, d, 0, 0, 0).unwrap()),
+            image_url: None,
+        }
+    }
+
+    fn titles(items: &[ContentItem]) -> Vec<&str> {
+        items.iter().map(|i| i.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_rank_mode() {
+        assert_eq!(RankMode::parse("recency"), Some(RankMode::Recency));
+        assert_eq!(RankMode::parse("source_priority"), Some(RankMode::SourcePriority));
+        assert_eq!(RankMode::parse("keyword-score"), Some(RankMode::KeywordScore));
+        assert_eq!(RankMode::parse("random"), None);
+    }
+
+    #[test]
+    fn test_rank_by_recency() {
+        let mut items = vec![
+            item("old", "", Some(1)),
+            item("undated", "", None),
+            item("new", "", Some(20)),
+            item("mid", "", Some(10)),
+        ];
+        rank_by_recency(&mut items);
+        assert_eq!(titles(&items), vec!["new", "mid", "old", "undated"]);
+    }
+
+    #[test]
+    fn test_rank_by_recency_is_stable_for_ties() {
+        let mut items = vec![
+            item("a", "", Some(5)),
+            item("b", "", Some(5)),
+            item("c", "", None),
+            item("d", "", None),
+        ];
+        rank_by_recency(&mut items);
+        assert_eq!(titles(&items), vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_rank_by_keyword_score() {
+        let keywords = vec!["rust".to_string(), "async".to_string()];
+        let mut items = vec![
+            item("Weather report", "sunny", None),
+            item("Async in practice", "nothing else", None),
+            item("Rust async deep dive", "more rust", None),
+            item("Cooking", "mentions rust once", None),
+        ];
+        rank_by_keyword_score(&mut items, &keywords);
+        assert_eq!(
+            titles(&items),
+            vec!["Rust async deep dive", "Async in practice", "Cooking", "Weather report"]
+        );
+    }
+}
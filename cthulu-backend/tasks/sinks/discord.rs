@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::split::split_message;
+use super::retry::Retrier;
+use super::{retry_after_secs, DeliveryError, Sink};
+
+/// Discord rejects message content longer than this (in characters).
+pub const MAX_MESSAGE_CHARS: usize = 2000;
+
+const FENCE: &str = "```";
+
+pub struct DiscordWebhookSink {
+    http_client: Arc<reqwest::Client>,
+    webhook_url: String,
+}
+
+impl DiscordWebhookSink {
+    pub fn new(http_client: Arc<reqwest::Client>, webhook_url: String) -> Self {
+        Self { http_client, webhook_url }
+    }
+}
+
+#[async_trait]
+impl Sink for DiscordWebhookSink {
+    /// Posts each chunk in order; the receipt is the message ids, comma-joined.
+    async fn deliver(&self, text: &str, retry: &Retrier) -> Result<Option<String>> {
+        let content = to_discord_markdown(text);
+        let chunks = split_message(&content, MAX_MESSAGE_CHARS);
+
+        let mut ids = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.iter().enumerate() {
+            let id = retry
+                .run(|| async {
+                    let response = self
+                        .http_client
+                        .post(&self.webhook_url)
+                        .query(&[("wait", "true")])
+                        .json(&json!({
+                            "content": chunk,
+                            // Model output must never ping @everyone / @here or users
+                            "allowed_mentions": { "parse": [] },
+                        }))
+                        .send()
+                        .await
+                        .context("failed to post to Discord webhook")?;
+
+                    if !response.status().is_success() {
+                        let status = response.status();
+                        let retry_after_secs = retry_after_secs(response.headers());
+                        let body = response.text().await.unwrap_or_default();
+                        return Err(DeliveryError {
+                            status,
+                            retry_after_secs,
+                            message: format!("Discord webhook returned {status} for part {}/{}: {body}", i + 1, chunks.len()),
+                        }
+                        .into());
+                    }
+
+                    let message: serde_json::Value = response.json().await.unwrap_or_default();
+                    Ok(message["id"].as_str().map(str::to_string))
+                })
+                .await?;
+            ids.extend(id);
+        }
+
+        tracing::info!(parts = chunks.len(), "Delivered message to Discord");
+        Ok((!ids.is_empty()).then(|| ids.join(",")))
+    }
+}
+
+/// Markdown for Discord, which renders most of it natively. Slack-style
+/// `*bold*` becomes `**bold**`; `**bold**`, `* bullets` and code are kept.
+pub fn to_discord_markdown(text: &str) -> String {
+    let mut in_fence = false;
+    text.lines()
+        .map(|line| {
+            if line.trim_start().starts_with(FENCE) {
+                in_fence = !in_fence;
+                return line.to_string();
+            }
+            if in_fence { line.to_string() } else { slack_bold_to_discord(line) }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Rewrite lone-asterisk emphasis (`*word*`) on one line to `**word**`,
+/// leaving `**`, list markers and inline code alone.
+fn slack_bold_to_discord(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let is_lone_star = |i: usize| {
+        chars[i] == '*' && (i == 0 || chars[i - 1] != '*') && chars.get(i + 1) != Some(&'*')
+    };
+
+    let mut out = String::with_capacity(line.len() + 4);
+    let mut in_code = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '`' {
+            in_code = !in_code;
+        } else if !in_code
+            && is_lone_star(i)
+            && chars.get(i + 1).is_some_and(|next| !next.is_whitespace())
+        {
+            // Closing star: not after whitespace, not before a word char
+            let close = (i + 1..chars.len()).find(|&j| {
+                is_lone_star(j)
+                    && !chars[j - 1].is_whitespace()
+                    && !chars.get(j + 1).is_some_and(|c| c.is_alphanumeric())
+            });
+            if let Some(close) = close {
+                out.push_str("**");
+                out.extend(&chars[i + 1..close]);
+                out.push_str("**");
+                i = close + 1;
+                continue;
+            }
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slack_bold_becomes_discord_bold() {
+        assert_eq!(to_discord_markdown("*BTC* up 5%"), "**BTC** up 5%");
+        assert_eq!(to_discord_markdown("a *daily brief* today"), "a **daily brief** today");
+        // Already-Discord markdown is left as-is
+        assert_eq!(to_discord_markdown("**BTC** up"), "**BTC** up");
+        assert_eq!(to_discord_markdown("* bullet item"), "* bullet item");
+        assert_eq!(to_discord_markdown("2 * 3 * 4"), "2 * 3 * 4");
+        assert_eq!(to_discord_markdown("`*not bold*` but *this*"), "`*not bold*` but **this**");
+        assert_eq!(to_discord_markdown("```\n*raw*\n```"), "```\n*raw*\n```");
+    }
+}
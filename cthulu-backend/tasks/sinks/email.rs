@@ -0,0 +1,300 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use super::slack::markdown::{find_closing_double_star, parse_md_link};
+use super::retry::Retrier;
+use super::Sink;
+
+/// Port used when the host env var has no `:port`; 465 means implicit TLS,
+/// anything else STARTTLS.
+const DEFAULT_SMTP_PORT: u16 = 587;
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+const FENCE: &str = "```";
+
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+}
+
+impl SmtpSettings {
+    /// Split `host` or `host:port` as read from the host env var.
+    pub fn parse_host(value: &str) -> Result<(String, u16)> {
+        let value = value.trim();
+        match value.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port
+                    .parse()
+                    .with_context(|| format!("invalid SMTP port in '{value}'"))?;
+                Ok((host.to_string(), port))
+            }
+            None => Ok((value.to_string(), DEFAULT_SMTP_PORT)),
+        }
+    }
+}
+
+/// Sends the output as a multipart email: the markdown as plain text and a
+/// basic HTML rendering.
+pub struct EmailSink {
+    smtp: SmtpSettings,
+    from: String,
+    to: Vec<String>,
+    subject_template: String,
+    flow_name: String,
+}
+
+impl EmailSink {
+    pub fn new(
+        smtp: SmtpSettings,
+        from: String,
+        to: Vec<String>,
+        subject_template: String,
+        flow_name: String,
+    ) -> Self {
+        Self { smtp, from, to, subject_template, flow_name }
+    }
+
+    fn build_message(&self, text: &str, subject: &str) -> Result<Message> {
+        let from: Mailbox = self
+            .from
+            .parse()
+            .with_context(|| format!("invalid email sink 'from' address '{}'", self.from))?;
+        let mut builder = Message::builder().from(from).subject(subject);
+        for to in &self.to {
+            let mailbox: Mailbox = to
+                .parse()
+                .with_context(|| format!("invalid email sink 'to' address '{to}'"))?;
+            builder = builder.to(mailbox);
+        }
+        builder
+            .multipart(MultiPart::alternative_plain_html(
+                text.to_string(),
+                markdown_to_html(text),
+            ))
+            .context("failed to build email")
+    }
+}
+
+/// Fill `{flow_name}` and `{date}` (UTC, `YYYY-MM-DD`) in a subject template.
+pub fn render_subject(template: &str, flow_name: &str, date: &str) -> String {
+    template.replace("{flow_name}", flow_name).replace("{date}", date)
+}
+
+#[async_trait]
+impl Sink for EmailSink {
+    /// The receipt is the recipient list, comma-joined.
+    async fn deliver(&self, text: &str, _retry: &Retrier) -> Result<Option<String>> {
+        let date = Utc::now().format("%Y-%m-%d").to_string();
+        let subject = render_subject(&self.subject_template, &self.flow_name, &date);
+        let message = self.build_message(text, &subject)?;
+
+        let SmtpSettings { host, port, username, password } = &self.smtp;
+        let relay = if *port == 465 {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+        }
+        .with_context(|| format!("invalid SMTP host '{host}'"))?;
+        let mailer = relay
+            .port(*port)
+            .credentials(Credentials::new(username.clone(), password.clone()))
+            .timeout(Some(SMTP_TIMEOUT))
+            .build();
+
+        // Covers connect, TLS and auth failures as well as rejected recipients
+        mailer
+            .send(message)
+            .await
+            .map_err(|e| anyhow::anyhow!("SMTP delivery via {host}:{port} failed: {e}"))?;
+
+        tracing::info!(recipients = self.to.len(), "Delivered message by email");
+        Ok(Some(self.to.join(",")))
+    }
+}
+
+/// Render markdown as basic HTML: headings, `-` / `*` bullets, paragraphs,
+/// fenced code, `---` rules, and inline `**bold**`, `` `code` `` and links.
+/// Everything else is escaped text.
+pub fn markdown_to_html(input: &str) -> String {
+    let mut html = String::with_capacity(input.len() * 2);
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut in_list = false;
+    let mut code: Option<Vec<&str>> = None;
+
+    fn flush_paragraph(html: &mut String, paragraph: &mut Vec<String>) {
+        if !paragraph.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", paragraph.join("<br>\n")));
+            paragraph.clear();
+        }
+    }
+    fn close_list(html: &mut String, in_list: &mut bool) {
+        if *in_list {
+            html.push_str("</ul>\n");
+            *in_list = false;
+        }
+    }
+
+    for line in input.lines() {
+        let trimmed = line.trim();
+
+        if let Some(lines) = &mut code {
+            if trimmed.starts_with(FENCE) {
+                html.push_str(&format!("<pre><code>{}</code></pre>\n", escape(&lines.join("\n"))));
+                code = None;
+            } else {
+                lines.push(line);
+            }
+            continue;
+        }
+        if trimmed.starts_with(FENCE) {
+            flush_paragraph(&mut html, &mut paragraph);
+            close_list(&mut html, &mut in_list);
+            code = Some(Vec::new());
+            continue;
+        }
+
+        if let Some(rest) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+            flush_paragraph(&mut html, &mut paragraph);
+            if !in_list {
+                html.push_str("<ul>\n");
+                in_list = true;
+            }
+            html.push_str(&format!("<li>{}</li>\n", convert_inline(rest.trim())));
+            continue;
+        }
+        close_list(&mut html, &mut in_list);
+
+        if trimmed.is_empty() {
+            flush_paragraph(&mut html, &mut paragraph);
+            continue;
+        }
+        if trimmed == "---" || trimmed == "***" || trimmed == "___" {
+            flush_paragraph(&mut html, &mut paragraph);
+            html.push_str("<hr>\n");
+            continue;
+        }
+        let heading = [("### ", "h3"), ("## ", "h2"), ("# ", "h1")]
+            .into_iter()
+            .find_map(|(prefix, tag)| trimmed.strip_prefix(prefix).map(|rest| (tag, rest)));
+        if let Some((tag, rest)) = heading {
+            flush_paragraph(&mut html, &mut paragraph);
+            html.push_str(&format!("<{tag}>{}</{tag}>\n", convert_inline(rest.trim())));
+            continue;
+        }
+        paragraph.push(convert_inline(trimmed));
+    }
+
+    // An unclosed fence still shows its code
+    if let Some(lines) = code {
+        html.push_str(&format!("<pre><code>{}</code></pre>\n", escape(&lines.join("\n"))));
+    }
+    flush_paragraph(&mut html, &mut paragraph);
+    close_list(&mut html, &mut in_list);
+    html
+}
+
+fn convert_inline(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len() + 16);
+    let mut i = 0;
+    while i < chars.len() {
+        let formatted = match chars[i] {
+            '`' => inline_code(&chars, i),
+            '*' if chars.get(i + 1) == Some(&'*') => bold(&chars, i),
+            '[' => link(&chars, i),
+            _ => None,
+        };
+        if let Some((formatted, end)) = formatted {
+            out.push_str(&formatted);
+            i = end;
+            continue;
+        }
+        push_escaped(&mut out, chars[i]);
+        i += 1;
+    }
+    out
+}
+
+fn inline_code(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let close = (start + 1..chars.len()).find(|&j| chars[j] == '`')?;
+    let code: String = chars[start + 1..close].iter().collect();
+    Some((format!("<code>{}</code>", escape(&code)), close + 1))
+}
+
+fn bold(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let close = find_closing_double_star(chars, start + 2)?;
+    if close == start + 2 {
+        return None;
+    }
+    let inner: String = chars[start + 2..close].iter().collect();
+    Some((format!("<strong>{}</strong>", convert_inline(&inner)), close + 2))
+}
+
+fn link(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let (text, url, end) = parse_md_link(chars, start)?;
+    // Only web links; anything else (e.g. `javascript:`) stays text
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return None;
+    }
+    Some((format!("<a href=\"{}\">{}</a>", escape(&url), escape(&text)), end))
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        '\'' => out.push_str("&#39;"),
+        c => out.push(c),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        push_escaped(&mut out, c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_to_html() {
+        let md = "# Weekly <Report>\n\nLine one with **bold** & `a<b>`.\nLine two: [docs](https://e.com/?a=1&b=2)\n\n- first\n- second\n\n---\n```\nlet x = 1 < 2;\n```";
+        assert_eq!(
+            markdown_to_html(md),
+            "<h1>Weekly &lt;Report&gt;</h1>\n\
+             <p>Line one with <strong>bold</strong> &amp; <code>a&lt;b&gt;</code>.<br>\n\
+             Line two: <a href=\"https://e.com/?a=1&amp;b=2\">docs</a></p>\n\
+             <ul>\n<li>first</li>\n<li>second</li>\n</ul>\n\
+             <hr>\n\
+             <pre><code>let x = 1 &lt; 2;</code></pre>\n"
+        );
+        // Non-web links are left as text
+        assert_eq!(markdown_to_html("[x](javascript:alert(1))"), "<p>[x](javascript:alert(1))</p>\n");
+    }
+
+    #[test]
+    fn test_subject_and_host_parsing() {
+        assert_eq!(
+            render_subject("{flow_name} report for {date}", "Weekly", "2026-01-02"),
+            "Weekly report for 2026-01-02"
+        );
+        assert_eq!(SmtpSettings::parse_host("smtp.e.com").unwrap(), ("smtp.e.com".into(), 587));
+        assert_eq!(SmtpSettings::parse_host("smtp.e.com:465").unwrap(), ("smtp.e.com".into(), 465));
+        assert!(SmtpSettings::parse_host("smtp.e.com:nope").is_err());
+    }
+}
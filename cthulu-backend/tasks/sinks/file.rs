@@ -0,0 +1,186 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+
+use super::retry::Retrier;
+use super::Sink;
+use crate::config::WorkingDirPolicy;
+
+/// Where file sinks write when no working-dir allowlist is configured,
+/// relative to the data dir.
+pub const DEFAULT_ROOT_SUBDIR: &str = "sink_files";
+
+/// Appends each delivery to a JSONL file as `{"timestamp", "text"}`.
+///
+/// Paths must pass the working-dir allowlist. Without one, they are confined
+/// to `default_root` (relative templates resolve under it), and a sink with
+/// neither refuses to write.
+pub struct FileSink {
+    path_template: String,
+    flow_id: String,
+    dir_policy: WorkingDirPolicy,
+    default_root: Option<PathBuf>,
+}
+
+impl FileSink {
+    pub fn new(
+        path_template: String,
+        flow_id: String,
+        dir_policy: WorkingDirPolicy,
+        default_root: Option<PathBuf>,
+    ) -> Self {
+        Self { path_template, flow_id, dir_policy, default_root }
+    }
+
+    /// The policy paths are checked against, and the path to write.
+    fn confine(&self, path: PathBuf) -> Result<(WorkingDirPolicy, PathBuf)> {
+        if !self.dir_policy.allowed_roots.is_empty() {
+            return Ok((self.dir_policy.clone(), path));
+        }
+        let root = self
+            .default_root
+            .clone()
+            .context("file sinks need NODE_WORKING_DIR_ALLOWLIST or a data dir to write under")?;
+        std::fs::create_dir_all(&root)
+            .with_context(|| format!("failed to create file sink root {}", root.display()))?;
+        // Joining an absolute path keeps it as is; the policy then rejects it
+        let path = root.join(path);
+        let policy = WorkingDirPolicy { allowed_roots: vec![root], strict: true };
+        Ok((policy, path))
+    }
+}
+
+/// Fill `{flow_id}` and `{date}` (UTC, `YYYY-MM-DD`) in a path template.
+pub fn render_path(template: &str, flow_id: &str, date: &str) -> PathBuf {
+    PathBuf::from(template.replace("{flow_id}", flow_id).replace("{date}", date))
+}
+
+#[async_trait]
+impl Sink for FileSink {
+    /// The receipt is the path written.
+    async fn deliver(&self, text: &str, _retry: &Retrier) -> Result<Option<String>> {
+        let now = Utc::now();
+        let path = render_path(&self.path_template, &self.flow_id, &now.format("%Y-%m-%d").to_string());
+        let (dir_policy, path) = self.confine(path)?;
+        let file_name = path
+            .file_name()
+            .with_context(|| format!("file sink path '{}' has no file name", path.display()))?;
+        // The directory goes through the working_dir allowlist before anything is created
+        let parent = match path.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p,
+            _ => Path::new("."),
+        };
+        let dir = dir_policy
+            .create(parent)
+            .with_context(|| format!("file sink path '{}' is not allowed", path.display()))?;
+        let path = dir.join(file_name);
+        // The dir is canonical, but the file itself could link anywhere
+        if tokio::fs::symlink_metadata(&path).await.is_ok_and(|m| m.file_type().is_symlink()) {
+            anyhow::bail!("file sink path '{}' is a symlink", path.display());
+        }
+
+        let mut line = serde_json::to_string(&json!({ "timestamp": now, "text": text }))?;
+        line.push('\n');
+        // One append-mode write per line, so concurrent runs never interleave
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        file.write_all(line.as_bytes())
+            .await
+            .with_context(|| format!("failed to append to {}", path.display()))?;
+
+        tracing::info!(path = %path.display(), "Appended output to file");
+        Ok(Some(path.display().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(root: &Path) -> WorkingDirPolicy {
+        WorkingDirPolicy { allowed_roots: vec![root.to_path_buf()], strict: true }
+    }
+
+    #[test]
+    fn test_render_path() {
+        assert_eq!(
+            render_path("/srv/out/{flow_id}/{date}.jsonl", "f1", "2026-01-02"),
+            PathBuf::from("/srv/out/f1/2026-01-02.jsonl")
+        );
+        assert_eq!(render_path("out.jsonl", "f1", "2026-01-02"), PathBuf::from("out.jsonl"));
+    }
+
+    #[tokio::test]
+    async fn test_deliveries_append_json_lines() {
+        let root = tempfile::tempdir().unwrap();
+        let template = format!("{}/archive/{{flow_id}}.jsonl", root.path().display());
+        let sink = FileSink::new(template, "f1".to_string(), policy(root.path()), None);
+
+        let receipt = sink.deliver("first", &Retrier::once()).await.unwrap().unwrap();
+        sink.deliver("second\nline", &Retrier::once()).await.unwrap();
+
+        assert!(receipt.ends_with("archive/f1.jsonl"));
+        let content = std::fs::read_to_string(&receipt).unwrap();
+        let lines: Vec<serde_json::Value> =
+            content.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["text"], "first");
+        assert_eq!(lines[1]["text"], "second\nline");
+        assert!(lines[1]["timestamp"].as_str().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_paths_outside_allowlist_are_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let sink = FileSink::new("/etc/cthulu.jsonl".to_string(), "f1".to_string(), policy(root.path()), None);
+        let err = sink.deliver("x", &Retrier::once()).await.unwrap_err();
+        assert!(format!("{err:#}").contains("outside the allowed directories"), "{err:#}");
+
+        let escape = format!("{}/../escape/out.jsonl", root.path().display());
+        let sink = FileSink::new(escape, "f1".to_string(), policy(root.path()), None);
+        assert!(sink.deliver("x", &Retrier::once()).await.is_err());
+        assert!(!root.path().parent().unwrap().join("escape").exists());
+    }
+
+    #[tokio::test]
+    async fn test_without_allowlist_writes_are_confined_to_default_root() {
+        let data = tempfile::tempdir().unwrap();
+        let root = data.path().join(DEFAULT_ROOT_SUBDIR);
+        let open = WorkingDirPolicy::default();
+
+        let sink = FileSink::new("archive/{flow_id}.jsonl".to_string(), "f1".to_string(), open.clone(), Some(root.clone()));
+        let receipt = sink.deliver("x", &Retrier::once()).await.unwrap().unwrap();
+        assert!(std::path::Path::new(&receipt).starts_with(root.canonicalize().unwrap()));
+
+        let sink = FileSink::new("/etc/cthulu.jsonl".to_string(), "f1".to_string(), open.clone(), Some(root.clone()));
+        assert!(sink.deliver("x", &Retrier::once()).await.is_err());
+
+        // Neither an allowlist nor a root: nothing is written
+        let sink = FileSink::new("out.jsonl".to_string(), "f1".to_string(), open, None);
+        assert!(sink.deliver("x", &Retrier::once()).await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlinked_target_is_refused() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let target = outside.path().join("victim.txt");
+        std::fs::write(&target, "").unwrap();
+        std::os::unix::fs::symlink(&target, root.path().join("out.jsonl")).unwrap();
+
+        let template = format!("{}/out.jsonl", root.path().display());
+        let sink = FileSink::new(template, "f1".to_string(), policy(root.path()), None);
+        let err = sink.deliver("x", &Retrier::once()).await.unwrap_err();
+        assert!(err.to_string().contains("symlink"), "{err:#}");
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "");
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::header::CONTENT_TYPE;
+use serde_json::json;
+
+use super::retry::Retrier;
+use super::{retry_after_secs, DeliveryError, Sink};
+
+/// How much of an error response body is kept in the failure message.
+const MAX_ERROR_BODY_CHARS: usize = 500;
+
+/// POSTs output to an arbitrary endpoint, as `{"text": ...}` JSON or as a
+/// raw `text/plain` body.
+pub struct HttpPostSink {
+    http_client: Arc<reqwest::Client>,
+    url: String,
+    /// Already resolved (`${VAR}` expanded).
+    headers: Vec<(String, String)>,
+    json: bool,
+}
+
+impl HttpPostSink {
+    pub fn new(http_client: Arc<reqwest::Client>, url: String, headers: Vec<(String, String)>, json: bool) -> Self {
+        Self { http_client, url, headers, json }
+    }
+}
+
+impl HttpPostSink {
+    async fn post(&self, text: &str) -> Result<Option<String>> {
+        let mut request = self.http_client.post(&self.url);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        request = if self.json {
+            request.json(&json!({ "text": text }))
+        } else {
+            request.header(CONTENT_TYPE, "text/plain; charset=utf-8").body(text.to_string())
+        };
+
+        let response = request.send().await.context("failed to POST to HTTP sink endpoint")?;
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after_secs = retry_after_secs(response.headers());
+            let body = response.text().await.unwrap_or_default();
+            let body: String = body.chars().take(MAX_ERROR_BODY_CHARS).collect();
+            return Err(DeliveryError {
+                status,
+                retry_after_secs,
+                message: format!("HTTP sink endpoint returned {status}: {body}"),
+            }
+            .into());
+        }
+
+        tracing::info!(%status, "Delivered output to HTTP endpoint");
+        Ok(Some(status.to_string()))
+    }
+}
+
+#[async_trait]
+impl Sink for HttpPostSink {
+    /// The receipt is the response status.
+    async fn deliver(&self, text: &str, retry: &Retrier) -> Result<Option<String>> {
+        retry.run(|| self.post(text)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    /// Answer one request with `status` and `body`; resolves to the raw request.
+    async fn serve_once(status: &'static str, body: String) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = vec![0u8; 64 * 1024];
+            // Read until the headers and the announced body have arrived
+            loop {
+                let n = sock.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((head, rest)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .lines()
+                        .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length: ").map(|v| v.parse::<usize>().unwrap()))
+                        .unwrap_or(0);
+                    if rest.len() >= length || n == 0 {
+                        break;
+                    }
+                }
+            }
+            let resp = format!(
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = sock.write_all(resp.as_bytes()).await;
+            String::from_utf8_lossy(&request).to_string()
+        });
+        (format!("http://{addr}/hook"), handle)
+    }
+
+    #[tokio::test]
+    async fn test_posts_json_and_plain_text_with_headers() {
+        let client = Arc::new(reqwest::Client::new());
+
+        let (url, request) = serve_once("200 OK", String::new()).await;
+        let headers = vec![("Authorization".to_string(), "Bearer s3cret".to_string())];
+        let sink = HttpPostSink::new(Arc::clone(&client), url, headers, true);
+        assert_eq!(sink.deliver("BTC up", &Retrier::once()).await.unwrap().as_deref(), Some("200 OK"));
+        let request = request.await.unwrap().to_ascii_lowercase();
+        assert!(request.starts_with("post /hook"));
+        assert!(request.contains("authorization: bearer s3cret"));
+        assert!(request.contains("content-type: application/json"));
+        assert!(request.ends_with(r#"{"text":"btc up"}"#));
+
+        let (url, request) = serve_once("204 No Content", String::new()).await;
+        let sink = HttpPostSink::new(client, url, vec![], false);
+        sink.deliver("raw *body*", &Retrier::once()).await.unwrap();
+        let request = request.await.unwrap();
+        assert!(request.to_ascii_lowercase().contains("content-type: text/plain"));
+        assert!(request.ends_with("\r\n\r\nraw *body*"));
+    }
+
+    #[tokio::test]
+    async fn test_non_2xx_fails_with_status_and_body_excerpt() {
+        let (url, _) = serve_once("502 Bad Gateway", "x".repeat(2000)).await;
+        let sink = HttpPostSink::new(Arc::new(reqwest::Client::new()), url, vec![], true);
+        let err = sink.deliver("hi", &Retrier::once()).await.unwrap_err();
+
+        let delivery = err.downcast_ref::<DeliveryError>().unwrap();
+        assert_eq!(delivery.status, reqwest::StatusCode::BAD_GATEWAY);
+        assert_eq!(err.to_string(), format!("HTTP sink endpoint returned 502 Bad Gateway: {}", "x".repeat(500)));
+    }
+}
@@ -1,10 +1,44 @@
+pub mod discord;
+pub mod email;
+pub mod file;
+pub mod http_post;
 pub mod notion;
+pub mod retry;
 pub mod slack;
+pub mod split;
+pub mod telegram;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+use retry::Retrier;
 
 #[async_trait]
 pub trait Sink: Send + Sync {
-    async fn deliver(&self, text: &str) -> Result<()>;
+    /// Deliver `text`, returning an identifier for what was delivered (Slack
+    /// message `ts`, webhook or HTTP status, Discord or Telegram message ids,
+    /// Notion page id, file path, email recipients) when the sink has one.
+    /// Each request goes through `retry`, so a failure part-way through a
+    /// multi-part delivery repeats only that part.
+    async fn deliver(&self, text: &str, retry: &Retrier) -> Result<Option<String>>;
+}
+
+/// A destination answered with a non-success HTTP status. `retry` uses the
+/// status and `Retry-After` to decide whether delivery is worth repeating.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct DeliveryError {
+    pub status: StatusCode,
+    pub retry_after_secs: Option<u64>,
+    pub message: String,
+}
+
+/// Seconds from a `Retry-After` header (the delta-seconds form).
+pub fn retry_after_secs(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
 }
@@ -4,41 +4,81 @@ use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde_json::{json, Value};
 
+use super::retry::Retrier;
 use super::Sink;
+use crate::tasks::notion::NotionClient;
 
-const NOTION_API_VERSION: &str = "2022-06-28";
 const MAX_BLOCKS_PER_REQUEST: usize = 100;
 
 pub struct NotionSink {
-    http_client: Arc<reqwest::Client>,
-    token: String,
+    client: NotionClient,
     database_id: String,
+    append_to_page: Option<String>,
 }
 
 impl NotionSink {
     pub fn new(http_client: Arc<reqwest::Client>, token: String, database_id: String) -> Self {
         Self {
-            http_client,
-            token,
+            client: NotionClient::new(http_client, token),
             database_id,
+            append_to_page: None,
         }
     }
+
+    /// Append to this page instead of creating a row in `database_id`.
+    pub fn with_append_to_page(mut self, page_id: Option<String>) -> Self {
+        self.append_to_page = page_id;
+        self
+    }
+
+    /// Appends in chunks, retrying each chunk on its own so none lands twice.
+    async fn append_blocks(&self, page_id: &str, blocks: &[Value], retry: &Retrier) -> Result<()> {
+        for chunk in blocks.chunks(MAX_BLOCKS_PER_REQUEST) {
+            let append_body = json!({ "children": chunk });
+            let path = format!("blocks/{page_id}/children");
+
+            retry
+                .run(|| self.client.patch(&path, &append_body))
+                .await
+                .context("failed to append blocks to Notion page")?;
+        }
+        Ok(())
+    }
+}
+
+/// Notion ids are UUIDs; catch pasted URLs or names before calling the API.
+fn validate_notion_id(id: &str, field: &str, hint: &str) -> Result<()> {
+    let stripped: String = id.chars().filter(|c| *c != '-').collect();
+    if stripped.len() != 32 || !stripped.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!(
+            "Invalid Notion {field} '{id}' — must be a 32-hex-char UUID (with or without dashes). {hint}"
+        );
+    }
+    Ok(())
 }
 
 #[async_trait]
 impl Sink for NotionSink {
-    async fn deliver(&self, text: &str) -> Result<()> {
-        // Validate database_id looks like a UUID before calling the API
-        let stripped: String = self.database_id.chars().filter(|c| *c != '-').collect();
-        if stripped.len() != 32 || !stripped.chars().all(|c| c.is_ascii_hexdigit()) {
-            anyhow::bail!(
-                "Invalid Notion database_id '{}' — must be a 32-hex-char UUID (with or without dashes). \
-                 Copy it from the Notion database URL: notion.so/<workspace>/<database_id>?v=...",
-                self.database_id
-            );
+    async fn deliver(&self, text: &str, retry: &Retrier) -> Result<Option<String>> {
+        let blocks = markdown_to_notion_blocks(text);
+
+        if let Some(page_id) = &self.append_to_page {
+            validate_notion_id(
+                page_id,
+                "append_to_page",
+                "Copy it from the end of the Notion page URL.",
+            )?;
+            self.append_blocks(page_id, &blocks, retry).await?;
+            tracing::info!(blocks = blocks.len(), "Appended message to Notion page");
+            return Ok(Some(page_id.clone()));
         }
 
-        let blocks = markdown_to_notion_blocks(text);
+        validate_notion_id(
+            &self.database_id,
+            "database_id",
+            "Copy it from the Notion database URL: notion.so/<workspace>/<database_id>?v=...",
+        )?;
+
         let title = extract_title(text);
 
         // First batch: create page with up to 100 blocks
@@ -60,59 +100,20 @@ impl Sink for NotionSink {
             "children": first_batch,
         });
 
-        let response = self
-            .http_client
-            .post("https://api.notion.com/v1/pages")
-            .header("Authorization", format!("Bearer {}", self.token))
-            .header("Notion-Version", NOTION_API_VERSION)
-            .json(&body)
-            .send()
+        let resp_body = retry
+            .run(|| self.client.post("pages", &body))
             .await
             .context("failed to create Notion page")?;
 
-        let status = response.status();
-        let resp_body: Value = response
-            .json()
-            .await
-            .context("failed to parse Notion API response")?;
-
-        if !status.is_success() {
-            let msg = resp_body["message"]
-                .as_str()
-                .unwrap_or("unknown error");
-            anyhow::bail!("Notion API returned {status}: {msg}");
-        }
-
         let page_id = resp_body["id"]
             .as_str()
             .context("Notion response missing page id")?;
 
         // Append remaining blocks in chunks
-        for chunk in remaining.chunks(MAX_BLOCKS_PER_REQUEST) {
-            let append_body = json!({ "children": chunk });
-
-            let resp = self
-                .http_client
-                .patch(format!(
-                    "https://api.notion.com/v1/blocks/{page_id}/children"
-                ))
-                .header("Authorization", format!("Bearer {}", self.token))
-                .header("Notion-Version", NOTION_API_VERSION)
-                .json(&append_body)
-                .send()
-                .await
-                .context("failed to append blocks to Notion page")?;
-
-            if !resp.status().is_success() {
-                let status = resp.status();
-                let body: Value = resp.json().await.unwrap_or_default();
-                let msg = body["message"].as_str().unwrap_or("unknown error");
-                anyhow::bail!("Notion append blocks returned {status}: {msg}");
-            }
-        }
+        self.append_blocks(page_id, remaining, retry).await?;
 
         tracing::info!("Delivered message to Notion");
-        Ok(())
+        Ok(Some(page_id.to_string()))
     }
 }
 
@@ -636,6 +637,14 @@ mod tests {
         assert!(title.contains("Brief"));
     }
 
+    #[test]
+    fn test_validate_notion_id() {
+        assert!(validate_notion_id("30aac5ee-1a2b-3c4d-5e6f-1234567890ab", "append_to_page", "").is_ok());
+        assert!(validate_notion_id("30aac5ee1a2b3c4d5e6f1234567890ab", "append_to_page", "").is_ok());
+        let err = validate_notion_id("https://notion.so/My-Page", "append_to_page", "").unwrap_err();
+        assert!(err.to_string().contains("append_to_page"));
+    }
+
     #[test]
     fn test_blocks_heading() {
         let blocks = markdown_to_notion_blocks("# H1\n## H2\n### H3");
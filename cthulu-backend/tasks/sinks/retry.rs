@@ -0,0 +1,317 @@
+//! Retrying sink delivery through rate limits and transient failures.
+//!
+//! Sinks retry each request they make through a [`Retrier`], so a
+//! multi-message sink that fails part-way repeats only the failed part, never
+//! the ones already posted.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use anyhow::{Error, Result};
+use reqwest::StatusCode;
+
+use super::{DeliveryError, Sink};
+use crate::tasks::notion::NotionError;
+
+/// Retries per sink node when its `max_retries` isn't set.
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Longest `Retry-After` we'll wait out; asked for more, the node fails.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts after the first.
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn with_max_retries(max_retries: u32) -> Self {
+        Self { max_retries, ..Self::default() }
+    }
+
+    /// Delay before the retry that follows failed `attempt` (1-based): the
+    /// doubling delay, capped, with its upper half scaled by `jitter` in `[0, 1)`.
+    fn backoff(&self, attempt: u32, jitter: f64) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        delay / 2 + delay.mul_f64(jitter) / 2
+    }
+
+    /// How long to wait after failed `attempt`, or `None` to give up.
+    fn delay_for(&self, err: &Error, attempt: u32) -> Option<Duration> {
+        if attempt > self.max_retries {
+            return None;
+        }
+        match classify(err) {
+            Retry::No => None,
+            Retry::After(delay) => (delay <= MAX_RETRY_AFTER).then_some(delay),
+            Retry::Backoff => Some(self.backoff(attempt, jitter())),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Retry {
+    No,
+    /// The destination said when (`Retry-After`).
+    After(Duration),
+    Backoff,
+}
+
+/// 429s, 5xx responses and dropped or timed-out connections are retried;
+/// other 4xx and non-HTTP errors fail fast.
+fn classify(err: &Error) -> Retry {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<DeliveryError>() {
+            return status_retry(e.status, e.retry_after_secs);
+        }
+        if let Some(e) = cause.downcast_ref::<NotionError>() {
+            return match e {
+                NotionError::RateLimited { retry_after_secs } => {
+                    status_retry(StatusCode::TOO_MANY_REQUESTS, *retry_after_secs)
+                }
+                NotionError::Api { status, .. } => StatusCode::from_u16(*status)
+                    .map_or(Retry::No, |status| status_retry(status, None)),
+                _ => Retry::No,
+            };
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return if e.is_timeout() || e.is_connect() { Retry::Backoff } else { Retry::No };
+        }
+    }
+    Retry::No
+}
+
+fn status_retry(status: StatusCode, retry_after_secs: Option<u64>) -> Retry {
+    if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+        retry_after_secs.map_or(Retry::Backoff, |secs| Retry::After(Duration::from_secs(secs)))
+    } else {
+        Retry::No
+    }
+}
+
+fn jitter() -> f64 {
+    getrandom::u32().map_or(0.5, |n| n as f64 / (u32::MAX as f64 + 1.0))
+}
+
+/// Retries the individual requests of one delivery, per its policy.
+#[derive(Debug)]
+pub struct Retrier {
+    policy: RetryPolicy,
+    /// Most attempts any one request has taken.
+    attempts: AtomicU32,
+}
+
+impl Retrier {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self { policy, attempts: AtomicU32::new(1) }
+    }
+
+    /// A retrier that makes a single attempt (tests).
+    #[cfg(test)]
+    pub(crate) fn once() -> Self {
+        Self::new(RetryPolicy::with_max_retries(0))
+    }
+
+    /// Run `request` until it succeeds or the policy gives up on it.
+    pub async fn run<T, F, Fut>(&self, mut request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            self.attempts.fetch_max(attempt, Ordering::Relaxed);
+            match request().await {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    let Some(delay) = self.policy.delay_for(&e, attempt) else {
+                        return Err(if attempt > 1 { e.context(format!("gave up after {attempt} attempts")) } else { e });
+                    };
+                    tracing::warn!(attempt, delay_ms = delay.as_millis() as u64, error = %e, "Sink delivery failed, retrying");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Most attempts any one request took.
+    pub fn attempts(&self) -> u32 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+}
+
+/// A successful delivery and how many attempts its slowest request took.
+#[derive(Debug)]
+pub struct Delivered {
+    pub receipt: Option<String>,
+    pub attempts: u32,
+}
+
+/// `sink.deliver(text)`, with each of its requests retried per `policy`.
+pub async fn deliver_with_retry(sink: &dyn Sink, text: &str, policy: &RetryPolicy) -> Result<Delivered> {
+    let retry = Retrier::new(policy.clone());
+    let receipt = sink.deliver(text, &retry).await?;
+    Ok(Delivered { receipt, attempts: retry.attempts() })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use super::*;
+
+    fn http_error(status: u16, retry_after_secs: Option<u64>) -> Error {
+        DeliveryError {
+            status: StatusCode::from_u16(status).unwrap(),
+            retry_after_secs,
+            message: format!("returned {status}"),
+        }
+        .into()
+    }
+
+    /// Fails with each queued error in turn, then succeeds.
+    struct FlakySink {
+        failures: Mutex<Vec<Error>>,
+        calls: Mutex<u32>,
+    }
+
+    impl FlakySink {
+        fn new(mut failures: Vec<Error>) -> Self {
+            failures.reverse();
+            Self { failures: Mutex::new(failures), calls: Mutex::new(0) }
+        }
+
+        fn calls(&self) -> u32 {
+            *self.calls.lock().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl Sink for FlakySink {
+        async fn deliver(&self, _text: &str, retry: &Retrier) -> Result<Option<String>> {
+            retry
+                .run(|| async {
+                    *self.calls.lock().unwrap() += 1;
+                    match self.failures.lock().unwrap().pop() {
+                        Some(e) => Err(e),
+                        None => Ok(Some("ts-1".to_string())),
+                    }
+                })
+                .await
+        }
+    }
+
+    /// Posts each line as its own part; the parts in `flaky` fail once.
+    struct MultiPartSink {
+        flaky: Mutex<Vec<String>>,
+        posted: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Sink for MultiPartSink {
+        async fn deliver(&self, text: &str, retry: &Retrier) -> Result<Option<String>> {
+            for part in text.lines() {
+                retry
+                    .run(|| async {
+                        let mut flaky = self.flaky.lock().unwrap();
+                        if let Some(i) = flaky.iter().position(|p| p == part) {
+                            flaky.remove(i);
+                            return Err(http_error(503, None));
+                        }
+                        self.posted.lock().unwrap().push(part.to_string());
+                        Ok(())
+                    })
+                    .await?;
+            }
+            Ok(None)
+        }
+    }
+
+    fn fast_policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_with_jitter_and_caps() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(1, 0.0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(1, 0.999), Duration::from_millis(500) + Duration::from_secs(1).mul_f64(0.999) / 2);
+        assert_eq!(policy.backoff(3, 0.0), Duration::from_secs(2));
+        assert_eq!(policy.backoff(30, 0.0), Duration::from_secs(15));
+        let j = jitter();
+        assert!((0.0..1.0).contains(&j));
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(&http_error(429, Some(7))), Retry::After(Duration::from_secs(7)));
+        assert_eq!(classify(&http_error(429, None)), Retry::Backoff);
+        assert_eq!(classify(&http_error(503, None).context("sink 'Slack' delivery failed")), Retry::Backoff);
+        assert_eq!(classify(&http_error(400, None)), Retry::No);
+        assert_eq!(classify(&http_error(404, Some(7))), Retry::No);
+        assert_eq!(classify(&anyhow::anyhow!("channel_not_found")), Retry::No);
+        assert_eq!(
+            classify(&NotionError::RateLimited { retry_after_secs: Some(2) }.into()),
+            Retry::After(Duration::from_secs(2))
+        );
+        assert_eq!(classify(&NotionError::NotFound { message: "gone".into() }.into()), Retry::No);
+
+        // A Retry-After too long to wait out gives up
+        assert_eq!(RetryPolicy::default().delay_for(&http_error(429, Some(3600)), 1), None);
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let sink = FlakySink::new(vec![http_error(503, None), http_error(429, Some(0))]);
+        let delivered = deliver_with_retry(&sink, "hi", &fast_policy(3)).await.unwrap();
+        assert_eq!(delivered.attempts, 3);
+        assert_eq!(delivered.receipt.as_deref(), Some("ts-1"));
+    }
+
+    #[tokio::test]
+    async fn test_only_the_failed_part_is_retried() {
+        let sink = MultiPartSink { flaky: Mutex::new(vec!["two".to_string()]), posted: Mutex::default() };
+        let delivered = deliver_with_retry(&sink, "one\ntwo\nthree", &fast_policy(3)).await.unwrap();
+        assert_eq!(delivered.attempts, 2);
+        assert_eq!(*sink.posted.lock().unwrap(), vec!["one", "two", "three"]);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_fail_fast_and_retries_run_out() {
+        let sink = FlakySink::new(vec![http_error(400, None)]);
+        let err = deliver_with_retry(&sink, "hi", &fast_policy(3)).await.unwrap_err();
+        assert_eq!(sink.calls(), 1);
+        assert_eq!(err.to_string(), "returned 400");
+
+        let sink = FlakySink::new((0..5).map(|_| http_error(502, None)).collect());
+        let err = deliver_with_retry(&sink, "hi", &fast_policy(2)).await.unwrap_err();
+        assert_eq!(sink.calls(), 3);
+        assert_eq!(format!("{err:#}"), "gave up after 3 attempts: returned 502");
+
+        let sink = FlakySink::new(vec![http_error(500, None)]);
+        assert!(deliver_with_retry(&sink, "hi", &fast_policy(0)).await.is_err());
+        assert_eq!(sink.calls(), 1);
+    }
+}
@@ -469,7 +469,7 @@ pub(crate) fn convert_links(input: &str) -> String {
 
 /// Try to parse a markdown link starting at position `start` (which should be '[').
 /// Returns (text, url, end_position) if successful.
-fn parse_md_link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+pub(crate) fn parse_md_link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
     // Find closing ]
     let mut i = start + 1;
     let mut text = String::new();
@@ -524,7 +524,7 @@ pub(crate) fn convert_bold(input: &str) -> String {
     out
 }
 
-fn find_closing_double_star(chars: &[char], start: usize) -> Option<usize> {
+pub(crate) fn find_closing_double_star(chars: &[char], start: usize) -> Option<usize> {
     let mut i = start;
     while i + 1 < chars.len() {
         if chars[i] == '*' && chars[i + 1] == '*' {
@@ -13,7 +13,87 @@ use serde_json::json;
 use blocks::*;
 use markdown::markdown_to_blocks;
 
-use super::Sink;
+use super::retry::Retrier;
+use super::{retry_after_secs, DeliveryError, Sink};
+
+// ---------------------------------------------------------------------------
+// Message options
+// ---------------------------------------------------------------------------
+
+/// Per-sink delivery behavior shared by the webhook and Web API paths.
+#[derive(Debug, Clone, Copy)]
+pub struct SlackMessageOptions {
+    pub unfurl_links: bool,
+    pub unfurl_media: bool,
+    pub sanitize_mentions: bool,
+    /// Blocks per `chat.postMessage` before truncating; clamped to Slack's limit.
+    pub max_blocks: usize,
+    /// Post the untruncated output as thread replies when a message overflows.
+    pub overflow_to_thread: bool,
+}
+
+impl Default for SlackMessageOptions {
+    fn default() -> Self {
+        Self {
+            unfurl_links: false,
+            unfurl_media: false,
+            sanitize_mentions: true,
+            max_blocks: MAX_BLOCKS_PER_MESSAGE,
+            overflow_to_thread: false,
+        }
+    }
+}
+
+impl SlackMessageOptions {
+    fn prepare<'a>(&self, text: &'a str) -> std::borrow::Cow<'a, str> {
+        if self.sanitize_mentions {
+            std::borrow::Cow::Owned(sanitize_mentions(text))
+        } else {
+            std::borrow::Cow::Borrowed(text)
+        }
+    }
+
+    /// Effective per-message block limit (room for the truncation notice,
+    /// never above what Slack accepts).
+    fn block_limit(&self) -> usize {
+        self.max_blocks.clamp(2, MAX_BLOCKS_PER_MESSAGE)
+    }
+}
+
+const BROADCAST_MENTIONS: &[&str] = &["here", "channel", "everyone"];
+
+/// Neutralize channel-wide mentions so model output can't ping everyone.
+///
+/// Rewrites both the special-mention syntax (`<!here>`, `<!channel|channel>`)
+/// and plain `@here` text into `@\u{200B}here`, which Slack renders the same
+/// but doesn't treat as a mention.
+pub fn sanitize_mentions(text: &str) -> String {
+    let mut out = text.to_string();
+    for name in BROADCAST_MENTIONS {
+        let marker = format!("<!{name}");
+        let neutral = format!("@\u{200B}{name}");
+        let mut search_from = 0;
+        while let Some(found) = out[search_from..].find(&marker) {
+            let start = search_from + found;
+            let after = start + marker.len();
+            let end = match out[after..].chars().next() {
+                Some('>') => Some(after + 1),
+                Some('|') => out[after..].find('>').map(|i| after + i + 1),
+                _ => None,
+            };
+            match end {
+                Some(end) => {
+                    out.replace_range(start..end, &neutral);
+                    search_from = start + neutral.len();
+                }
+                // e.g. `<!heresy>` — not a mention
+                None => search_from = after,
+            }
+        }
+        out = out.replace(&format!("@{name}"), &neutral);
+    }
+    out
+}
 
 // ---------------------------------------------------------------------------
 // SlackWebhookSink
@@ -22,18 +102,27 @@ use super::Sink;
 pub struct SlackWebhookSink {
     http_client: Arc<reqwest::Client>,
     webhook_url: String,
+    options: SlackMessageOptions,
 }
 
 impl SlackWebhookSink {
-    pub fn new(http_client: Arc<reqwest::Client>, webhook_url: String) -> Self {
-        Self { http_client, webhook_url }
+    pub fn new(
+        http_client: Arc<reqwest::Client>,
+        webhook_url: String,
+        options: SlackMessageOptions,
+    ) -> Self {
+        Self { http_client, webhook_url, options }
     }
 }
 
 #[async_trait]
 impl Sink for SlackWebhookSink {
-    async fn deliver(&self, text: &str) -> Result<()> {
-        post_to_url(&self.http_client, &self.webhook_url, text).await
+    async fn deliver(&self, text: &str, retry: &Retrier) -> Result<Option<String>> {
+        let text = self.options.prepare(text);
+        let status = retry
+            .run(|| post_to_url(&self.http_client, &self.webhook_url, &text, &self.options))
+            .await?;
+        Ok(Some(format!("HTTP {}", status.as_u16())))
     }
 }
 
@@ -41,22 +130,57 @@ impl Sink for SlackWebhookSink {
 // SlackApiSink
 // ---------------------------------------------------------------------------
 
+/// Web API endpoint messages are posted to.
+const POST_MESSAGE_URL: &str = "https://slack.com/api/chat.postMessage";
+
 pub struct SlackApiSink {
     http_client: Arc<reqwest::Client>,
     bot_token: String,
     channel: String,
+    options: SlackMessageOptions,
+    post_message_url: String,
 }
 
 impl SlackApiSink {
-    pub fn new(http_client: Arc<reqwest::Client>, bot_token: String, channel: String) -> Self {
-        Self { http_client, bot_token, channel }
+    pub fn new(
+        http_client: Arc<reqwest::Client>,
+        bot_token: String,
+        channel: String,
+        options: SlackMessageOptions,
+    ) -> Self {
+        Self {
+            http_client,
+            bot_token,
+            channel,
+            options,
+            post_message_url: POST_MESSAGE_URL.to_string(),
+        }
+    }
+
+    /// Point the sink at another `chat.postMessage` endpoint (tests).
+    #[cfg(test)]
+    pub(crate) fn with_post_message_url(mut self, url: impl Into<String>) -> Self {
+        self.post_message_url = url.into();
+        self
     }
 }
 
 #[async_trait]
 impl Sink for SlackApiSink {
-    async fn deliver(&self, text: &str) -> Result<()> {
-        post_threaded_blocks(&self.http_client, &self.bot_token, &self.channel, text).await
+    /// Returns the `ts` of the main message.
+    async fn deliver(&self, text: &str, retry: &Retrier) -> Result<Option<String>> {
+        let text = self.options.prepare(text);
+        post_threaded_blocks(
+            &self.http_client,
+            &self.post_message_url,
+            &self.bot_token,
+            &self.channel,
+            &text,
+            &self.options,
+            retry,
+        )
+        .await
+        .map(Some)
     }
 }
 
@@ -64,28 +188,44 @@ impl Sink for SlackApiSink {
 // Webhook (legacy) path
 // ---------------------------------------------------------------------------
 
+/// Incoming-webhook payload for already-converted mrkdwn text.
+fn webhook_payload(slack_text: &str, options: &SlackMessageOptions) -> serde_json::Value {
+    json!({
+        "text": slack_text,
+        "unfurl_links": options.unfurl_links,
+        "unfurl_media": options.unfurl_media,
+    })
+}
+
 async fn post_to_url(
     client: &reqwest::Client,
     webhook_url: &str,
     text: &str,
-) -> Result<()> {
+    options: &SlackMessageOptions,
+) -> Result<reqwest::StatusCode> {
     let slack_text = markdown::markdown_to_slack(text);
 
     let response = client
         .post(webhook_url)
-        .json(&json!({ "text": slack_text }))
+        .json(&webhook_payload(&slack_text, options))
         .send()
         .await
         .context("failed to post to Slack webhook")?;
 
     if !response.status().is_success() {
         let status = response.status();
+        let retry_after_secs = retry_after_secs(response.headers());
         let body = response.text().await.unwrap_or_default();
-        anyhow::bail!("Slack webhook returned {status}: {body}");
+        return Err(DeliveryError {
+            status,
+            retry_after_secs,
+            message: format!("Slack webhook returned {status}: {body}"),
+        }
+        .into());
     }
 
     tracing::info!("Delivered message to Slack");
-    Ok(())
+    Ok(response.status())
 }
 
 // ---------------------------------------------------------------------------
@@ -96,55 +236,73 @@ async fn post_to_url(
 ///
 /// If `full_text` contains a `---THREAD---` delimiter, the part above becomes
 /// the main channel message and the part below is posted as a thread reply.
+/// With `overflow_to_thread`, a main message over the block limit is posted
+/// truncated and then in full, split across thread replies. Returns the main
+/// message's `ts`. Each post is retried on its own through `retry`.
 async fn post_threaded_blocks(
     client: &reqwest::Client,
+    url: &str,
     bot_token: &str,
     channel: &str,
     full_text: &str,
-) -> Result<()> {
+    options: &SlackMessageOptions,
+    retry: &Retrier,
+) -> Result<String> {
     let parts: Vec<&str> = full_text.splitn(2, "---THREAD---").collect();
 
     let main_text = parts[0].trim();
     let thread_text = parts.get(1).map(|s| s.trim());
 
     let main_blocks = markdown_to_blocks(main_text);
-    let ts = post_blocks(client, bot_token, channel, &main_blocks, None)
+    let ts = retry
+        .run(|| post_blocks(client, url, bot_token, channel, &main_blocks, None, options))
         .await
         .context("failed to post main message")?;
 
+    let limit = options.block_limit();
+    if options.overflow_to_thread && main_blocks.len() > limit {
+        for chunk in main_blocks.chunks(limit) {
+            retry
+                .run(|| post_blocks(client, url, bot_token, channel, chunk, Some(&ts), options))
+                .await
+                .context("failed to post full output to thread")?;
+        }
+    }
+
     if let Some(detail) = thread_text {
         if !detail.is_empty() {
             let thread_blocks = markdown_to_blocks(detail);
-            post_blocks(client, bot_token, channel, &thread_blocks, Some(&ts))
-                .await
-                .context("failed to post thread reply")?;
+            if options.overflow_to_thread {
+                for chunk in thread_blocks.chunks(limit) {
+                    retry
+                        .run(|| post_blocks(client, url, bot_token, channel, chunk, Some(&ts), options))
+                        .await
+                        .context("failed to post thread reply")?;
+                }
+            } else {
+                retry
+                    .run(|| post_blocks(client, url, bot_token, channel, &thread_blocks, Some(&ts), options))
+                    .await
+                    .context("failed to post thread reply")?;
+            }
         }
     }
 
     tracing::info!("Delivered Block Kit message to Slack");
-    Ok(())
+    Ok(ts)
 }
 
 /// Post blocks to Slack via `chat.postMessage`. Returns the message `ts`.
 async fn post_blocks(
     client: &reqwest::Client,
+    url: &str,
     bot_token: &str,
     channel: &str,
     blocks: &[Block],
     thread_ts: Option<&str>,
+    options: &SlackMessageOptions,
 ) -> Result<String> {
-    let blocks = if blocks.len() > MAX_BLOCKS_PER_MESSAGE {
-        let mut truncated = blocks[..MAX_BLOCKS_PER_MESSAGE - 1].to_vec();
-        truncated.push(Block::Section {
-            text: TextObject {
-                kind: "mrkdwn",
-                text: "_Message truncated — too many blocks._".to_string(),
-            },
-        });
-        truncated
-    } else {
-        blocks.to_vec()
-    };
+    let blocks = truncate_blocks(blocks, options.block_limit());
 
     // Build a fallback plain-text summary from all text-bearing blocks
     let fallback: String = blocks
@@ -185,18 +343,10 @@ async fn post_blocks(
         .collect::<Vec<_>>()
         .join("\n");
 
-    let mut body = json!({
-        "channel": channel,
-        "blocks": blocks,
-        "text": fallback,
-    });
-
-    if let Some(ts) = thread_ts {
-        body["thread_ts"] = json!(ts);
-    }
+    let body = post_message_body(channel, &blocks, &fallback, thread_ts, options);
 
     let response = client
-        .post("https://slack.com/api/chat.postMessage")
+        .post(url)
         .header("Authorization", format!("Bearer {bot_token}"))
         .json(&body)
         .send()
@@ -204,12 +354,24 @@ async fn post_blocks(
         .context("failed to call chat.postMessage")?;
 
     let status = response.status();
-    let resp_body: serde_json::Value = response
-        .json()
-        .await
-        .context("failed to parse Slack API response")?;
+    let retry_after_secs = retry_after_secs(response.headers());
+    let resp_body: serde_json::Value = if status.is_success() {
+        response.json().await.context("failed to parse Slack API response")?
+    } else {
+        // Rate-limit and gateway errors may not have a JSON body
+        response.json().await.unwrap_or_default()
+    };
 
-    if !status.is_success() || resp_body["ok"].as_bool() != Some(true) {
+    if !status.is_success() {
+        let err = resp_body["error"].as_str().unwrap_or("unknown error");
+        return Err(DeliveryError {
+            status,
+            retry_after_secs,
+            message: format!("chat.postMessage failed ({status}): {err}"),
+        }
+        .into());
+    }
+    if resp_body["ok"].as_bool() != Some(true) {
         let err = resp_body["error"].as_str().unwrap_or("unknown error");
         anyhow::bail!("chat.postMessage failed ({status}): {err}");
     }
@@ -220,6 +382,92 @@ async fn post_blocks(
         .context("Slack response missing ts field")
 }
 
+/// Fit `blocks` into `max` blocks without losing the outline or the ending.
+///
+/// Every header is kept, as is the last section (from the final header on,
+/// capped at half the budget) since that's usually the summary. Body blocks
+/// fill the rest in order, and a notice marks where content was dropped.
+pub(crate) fn truncate_blocks(blocks: &[Block], max: usize) -> Vec<Block> {
+    if blocks.len() <= max {
+        return blocks.to_vec();
+    }
+    let budget = max.saturating_sub(1).max(1);
+    let is_header = |b: &Block| matches!(b, Block::Header { .. });
+
+    let tail_start = blocks
+        .iter()
+        .rposition(is_header)
+        .filter(|&i| i > 0)
+        .unwrap_or(blocks.len() - 1);
+    let tail_len = (blocks.len() - tail_start).min(budget.div_ceil(2));
+
+    let mut keep = vec![false; blocks.len()];
+    keep[tail_start..tail_start + tail_len].fill(true);
+    let mut remaining = budget - tail_len;
+
+    // Headers first, so the outline survives, then body blocks in order.
+    for (i, block) in blocks[..tail_start].iter().enumerate() {
+        if remaining == 0 {
+            break;
+        }
+        if is_header(block) {
+            keep[i] = true;
+            remaining -= 1;
+        }
+    }
+    for kept in keep[..tail_start].iter_mut() {
+        if remaining == 0 {
+            break;
+        }
+        if !*kept {
+            *kept = true;
+            remaining -= 1;
+        }
+    }
+
+    let omitted = keep.iter().filter(|k| !**k).count();
+    let notice = Block::Section {
+        text: TextObject {
+            kind: "mrkdwn",
+            text: format!("_Message truncated — {omitted} blocks omitted._"),
+        },
+    };
+
+    let mut out = Vec::with_capacity(budget + 1);
+    out.extend(
+        blocks[..tail_start]
+            .iter()
+            .zip(&keep)
+            .filter(|(_, k)| **k)
+            .map(|(b, _)| b.clone()),
+    );
+    out.push(notice);
+    out.extend_from_slice(&blocks[tail_start..tail_start + tail_len]);
+    out
+}
+
+/// Request body for `chat.postMessage`.
+fn post_message_body(
+    channel: &str,
+    blocks: &[Block],
+    fallback: &str,
+    thread_ts: Option<&str>,
+    options: &SlackMessageOptions,
+) -> serde_json::Value {
+    let mut body = json!({
+        "channel": channel,
+        "blocks": blocks,
+        "text": fallback,
+        "unfurl_links": options.unfurl_links,
+        "unfurl_media": options.unfurl_media,
+    });
+
+    if let Some(ts) = thread_ts {
+        body["thread_ts"] = json!(ts);
+    }
+    body
+}
+
 /// Extract plain text from a slice of rich text inlines.
 fn extract_inline_text(inlines: &[RichTextInline]) -> String {
     inlines
@@ -1,5 +1,11 @@
 use super::blocks::*;
 use super::markdown::*;
+use super::{
+    post_message_body, sanitize_mentions, truncate_blocks, webhook_payload, SlackApiSink,
+    SlackMessageOptions,
+};
+use crate::tasks::sinks::retry::Retrier;
+use crate::tasks::sinks::Sink;
 
 // --- Webhook (mrkdwn) tests ---
 
@@ -388,3 +394,153 @@ fn test_stats_block_surrounded_by_content() {
     assert!(matches!(&blocks[1], Block::RichText { .. }));
     assert!(matches!(&blocks[2], Block::SectionFields { .. }));
 }
+
+// --- Mentions & unfurl options ---
+
+#[test]
+fn test_sanitize_mentions_plain_text() {
+    let out = sanitize_mentions("Heads up @here and @channel, also @everyone");
+    assert!(!out.contains("@here"));
+    assert!(!out.contains("@channel"));
+    assert!(!out.contains("@everyone"));
+    assert!(out.contains("@\u{200B}here"));
+}
+
+#[test]
+fn test_sanitize_mentions_special_syntax() {
+    let out = sanitize_mentions("<!here> deploy done, cc <!channel|channel> and <!everyone>");
+    assert!(!out.contains("<!"));
+    assert_eq!(
+        out,
+        "@\u{200B}here deploy done, cc @\u{200B}channel and @\u{200B}everyone"
+    );
+}
+
+#[test]
+fn test_sanitize_mentions_leaves_other_text_alone() {
+    let text = "Ping <@U123> about <!subteam^S1> and email me@example.com";
+    assert_eq!(sanitize_mentions(text), text);
+}
+
+#[test]
+fn test_unfurl_flags_default_off_in_post_message_body() {
+    let blocks = markdown_to_blocks("see https://example.com");
+    let body = post_message_body("#news", &blocks, "fallback", None, &SlackMessageOptions::default());
+    assert_eq!(body["unfurl_links"], false);
+    assert_eq!(body["unfurl_media"], false);
+    assert!(body.get("thread_ts").is_none());
+}
+
+#[test]
+fn test_unfurl_flags_passed_through() {
+    let options = SlackMessageOptions {
+        unfurl_links: true,
+        unfurl_media: false,
+        sanitize_mentions: true,
+        ..SlackMessageOptions::default()
+    };
+    let blocks = markdown_to_blocks("hello");
+    let body = post_message_body("#news", &blocks, "hello", Some("123.456"), &options);
+    assert_eq!(body["unfurl_links"], true);
+    assert_eq!(body["unfurl_media"], false);
+    assert_eq!(body["thread_ts"], "123.456");
+
+    let payload = webhook_payload("hello", &options);
+    assert_eq!(payload["unfurl_links"], true);
+    assert_eq!(payload["unfurl_media"], false);
+}
+
+// --- Truncation tests ---
+
+fn block_text(block: &Block) -> &str {
+    match block {
+        Block::Header { text } | Block::Section { text } => &text.text,
+        _ => "",
+    }
+}
+
+#[test]
+fn test_truncation_keeps_headers_and_conclusion() {
+    let mut md = String::new();
+    for section in 1..=3 {
+        md.push_str(&format!("# Part {section}\n\n"));
+        for para in 1..=10 {
+            md.push_str(&format!("Paragraph {section}.{para}\n\n"));
+        }
+    }
+    md.push_str("# Conclusion\n\nThe summary that matters.\n");
+    let blocks = markdown_to_blocks(&md);
+    assert!(blocks.len() > 20);
+
+    let truncated = truncate_blocks(&blocks, 20);
+    assert_eq!(truncated.len(), 20);
+    let texts: Vec<&str> = truncated.iter().map(block_text).collect();
+    for header in ["Part 1", "Part 2", "Part 3", "Conclusion"] {
+        assert!(
+            texts.iter().any(|t| t.ends_with(header)),
+            "missing header {header}: {texts:?}"
+        );
+    }
+    assert!(texts.last().unwrap().contains("The summary that matters."));
+    assert!(texts.iter().any(|t| t.contains("blocks omitted")));
+    // Body text is kept in order from the top.
+    assert!(texts.iter().any(|t| t.contains("Paragraph 1.1")));
+}
+
+#[test]
+fn test_truncation_is_noop_under_limit() {
+    let blocks = markdown_to_blocks("# Title\n\nshort");
+    assert_eq!(truncate_blocks(&blocks, 50).len(), blocks.len());
+}
+
+#[test]
+fn test_configurable_block_limit() {
+    let md: String = (1..=30).map(|i| format!("Line {i}\n\n")).collect();
+    let blocks = markdown_to_blocks(&md);
+
+    let options = SlackMessageOptions {
+        max_blocks: 10,
+        ..SlackMessageOptions::default()
+    };
+    let truncated = truncate_blocks(&blocks, options.block_limit());
+    assert_eq!(truncated.len(), 10);
+    // No headers: the final block stands in for the conclusion.
+    assert!(block_text(truncated.last().unwrap()).contains("Line 30"));
+
+    // Limits above Slack's cap are clamped.
+    let options = SlackMessageOptions {
+        max_blocks: 500,
+        ..SlackMessageOptions::default()
+    };
+    assert_eq!(options.block_limit(), MAX_BLOCKS_PER_MESSAGE);
+}
+
+#[tokio::test]
+async fn test_api_delivery_returns_message_ts() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut sock, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0u8; 64 * 1024];
+        let _ = sock.read(&mut buf).await;
+        let body = r#"{"ok":true,"channel":"C123","ts":"1712345678.000100"}"#;
+        let resp = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let _ = sock.write_all(resp.as_bytes()).await;
+    });
+
+    let sink = SlackApiSink::new(
+        std::sync::Arc::new(reqwest::Client::new()),
+        "xoxb-test".to_string(),
+        "C123".to_string(),
+        SlackMessageOptions::default(),
+    )
+    .with_post_message_url(format!("http://{addr}/api/chat.postMessage"));
+
+    let receipt = sink.deliver("# Digest\n\nAll quiet.", &Retrier::once()).await.unwrap();
+    assert_eq!(receipt.as_deref(), Some("1712345678.000100"));
+}
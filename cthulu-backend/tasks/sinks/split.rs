@@ -0,0 +1,126 @@
+//! Splitting long sink output into platform-sized messages.
+
+const FENCE: &str = "```";
+
+/// Split `text` into messages of at most `limit` characters, breaking at
+/// line ends where possible. A code block cut in two is closed and reopened
+/// so both parts render.
+pub fn split_message(text: &str, limit: usize) -> Vec<String> {
+    // Room to close (and reopen) a fence around a split
+    let budget = limit.saturating_sub(FENCE.len() + 1).max(1);
+    let mut messages = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+    let mut fence: Option<String> = None;
+
+    for line in text.split('\n') {
+        // Inside a code block a split piece may also need the reopened fence
+        let reopen = fence.as_ref().map_or(0, |f| f.chars().count() + 1);
+        for piece in hard_wrap(line, budget.saturating_sub(reopen).max(1)) {
+            let piece_chars = piece.chars().count();
+            let sep = usize::from(!current.is_empty());
+            if current_chars + sep + piece_chars > budget && !current.is_empty() {
+                if fence.is_some() {
+                    current.push('\n');
+                    current.push_str(FENCE);
+                }
+                messages.push(std::mem::take(&mut current));
+                current_chars = 0;
+                if let Some(opener) = &fence {
+                    current.push_str(opener);
+                    current_chars = opener.chars().count();
+                }
+            }
+            if !current.is_empty() {
+                current.push('\n');
+                current_chars += 1;
+            }
+            current.push_str(&piece);
+            current_chars += piece_chars;
+        }
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with(FENCE) {
+            fence = match fence {
+                Some(_) => None,
+                // Reopen with the same language tag
+                None => Some(trimmed.to_string()),
+            };
+        }
+    }
+    if !current.trim().is_empty() {
+        messages.push(current);
+    }
+    messages
+}
+
+/// Break a line longer than `max` characters into pieces of at most `max`
+/// characters. A piece never ends on a dangling `\`, so backslash escapes
+/// stay with the character they escape.
+fn hard_wrap(line: &str, max: usize) -> Vec<String> {
+    if line.chars().count() <= max {
+        return vec![line.to_string()];
+    }
+    let chars: Vec<char> = line.chars().collect();
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = (start + max).min(chars.len());
+        let trailing = chars[start..end].iter().rev().take_while(|&&c| c == '\\').count();
+        if end < chars.len() && trailing % 2 == 1 && end - start > 1 {
+            end -= 1;
+        }
+        pieces.push(chars[start..end].iter().collect());
+        start = end;
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_message_respects_limit() {
+        let line = "x".repeat(90);
+        let text = [line.as_str(); 50].join("\n");
+        let parts = split_message(&text, 2000);
+        assert!(parts.len() > 1);
+        assert!(parts.iter().all(|p| p.chars().count() <= 2000));
+        // Nothing is lost: the parts rejoin to the original
+        assert_eq!(parts.join("\n"), text);
+
+        assert_eq!(split_message("short", 2000), vec!["short"]);
+    }
+
+    #[test]
+    fn test_split_message_long_line_and_fences() {
+        let long = "é".repeat(4500);
+        let parts = split_message(&long, 2000);
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|p| p.chars().count() <= 2000));
+
+        let code = format!("intro\n```rust\n{}\n```\nafter", ["let x = 1;"; 300].join("\n"));
+        let parts = split_message(&code, 2000);
+        assert!(parts.len() > 1);
+        for part in &parts {
+            assert!(part.chars().count() <= 2000);
+            // Every part has balanced fences
+            assert_eq!(part.matches(FENCE).count() % 2, 0, "unbalanced part: {part}");
+        }
+        assert!(parts[1].starts_with("```rust\n"));
+    }
+
+    #[test]
+    fn test_split_message_keeps_escapes_whole() {
+        let line = "a\\.".repeat(100);
+        for limit in [10, 11, 12] {
+            let parts = split_message(&line, limit);
+            assert_eq!(parts.concat(), line);
+            for part in &parts {
+                let trailing = part.chars().rev().take_while(|&c| c == '\\').count();
+                assert_eq!(trailing % 2, 0, "dangling escape in {part:?}");
+            }
+        }
+    }
+}
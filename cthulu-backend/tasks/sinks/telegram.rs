@@ -0,0 +1,250 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::slack::markdown::{find_closing_double_star, parse_md_link};
+use super::split::split_message;
+use super::retry::Retrier;
+use super::{retry_after_secs, DeliveryError, Sink};
+
+/// Telegram rejects message text longer than this (in characters).
+pub const MAX_MESSAGE_CHARS: usize = 4096;
+
+const API_BASE: &str = "https://api.telegram.org";
+
+const FENCE: &str = "```";
+
+/// Characters MarkdownV2 reserves outside code; each needs a `\` to be literal.
+const RESERVED: &[char] = &[
+    '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!', '\\',
+];
+
+pub struct TelegramSink {
+    http_client: Arc<reqwest::Client>,
+    bot_token: String,
+    chat_id: String,
+}
+
+impl TelegramSink {
+    pub fn new(http_client: Arc<reqwest::Client>, bot_token: String, chat_id: String) -> Self {
+        Self { http_client, bot_token, chat_id }
+    }
+}
+
+#[async_trait]
+impl Sink for TelegramSink {
+    /// Sends each chunk in order; the receipt is the message ids, comma-joined.
+    async fn deliver(&self, text: &str, retry: &Retrier) -> Result<Option<String>> {
+        let chunks = split_message(&markdown_to_telegram(text), MAX_MESSAGE_CHARS);
+        let url = format!("{API_BASE}/bot{}/sendMessage", self.bot_token);
+
+        let mut ids = Vec::with_capacity(chunks.len());
+        for (i, chunk) in chunks.iter().enumerate() {
+            let id = retry
+                .run(|| async {
+                    let response = self
+                        .http_client
+                        .post(&url)
+                        .json(&json!({
+                            "chat_id": self.chat_id,
+                            "text": chunk,
+                            "parse_mode": "MarkdownV2",
+                            "link_preview_options": { "is_disabled": true },
+                        }))
+                        .send()
+                        .await
+                        // The URL carries the bot token; keep it out of the error
+                        .map_err(|e| anyhow::anyhow!("failed to post to Telegram: {}", e.without_url()))?;
+
+                    let status = response.status();
+                    let header_retry_after = retry_after_secs(response.headers());
+                    let body: serde_json::Value = response.json().await.unwrap_or_default();
+                    if !status.is_success() || body["ok"].as_bool() != Some(true) {
+                        let description = body["description"].as_str().unwrap_or("no description");
+                        let message = format!(
+                            "Telegram sendMessage returned {status} for part {}/{}: {description}",
+                            i + 1,
+                            chunks.len()
+                        );
+                        if status.is_success() {
+                            anyhow::bail!(message);
+                        }
+                        return Err(DeliveryError {
+                            status,
+                            // Telegram reports flood waits in the body
+                            retry_after_secs: header_retry_after.or(body["parameters"]["retry_after"].as_u64()),
+                            message,
+                        }
+                        .into());
+                    }
+                    Ok(body["result"]["message_id"].as_i64().map(|id| id.to_string()))
+                })
+                .await?;
+            ids.extend(id);
+        }
+
+        tracing::info!(parts = chunks.len(), "Delivered message to Telegram");
+        Ok((!ids.is_empty()).then(|| ids.join(",")))
+    }
+}
+
+/// Convert markdown to Telegram MarkdownV2. Headers become bold, `-` / `*`
+/// bullets become `•`, and `**bold**`, `` `code` `` and `[text](url)` keep
+/// their meaning; every other reserved character is escaped. Inside fenced
+/// code blocks only `` ` `` and `\` are escaped, so code comes through as written.
+pub fn markdown_to_telegram(input: &str) -> String {
+    let mut in_fence = false;
+    input
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with(FENCE) {
+                in_fence = !in_fence;
+                return trimmed.trim_end().to_string();
+            }
+            if in_fence { escape_code(line) } else { convert_line(line) }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn convert_line(line: &str) -> String {
+    let trimmed = line.trim();
+    for prefix in ["### ", "## ", "# "] {
+        if let Some(rest) = trimmed.strip_prefix(prefix) {
+            return format!("*{}*", escape(rest.trim()));
+        }
+    }
+    let indent = &line[..line.len() - line.trim_start().len()];
+    if let Some(rest) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+        return format!("{indent}• {}", convert_inline(rest));
+    }
+    convert_inline(line)
+}
+
+/// Escape one line of prose, keeping inline code, `**bold**` and links.
+fn convert_inline(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len() + 8);
+    let mut i = 0;
+    while i < chars.len() {
+        let formatted = match chars[i] {
+            '`' => inline_code(&chars, i),
+            '*' if chars.get(i + 1) == Some(&'*') => bold(&chars, i),
+            '[' => link(&chars, i),
+            _ => None,
+        };
+        if let Some((formatted, end)) = formatted {
+            out.push_str(&formatted);
+            i = end;
+            continue;
+        }
+        push_escaped(&mut out, chars[i]);
+        i += 1;
+    }
+    out
+}
+
+fn inline_code(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let close = (start + 1..chars.len()).find(|&j| chars[j] == '`')?;
+    let code: String = chars[start + 1..close].iter().collect();
+    Some((format!("`{}`", escape_code(&code)), close + 1))
+}
+
+fn bold(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let close = find_closing_double_star(chars, start + 2)?;
+    if close == start + 2 {
+        return None;
+    }
+    let inner: String = chars[start + 2..close].iter().collect();
+    Some((format!("*{}*", escape(&inner)), close + 2))
+}
+
+fn link(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let (text, url, end) = parse_md_link(chars, start)?;
+    // Within the (...) part only `)` and `\` are special
+    let url: String = url
+        .chars()
+        .flat_map(|c| if matches!(c, ')' | '\\') { vec!['\\', c] } else { vec![c] })
+        .collect();
+    Some((format!("[{}]({url})", escape(&text)), end))
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    if RESERVED.contains(&c) {
+        out.push('\\');
+    }
+    out.push(c);
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        push_escaped(&mut out, c);
+    }
+    out
+}
+
+/// Telegram requires `` ` `` and `\` escaped inside code entities, and
+/// nothing else.
+fn escape_code(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '`' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserved_characters_are_escaped() {
+        assert_eq!(
+            markdown_to_telegram("BTC: $64,000 (+5.2%) - nice! a_b #1 {x} [y] ~z | > = *"),
+            r"BTC: $64,000 \(\+5\.2%\) \- nice\! a\_b \#1 \{x\} \[y\] \~z \| \> \= \*"
+        );
+        assert_eq!(markdown_to_telegram(r"C:\temp"), r"C:\\temp");
+    }
+
+    #[test]
+    fn test_markdown_is_converted() {
+        assert_eq!(markdown_to_telegram("# Daily_Report 1.0"), r"*Daily\_Report 1\.0*");
+        assert_eq!(markdown_to_telegram("- **BTC** up 5.2%"), r"• *BTC* up 5\.2%");
+        assert_eq!(markdown_to_telegram("  * nested."), r"  • nested\.");
+        assert_eq!(
+            markdown_to_telegram("See [v1.2 notes](https://e.com/a_b.html?x=1-2) now."),
+            r"See [v1\.2 notes](https://e.com/a_b.html?x=1-2) now\."
+        );
+        assert_eq!(markdown_to_telegram("run `a_b.c(1) - 2` now"), r"run `a_b.c(1) - 2` now");
+        // Unclosed markup is literal text
+        assert_eq!(markdown_to_telegram("a ** b ` c [d]"), r"a \*\* b \` c \[d\]");
+    }
+
+    #[test]
+    fn test_code_blocks_survive_escaping_and_splitting() {
+        let input = "Result:\n```rust\nlet x = a.b(c) - 1; // #1 {ok}!\nlet p = \"C:\\\\\"; `q`\n```\nDone.";
+        assert_eq!(
+            markdown_to_telegram(input),
+            "Result:\n```rust\nlet x = a.b(c) - 1; // #1 {ok}!\nlet p = \"C:\\\\\\\\\"; \\`q\\`\n```\nDone\\."
+        );
+
+        let long = format!("intro.\n```\n{}\n```\nafter!", ["fn f() -> u8 { 1 }"; 600].join("\n"));
+        let parts = split_message(&markdown_to_telegram(&long), MAX_MESSAGE_CHARS);
+        assert!(parts.len() > 1);
+        for part in &parts {
+            assert!(part.chars().count() <= MAX_MESSAGE_CHARS);
+            assert_eq!(part.matches(FENCE).count() % 2, 0, "unbalanced part: {part}");
+            // Code lines are untouched in every part
+            assert!(part.lines().any(|l| l == "fn f() -> u8 { 1 }"));
+        }
+        assert!(parts[0].starts_with(r"intro\."));
+        assert!(parts.last().unwrap().ends_with(r"after\!"));
+    }
+}
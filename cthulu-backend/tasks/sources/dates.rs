@@ -0,0 +1,138 @@
+//! Date parsing shared by the feed and scraping sources.
+//!
+//! Feeds in the wild mix RFC 2822 variants, ISO 8601 with and without an
+//! offset, and bare dates. Everything is normalized to UTC; a timestamp
+//! without a zone is taken to already be UTC.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+/// Formats carrying an explicit offset.
+const ZONED_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%dT%H:%M%:z",
+    "%Y-%m-%d %H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S %z",
+    "%a, %d %b %Y %H:%M %z",
+    "%d %b %Y %H:%M:%S %z",
+];
+
+/// Timestamps without a zone, read as UTC.
+const NAIVE_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%a, %d %b %Y %H:%M:%S",
+    "%d %b %Y %H:%M:%S",
+];
+
+/// Bare dates, read as midnight UTC.
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%B %d, %Y", "%b %d, %Y", "%d %B %Y", "%d %b %Y"];
+
+/// Parse a published/updated date in any of the common feed formats,
+/// returning it in UTC. Unrecognized input gives `None` (logged at debug).
+pub fn parse_date(raw: &str) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+
+    let parsed = DateTime::parse_from_rfc3339(raw)
+        .or_else(|_| DateTime::parse_from_rfc2822(raw))
+        .ok()
+        .or_else(|| ZONED_FORMATS.iter().find_map(|f| DateTime::parse_from_str(raw, f).ok()))
+        .map(|dt| dt.with_timezone(&Utc))
+        .or_else(|| {
+            NAIVE_FORMATS
+                .iter()
+                .find_map(|f| NaiveDateTime::parse_from_str(raw, f).ok())
+                .map(|dt| dt.and_utc())
+        })
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|f| NaiveDate::parse_from_str(raw, f).ok())
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|dt| dt.and_utc())
+        });
+
+    if parsed.is_none() {
+        tracing::debug!(date = raw, "unrecognized date format");
+    }
+    parsed
+}
+
+/// Parse with a caller-supplied chrono format first (as a timestamp, then as
+/// a date), falling back to [`parse_date`].
+pub fn parse_date_with(raw: &str, format: Option<&str>) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    format
+        .and_then(|fmt| {
+            NaiveDateTime::parse_from_str(raw, fmt)
+                .ok()
+                .map(|dt| dt.and_utc())
+                .or_else(|| {
+                    NaiveDate::parse_from_str(raw, fmt)
+                        .ok()
+                        .and_then(|d| d.and_hms_opt(0, 0, 0))
+                        .map(|dt| dt.and_utc())
+                })
+        })
+        .or_else(|| parse_date(raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(raw: &str) -> String {
+        parse_date(raw)
+            .unwrap_or_else(|| panic!("failed to parse {raw:?}"))
+            .to_rfc3339()
+    }
+
+    #[test]
+    fn test_real_world_dates() {
+        let cases = [
+            // RFC 2822 and its common variants
+            ("Wed, 10 Jan 2024 15:04:05 +0000", "2024-01-10T15:04:05+00:00"),
+            ("Wed, 10 Jan 2024 15:04:05 GMT", "2024-01-10T15:04:05+00:00"),
+            ("Wed, 10 Jan 2024 10:04:05 EST", "2024-01-10T15:04:05+00:00"),
+            ("10 Jan 2024 15:04:05 +0100", "2024-01-10T14:04:05+00:00"),
+            ("Wed, 10 Jan 2024 15:04 +0000", "2024-01-10T15:04:00+00:00"),
+            // ISO 8601 / RFC 3339
+            ("2024-01-10T15:04:05Z", "2024-01-10T15:04:05+00:00"),
+            ("2024-01-10T17:04:05+02:00", "2024-01-10T15:04:05+00:00"),
+            ("2024-01-10T15:04:05.123+0000", "2024-01-10T15:04:05.123+00:00"),
+            ("2024-01-10T17:04+02:00", "2024-01-10T15:04:00+00:00"),
+            // No zone: taken as UTC
+            ("2024-01-10T15:04:05", "2024-01-10T15:04:05+00:00"),
+            ("2024-01-10 15:04:05", "2024-01-10T15:04:05+00:00"),
+            ("Wed, 10 Jan 2024 15:04:05", "2024-01-10T15:04:05+00:00"),
+            // Dates only
+            ("2024-01-10", "2024-01-10T00:00:00+00:00"),
+            ("January 10, 2024", "2024-01-10T00:00:00+00:00"),
+            ("10 Jan 2024", "2024-01-10T00:00:00+00:00"),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(utc(raw), expected, "input {raw:?}");
+        }
+    }
+
+    #[test]
+    fn test_unparseable_dates_are_none() {
+        assert!(parse_date("").is_none());
+        assert!(parse_date("yesterday").is_none());
+        assert!(parse_date("March 1st").is_none());
+    }
+
+    #[test]
+    fn test_custom_format_falls_back() {
+        assert_eq!(
+            parse_date_with("10/01/2024", Some("%d/%m/%Y")).unwrap().to_rfc3339(),
+            "2024-01-10T00:00:00+00:00"
+        );
+        // Doesn't match the format but is still a recognizable date.
+        assert!(parse_date_with("2024-01-10", Some("%d/%m/%Y")).is_some());
+    }
+}
@@ -0,0 +1,185 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use reqwest::header::LINK;
+use serde::Deserialize;
+
+use super::ContentItem;
+
+const API_BASE: &str = "https://api.github.com";
+
+const PER_PAGE: usize = 100;
+
+/// Pages fetched per repo before giving up (with a warning) on the rest.
+const MAX_PAGES: usize = 20;
+
+/// Longest issue body excerpt used as an item summary.
+const MAX_SUMMARY_CHARS: usize = 500;
+
+/// Which issues to list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueState {
+    #[default]
+    Open,
+    Closed,
+    All,
+}
+
+impl IssueState {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "open" => Ok(Self::Open),
+            "closed" => Ok(Self::Closed),
+            "all" => Ok(Self::All),
+            other => bail!("github-issues state must be open, closed or all, got '{other}'"),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Closed => "closed",
+            Self::All => "all",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Issue {
+    title: String,
+    html_url: String,
+    body: Option<String>,
+    created_at: DateTime<Utc>,
+    /// Set when the "issue" is a pull request, which the issues API also lists.
+    pull_request: Option<serde_json::Value>,
+}
+
+/// Fetch issues from each repo in turn. `since_days` keeps issues updated
+/// within the window; `labels` must all match.
+pub async fn fetch_issues(
+    http_client: &reqwest::Client,
+    token: &str,
+    repos: &[String],
+    state: IssueState,
+    labels: &[String],
+    since_days: u64,
+) -> Result<Vec<ContentItem>> {
+    let since = (Utc::now() - chrono::Duration::days(since_days.min(36_500) as i64)).to_rfc3339();
+    let mut items = Vec::new();
+    for repo in repos {
+        let url = format!("{API_BASE}/repos/{repo}/issues");
+        let mut query = vec![
+            ("state", state.as_str().to_string()),
+            ("since", since.clone()),
+            ("per_page", PER_PAGE.to_string()),
+            ("sort", "created".to_string()),
+            ("direction", "desc".to_string()),
+        ];
+        if !labels.is_empty() {
+            query.push(("labels", labels.join(",")));
+        }
+
+        let mut request = http_client.get(&url).query(&query);
+        let mut pages = 0;
+        loop {
+            let resp = request
+                .header("Authorization", format!("Bearer {token}"))
+                .header("Accept", "application/vnd.github+json")
+                .header("User-Agent", "cthulu-bot")
+                .header("X-GitHub-Api-Version", "2022-11-28")
+                .send()
+                .await
+                .with_context(|| format!("GitHub issues request failed for {repo}"))?;
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let body = resp.text().await.unwrap_or_default();
+                bail!("GitHub issues API returned {status} for {repo}: {body}");
+            }
+
+            let next = resp
+                .headers()
+                .get(LINK)
+                .and_then(|v| v.to_str().ok())
+                .and_then(next_link);
+            let issues: Vec<Issue> = resp
+                .json()
+                .await
+                .with_context(|| format!("failed to parse GitHub issues for {repo}"))?;
+            items.extend(issues.into_iter().filter(|i| i.pull_request.is_none()).map(to_content_item));
+
+            pages += 1;
+            let Some(next) = next else { break };
+            if pages >= MAX_PAGES {
+                tracing::warn!(repo = %repo, pages, "GitHub issues page cap reached, later issues skipped");
+                break;
+            }
+            // The next link already carries the query
+            request = http_client.get(next);
+        }
+    }
+    Ok(items)
+}
+
+fn to_content_item(issue: Issue) -> ContentItem {
+    let body = issue.body.unwrap_or_default();
+    let body = body.trim();
+    let mut summary: String = body.chars().take(MAX_SUMMARY_CHARS).collect();
+    if summary.len() < body.len() {
+        summary.push('…');
+    }
+    ContentItem {
+        title: issue.title,
+        url: issue.html_url,
+        summary,
+        published: Some(issue.created_at),
+        image_url: None,
+    }
+}
+
+/// The `rel="next"` URL from a GitHub `Link` header.
+fn next_link(header: &str) -> Option<String> {
+    header.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        params
+            .split(';')
+            .any(|p| p.trim() == r#"rel="next""#)
+            .then(|| url.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_link() {
+        let header = r#"<https://api.github.com/repositories/1/issues?page=2>; rel="next", <https://api.github.com/repositories/1/issues?page=9>; rel="last""#;
+        assert_eq!(
+            next_link(header).as_deref(),
+            Some("https://api.github.com/repositories/1/issues?page=2")
+        );
+        let last_page = r#"<https://api.github.com/repositories/1/issues?page=1>; rel="prev", <https://api.github.com/repositories/1/issues?page=1>; rel="first""#;
+        assert_eq!(next_link(last_page), None);
+    }
+
+    #[test]
+    fn test_issues_skip_pull_requests_and_excerpt_body() {
+        let json = format!(
+            r#"[
+                {{"title": "Crash on start", "html_url": "https://github.com/o/r/issues/1", "body": "{}", "created_at": "2025-01-15T10:00:00Z"}},
+                {{"title": "A PR", "html_url": "https://github.com/o/r/pull/2", "body": null, "created_at": "2025-01-15T11:00:00Z", "pull_request": {{"url": "x"}}}}
+            ]"#,
+            "x".repeat(600)
+        );
+        let issues: Vec<Issue> = serde_json::from_str(&json).unwrap();
+        let items: Vec<ContentItem> =
+            issues.into_iter().filter(|i| i.pull_request.is_none()).map(to_content_item).collect();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].url, "https://github.com/o/r/issues/1");
+        assert_eq!(items[0].summary.chars().count(), MAX_SUMMARY_CHARS + 1);
+        assert!(items[0].published.is_some());
+        assert_eq!(IssueState::parse("closed").unwrap(), IssueState::Closed);
+        assert!(IssueState::parse("merged").is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{Context, Result, bail};
+use chrono::{TimeZone, Utc};
+use futures::future::join_all;
+use serde::Deserialize;
+
+use super::ContentItem;
+
+const API_BASE: &str = "https://hacker-news.firebaseio.com/v0";
+const ITEM_LINK: &str = "https://news.ycombinator.com/item?id=";
+
+/// Which HN story list to read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StoryType {
+    #[default]
+    Top,
+    Best,
+    New,
+}
+
+impl StoryType {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "top" => Ok(Self::Top),
+            "best" => Ok(Self::Best),
+            "new" => Ok(Self::New),
+            other => bail!("hackernews story_type must be top, best or new, got '{other}'"),
+        }
+    }
+
+    fn endpoint(self) -> &'static str {
+        match self {
+            Self::Top => "topstories",
+            Self::Best => "beststories",
+            Self::New => "newstories",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Item {
+    id: u64,
+    title: Option<String>,
+    url: Option<String>,
+    score: Option<i64>,
+    /// Comment count.
+    descendants: Option<i64>,
+    time: Option<i64>,
+    #[serde(default)]
+    deleted: bool,
+    #[serde(default)]
+    dead: bool,
+}
+
+pub async fn fetch_stories(
+    client: &reqwest::Client,
+    story_type: StoryType,
+    limit: usize,
+) -> Result<Vec<ContentItem>> {
+    let ids: Vec<u64> = client
+        .get(format!("{API_BASE}/{}.json", story_type.endpoint()))
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .context("failed to fetch hackernews story list")?
+        .error_for_status()
+        .context("hackernews story list returned error status")?
+        .json()
+        .await
+        .context("failed to parse hackernews story list")?;
+
+    let fetches = ids.into_iter().take(limit).map(|id| async move {
+        let item = fetch_item(client, id).await;
+        if let Err(e) = &item {
+            tracing::warn!(id, error = %e, "Failed to fetch hackernews item");
+        }
+        item.ok().and_then(to_content_item)
+    });
+    Ok(join_all(fetches).await.into_iter().flatten().collect())
+}
+
+async fn fetch_item(client: &reqwest::Client, id: u64) -> Result<Item> {
+    client
+        .get(format!("{API_BASE}/item/{id}.json"))
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+        .context("failed to parse hackernews item")
+}
+
+/// `None` for deleted, dead or untitled items. Stories without a URL (Ask
+/// HN, Show HN text posts) link to their HN discussion instead.
+fn to_content_item(item: Item) -> Option<ContentItem> {
+    if item.deleted || item.dead {
+        return None;
+    }
+    let title = item.title.filter(|t| !t.is_empty())?;
+    let discussion = format!("{ITEM_LINK}{}", item.id);
+    let url = item.url.filter(|u| !u.is_empty()).unwrap_or_else(|| discussion.clone());
+
+    Some(ContentItem {
+        title,
+        url,
+        summary: format!(
+            "{} points · {} comments · {discussion}",
+            item.score.unwrap_or(0),
+            item.descendants.unwrap_or(0)
+        ),
+        published: item.time.and_then(|t| Utc.timestamp_opt(t, 0).single()),
+        image_url: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(json: &str) -> Item {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_story_maps_to_content_item() {
+        let story = to_content_item(item(
+            r#"{"id": 8863, "type": "story", "title": "My YC app", "url": "https://example.com/app",
+                "score": 104, "descendants": 71, "time": 1175714200, "by": "dhouston", "kids": [9224]}"#,
+        ))
+        .unwrap();
+        assert_eq!(story.title, "My YC app");
+        assert_eq!(story.url, "https://example.com/app");
+        assert_eq!(story.summary, "104 points · 71 comments · https://news.ycombinator.com/item?id=8863");
+        assert_eq!(story.published.unwrap().timestamp(), 1175714200);
+    }
+
+    #[test]
+    fn test_ask_hn_falls_back_to_item_link() {
+        let ask = to_content_item(item(
+            r#"{"id": 121003, "type": "story", "title": "Ask HN: The Arc Effect", "text": "<i>or</i> HN", "score": 25, "time": 1203647620}"#,
+        ))
+        .unwrap();
+        assert_eq!(ask.url, "https://news.ycombinator.com/item?id=121003");
+        assert!(ask.summary.starts_with("25 points · 0 comments"));
+
+        assert!(to_content_item(item(r#"{"id": 1, "title": "gone", "dead": true}"#)).is_none());
+        assert!(to_content_item(item(r#"{"id": 2, "deleted": true}"#)).is_none());
+    }
+
+    #[test]
+    fn test_story_type() {
+        assert_eq!(StoryType::parse("best").unwrap().endpoint(), "beststories");
+        assert_eq!(StoryType::default().endpoint(), "topstories");
+        assert!(StoryType::parse("ask").is_err());
+    }
+}
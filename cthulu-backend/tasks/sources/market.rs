@@ -3,6 +3,106 @@ use serde::Deserialize;
 
 const BROWSER_UA: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
 
+// ── Providers ────────────────────────────────────────────────────
+
+/// Where coin prices come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CryptoProvider {
+    /// Keyless public API; `COINGECKO_API_KEY` switches to the Pro API.
+    CoinGecko,
+    /// Requires `COINMARKETCAP_API_KEY`.
+    CoinMarketCap,
+}
+
+/// Where the S&P 500 quote comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StockProvider {
+    /// Keyless Yahoo Finance chart endpoint.
+    Yahoo,
+    /// Requires `ALPHA_VANTAGE_API_KEY`; quotes SPY as the S&P 500 proxy.
+    AlphaVantage,
+}
+
+/// Provider choice and API keys for the market snapshot.
+#[derive(Debug, Clone)]
+pub struct MarketConfig {
+    pub crypto: CryptoProvider,
+    pub stocks: StockProvider,
+    pub coingecko_api_key: Option<String>,
+    pub coinmarketcap_api_key: Option<String>,
+    pub alpha_vantage_api_key: Option<String>,
+}
+
+impl MarketConfig {
+    pub fn from_env() -> Self {
+        Self::from_raw_values(
+            std::env::var("MARKET_CRYPTO_PROVIDER").ok().as_deref(),
+            std::env::var("MARKET_STOCK_PROVIDER").ok().as_deref(),
+            std::env::var("COINGECKO_API_KEY").ok().as_deref(),
+            std::env::var("COINMARKETCAP_API_KEY").ok().as_deref(),
+            std::env::var("ALPHA_VANTAGE_API_KEY").ok().as_deref(),
+        )
+    }
+
+    /// Keyed providers without a key fall back to the keyless defaults.
+    pub fn from_raw_values(
+        crypto: Option<&str>,
+        stocks: Option<&str>,
+        coingecko_api_key: Option<&str>,
+        coinmarketcap_api_key: Option<&str>,
+        alpha_vantage_api_key: Option<&str>,
+    ) -> Self {
+        let key = |v: Option<&str>| v.map(str::trim).filter(|s| !s.is_empty()).map(String::from);
+        let coingecko_api_key = key(coingecko_api_key);
+        let coinmarketcap_api_key = key(coinmarketcap_api_key);
+        let alpha_vantage_api_key = key(alpha_vantage_api_key);
+
+        let crypto = match crypto.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("coinmarketcap") if coinmarketcap_api_key.is_some() => CryptoProvider::CoinMarketCap,
+            Some("coinmarketcap") => {
+                tracing::warn!("MARKET_CRYPTO_PROVIDER=coinmarketcap needs COINMARKETCAP_API_KEY; using CoinGecko");
+                CryptoProvider::CoinGecko
+            }
+            _ => CryptoProvider::CoinGecko,
+        };
+        let stocks = match stocks.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("alphavantage" | "alpha_vantage") if alpha_vantage_api_key.is_some() => StockProvider::AlphaVantage,
+            Some("alphavantage" | "alpha_vantage") => {
+                tracing::warn!("MARKET_STOCK_PROVIDER=alphavantage needs ALPHA_VANTAGE_API_KEY; using Yahoo");
+                StockProvider::Yahoo
+            }
+            _ => StockProvider::Yahoo,
+        };
+
+        MarketConfig {
+            crypto,
+            stocks,
+            coingecko_api_key,
+            coinmarketcap_api_key,
+            alpha_vantage_api_key,
+        }
+    }
+}
+
+/// A coin price, whichever provider it came from.
+#[derive(Debug, Clone, PartialEq)]
+struct CoinQuote {
+    symbol: String,
+    price: f64,
+    change_24h: Option<f64>,
+}
+
+/// An index (or index proxy) quote, whichever provider it came from.
+#[derive(Debug, Clone, PartialEq)]
+struct IndexQuote {
+    label: &'static str,
+    price: f64,
+    change_pct: f64,
+}
+
+/// Coins in the snapshot: CoinGecko ids and ticker symbols.
+const COINS: &[(&str, &str)] = &[("bitcoin", "BTC"), ("ethereum", "ETH")];
+
 // ── CoinGecko types ──────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -12,6 +112,25 @@ struct CoinMarket {
     price_change_percentage_24h: Option<f64>,
 }
 
+// ── CoinMarketCap types ──────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct CmcQuotesResponse {
+    data: std::collections::HashMap<String, CmcCoin>,
+}
+
+#[derive(Deserialize)]
+struct CmcCoin {
+    symbol: String,
+    quote: std::collections::HashMap<String, CmcQuote>,
+}
+
+#[derive(Deserialize)]
+struct CmcQuote {
+    price: f64,
+    percent_change_24h: Option<f64>,
+}
+
 // ── CNN Fear & Greed types ───────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -62,14 +181,38 @@ struct YahooMeta {
     chart_previous_close: f64,
 }
 
+// ── Alpha Vantage types ──────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct AlphaVantageResponse {
+    #[serde(rename = "Global Quote")]
+    global_quote: Option<AlphaVantageQuote>,
+    /// Set instead of a quote when rate-limited.
+    #[serde(rename = "Note", alias = "Information")]
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AlphaVantageQuote {
+    #[serde(rename = "05. price")]
+    price: String,
+    #[serde(rename = "10. change percent")]
+    change_percent: String,
+}
+
 // ── Public API ───────────────────────────────────────────────────
 
+/// Market snapshot using the providers configured in the environment.
 pub async fn fetch_market_snapshot(client: &reqwest::Client) -> Result<String> {
+    fetch_market_snapshot_with(client, &MarketConfig::from_env()).await
+}
+
+pub async fn fetch_market_snapshot_with(client: &reqwest::Client, config: &MarketConfig) -> Result<String> {
     let (cnn_result, crypto_fng_result, coins_result, sp500_result) = tokio::join!(
         fetch_cnn_fear_greed(client),
         fetch_crypto_fear_greed(client),
-        fetch_coin_prices(client),
-        fetch_sp500(client),
+        fetch_coin_prices(client, config),
+        fetch_sp500(client, config),
     );
 
     let mut lines = Vec::new();
@@ -106,8 +249,8 @@ pub async fn fetch_market_snapshot(client: &reqwest::Client) -> Result<String> {
         Ok(coins) => {
             for coin in coins {
                 let symbol = coin.symbol.to_uppercase();
-                let price = format_price(coin.current_price);
-                let change = coin.price_change_percentage_24h.unwrap_or(0.0);
+                let price = format_price(coin.price);
+                let change = coin.change_24h.unwrap_or(0.0);
                 let sign = if change >= 0.0 { "+" } else { "" };
                 let color = change_color(change);
                 lines.push(format!(
@@ -116,22 +259,22 @@ pub async fn fetch_market_snapshot(client: &reqwest::Client) -> Result<String> {
             }
         }
         Err(e) => {
-            tracing::warn!(error = %e, "CoinGecko fetch failed");
+            tracing::warn!(error = %e, provider = ?config.crypto, "Coin price fetch failed");
         }
     }
 
     // S&P 500
     match sp500_result {
-        Ok((price, change_pct)) => {
+        Ok(IndexQuote { label, price, change_pct }) => {
             let formatted = format_price(price);
             let sign = if change_pct >= 0.0 { "+" } else { "" };
             let color = change_color(change_pct);
             lines.push(format!(
-                "- **S&P 500**: {formatted} {{{color}:({sign}{change_pct:.1}%)}}"
+                "- **{label}**: {formatted} {{{color}:({sign}{change_pct:.1}%)}}"
             ));
         }
         Err(e) => {
-            tracing::warn!(error = %e, "S&P 500 fetch failed");
+            tracing::warn!(error = %e, provider = ?config.stocks, "S&P 500 fetch failed");
         }
     }
 
@@ -176,36 +319,118 @@ async fn fetch_crypto_fear_greed(client: &reqwest::Client) -> Result<(String, St
     Ok((entry.value, entry.value_classification))
 }
 
-async fn fetch_coin_prices(client: &reqwest::Client) -> Result<Vec<CoinMarket>> {
-    let resp = client
-        .get("https://api.coingecko.com/api/v3/coins/markets")
+async fn fetch_coin_prices(client: &reqwest::Client, config: &MarketConfig) -> Result<Vec<CoinQuote>> {
+    match config.crypto {
+        CryptoProvider::CoinGecko => fetch_coingecko(client, config.coingecko_api_key.as_deref()).await,
+        CryptoProvider::CoinMarketCap => {
+            let key = config.coinmarketcap_api_key.as_deref().unwrap_or_default();
+            fetch_coinmarketcap(client, key).await
+        }
+    }
+}
+
+async fn fetch_coingecko(client: &reqwest::Client, api_key: Option<&str>) -> Result<Vec<CoinQuote>> {
+    let ids: Vec<&str> = COINS.iter().map(|(id, _)| *id).collect();
+    let base = match api_key {
+        Some(_) => "https://pro-api.coingecko.com/api/v3",
+        None => "https://api.coingecko.com/api/v3",
+    };
+    let mut req = client
+        .get(format!("{base}/coins/markets"))
         .header("User-Agent", "cthulu-bot")
         .query(&[
             ("vs_currency", "usd"),
-            ("ids", "bitcoin,ethereum"),
+            ("ids", ids.join(",").as_str()),
             ("order", "market_cap_desc"),
-        ])
+        ]);
+    if let Some(key) = api_key {
+        req = req.header("x-cg-pro-api-key", key);
+    }
+    let body = req
         .timeout(std::time::Duration::from_secs(10))
         .send()
         .await?
         .error_for_status()?
-        .json()
+        .text()
         .await?;
 
-    Ok(resp)
+    parse_coingecko(&body)
+}
+
+fn parse_coingecko(body: &str) -> Result<Vec<CoinQuote>> {
+    let markets: Vec<CoinMarket> = serde_json::from_str(body)?;
+    Ok(markets
+        .into_iter()
+        .map(|m| CoinQuote {
+            symbol: m.symbol,
+            price: m.current_price,
+            change_24h: m.price_change_percentage_24h,
+        })
+        .collect())
 }
 
-async fn fetch_sp500(client: &reqwest::Client) -> Result<(f64, f64)> {
-    let resp: YahooChartResponse = client
+async fn fetch_coinmarketcap(client: &reqwest::Client, api_key: &str) -> Result<Vec<CoinQuote>> {
+    let symbols: Vec<&str> = COINS.iter().map(|(_, symbol)| *symbol).collect();
+    let body = client
+        .get("https://pro-api.coinmarketcap.com/v1/cryptocurrency/quotes/latest")
+        .header("X-CMC_PRO_API_KEY", api_key)
+        .query(&[("symbol", symbols.join(",").as_str()), ("convert", "USD")])
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    parse_coinmarketcap(&body)
+}
+
+/// CoinMarketCap keys coins by symbol; keep the snapshot's usual order.
+fn parse_coinmarketcap(body: &str) -> Result<Vec<CoinQuote>> {
+    let mut resp: CmcQuotesResponse = serde_json::from_str(body)?;
+    COINS
+        .iter()
+        .filter_map(|(_, symbol)| resp.data.remove(*symbol))
+        .map(|coin| {
+            let usd = coin
+                .quote
+                .get("USD")
+                .ok_or_else(|| anyhow::anyhow!("CoinMarketCap returned no USD quote for {}", coin.symbol))?;
+            Ok(CoinQuote {
+                symbol: coin.symbol,
+                price: usd.price,
+                change_24h: usd.percent_change_24h,
+            })
+        })
+        .collect()
+}
+
+async fn fetch_sp500(client: &reqwest::Client, config: &MarketConfig) -> Result<IndexQuote> {
+    match config.stocks {
+        StockProvider::Yahoo => fetch_yahoo_sp500(client).await,
+        StockProvider::AlphaVantage => {
+            let key = config.alpha_vantage_api_key.as_deref().unwrap_or_default();
+            fetch_alpha_vantage_sp500(client, key).await
+        }
+    }
+}
+
+async fn fetch_yahoo_sp500(client: &reqwest::Client) -> Result<IndexQuote> {
+    let body = client
         .get("https://query1.finance.yahoo.com/v8/finance/chart/%5EGSPC?interval=1d&range=1d")
         .header("User-Agent", BROWSER_UA)
         .timeout(std::time::Duration::from_secs(10))
         .send()
         .await?
         .error_for_status()?
-        .json()
+        .text()
         .await?;
 
+    parse_yahoo(&body)
+}
+
+fn parse_yahoo(body: &str) -> Result<IndexQuote> {
+    let resp: YahooChartResponse = serde_json::from_str(body)?;
     let result = resp
         .chart
         .result
@@ -220,7 +445,37 @@ async fn fetch_sp500(client: &reqwest::Client) -> Result<(f64, f64)> {
         0.0
     };
 
-    Ok((price, change_pct))
+    Ok(IndexQuote { label: "S&P 500", price, change_pct })
+}
+
+async fn fetch_alpha_vantage_sp500(client: &reqwest::Client, api_key: &str) -> Result<IndexQuote> {
+    let body = client
+        .get("https://www.alphavantage.co/query")
+        .query(&[("function", "GLOBAL_QUOTE"), ("symbol", "SPY"), ("apikey", api_key)])
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    parse_alpha_vantage(&body)
+}
+
+/// Alpha Vantage has no index quotes, so SPY stands in for the S&P 500.
+fn parse_alpha_vantage(body: &str) -> Result<IndexQuote> {
+    let resp: AlphaVantageResponse = serde_json::from_str(body)?;
+    let quote = match resp.global_quote {
+        Some(quote) => quote,
+        None => anyhow::bail!(
+            "Alpha Vantage returned no quote: {}",
+            resp.note.as_deref().unwrap_or("empty response")
+        ),
+    };
+    let price = quote.price.trim().parse::<f64>()?;
+    let change_pct = quote.change_percent.trim().trim_end_matches('%').parse::<f64>()?;
+
+    Ok(IndexQuote { label: "S&P 500 (SPY)", price, change_pct })
 }
 
 // ── Formatting helpers ───────────────────────────────────────────
@@ -321,6 +576,76 @@ mod tests {
         assert_eq!(progress_bar(30.0), "███░░░░░░░");
     }
 
+    #[test]
+    fn test_market_config_providers() {
+        let config = MarketConfig::from_raw_values(None, None, None, None, None);
+        assert_eq!(config.crypto, CryptoProvider::CoinGecko);
+        assert_eq!(config.stocks, StockProvider::Yahoo);
+
+        let config =
+            MarketConfig::from_raw_values(Some("CoinMarketCap"), Some("alphavantage"), None, Some("cmc"), Some("av"));
+        assert_eq!(config.crypto, CryptoProvider::CoinMarketCap);
+        assert_eq!(config.stocks, StockProvider::AlphaVantage);
+
+        // Keyed providers need their key
+        let config = MarketConfig::from_raw_values(Some("coinmarketcap"), Some("alphavantage"), None, Some(" "), None);
+        assert_eq!(config.crypto, CryptoProvider::CoinGecko);
+        assert_eq!(config.stocks, StockProvider::Yahoo);
+    }
+
+    fn btc_eth() -> Vec<CoinQuote> {
+        vec![
+            CoinQuote { symbol: "BTC".to_string(), price: 97000.5, change_24h: Some(2.5) },
+            CoinQuote { symbol: "ETH".to_string(), price: 3200.25, change_24h: Some(-1.25) },
+        ]
+    }
+
+    #[test]
+    fn test_parse_coingecko() {
+        let body = r#"[
+            {"id":"bitcoin","symbol":"btc","current_price":97000.5,"price_change_percentage_24h":2.5},
+            {"id":"ethereum","symbol":"eth","current_price":3200.25,"price_change_percentage_24h":-1.25}
+        ]"#;
+        let mut coins = parse_coingecko(body).unwrap();
+        for coin in &mut coins {
+            coin.symbol.make_ascii_uppercase();
+        }
+        assert_eq!(coins, btc_eth());
+    }
+
+    #[test]
+    fn test_parse_coinmarketcap() {
+        let body = r#"{
+            "status": {"error_code": 0},
+            "data": {
+                "ETH": {"id":1027,"symbol":"ETH","quote":{"USD":{"price":3200.25,"percent_change_24h":-1.25}}},
+                "BTC": {"id":1,"symbol":"BTC","quote":{"USD":{"price":97000.5,"percent_change_24h":2.5}}}
+            }
+        }"#;
+        assert_eq!(parse_coinmarketcap(body).unwrap(), btc_eth());
+    }
+
+    #[test]
+    fn test_parse_yahoo() {
+        let body = r#"{"chart":{"result":[{"meta":{"regularMarketPrice":5050.0,"chartPreviousClose":5000.0}}],"error":null}}"#;
+        assert_eq!(
+            parse_yahoo(body).unwrap(),
+            IndexQuote { label: "S&P 500", price: 5050.0, change_pct: 1.0 }
+        );
+    }
+
+    #[test]
+    fn test_parse_alpha_vantage() {
+        let body = r#"{"Global Quote":{"01. symbol":"SPY","05. price":"512.3400","08. previous close":"510.0000","10. change percent":"0.4588%"}}"#;
+        let quote = parse_alpha_vantage(body).unwrap();
+        assert_eq!(quote.label, "S&P 500 (SPY)");
+        assert_eq!(quote.price, 512.34);
+        assert!((quote.change_pct - 0.4588).abs() < 1e-9);
+
+        let limited = r#"{"Note":"Thank you for using Alpha Vantage! Our standard API rate limit is 25 requests per day."}"#;
+        assert!(parse_alpha_vantage(limited).unwrap_err().to_string().contains("rate limit"));
+    }
+
     #[test]
     fn test_change_color() {
         assert_eq!(change_color(2.3), "green");
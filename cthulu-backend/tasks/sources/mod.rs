@@ -1,15 +1,25 @@
+pub mod dates;
 pub mod google_sheets;
+pub mod hackernews;
 pub mod market;
 pub mod github_prs;
+pub mod github_issues;
+pub mod reddit;
+pub mod rest;
 pub mod rss;
+pub mod sitemap;
 pub mod web_scrape;
+pub mod youtube;
+
+use std::collections::BTreeMap;
 
 use chrono::{DateTime, Utc};
 use futures::future::join_all;
+use serde::{Deserialize, Serialize};
 
 use crate::config::SourceConfig;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContentItem {
     pub title: String,
     pub url: String,
@@ -18,6 +28,18 @@ pub struct ContentItem {
     pub image_url: Option<String>,
 }
 
+/// Attach a source's configured headers to `request`, expanding `${VAR}`
+/// references from the environment.
+pub(crate) fn with_headers(
+    mut request: reqwest::RequestBuilder,
+    headers: &BTreeMap<String, String>,
+) -> anyhow::Result<reqwest::RequestBuilder> {
+    for (name, value) in rest::resolve_headers(headers, |var| std::env::var(var).ok())? {
+        request = request.header(name, value);
+    }
+    Ok(request)
+}
+
 fn keyword_matches(item: &ContentItem, keywords: &[String]) -> bool {
     if keywords.is_empty() {
         return true;
@@ -35,8 +57,8 @@ pub async fn fetch_all(
         .iter()
         .map(|source| async move {
             match source {
-                SourceConfig::Rss { url, limit, keywords } => {
-                    match rss::fetch_feed(http_client, url, *limit).await {
+                SourceConfig::Rss { url, limit, keywords, headers } => {
+                    match rss::fetch_feed(http_client, url, *limit, headers).await {
                         Ok(feed_items) => {
                             let filtered: Vec<_> = feed_items
                                 .into_iter()
@@ -51,8 +73,8 @@ pub async fn fetch_all(
                         }
                     }
                 }
-                SourceConfig::WebScrape { url, keywords } => {
-                    match web_scrape::fetch_page_text(http_client, url).await {
+                SourceConfig::WebScrape { url, keywords, headers } => {
+                    match web_scrape::fetch_page_text(http_client, url, headers).await {
                         Ok(items) => {
                             let filtered: Vec<_> = items
                                 .into_iter()
@@ -83,6 +105,22 @@ pub async fn fetch_all(
                         }
                     }
                 }
+                SourceConfig::GithubIssues { repos, state, labels, since_days } => {
+                    let Some(token) = github_token else {
+                        tracing::error!("GithubIssues source requires GITHUB_TOKEN but none is set");
+                        return Vec::new();
+                    };
+                    match github_issues::fetch_issues(http_client, token, repos, *state, labels, *since_days).await {
+                        Ok(items) => {
+                            tracing::debug!(repos = ?repos, count = items.len(), "Fetched GitHub issues");
+                            items
+                        }
+                        Err(e) => {
+                            tracing::error!(repos = ?repos, error = %e, "Failed to fetch GitHub issues");
+                            Vec::new()
+                        }
+                    }
+                }
                 SourceConfig::GoogleSheets {
                     spreadsheet_id, range, service_account_key_env, limit,
                 } => {
@@ -110,13 +148,14 @@ pub async fn fetch_all(
                 SourceConfig::WebScraper {
                     url, base_url, items_selector, title_selector,
                     url_selector, summary_selector, date_selector,
-                    date_format, limit,
+                    date_format, limit, headers, next_page_selector, max_pages,
                 } => {
                     match web_scrape::fetch_page(
                         http_client, url, items_selector,
                         title_selector.as_deref(), url_selector.as_deref(),
                         summary_selector.as_deref(), date_selector.as_deref(),
                         date_format.as_deref(), *limit, base_url.as_deref(),
+                        headers, next_page_selector.as_deref(), *max_pages,
                     ).await {
                         Ok(items) => {
                             tracing::debug!(url = %url, count = items.len(), "Fetched web scrape");
@@ -128,6 +167,94 @@ pub async fn fetch_all(
                         }
                     }
                 }
+                SourceConfig::YouTube { channel_id, playlist_id, limit, keywords } => {
+                    let url = match youtube::feed_url(channel_id.as_deref(), playlist_id.as_deref()) {
+                        Ok(url) => url,
+                        Err(e) => {
+                            tracing::error!(error = %e, "Invalid YouTube source config");
+                            return Vec::new();
+                        }
+                    };
+                    match youtube::fetch_videos(http_client, &url, *limit).await {
+                        Ok(items) => {
+                            let filtered: Vec<_> = items
+                                .into_iter()
+                                .filter(|item| keyword_matches(item, keywords))
+                                .collect();
+                            tracing::debug!(url = %url, count = filtered.len(), "Fetched YouTube feed");
+                            filtered
+                        }
+                        Err(e) => {
+                            tracing::warn!(url = %url, error = %e, "Failed to fetch YouTube feed");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::Sitemap { url, limit, since_days, keywords, fetch_titles } => {
+                    match sitemap::fetch_sitemap(http_client, url, *limit, *since_days, *fetch_titles).await {
+                        Ok(items) => {
+                            let filtered: Vec<_> = items
+                                .into_iter()
+                                .filter(|item| keyword_matches(item, keywords))
+                                .collect();
+                            tracing::debug!(url = %url, count = filtered.len(), "Fetched sitemap");
+                            filtered
+                        }
+                        Err(e) => {
+                            tracing::warn!(url = %url, error = %e, "Failed to fetch sitemap");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::HackerNews { story_type, limit, keywords } => {
+                    match hackernews::fetch_stories(http_client, *story_type, *limit).await {
+                        Ok(items) => {
+                            let filtered: Vec<_> = items
+                                .into_iter()
+                                .filter(|item| keyword_matches(item, keywords))
+                                .collect();
+                            tracing::debug!(story_type = ?story_type, count = filtered.len(), "Fetched Hacker News stories");
+                            filtered
+                        }
+                        Err(e) => {
+                            tracing::warn!(story_type = ?story_type, error = %e, "Failed to fetch Hacker News stories");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::Reddit { subreddit, sort, limit, keywords } => {
+                    match reddit::fetch_posts(http_client, subreddit, *sort, *limit).await {
+                        Ok(items) => {
+                            let filtered: Vec<_> = items
+                                .into_iter()
+                                .filter(|item| keyword_matches(item, keywords))
+                                .collect();
+                            tracing::debug!(subreddit = %subreddit, count = filtered.len(), "Fetched subreddit");
+                            filtered
+                        }
+                        Err(e) => {
+                            tracing::warn!(subreddit = %subreddit, error = %e, "Failed to fetch subreddit");
+                            Vec::new()
+                        }
+                    }
+                }
+                SourceConfig::Rest(rest_config) => {
+                    let url = &rest_config.url;
+                    match rest::fetch_rest(http_client, rest_config).await {
+                        Ok(items) => {
+                            let filtered: Vec<_> = items
+                                .into_iter()
+                                .filter(|item| keyword_matches(item, &rest_config.keywords))
+                                .collect();
+                            tracing::debug!(url = %url, count = filtered.len(), "Fetched REST source");
+                            filtered
+                        }
+                        Err(e) => {
+                            tracing::warn!(url = %url, error = %e, "Failed to fetch REST source");
+                            Vec::new()
+                        }
+                    }
+                }
             }
         })
         .collect();
@@ -0,0 +1,197 @@
+use anyhow::{Context, Result, bail};
+use chrono::{TimeZone, Utc};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::ContentItem;
+
+const BASE_URL: &str = "https://www.reddit.com";
+
+/// Reddit throttles or blocks generic agents; its API rules ask for
+/// `<platform>:<app id>:<version>`.
+const USER_AGENT: &str = concat!("server:cthulu:", env!("CARGO_PKG_VERSION"), " (flow content source)");
+
+/// Longest self-post excerpt used as an item summary.
+const MAX_SUMMARY_CHARS: usize = 500;
+
+/// Subreddit listing order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedditSort {
+    #[default]
+    Hot,
+    New,
+    Top,
+    Rising,
+}
+
+impl RedditSort {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "hot" => Ok(Self::Hot),
+            "new" => Ok(Self::New),
+            "top" => Ok(Self::Top),
+            "rising" => Ok(Self::Rising),
+            other => bail!("reddit sort must be hot, new, top or rising, got '{other}'"),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Hot => "hot",
+            Self::New => "new",
+            Self::Top => "top",
+            Self::Rising => "rising",
+        }
+    }
+}
+
+/// Accept `rust`, `r/rust` or `/r/rust`; reject anything that isn't a
+/// subreddit name.
+pub fn normalize_subreddit(value: &str) -> Result<String> {
+    let name = value.trim().trim_start_matches('/');
+    let name = name.strip_prefix("r/").unwrap_or(name).trim_end_matches('/');
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        bail!("invalid subreddit '{value}'");
+    }
+    Ok(name.to_string())
+}
+
+pub fn listing_url(subreddit: &str, sort: RedditSort, limit: usize) -> String {
+    format!("{BASE_URL}/r/{subreddit}/{}.json?limit={limit}", sort.as_str())
+}
+
+/// Fetch a subreddit listing. A 429 (Reddit rate-limits unauthenticated
+/// JSON reads) logs a warning and yields no items rather than failing.
+pub async fn fetch_posts(
+    client: &reqwest::Client,
+    subreddit: &str,
+    sort: RedditSort,
+    limit: usize,
+) -> Result<Vec<ContentItem>> {
+    let url = listing_url(subreddit, sort, limit);
+    let response = client
+        .get(&url)
+        .header("User-Agent", USER_AGENT)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .context("failed to fetch subreddit")?;
+
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        tracing::warn!(url = %url, "Reddit rate limit hit, skipping subreddit this run");
+        return Ok(Vec::new());
+    }
+    let listing: Value = response
+        .error_for_status()
+        .with_context(|| format!("subreddit returned error status: {url}"))?
+        .json()
+        .await
+        .context("failed to parse subreddit listing")?;
+
+    Ok(parse_listing(&listing, limit))
+}
+
+fn parse_listing(listing: &Value, limit: usize) -> Vec<ContentItem> {
+    let Some(children) = listing["data"]["children"].as_array() else {
+        return Vec::new();
+    };
+    children
+        .iter()
+        .map(|child| &child["data"])
+        // Pinned mod posts aren't content
+        .filter(|post| !post["stickied"].as_bool().unwrap_or(false))
+        .filter_map(to_content_item)
+        .take(limit)
+        .collect()
+}
+
+fn to_content_item(post: &Value) -> Option<ContentItem> {
+    let title = post["title"].as_str().filter(|t| !t.is_empty())?.to_string();
+    let permalink = post["permalink"].as_str()?;
+    let selftext = post["selftext"].as_str().unwrap_or_default().trim();
+    let summary = if !selftext.is_empty() {
+        let mut excerpt: String = selftext.chars().take(MAX_SUMMARY_CHARS).collect();
+        if excerpt.len() < selftext.len() {
+            excerpt.push('…');
+        }
+        excerpt
+    } else {
+        // Link posts have no body; say where they point
+        post["url"]
+            .as_str()
+            .filter(|u| !u.contains(permalink))
+            .map(|u| format!("Link: {u}"))
+            .unwrap_or_default()
+    };
+    let image_url = post["thumbnail"].as_str().filter(|t| t.starts_with("http")).map(String::from);
+
+    Some(ContentItem {
+        title,
+        url: format!("{BASE_URL}{permalink}"),
+        summary,
+        published: post["created_utc"]
+            .as_f64()
+            .and_then(|t| Utc.timestamp_opt(t as i64, 0).single()),
+        image_url,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_listing() {
+        let listing = json!({
+            "kind": "Listing",
+            "data": { "children": [
+                { "kind": "t3", "data": {
+                    "title": "Weekly thread", "permalink": "/r/rust/comments/1/weekly/",
+                    "selftext": "Ask anything", "stickied": true, "created_utc": 1700000000.0
+                }},
+                { "kind": "t3", "data": {
+                    "title": "Rust 2.0 when?", "permalink": "/r/rust/comments/2/rust_20/",
+                    "url": "https://www.reddit.com/r/rust/comments/2/rust_20/",
+                    "selftext": "x".repeat(600), "created_utc": 1700000100.0, "thumbnail": "self"
+                }},
+                { "kind": "t3", "data": {
+                    "title": "Blog: async traits", "permalink": "/r/rust/comments/3/blog/",
+                    "url": "https://blog.example.com/async", "selftext": "",
+                    "created_utc": 1700000200.0, "thumbnail": "https://b.thumbs.redditmedia.com/t.jpg"
+                }},
+            ]}
+        });
+
+        let items = parse_listing(&listing, 10);
+        assert_eq!(items.len(), 2, "stickied post is dropped");
+
+        assert_eq!(items[0].url, "https://www.reddit.com/r/rust/comments/2/rust_20/");
+        assert_eq!(items[0].summary.chars().count(), MAX_SUMMARY_CHARS + 1);
+        assert_eq!(items[0].published.unwrap().timestamp(), 1700000100);
+        assert!(items[0].image_url.is_none());
+
+        assert_eq!(items[1].summary, "Link: https://blog.example.com/async");
+        assert_eq!(items[1].image_url.as_deref(), Some("https://b.thumbs.redditmedia.com/t.jpg"));
+
+        assert_eq!(parse_listing(&listing, 1).len(), 1);
+        assert!(parse_listing(&json!({"error": 404}), 10).is_empty());
+    }
+
+    #[test]
+    fn test_subreddit_and_sort() {
+        assert_eq!(normalize_subreddit("r/rust").unwrap(), "rust");
+        assert_eq!(normalize_subreddit(" /r/Local_LLaMA/ ").unwrap(), "Local_LLaMA");
+        assert!(normalize_subreddit("rust/../../api").is_err());
+        assert!(normalize_subreddit("").is_err());
+
+        assert_eq!(
+            listing_url("rust", RedditSort::parse("top").unwrap(), 5),
+            "https://www.reddit.com/r/rust/top.json?limit=5"
+        );
+        assert_eq!(RedditSort::default(), RedditSort::Hot);
+        assert!(RedditSort::parse("controversial").is_err());
+    }
+}
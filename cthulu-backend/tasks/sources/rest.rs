@@ -0,0 +1,333 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+
+use super::ContentItem;
+use crate::config::RestSourceConfig;
+
+/// One step of a path.
+#[derive(Debug, PartialEq)]
+enum Segment<'p> {
+    Key(&'p str),
+    Index(usize),
+    /// `*` / `[*]`: only as the last step, where it names the array itself.
+    Wildcard,
+}
+
+/// Split a dot path or a JSONPath-style one (`$.data.items[*]`,
+/// `media[0].url`, `['odd.key']`). `None` if a bracket is malformed.
+fn segments(path: &str) -> Option<Vec<Segment<'_>>> {
+    let mut rest = path.strip_prefix('$').unwrap_or(path);
+    let mut out = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']')?;
+            let inner = after[..end].trim();
+            let quoted = inner.len() >= 2
+                && ((inner.starts_with('\'') && inner.ends_with('\''))
+                    || (inner.starts_with('"') && inner.ends_with('"')));
+            out.push(match inner {
+                "*" => Segment::Wildcard,
+                _ if quoted => Segment::Key(&inner[1..inner.len() - 1]),
+                _ => Segment::Index(inner.parse().ok()?),
+            });
+            rest = &after[end + 1..];
+            continue;
+        }
+        let end = rest.find(['.', '[']).unwrap_or(rest.len());
+        let key = &rest[..end];
+        out.push(if key == "*" { Segment::Wildcard } else { Segment::Key(key) });
+        rest = &rest[end..];
+    }
+    Some(out)
+}
+
+/// Look up a path in a JSON value: dot-separated (`data.items`,
+/// `media.0.url`) or JSONPath-style (`$.data.items[*]`, `media[0].url`).
+/// An empty path or `$` returns the value itself.
+pub fn extract_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let segments = segments(path)?;
+    let last = segments.len().saturating_sub(1);
+    segments.iter().enumerate().try_fold(value, |current, (i, segment)| match segment {
+        Segment::Key(key) => match current {
+            Value::Array(arr) => key.parse::<usize>().ok().and_then(|idx| arr.get(idx)),
+            _ => current.get(*key),
+        },
+        Segment::Index(index) => current.as_array()?.get(*index),
+        Segment::Wildcard => (i == last && current.is_array()).then_some(current),
+    })
+}
+
+/// String form of a scalar at `path`; `None` for missing, null, or non-scalar values.
+pub fn extract_string(value: &Value, path: &str) -> Option<String> {
+    match extract_path(value, path)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Expand `${VAR}` references in header values using `lookup`. A missing
+/// variable is an error so a request is never sent with a blank credential.
+pub fn resolve_headers(
+    headers: &BTreeMap<String, String>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<(String, String)>> {
+    headers
+        .iter()
+        .map(|(name, template)| {
+            let mut value = String::new();
+            let mut rest = template.as_str();
+            while let Some(start) = rest.find("${") {
+                value.push_str(&rest[..start]);
+                let after = &rest[start + 2..];
+                let end = after
+                    .find('}')
+                    .with_context(|| format!("unterminated ${{...}} in header '{name}'"))?;
+                let var = &after[..end];
+                let resolved = lookup(var)
+                    .with_context(|| format!("header '{name}' references unset env var {var}"))?;
+                value.push_str(&resolved);
+                rest = &after[end + 1..];
+            }
+            value.push_str(rest);
+            Ok((name.clone(), value))
+        })
+        .collect()
+}
+
+fn parse_date(value: &Value, path: &str) -> Option<DateTime<Utc>> {
+    match extract_path(value, path)? {
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|d| d.with_timezone(&Utc)),
+        Value::Number(n) => n.as_i64().and_then(|secs| Utc.timestamp_opt(secs, 0).single()),
+        _ => None,
+    }
+}
+
+fn to_item(raw: &Value, config: &RestSourceConfig) -> ContentItem {
+    ContentItem {
+        title: extract_string(raw, &config.title_path).unwrap_or_default(),
+        url: extract_string(raw, &config.url_path).unwrap_or_default(),
+        summary: extract_string(raw, &config.summary_path).unwrap_or_default(),
+        published: config.date_path.as_deref().and_then(|p| parse_date(raw, p)),
+        image_url: config.image_path.as_deref().and_then(|p| extract_string(raw, p)),
+    }
+}
+
+/// Walk the cursor chain: fetch a page, map its items, then follow
+/// `cursor_path` until it runs out, `limit` items are collected, or
+/// `max_pages` pages have been read.
+async fn collect_pages<F, Fut>(config: &RestSourceConfig, mut fetch_page: F) -> Result<Vec<ContentItem>>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Value>>,
+{
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    for _ in 0..config.max_pages.max(1) {
+        let page = fetch_page(cursor.clone()).await?;
+        let Some(raw_items) = extract_path(&page, &config.items_path).and_then(Value::as_array) else {
+            bail!("'{}' is not an array in the response", config.items_path);
+        };
+        items.extend(raw_items.iter().map(|raw| to_item(raw, config)));
+
+        if items.len() >= config.limit {
+            items.truncate(config.limit);
+            break;
+        }
+        let next = config
+            .cursor_path
+            .as_deref()
+            .and_then(|p| extract_string(&page, p))
+            .filter(|c| !c.is_empty());
+        match next {
+            // An empty page or a cursor that doesn't move means we're done.
+            Some(next) if !raw_items.is_empty() && cursor.as_ref() != Some(&next) => {
+                cursor = Some(next);
+            }
+            _ => break,
+        }
+    }
+
+    Ok(items)
+}
+
+pub async fn fetch_rest(client: &reqwest::Client, config: &RestSourceConfig) -> Result<Vec<ContentItem>> {
+    let headers = resolve_headers(&config.headers, |var| std::env::var(var).ok())?;
+
+    collect_pages(config, |cursor| {
+        let mut request = match cursor.as_deref() {
+            // Some APIs hand back the full next-page URL instead of a token.
+            Some(next) if next.starts_with("http://") || next.starts_with("https://") => client.get(next),
+            Some(token) => client
+                .get(&config.url)
+                .query(&config.query)
+                .query(&[(config.cursor_param.as_str(), token)]),
+            None => client.get(&config.url).query(&config.query),
+        };
+        for (name, value) in &headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+
+        async move {
+            let resp = request.send().await.context("REST source request failed")?;
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let body = resp.text().await.unwrap_or_default();
+                bail!("REST source returned {status}: {body}");
+            }
+            resp.json::<Value>().await.context("REST source returned invalid JSON")
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    fn config() -> RestSourceConfig {
+        RestSourceConfig {
+            url: "https://api.example.com/posts".to_string(),
+            query: BTreeMap::new(),
+            headers: BTreeMap::new(),
+            items_path: "data.posts".to_string(),
+            title_path: "attributes.title".to_string(),
+            url_path: "links.html".to_string(),
+            summary_path: "attributes.excerpt".to_string(),
+            date_path: Some("attributes.created_at".to_string()),
+            image_path: None,
+            cursor_path: Some("meta.next_cursor".to_string()),
+            cursor_param: "cursor".to_string(),
+            max_pages: 5,
+            limit: 10,
+            keywords: vec![],
+        }
+    }
+
+    fn post(title: &str) -> Value {
+        json!({
+            "attributes": { "title": title, "excerpt": "text", "created_at": "2026-01-02T03:04:05Z" },
+            "links": { "html": format!("https://example.com/{title}") }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_two_page_cursor_traversal() {
+        let pages = [
+            json!({ "data": { "posts": [post("a"), post("b")] }, "meta": { "next_cursor": "p2" } }),
+            json!({ "data": { "posts": [post("c")] }, "meta": { "next_cursor": null } }),
+        ];
+        let seen = Mutex::new(Vec::new());
+
+        let items = collect_pages(&config(), |cursor| {
+            let idx = seen.lock().unwrap().len();
+            seen.lock().unwrap().push(cursor);
+            let page = pages[idx].clone();
+            async move { Ok(page) }
+        })
+        .await
+        .unwrap();
+
+        let titles: Vec<&str> = items.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["a", "b", "c"]);
+        assert_eq!(items[0].url, "https://example.com/a");
+        assert_eq!(items[0].summary, "text");
+        assert!(items[0].published.is_some());
+        assert_eq!(*seen.lock().unwrap(), vec![None, Some("p2".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_traversal_stops_at_limit_and_max_pages() {
+        let page = json!({ "data": { "posts": [post("a"), post("b")] }, "meta": { "next_cursor": "again" } });
+        let calls = Mutex::new(0);
+
+        let mut cfg = config();
+        cfg.limit = 3;
+        let items = collect_pages(&cfg, |_| {
+            *calls.lock().unwrap() += 1;
+            let page = page.clone();
+            async move { Ok(page) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(*calls.lock().unwrap(), 2);
+
+        // The cursor never changes, so traversal stops after the second page.
+        *calls.lock().unwrap() = 0;
+        cfg.limit = 100;
+        collect_pages(&cfg, |_| {
+            *calls.lock().unwrap() += 1;
+            let page = page.clone();
+            async move { Ok(page) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(*calls.lock().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_items_path_must_be_array() {
+        let err = collect_pages(&config(), |_| async { Ok(json!({ "data": {} })) })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("data.posts"));
+    }
+
+    #[test]
+    fn test_resolve_headers_from_env() {
+        let mut headers = BTreeMap::new();
+        headers.insert("Authorization".to_string(), "Bearer ${API_TOKEN}".to_string());
+        headers.insert("Accept".to_string(), "application/json".to_string());
+        let lookup = |var: &str| (var == "API_TOKEN").then(|| "s3cret".to_string());
+
+        let resolved = resolve_headers(&headers, lookup).unwrap();
+        assert_eq!(
+            resolved,
+            vec![
+                ("Accept".to_string(), "application/json".to_string()),
+                ("Authorization".to_string(), "Bearer s3cret".to_string()),
+            ]
+        );
+
+        headers.insert("X-Key".to_string(), "${MISSING}".to_string());
+        let err = resolve_headers(&headers, lookup).unwrap_err();
+        assert!(err.to_string().contains("MISSING"));
+    }
+
+    #[test]
+    fn test_extract_path() {
+        let v = json!({ "a": { "b": [{ "c": 7 }] }, "flag": true });
+        assert_eq!(extract_string(&v, "a.b.0.c").as_deref(), Some("7"));
+        assert_eq!(extract_string(&v, "flag").as_deref(), Some("true"));
+        assert!(extract_path(&v, "a.missing").is_none());
+        assert_eq!(extract_path(&v, ""), Some(&v));
+    }
+
+    #[test]
+    fn test_extract_jsonpath_style() {
+        let v = json!({ "data": { "items": [{ "title": "a", "links": { "self.url": "https://e.com/a" } }] } });
+        assert_eq!(extract_path(&v, "$"), Some(&v));
+        assert!(extract_path(&v, "$.data.items[*]").unwrap().is_array());
+        assert_eq!(extract_string(&v, "$.data.items[0].title").as_deref(), Some("a"));
+        assert_eq!(extract_string(&v, "data.items[0].links['self.url']").as_deref(), Some("https://e.com/a"));
+        // Wildcards only name the array at the end; malformed brackets resolve to nothing
+        assert!(extract_path(&v, "$.data.items[*].title").is_none());
+        assert!(extract_path(&v, "$.data.items[0").is_none());
+        assert!(extract_path(&v, "$.data.items[x]").is_none());
+    }
+}
@@ -1,16 +1,21 @@
+use std::collections::BTreeMap;
+
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use futures::future::join_all;
 
-use super::ContentItem;
+use super::dates::parse_date;
+use super::{with_headers, ContentItem};
 
+/// `headers` go on the feed request only, not the per-article og:image fetches,
+/// which may hit other hosts.
 pub async fn fetch_feed(
     client: &reqwest::Client,
     url: &str,
     limit: usize,
+    headers: &BTreeMap<String, String>,
 ) -> Result<Vec<ContentItem>> {
-    let bytes = client
-        .get(url)
+    let bytes = with_headers(client.get(url), headers)?
         .timeout(std::time::Duration::from_secs(30))
         .send()
         .await
@@ -21,7 +26,7 @@ pub async fn fetch_feed(
         .await
         .context("failed to read feed body")?;
 
-    let feed = feed_rs::parser::parse(&bytes[..]).context("failed to parse feed")?;
+    let feed = parse_feed(&bytes).context("failed to parse feed")?;
 
     let mut items: Vec<ContentItem> = feed
         .entries
@@ -76,6 +81,15 @@ pub async fn fetch_feed(
     Ok(items)
 }
 
+/// Parse feed bytes, reading entry dates with the shared [`parse_date`] so
+/// feeds with non-standard timestamps still get a `published` date.
+fn parse_feed(bytes: &[u8]) -> Result<feed_rs::model::Feed, feed_rs::parser::ParseFeedError> {
+    feed_rs::parser::Builder::new()
+        .timestamp_parser(parse_date)
+        .build()
+        .parse(bytes)
+}
+
 async fn extract_og_image(client: &reqwest::Client, url: &str) -> Option<String> {
     if url.is_empty() {
         return None;
@@ -162,6 +176,27 @@ mod tests {
         assert_eq!(entry.links[0].href, "https://example.com/1");
     }
 
+    #[test]
+    fn test_parse_feed_lenient_dates() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0">
+          <channel>
+            <title>Test Feed</title>
+            <item><title>No zone</title><pubDate>2024-01-10 15:04:05</pubDate></item>
+            <item><title>Garbage</title><pubDate>sometime last week</pubDate></item>
+          </channel>
+        </rss>"#;
+
+        let feed = parse_feed(xml.as_bytes()).unwrap();
+        assert_eq!(feed.entries.len(), 2);
+        assert_eq!(
+            feed.entries[0].published.unwrap().to_rfc3339(),
+            "2024-01-10T15:04:05+00:00"
+        );
+        // Kept, just undated.
+        assert!(feed.entries[1].published.is_none());
+    }
+
     #[test]
     fn test_parse_atom_feed() {
         let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
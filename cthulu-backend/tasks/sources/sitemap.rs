@@ -0,0 +1,460 @@
+use std::io::Read;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+
+use super::ContentItem;
+
+/// Sitemap-index files can nest; stop following after this many levels.
+const MAX_INDEX_DEPTH: usize = 3;
+/// Upper bound on sitemap files fetched for a single source.
+const MAX_SITEMAP_FETCHES: usize = 50;
+/// The sitemap protocol's limit on a sitemap file, uncompressed.
+const MAX_SITEMAP_BYTES: usize = 50 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+struct SitemapEntry {
+    loc: String,
+    lastmod: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Sitemap {
+    /// `<sitemapindex>` — entries point at child sitemaps.
+    Index(Vec<SitemapEntry>),
+    /// `<urlset>` — entries are pages.
+    UrlSet(Vec<SitemapEntry>),
+}
+
+/// Fetch pages changed within `since_days` from a sitemap (following
+/// sitemap-index files), newest first.
+pub async fn fetch_sitemap(
+    client: &reqwest::Client,
+    url: &str,
+    limit: usize,
+    since_days: u64,
+    fetch_titles: bool,
+) -> Result<Vec<ContentItem>> {
+    let cutoff = Utc::now() - chrono::Duration::days(since_days as i64);
+
+    let mut pending = vec![(url.to_string(), 0usize)];
+    let mut pages = Vec::new();
+    let mut fetched = 0;
+
+    while let Some((sitemap_url, depth)) = pending.pop() {
+        if fetched >= MAX_SITEMAP_FETCHES {
+            tracing::warn!(url = %url, "sitemap fetch limit reached, skipping remaining sitemaps");
+            break;
+        }
+        fetched += 1;
+
+        let xml = match fetch_xml(client, &sitemap_url).await {
+            Ok(xml) => xml,
+            // The root must load; a broken child shouldn't sink the whole source
+            Err(e) if depth > 0 => {
+                tracing::warn!(url = %sitemap_url, error = %e, "failed to fetch child sitemap");
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        match parse_sitemap(&xml) {
+            Sitemap::Index(children) => {
+                if depth >= MAX_INDEX_DEPTH {
+                    continue;
+                }
+                // A child whose own lastmod predates the window has nothing new
+                pending.extend(
+                    children
+                        .into_iter()
+                        .filter(|c| c.lastmod.is_none_or(|m| m >= cutoff))
+                        .map(|c| (c.loc, depth + 1)),
+                );
+            }
+            Sitemap::UrlSet(entries) => pages.extend(entries),
+        }
+    }
+
+    let mut items = recent_items(pages, cutoff, limit);
+
+    if fetch_titles {
+        let titles = join_all(items.iter().map(|item| fetch_page_title(client, &item.url))).await;
+        for (item, title) in items.iter_mut().zip(titles) {
+            if let Some(title) = title {
+                item.title = title;
+            }
+        }
+    }
+
+    Ok(items)
+}
+
+async fn fetch_xml(client: &reqwest::Client, url: &str) -> Result<String> {
+    let mut resp = client
+        .get(url)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .context("failed to fetch sitemap")?
+        .error_for_status()
+        .with_context(|| format!("sitemap returned error status: {url}"))?;
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = resp.chunk().await.context("failed to read sitemap body")? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_SITEMAP_BYTES {
+            anyhow::bail!("sitemap is larger than {MAX_SITEMAP_BYTES} bytes: {url}");
+        }
+    }
+
+    // Inflating is CPU-bound; keep it off the async workers
+    tokio::task::spawn_blocking(move || decode_body(&bytes, MAX_SITEMAP_BYTES))
+        .await
+        .context("sitemap decode task failed")?
+        .with_context(|| format!("failed to decode sitemap: {url}"))
+}
+
+/// Sitemaps are often served as `.xml.gz`; detect gzip by its magic bytes.
+/// Fails once the decoded sitemap passes `max` bytes.
+fn decode_body(bytes: &[u8], max: usize) -> Result<String> {
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut xml = Vec::new();
+        flate2::read::GzDecoder::new(bytes)
+            .take(max as u64 + 1)
+            .read_to_end(&mut xml)
+            .context("invalid gzip sitemap")?;
+        if xml.len() > max {
+            anyhow::bail!("sitemap is larger than {max} bytes uncompressed");
+        }
+        String::from_utf8(xml).context("invalid gzip sitemap")
+    } else {
+        if bytes.len() > max {
+            anyhow::bail!("sitemap is larger than {max} bytes");
+        }
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+fn parse_sitemap(xml: &str) -> Sitemap {
+    if xml.contains("<sitemapindex") {
+        Sitemap::Index(parse_entries(xml, "sitemap"))
+    } else {
+        Sitemap::UrlSet(parse_entries(xml, "url"))
+    }
+}
+
+fn parse_entries(xml: &str, tag: &str) -> Vec<SitemapEntry> {
+    element_bodies(xml, tag)
+        .into_iter()
+        .filter_map(|body| {
+            let loc = element_bodies(body, "loc").first().map(|l| xml_text(l))?;
+            if loc.is_empty() {
+                return None;
+            }
+            let lastmod = element_bodies(body, "lastmod")
+                .first()
+                .and_then(|m| parse_lastmod(&xml_text(m)));
+            Some(SitemapEntry { loc, lastmod })
+        })
+        .collect()
+}
+
+/// Inner content of every `<tag>...</tag>` (or `<tag attr="">...</tag>`) in `xml`.
+fn element_bodies<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut bodies = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(pos) = xml[search_from..].find(&open) {
+        let start = search_from + pos;
+        let after_name = start + open.len();
+        // Make sure we matched `<url>` / `<url ...>`, not `<urlset>`
+        let boundary = xml[after_name..].chars().next();
+        if !matches!(boundary, Some('>') | Some(' ') | Some('\t') | Some('\n') | Some('\r')) {
+            search_from = after_name;
+            continue;
+        }
+        let Some(tag_end) = xml[after_name..].find('>') else {
+            break;
+        };
+        let body_start = after_name + tag_end + 1;
+        let Some(body_len) = xml[body_start..].find(&close) else {
+            break;
+        };
+        bodies.push(&xml[body_start..body_start + body_len]);
+        search_from = body_start + body_len + close.len();
+    }
+    bodies
+}
+
+/// Trim, unwrap CDATA and decode the predefined XML entities.
+fn xml_text(raw: &str) -> String {
+    let trimmed = raw.trim();
+    let inner = trimmed
+        .strip_prefix("<![CDATA[")
+        .and_then(|s| s.strip_suffix("]]>"))
+        .unwrap_or(trimmed);
+    inner
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// `<lastmod>` uses W3C datetime: a full timestamp or just a date.
+fn parse_lastmod(raw: &str) -> Option<DateTime<Utc>> {
+    super::dates::parse_date(raw)
+}
+
+/// Keep pages modified since `cutoff` (undated pages can't be shown to be
+/// recent and are dropped), newest first, deduplicated by URL.
+fn recent_items(
+    mut pages: Vec<SitemapEntry>,
+    cutoff: DateTime<Utc>,
+    limit: usize,
+) -> Vec<ContentItem> {
+    pages.retain(|p| p.lastmod.is_some_and(|m| m >= cutoff));
+    pages.sort_by_key(|e| std::cmp::Reverse(e.lastmod));
+
+    let mut seen = std::collections::HashSet::new();
+    pages
+        .into_iter()
+        .filter(|p| seen.insert(p.loc.clone()))
+        .take(limit)
+        .map(|p| ContentItem {
+            title: slug_title(&p.loc),
+            url: p.loc,
+            summary: String::new(),
+            published: p.lastmod,
+            image_url: None,
+        })
+        .collect()
+}
+
+/// Human-ish title from a URL's last path segment:
+/// `https://x.com/blog/my-first-post.html` → `my first post`.
+fn slug_title(url: &str) -> String {
+    let without_query = url.split(['?', '#']).next().unwrap_or(url);
+    let path = without_query
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(without_query);
+    let mut segments = path.split('/').filter(|s| !s.is_empty());
+    let host = segments.next().unwrap_or_default();
+    let Some(last) = segments.next_back() else {
+        return host.to_string();
+    };
+    let stem = last
+        .rsplit_once('.')
+        .filter(|(_, ext)| matches!(*ext, "html" | "htm" | "php" | "aspx"))
+        .map(|(stem, _)| stem)
+        .unwrap_or(last);
+    stem.replace(['-', '_'], " ")
+}
+
+async fn fetch_page_title(client: &reqwest::Client, url: &str) -> Option<String> {
+    let html = client
+        .get(url)
+        .timeout(std::time::Duration::from_secs(5))
+        .send()
+        .await
+        .ok()?
+        .text()
+        .await
+        .ok()?;
+    extract_title_from_html(&html)
+}
+
+fn extract_title_from_html(html: &str) -> Option<String> {
+    // Lowercase only to locate the tag; slice the original for the text
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title>")?;
+    let title = xml_text(&html[start..end]);
+    (!title.is_empty()).then_some(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::Write;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn days_ago(days: i64) -> String {
+        (Utc::now() - chrono::Duration::days(days)).format("%Y-%m-%d").to_string()
+    }
+
+    fn gzip(data: &str) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Minimal HTTP server returning fixed bodies by path.
+    async fn serve(routes: HashMap<String, Vec<u8>>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut sock, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = sock.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+                let (status, body) = match routes.get(&path) {
+                    Some(body) => ("200 OK", body.clone()),
+                    None => ("404 Not Found", Vec::new()),
+                };
+                let head = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = sock.write_all(head.as_bytes()).await;
+                let _ = sock.write_all(&body).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn test_parse_urlset() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+          <url><loc>https://example.com/a?x=1&amp;y=2</loc><lastmod>2024-03-01</lastmod></url>
+          <url>
+            <loc><![CDATA[https://example.com/b]]></loc>
+            <lastmod>2024-03-02T10:30:00+00:00</lastmod>
+          </url>
+          <url><loc>https://example.com/c</loc></url>
+        </urlset>"#;
+        let Sitemap::UrlSet(entries) = parse_sitemap(xml) else {
+            panic!("expected urlset");
+        };
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].loc, "https://example.com/a?x=1&y=2");
+        assert_eq!(entries[1].loc, "https://example.com/b");
+        assert!(entries[1].lastmod.is_some());
+        assert!(entries[2].lastmod.is_none());
+    }
+
+    #[test]
+    fn test_parse_lastmod_formats() {
+        assert!(parse_lastmod("2024-03-01").is_some());
+        assert!(parse_lastmod("2024-03-01T10:30:00Z").is_some());
+        assert!(parse_lastmod("2024-03-01T10:30+02:00").is_some());
+        assert!(parse_lastmod("March 1st").is_none());
+    }
+
+    #[test]
+    fn test_slug_title() {
+        assert_eq!(slug_title("https://example.com/blog/my-first_post.html"), "my first post");
+        assert_eq!(slug_title("https://example.com/docs/getting-started/"), "getting started");
+        assert_eq!(slug_title("https://example.com/"), "example.com");
+        assert_eq!(slug_title("https://example.com/v1.2-release?ref=x"), "v1.2 release");
+    }
+
+    #[test]
+    fn test_recent_items_filters_sorts_and_limits() {
+        let now = Utc::now();
+        let entry = |loc: &str, days: Option<i64>| SitemapEntry {
+            loc: loc.to_string(),
+            lastmod: days.map(|d| now - chrono::Duration::days(d)),
+        };
+        let pages = vec![
+            entry("https://x.com/old", Some(30)),
+            entry("https://x.com/mid", Some(3)),
+            entry("https://x.com/new", Some(1)),
+            entry("https://x.com/undated", None),
+            entry("https://x.com/new", Some(1)),
+        ];
+        let items = recent_items(pages, now - chrono::Duration::days(7), 10);
+        let urls: Vec<_> = items.iter().map(|i| i.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://x.com/new", "https://x.com/mid"]);
+
+        let pages = vec![entry("https://x.com/a", Some(1)), entry("https://x.com/b", Some(2))];
+        assert_eq!(recent_items(pages, now - chrono::Duration::days(7), 1).len(), 1);
+    }
+
+    #[test]
+    fn test_decode_body_caps_the_decoded_size() {
+        let xml = "<urlset></urlset>";
+        assert_eq!(decode_body(&gzip(xml), 64).unwrap(), xml);
+        assert_eq!(decode_body(xml.as_bytes(), 64).unwrap(), xml);
+
+        // A small gzip body can inflate far past the cap
+        let bomb = gzip(&"a".repeat(100_000));
+        assert!(bomb.len() < 1_000);
+        let err = decode_body(&bomb, 1_000).unwrap_err();
+        assert!(err.to_string().contains("larger than 1000 bytes"));
+        assert!(decode_body(&[b'a'; 65], 64).is_err());
+    }
+
+    #[test]
+    fn test_extract_title_from_html() {
+        let html = "<html><head><TITLE> Release &amp; Notes </TITLE></head></html>";
+        assert_eq!(extract_title_from_html(html).as_deref(), Some("Release & Notes"));
+        assert_eq!(extract_title_from_html("<html></html>"), None);
+    }
+
+    #[tokio::test]
+    async fn test_sitemap_index_follows_child_sitemaps() {
+        let recent = days_ago(1);
+        let older = days_ago(2);
+        let stale = days_ago(90);
+
+        let mut routes = HashMap::new();
+        routes.insert(
+            "/posts.xml".to_string(),
+            format!(
+                r#"<urlset><url><loc>https://example.com/posts/hello-world</loc><lastmod>{recent}</lastmod></url>
+                <url><loc>https://example.com/posts/ancient</loc><lastmod>{stale}</lastmod></url></urlset>"#
+            )
+            .into_bytes(),
+        );
+        routes.insert(
+            "/pages.xml.gz".to_string(),
+            gzip(&format!(
+                r#"<urlset><url><loc>https://example.com/about-us</loc><lastmod>{older}</lastmod></url></urlset>"#
+            )),
+        );
+        routes.insert(
+            "/archive.xml".to_string(),
+            format!(
+                r#"<urlset><url><loc>https://example.com/archived</loc><lastmod>{recent}</lastmod></url></urlset>"#
+            )
+            .into_bytes(),
+        );
+
+        // The index references the children by absolute URL, so it gets its own server
+        let children = serve(routes).await;
+        let index = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <sitemap><loc>{children}/posts.xml</loc><lastmod>{recent}</lastmod></sitemap>
+              <sitemap><loc>{children}/pages.xml.gz</loc></sitemap>
+              <sitemap><loc>{children}/archive.xml</loc><lastmod>{stale}</lastmod></sitemap>
+              <sitemap><loc>{children}/missing.xml</loc></sitemap>
+            </sitemapindex>"#
+        );
+        let root = serve(HashMap::from([("/sitemap.xml".to_string(), index.into_bytes())])).await;
+
+        let client = reqwest::Client::new();
+        let items = fetch_sitemap(&client, &format!("{root}/sitemap.xml"), 10, 7, false)
+            .await
+            .unwrap();
+
+        let urls: Vec<_> = items.iter().map(|i| i.url.as_str()).collect();
+        // Newest first; stale page dropped; stale child sitemap never followed;
+        // missing child skipped without failing the source.
+        assert_eq!(
+            urls,
+            vec!["https://example.com/posts/hello-world", "https://example.com/about-us"]
+        );
+        assert_eq!(items[0].title, "hello world");
+        assert_eq!(items[1].title, "about us");
+    }
+}
@@ -1,8 +1,19 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use scraper::{Html, Selector};
 
-use super::ContentItem;
+use super::dates::parse_date_with;
+use super::{with_headers, ContentItem};
+
+/// Pause between page fetches when following `next_page_selector`.
+const PAGE_DELAY: Duration = Duration::from_millis(500);
 
+/// Scrape `url`, then follow `next_page_selector` links for up to
+/// `max_pages` pages in all until `limit` items are collected. A next link
+/// to a page already visited ends the walk.
+#[allow(clippy::too_many_arguments)]
 pub async fn fetch_page(
     client: &reqwest::Client,
     url: &str,
@@ -14,9 +25,51 @@ pub async fn fetch_page(
     date_format: Option<&str>,
     limit: usize,
     base_url: Option<&str>,
+    headers: &BTreeMap<String, String>,
+    next_page_selector: Option<&str>,
+    max_pages: usize,
 ) -> Result<Vec<ContentItem>> {
-    let html = client
-        .get(url)
+    let next_sel = next_page_selector
+        .map(|s| Selector::parse(s).map_err(|e| anyhow::anyhow!("invalid next page selector: {:?}", e)))
+        .transpose()?;
+
+    let mut items = Vec::new();
+    let mut visited = HashSet::new();
+    let mut page_url = url.to_string();
+    for page in 0..max_pages.max(1) {
+        if page > 0 {
+            tokio::time::sleep(PAGE_DELAY).await;
+        }
+        visited.insert(page_url.clone());
+        let html = fetch_html(client, &page_url, headers).await?;
+        items.extend(parse_page(
+            &html,
+            items_selector,
+            title_selector,
+            url_selector,
+            summary_selector,
+            date_selector,
+            date_format,
+            limit - items.len(),
+            base_url,
+        )?);
+        if items.len() >= limit {
+            break;
+        }
+        let Some(next) = next_sel.as_ref().and_then(|sel| next_page_url(&html, sel, &page_url)) else {
+            break;
+        };
+        if visited.contains(&next) {
+            tracing::debug!(url = %next, "Next page already visited, stopping");
+            break;
+        }
+        page_url = next;
+    }
+    Ok(items)
+}
+
+async fn fetch_html(client: &reqwest::Client, url: &str, headers: &BTreeMap<String, String>) -> Result<String> {
+    with_headers(client.get(url), headers)?
         .timeout(std::time::Duration::from_secs(30))
         .send()
         .await
@@ -25,9 +78,18 @@ pub async fn fetch_page(
         .with_context(|| format!("page returned error status: {url}"))?
         .text()
         .await
-        .context("failed to read page body")?;
+        .context("failed to read page body")
+}
 
-    parse_page(&html, items_selector, title_selector, url_selector, summary_selector, date_selector, date_format, limit, base_url)
+/// The first `next_sel` match's `href`, resolved against the page it is on.
+fn next_page_url(html: &str, next_sel: &Selector, page_url: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let href = document.select(next_sel).find_map(|el| el.value().attr("href"))?.trim();
+    if href.is_empty() || href.starts_with('#') {
+        return None;
+    }
+    let next = reqwest::Url::parse(page_url).ok()?.join(href).ok()?;
+    Some(next.to_string())
 }
 
 fn parse_page(
@@ -84,29 +146,7 @@ fn parse_page(
         let published = date_sel
             .as_ref()
             .and_then(|sel| element.select(sel).next())
-            .and_then(|el| {
-                let text = el.text().collect::<String>();
-                let text = text.trim();
-                if let Some(fmt) = date_format {
-                    chrono::NaiveDateTime::parse_from_str(text, fmt)
-                        .ok()
-                        .map(|dt| dt.and_utc())
-                        .or_else(|| {
-                            chrono::NaiveDate::parse_from_str(text, fmt)
-                                .ok()
-                                .map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc())
-                        })
-                } else {
-                    chrono::DateTime::parse_from_rfc3339(text)
-                        .ok()
-                        .map(|dt| dt.with_timezone(&chrono::Utc))
-                        .or_else(|| {
-                            chrono::DateTime::parse_from_rfc2822(text)
-                                .ok()
-                                .map(|dt| dt.with_timezone(&chrono::Utc))
-                        })
-                }
-            });
+            .and_then(|el| parse_date_with(&el.text().collect::<String>(), date_format));
 
         if title.is_empty() && item_url.is_empty() {
             continue;
@@ -145,10 +185,17 @@ fn resolve_url(raw: &str, base_url: Option<&str>) -> String {
 
 /// Simple full-page text fetcher for `WebScrape` source variant.
 /// Strips all HTML tags and returns the page body as a single ContentItem.
-pub async fn fetch_page_text(client: &reqwest::Client, url: &str) -> Result<Vec<ContentItem>> {
-    let html = client
-        .get(url)
-        .header("User-Agent", "Mozilla/5.0 (compatible; Cthulu/1.0)")
+pub async fn fetch_page_text(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &BTreeMap<String, String>,
+) -> Result<Vec<ContentItem>> {
+    let mut request = client.get(url);
+    // A configured User-Agent replaces the default rather than adding a second one.
+    if !headers.keys().any(|k| k.eq_ignore_ascii_case("user-agent")) {
+        request = request.header("User-Agent", "Mozilla/5.0 (compatible; Cthulu/1.0)");
+    }
+    let html = with_headers(request, headers)?
         .timeout(std::time::Duration::from_secs(30))
         .send()
         .await
@@ -289,6 +336,23 @@ mod tests {
         assert_eq!(items[1].url, "https://example.com/news/2");
     }
 
+    #[test]
+    fn test_next_page_url() {
+        let next = Selector::parse("a.next").unwrap();
+        let html = r#"<a class="next" href="?page=3">Next</a>"#;
+        assert_eq!(
+            next_page_url(html, &next, "https://example.com/news?page=2").as_deref(),
+            Some("https://example.com/news?page=3")
+        );
+        let html = r#"<a class="next" href="/archive/3/">Next</a>"#;
+        assert_eq!(
+            next_page_url(html, &next, "https://example.com/archive/2/").as_deref(),
+            Some("https://example.com/archive/3/")
+        );
+        assert_eq!(next_page_url(r##"<a class="next" href="#">Next</a>"##, &next, "https://example.com/"), None);
+        assert_eq!(next_page_url("<p>last page</p>", &next, "https://example.com/"), None);
+    }
+
     #[test]
     fn test_parse_page_limit() {
         let items = parse_page(
@@ -414,4 +478,45 @@ mod tests {
         let html = "<html><head><title></title></head><body></body></html>";
         assert_eq!(extract_title(html), None);
     }
+
+    #[tokio::test]
+    async fn test_configured_headers_are_sent() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Records the raw request and answers with a tiny page
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = sock.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let body = "<html><head><title>Members</title></head><body>hi</body></html>";
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            sock.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_lowercase()
+        });
+
+        let mut headers = BTreeMap::new();
+        headers.insert("Authorization".to_string(), "Bearer abc123".to_string());
+        headers.insert("User-Agent".to_string(), "custom-agent".to_string());
+        let items = fetch_page_text(&reqwest::Client::new(), &format!("http://{addr}/"), &headers)
+            .await
+            .unwrap();
+        assert_eq!(items[0].title, "Members");
+
+        let request = server.await.unwrap();
+        assert!(request.contains("authorization: bearer abc123"));
+        assert!(request.contains("user-agent: custom-agent"));
+        assert!(!request.contains("cthulu/1.0"));
+    }
 }
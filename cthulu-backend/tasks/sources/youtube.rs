@@ -0,0 +1,153 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+
+use super::ContentItem;
+
+const FEED_BASE: &str = "https://www.youtube.com/feeds/videos.xml";
+
+/// Build the public uploads feed URL for a channel or playlist.
+pub fn feed_url(channel_id: Option<&str>, playlist_id: Option<&str>) -> Result<String> {
+    match (channel_id, playlist_id) {
+        (Some(id), _) if !id.is_empty() => Ok(format!("{FEED_BASE}?channel_id={id}")),
+        (_, Some(id)) if !id.is_empty() => Ok(format!("{FEED_BASE}?playlist_id={id}")),
+        _ => bail!("youtube source requires 'channel_id' or 'playlist_id'"),
+    }
+}
+
+pub async fn fetch_videos(
+    client: &reqwest::Client,
+    url: &str,
+    limit: usize,
+) -> Result<Vec<ContentItem>> {
+    let bytes = client
+        .get(url)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .context("failed to fetch youtube feed")?
+        .error_for_status()
+        .with_context(|| format!("youtube feed returned error status: {url}"))?
+        .bytes()
+        .await
+        .context("failed to read youtube feed body")?;
+
+    parse_feed(&bytes, limit)
+}
+
+fn parse_feed(bytes: &[u8], limit: usize) -> Result<Vec<ContentItem>> {
+    let feed = feed_rs::parser::parse(bytes).context("failed to parse youtube feed")?;
+
+    let items = feed
+        .entries
+        .into_iter()
+        .take(limit)
+        .map(|entry| {
+            let title = entry.title.map(|t| t.content).unwrap_or_default();
+            let url = entry
+                .links
+                .first()
+                .map(|l| l.href.clone())
+                .unwrap_or_default();
+            let media = entry.media.first();
+            let summary = media
+                .and_then(|m| m.description.as_ref())
+                .map(|d| d.content.clone())
+                .unwrap_or_default();
+            // Prefer the feed's media:thumbnail; fall back to the well-known
+            // thumbnail URL derived from the video id ("yt:video:<id>").
+            let image_url = media
+                .and_then(|m| m.thumbnails.first())
+                .map(|t| t.image.uri.clone())
+                .or_else(|| {
+                    entry
+                        .id
+                        .strip_prefix("yt:video:")
+                        .map(|id| format!("https://i.ytimg.com/vi/{id}/hqdefault.jpg"))
+                });
+            let published: Option<DateTime<Utc>> = entry.published.or(entry.updated);
+
+            ContentItem {
+                title,
+                url,
+                summary,
+                published,
+                image_url,
+            }
+        })
+        .collect();
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns:media="http://search.yahoo.com/mrss/" xmlns="http://www.w3.org/2005/Atom">
+  <title>Example Channel</title>
+  <entry>
+    <id>yt:video:abc123</id>
+    <yt:videoId>abc123</yt:videoId>
+    <title>First Video</title>
+    <link rel="alternate" href="https://www.youtube.com/watch?v=abc123"/>
+    <published>2026-03-01T12:00:00+00:00</published>
+    <updated>2026-03-02T12:00:00+00:00</updated>
+    <media:group>
+      <media:title>First Video</media:title>
+      <media:thumbnail url="https://i1.ytimg.com/vi/abc123/hqdefault.jpg" width="480" height="360"/>
+      <media:description>All about the first video.</media:description>
+    </media:group>
+  </entry>
+  <entry>
+    <id>yt:video:def456</id>
+    <title>Second Video</title>
+    <link rel="alternate" href="https://www.youtube.com/watch?v=def456"/>
+    <published>2026-02-01T12:00:00+00:00</published>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn test_feed_url() {
+        assert_eq!(
+            feed_url(Some("UC123"), None).unwrap(),
+            "https://www.youtube.com/feeds/videos.xml?channel_id=UC123"
+        );
+        assert_eq!(
+            feed_url(None, Some("PL9")).unwrap(),
+            "https://www.youtube.com/feeds/videos.xml?playlist_id=PL9"
+        );
+        assert!(feed_url(None, None).is_err());
+        assert!(feed_url(Some(""), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_youtube_feed() {
+        let items = parse_feed(SAMPLE_FEED.as_bytes(), 10).unwrap();
+        assert_eq!(items.len(), 2);
+
+        assert_eq!(items[0].title, "First Video");
+        assert_eq!(items[0].url, "https://www.youtube.com/watch?v=abc123");
+        assert_eq!(items[0].summary, "All about the first video.");
+        assert_eq!(
+            items[0].image_url.as_deref(),
+            Some("https://i1.ytimg.com/vi/abc123/hqdefault.jpg")
+        );
+        assert_eq!(
+            items[0].published.unwrap().to_rfc3339(),
+            "2026-03-01T12:00:00+00:00"
+        );
+
+        // No media group — thumbnail derived from the video id
+        assert_eq!(
+            items[1].image_url.as_deref(),
+            Some("https://i.ytimg.com/vi/def456/hqdefault.jpg")
+        );
+    }
+
+    #[test]
+    fn test_parse_youtube_feed_respects_limit() {
+        let items = parse_feed(SAMPLE_FEED.as_bytes(), 1).unwrap();
+        assert_eq!(items.len(), 1);
+    }
+}
@@ -421,6 +421,12 @@ pub fn parse_template_yaml(yaml: &str) -> Result<Flow> {
         nodes,
         edges,
         version: 0,
+        max_runtime_secs: None,
+        capture_prompts: true,
+        sandbox_backend: None,
+        cost_cap: None,
+        default_session_budget_usd: None,
+        output_transform: vec![],
         created_at: now,
         updated_at: now,
     })
@@ -476,7 +482,10 @@ fn label_for_source(kind: &str) -> String {
         "web-scrape" => "Web Scrape".to_string(),
         "web-scraper" => "Web Scraper".to_string(),
         "github-merged-prs" => "GitHub PRs".to_string(),
+        "github-issues" => "GitHub Issues".to_string(),
         "market-data" => "Market Data".to_string(),
+        "hackernews" => "Hacker News".to_string(),
+        "reddit" => "Reddit".to_string(),
         other => slug_to_title(other),
     }
 }
@@ -484,7 +493,12 @@ fn label_for_source(kind: &str) -> String {
 fn label_for_sink(kind: &str) -> String {
     match kind {
         "slack" => "Slack".to_string(),
+        "discord" => "Discord".to_string(),
+        "telegram" => "Telegram".to_string(),
+        "file" => "File".to_string(),
+        "http-post" => "HTTP POST".to_string(),
         "notion" => "Notion".to_string(),
+        "email" => "Email".to_string(),
         other => slug_to_title(other),
     }
 }
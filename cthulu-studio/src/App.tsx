@@ -63,6 +63,8 @@ export default function App() {
     flowVersionRef,
     dispatchFlowUpdate,
     initFlow,
+    saveError,
+    clearSaveError,
   } = useFlowDispatch(dispatchApi, activeFlowIdRef);
 
   const activeFlowMeta = useMemo(() => {
@@ -373,6 +375,12 @@ export default function App() {
         {activeView === "dashboard" && <DashboardView />}
 
         <div style={{ display: activeView === "flow-editor" ? "contents" : "none" }}>
+          {saveError && (
+            <div className="tg-error-banner">
+              {saveError}
+              <button onClick={clearSaveError}>✕</button>
+            </div>
+          )}
           <FlowWorkspaceView
             flowId={activeFlowId}
             canonicalFlow={canonicalFlow}
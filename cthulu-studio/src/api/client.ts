@@ -102,6 +102,26 @@ export async function createFlow(
   });
 }
 
+/** Import a flow YAML from an https URL (raw GitHub file, gist). The flow is saved disabled. */
+export async function importFlowFromUrl(url: string): Promise<{ id: string }> {
+  return apiFetch<{ id: string }>("/flows/import-url", {
+    method: "POST",
+    body: JSON.stringify({ url }),
+  });
+}
+
+export async function importFlowYaml(yaml: string): Promise<{ id: string }> {
+  return apiFetch<{ id: string }>("/flows/import", {
+    method: "POST",
+    headers: { "Content-Type": "application/yaml" },
+    body: yaml,
+  });
+}
+
+export function flowExportUrl(id: string): string {
+  return `${getBaseUrl()}/api/flows/${id}/export`;
+}
+
 export async function updateFlow(
   id: string,
   updates: {
@@ -123,10 +143,30 @@ export async function deleteFlow(id: string): Promise<void> {
   await apiFetch(`/flows/${id}`, { method: "DELETE" });
 }
 
+export async function duplicateFlow(id: string): Promise<{ id: string }> {
+  return apiFetch<{ id: string }>(`/flows/${id}/duplicate`, { method: "POST" });
+}
+
 export async function triggerFlow(
-  id: string
-): Promise<{ status: string; flow_id: string }> {
-  return apiFetch(`/flows/${id}/trigger`, { method: "POST" });
+  id: string,
+  promptOverrides?: Record<string, string>,
+  dryRun?: boolean
+): Promise<{ status: string; flow_id: string; dry_run?: boolean }> {
+  const body = {
+    ...(promptOverrides && { prompt_overrides: promptOverrides }),
+    ...(dryRun && { dry_run: true }),
+  };
+  return apiFetch(`/flows/${id}/trigger`, {
+    method: "POST",
+    ...(Object.keys(body).length > 0 && { body: JSON.stringify(body) }),
+  });
+}
+
+export async function retryFlowRun(
+  id: string,
+  runId: string
+): Promise<{ status: string; flow_id: string; resumed_from: string }> {
+  return apiFetch(`/flows/${id}/runs/${runId}/retry`, { method: "POST" });
 }
 
 export async function getFlowRuns(id: string): Promise<FlowRun[]> {
@@ -175,6 +215,7 @@ export interface InteractSessionInfo {
   summary: string;
   message_count: number;
   total_cost: number;
+  cost_budget_usd?: number | null;
   created_at: string;
   busy: boolean;
   process_alive?: boolean;
@@ -203,6 +244,29 @@ export async function deleteAgentSession(
   });
 }
 
+export async function setSessionWorkingDir(
+  agentId: string,
+  sessionId: string,
+  workingDir: string
+): Promise<{ session_id: string; working_dir: string }> {
+  return apiFetch(`/agents/${agentId}/sessions/${sessionId}`, {
+    method: "PATCH",
+    body: JSON.stringify({ working_dir: workingDir }),
+  });
+}
+
+/** Set the session's cost budget in USD; 0 removes it. */
+export async function setSessionCostBudget(
+  agentId: string,
+  sessionId: string,
+  costBudgetUsd: number
+): Promise<{ session_id: string; working_dir: string; cost_budget_usd: number | null }> {
+  return apiFetch(`/agents/${agentId}/sessions/${sessionId}`, {
+    method: "PATCH",
+    body: JSON.stringify({ cost_budget_usd: costBudgetUsd }),
+  });
+}
+
 export async function stopAgentChat(
   agentId: string,
   sessionId?: string
@@ -220,6 +284,7 @@ export interface SessionStatus {
   process_alive: boolean;
   message_count: number;
   total_cost: number;
+  cost_budget_usd: number | null;
 }
 
 export async function getSessionStatus(
@@ -319,6 +384,14 @@ export async function getSessionLog(
   return data.lines;
 }
 
+export function sessionExportUrl(
+  agentId: string,
+  sessionId: string,
+  format: "markdown" | "json" = "markdown"
+): string {
+  return `${getBaseUrl()}/api/agents/${agentId}/sessions/${sessionId}/export?format=${format}`;
+}
+
 /** Subscribe to a live flow-run session via SSE. Returns cleanup function. */
 export function streamSessionLog(
   agentId: string,
@@ -347,6 +420,21 @@ export function streamSessionLog(
   return () => source.close();
 }
 
+export interface SearchHit {
+  type: "flow" | "agent" | "prompt";
+  id: string;
+  name: string;
+  score: number;
+  matched: string;
+}
+
+export async function search(query: string, limit?: number): Promise<SearchHit[]> {
+  const params = new URLSearchParams({ q: query });
+  if (limit !== undefined) params.set("limit", String(limit));
+  const data = await apiFetch<{ hits: SearchHit[] }>(`/search?${params}`);
+  return data.hits;
+}
+
 export async function listPrompts(): Promise<SavedPrompt[]> {
   const data = await apiFetch<{ prompts: SavedPrompt[] }>("/prompts");
   return data.prompts;
@@ -406,9 +494,10 @@ export interface ScheduleInfo {
   flow_id: string;
   trigger_kind: string | null;
   enabled?: boolean;
-  schedule?: string;
+  schedule?: string | string[];
   next_run: string | null;
   next_runs?: string[];
+  schedules?: { schedule: string; next_run: string | null }[];
   poll_interval_secs?: number;
   error?: string;
 }
@@ -422,6 +511,8 @@ export interface SchedulerFlowStatus {
   name: string;
   enabled: boolean;
   scheduler_active: boolean;
+  restart_count: number;
+  cost_window: { max_usd: number; window_hours: number; spent_usd: number } | null;
 }
 
 export interface SchedulerStatus {
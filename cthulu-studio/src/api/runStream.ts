@@ -14,6 +14,9 @@ export function subscribeToRuns(
     "node_started",
     "node_completed",
     "node_failed",
+    "waiting_for_lock",
+    "cost_cap_exceeded",
+    "prompt_rendered",
     "run_completed",
     "run_failed",
     "log",
@@ -39,11 +39,11 @@ OUTPUT FORMAT — use a fenced json code block:
 \`\`\`
 
 NODE TYPES:
-- trigger: cron (schedule), github-pr (repo), webhook, manual
-- source: rss (url, limit?, keywords?), web-scrape (url), web-scraper (url, items_selector, title_selector, url_selector), github-merged-prs (repos, since_days?), market-data (no config), google-sheets (spreadsheet_id, range, service_account_key_env)
+- trigger: cron (schedule), github-pr (repo), webhook (path, secret? checked against X-Webhook-Secret; body available as {{webhook_payload}}), manual
+- source: rss (url, limit?, keywords?), web-scrape (url), web-scraper (url, items_selector, title_selector, url_selector, next_page_selector?, max_pages?), github-merged-prs (repos, since_days?), github-issues (repos, state? open|closed|all, labels?, since_days?), market-data (no config), google-sheets (spreadsheet_id, range, service_account_key_env), hackernews (story_type? top|best|new, limit?, keywords?), reddit (subreddit, sort? hot|new|top|rising, limit?, keywords?)
 - filter: keyword (keywords, mode?, field?)
-- executor: claude-code (prompt REQUIRED, permissions?, working_dir?)
-- sink: slack (webhook_url_env?, bot_token_env?, channel?), notion (token_env, database_id)
+- executor: claude-code (prompt REQUIRED, permissions?, working_dir?, model?, max_turns?, timeout_secs? default 600, filters? e.g. [{kind: date-window, max_age_hours, drop_undated?}, {kind: dedup, key? url|title, max_remembered?}, {kind: limit, count, strategy? head|tail|newest}])
+- sink: slack (webhook_url_env?, bot_token_env?, channel?), notion (token_env, database_id, or append_to_page to add blocks to an existing page), discord (webhook_url_env), telegram (bot_token_env, chat_id), file (path with {flow_id}/{date}), http-post (url, headers?, content_type?), email (smtp_host_env, smtp_user_env, smtp_pass_env, from, to, subject? with {flow_name}/{date}); every sink also takes max_retries? (default 3)
 
 EDGE WIRING: trigger→source, source→executor (or source→filter→executor), executor→sink. "edges": "auto" handles this.
 
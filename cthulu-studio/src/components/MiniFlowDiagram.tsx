@@ -83,6 +83,7 @@ function kindLabel(kind: string): string {
     "web-scrape": "Web Scrape",
     "web-scraper": "Scraper",
     "github-merged-prs": "GitHub PRs",
+    "github-issues": "Issues",
     "market-data": "Market",
     keyword: "Filter",
     "claude-code": "Claude",
@@ -67,6 +67,9 @@ function ExecutorConfigPanel({
   const agentId = (config.agent_id as string) || "";
   const prompt = (config.prompt as string) || "";
   const workingDir = (config.working_dir as string) || "";
+  const model = (config.model as string) || "";
+  const maxTurns = typeof config.max_turns === "number" ? String(config.max_turns) : "";
+  const timeoutSecs = typeof config.timeout_secs === "number" ? String(config.timeout_secs) : "";
 
   const updateConfig = useCallback(
     (updates: Record<string, unknown>) => {
@@ -141,6 +144,45 @@ function ExecutorConfigPanel({
           value={workingDir}
           onChange={(e) => updateConfig({ working_dir: e.target.value })}
         />
+
+        <label className="node-config-label" style={{ marginTop: 12 }}>
+          Model
+        </label>
+        <input
+          className="node-config-input"
+          type="text"
+          placeholder="Default (e.g. sonnet, opus)"
+          value={model}
+          onChange={(e) => updateConfig({ model: e.target.value })}
+        />
+
+        <label className="node-config-label" style={{ marginTop: 12 }}>
+          Max Turns
+        </label>
+        <input
+          className="node-config-input"
+          type="number"
+          min={1}
+          placeholder="No cap"
+          value={maxTurns}
+          onChange={(e) =>
+            updateConfig({ max_turns: e.target.value ? Number(e.target.value) : undefined })
+          }
+        />
+
+        <label className="node-config-label" style={{ marginTop: 12 }}>
+          Timeout (seconds)
+        </label>
+        <input
+          className="node-config-input"
+          type="number"
+          min={1}
+          placeholder="600"
+          value={timeoutSecs}
+          onChange={(e) =>
+            updateConfig({ timeout_secs: e.target.value ? Number(e.target.value) : undefined })
+          }
+        />
       </div>
     </div>
   );
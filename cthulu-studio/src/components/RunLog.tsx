@@ -7,6 +7,9 @@ const EVENT_COLORS: Record<string, string> = {
   node_started: "var(--accent)",
   node_completed: "var(--success)",
   node_failed: "var(--danger)",
+  waiting_for_lock: "var(--text-secondary)",
+  cost_cap_exceeded: "var(--danger)",
+  prompt_rendered: "var(--text-secondary)",
   run_completed: "var(--success)",
   run_failed: "var(--danger)",
   log: "var(--text-secondary)",
@@ -17,6 +20,9 @@ const EVENT_LABELS: Record<string, string> = {
   node_started: "NODE",
   node_completed: "DONE",
   node_failed: "FAIL",
+  waiting_for_lock: "WAIT",
+  cost_cap_exceeded: "CAP",
+  prompt_rendered: "DRY",
   run_completed: "DONE",
   run_failed: "FAIL",
   log: "LOG",
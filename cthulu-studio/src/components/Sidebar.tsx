@@ -354,7 +354,10 @@ export default function Sidebar({
                     className="data-[state=checked]:bg-[var(--success)]"
                   />
                 </div>
-                <div className="sidebar-item-meta">{flow.node_count} nodes</div>
+                <div className="sidebar-item-meta">
+                  {flow.node_count} nodes
+                  {flow.last_run && ` · last run ${flow.last_run.status}`}
+                </div>
               </div>
             ))}
             {flows.length === 0 && (
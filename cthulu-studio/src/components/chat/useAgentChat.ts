@@ -2,6 +2,7 @@ import { useState, useRef, useCallback, useMemo, useEffect } from "react";
 import type { ThreadMessageLike } from "@assistant-ui/react";
 import { startAgentChat, reconnectAgentChat } from "../../api/interactStream";
 import { stopAgentChat, getSessionStatus, getSessionLog, getGitSnapshot } from "../../api/client";
+import { log } from "../../api/logger";
 import { replayLogLines, type ContentPart, type ToolCallPart } from "./chatParser";
 import { fileToBase64 } from "./chatUtils";
 import type { MultiRepoSnapshot } from "./FilePreviewContext";
@@ -79,6 +80,16 @@ function applySSEEvent(
         setGitSnapshot(data as MultiRepoSnapshot);
       }
       return false;
+    } else if (event.type === "session_expired") {
+      const text = data.restart === "auto"
+        ? "This conversation expired on the Claude side. Send your message again to continue in a fresh conversation."
+        : "This conversation expired on the Claude side. Start a new session to continue.";
+      partsRef.current = [...partsRef.current, { type: "text", text }];
+      return true;
+    } else if (event.type === "warning") {
+      // Non-fatal: the turn carries on.
+      log("warn", data.message || "Chat warning", data.code);
+      return false;
     } else if (event.type === "result") {
       const hasText = partsRef.current.some((p) => p.type === "text");
       if (data.text && !hasText) {
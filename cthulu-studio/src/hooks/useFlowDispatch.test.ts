@@ -272,6 +272,34 @@ describe("useFlowDispatch", () => {
       expect(result.current.updateSignal.counter).toBeGreaterThan(counterBeforeSave);
       expect(result.current.updateSignal.source).toBe("server");
     });
+
+    it("422 validation error surfaces the problems until a save succeeds", async () => {
+      const updateFlow = vi.fn()
+        .mockRejectedValueOnce(new Error(
+          'API error 422: {"error":"invalid flow","problems":["flow graph has a cycle"]}'
+        ))
+        .mockResolvedValue(makeFlow({ version: 2 }));
+      const { result, mockApi } = setup({ updateFlow });
+
+      act(() => {
+        result.current.dispatchFlowUpdate("canvas", { name: "Cyclic" });
+      });
+      await act(async () => {
+        await vi.advanceTimersByTimeAsync(500);
+      });
+
+      expect(result.current.saveError).toBe("Not saved: flow graph has a cycle");
+      expect(mockApi.getFlow).not.toHaveBeenCalled();
+
+      act(() => {
+        result.current.dispatchFlowUpdate("canvas", { name: "Fixed" });
+      });
+      await act(async () => {
+        await vi.advanceTimersByTimeAsync(500);
+      });
+
+      expect(result.current.saveError).toBeNull();
+    });
   });
 
   // --- No-diff guard ---
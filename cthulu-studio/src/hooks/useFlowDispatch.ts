@@ -28,6 +28,23 @@ export interface UseFlowDispatchReturn {
   flowVersionRef: React.RefObject<number>;
   dispatchFlowUpdate: (source: UpdateSource, updates: Partial<Flow>) => void;
   initFlow: (flow: Flow) => void;
+  /** Why the last autosave was rejected (422), until a save succeeds. */
+  saveError: string | null;
+  clearSaveError: () => void;
+}
+
+/** The problems listed in a 422 validation error, or null for other errors. */
+export function validationProblems(e: unknown): string[] | null {
+  if (!(e instanceof Error)) return null;
+  const match = e.message.match(/^API error 422: ([\s\S]*)$/);
+  if (!match) return null;
+  try {
+    const body = JSON.parse(match[1]);
+    if (Array.isArray(body.problems)) return body.problems;
+    return [body.error ?? match[1]];
+  } catch {
+    return [match[1]];
+  }
 }
 
 export function useFlowDispatch(
@@ -36,6 +53,7 @@ export function useFlowDispatch(
 ): UseFlowDispatchReturn {
   const [canonicalFlow, setCanonicalFlow] = useState<Flow | null>(null);
   const [updateSignal, setUpdateSignal] = useState<UpdateSignal>({ counter: 0, source: "init" });
+  const [saveError, setSaveError] = useState<string | null>(null);
 
   const updateCounterRef = useRef(0);
   const flowVersionRef = useRef<number>(0);
@@ -101,8 +119,14 @@ export function useFlowDispatch(
           setCanonicalFlow((prev) =>
             prev ? { ...prev, version: updated.version } : prev
           );
+          setSaveError(null);
           api.onSaveComplete?.();
         } catch (e) {
+          const problems = validationProblems(e);
+          if (problems) {
+            setSaveError(`Not saved: ${problems.join("; ")}`);
+            return;
+          }
           // On 409 Conflict, re-fetch and dispatch as server update
           if (e instanceof Error && e.message.includes("409")) {
             try {
@@ -131,17 +155,22 @@ export function useFlowDispatch(
     setCanonicalFlow(flow);
     canonicalFlowRef.current = flow;
     flowVersionRef.current = flow.version;
+    setSaveError(null);
 
     // Bump update signal with "init" source so consumers seed from it
     updateCounterRef.current += 1;
     setUpdateSignal({ counter: updateCounterRef.current, source: "init" });
   }, []);
 
+  const clearSaveError = useCallback(() => setSaveError(null), []);
+
   return {
     canonicalFlow,
     updateSignal,
     flowVersionRef,
     dispatchFlowUpdate,
     initFlow,
+    saveError,
+    clearSaveError,
   };
 }
@@ -4,9 +4,9 @@
  */
 
 const triggerKinds = ["cron", "github-pr", "manual", "webhook"];
-const sourceKinds = ["rss", "web-scrape", "github-merged-prs", "market-data"];
+const sourceKinds = ["rss", "web-scrape", "github-merged-prs", "github-issues", "market-data", "hackernews", "reddit"];
 const executorKinds = ["claude-code", "claude-api"];
-const sinkKinds = ["slack", "notion"];
+const sinkKinds = ["slack", "notion", "discord", "telegram", "file", "http-post", "email"];
 
 export const flowJsonSchema = {
   $schema: "http://json-schema.org/draft-07/schema#",
@@ -45,15 +45,24 @@ export interface FlowSummary {
   edge_count: number;
   created_at: string;
   updated_at: string;
+  last_run: LastRun | null;
 }
 
 export type RunStatus = "running" | "success" | "failed";
 
+export interface LastRun {
+  run_id: string;
+  status: RunStatus;
+  finished_at: string;
+  cost_usd: number | null;
+}
+
 export interface NodeRun {
   node_id: string;
   status: RunStatus;
   started_at: string;
   finished_at: string | null;
+  duration_ms: number | null;
   output_preview: string | null;
 }
 
@@ -63,8 +72,10 @@ export interface FlowRun {
   status: RunStatus;
   started_at: string;
   finished_at: string | null;
+  total_duration_ms: number | null;
   node_runs: NodeRun[];
   error: string | null;
+  resumed_from?: string;
 }
 
 export interface NodeTypeSchema {
@@ -90,6 +101,8 @@ export interface SessionInfo {
   permissions: string[];
   append_system_prompt: string | null;
   working_dir: string;
+  model: string | null;
+  max_turns: number | null;
   sources_summary: string;
   sinks_summary: string;
 }
@@ -16,7 +16,16 @@ export function validateNode(node: FlowNode): string[] {
 
   switch (node.kind) {
     case "cron":
-      if (!cfg.schedule || !(cfg.schedule as string).trim()) {
+      if (Array.isArray(cfg.schedule)) {
+        if (cfg.schedule.length === 0) {
+          errors.push("Schedule is required");
+        }
+        cfg.schedule.forEach((entry: unknown, i: number) => {
+          if (typeof entry !== "string" || !isValidCron(entry)) {
+            errors.push(`Schedule ${i + 1} must be a valid cron expression (5-6 tokens)`);
+          }
+        });
+      } else if (!cfg.schedule || !(cfg.schedule as string).trim()) {
         errors.push("Schedule is required");
       } else if (!isValidCron(cfg.schedule as string)) {
         errors.push("Schedule must be a valid cron expression (5-6 tokens)");
@@ -46,10 +55,16 @@ export function validateNode(node: FlowNode): string[] {
       }
       break;
     case "github-merged-prs":
+    case "github-issues":
       if (!Array.isArray(cfg.repos) || cfg.repos.length === 0) {
         errors.push("Repos is required");
       }
       break;
+    case "reddit":
+      if (!cfg.subreddit || !(cfg.subreddit as string).trim()) {
+        errors.push("Subreddit is required");
+      }
+      break;
     case "claude-code":
       if (!cfg.agent_id || !(cfg.agent_id as string).trim()) {
         errors.push("Select an agent for this executor");
@@ -57,6 +72,32 @@ export function validateNode(node: FlowNode): string[] {
       if (!cfg.prompt || !(cfg.prompt as string).trim()) {
         errors.push("Prompt is required");
       }
+      if (
+        Array.isArray(cfg.filters) &&
+        cfg.filters.some(
+          (f: Record<string, unknown>) =>
+            f?.kind === "date-window" && typeof f.max_age_hours !== "number"
+        )
+      ) {
+        errors.push("Date-window filter needs max_age_hours");
+      }
+      if (
+        Array.isArray(cfg.filters) &&
+        cfg.filters.some(
+          (f: Record<string, unknown>) =>
+            f?.kind === "dedup" && f.key !== undefined && f.key !== "url" && f.key !== "title"
+        )
+      ) {
+        errors.push("Dedup filter key must be url or title");
+      }
+      if (
+        Array.isArray(cfg.filters) &&
+        cfg.filters.some(
+          (f: Record<string, unknown>) => f?.kind === "limit" && typeof f.count !== "number"
+        )
+      ) {
+        errors.push("Limit filter needs count");
+      }
       break;
     case "slack":
       if (
@@ -66,11 +107,56 @@ export function validateNode(node: FlowNode): string[] {
         errors.push("Webhook URL or Bot Token is required");
       }
       break;
+    case "discord":
+      if (!cfg.webhook_url_env || !(cfg.webhook_url_env as string).trim()) {
+        errors.push("Webhook URL env is required");
+      }
+      break;
+    case "telegram":
+      if (!cfg.bot_token_env || !(cfg.bot_token_env as string).trim()) {
+        errors.push("Bot token env is required");
+      }
+      if (cfg.chat_id === undefined || cfg.chat_id === null || !String(cfg.chat_id).trim()) {
+        errors.push("Chat ID is required");
+      }
+      break;
+    case "file":
+      if (!cfg.path || !(cfg.path as string).trim()) {
+        errors.push("Path is required");
+      }
+      break;
+    case "email":
+      for (const [key, label] of [
+        ["smtp_host_env", "SMTP host env"],
+        ["smtp_user_env", "SMTP user env"],
+        ["smtp_pass_env", "SMTP password env"],
+        ["from", "From address"],
+      ]) {
+        if (!cfg[key] || !(cfg[key] as string).trim()) {
+          errors.push(`${label} is required`);
+        }
+      }
+      if (
+        !cfg.to ||
+        (Array.isArray(cfg.to) ? cfg.to.length === 0 : !String(cfg.to).trim())
+      ) {
+        errors.push("At least one recipient is required");
+      }
+      break;
+    case "http-post":
+      if (!cfg.url || !(cfg.url as string).trim()) {
+        errors.push("URL is required");
+      }
+      break;
     case "notion":
       if (!cfg.token_env || !(cfg.token_env as string).trim()) {
         errors.push("Token env is required");
       }
-      if (!cfg.database_id || !(cfg.database_id as string).trim()) {
+      if (cfg.append_to_page && (cfg.append_to_page as string).trim()) {
+        if (!isValidUuid(cfg.append_to_page as string)) {
+          errors.push("Append-to page ID must be a valid UUID");
+        }
+      } else if (!cfg.database_id || !(cfg.database_id as string).trim()) {
         errors.push("Database ID is required");
       } else if (!isValidUuid(cfg.database_id as string)) {
         errors.push("Database ID must be a valid UUID (e.g. 30aac5ee-1a2b-3c4d-5e6f-1234567890ab)");